@@ -19,6 +19,9 @@ struct ExecuteRequest {
     prompt: String,
     cwd: String,
     agent_type: String,
+    /// Extra agent command line arguments
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    args: Vec<String>,
 }
 
 #[derive(Serialize)]
@@ -46,6 +49,7 @@ pub trait WorkerClientApi: Send + Sync {
         prompt: String,
         cwd: PathBuf,
         agent_type: AgentType,
+        args: Vec<String>,
         event_tx: mpsc::Sender<AgentEvent>,
     ) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>>;
 
@@ -76,6 +80,7 @@ impl WorkerClient {
         prompt: String,
         cwd: PathBuf,
         agent_type: AgentType,
+        args: Vec<String>,
         event_tx: mpsc::Sender<AgentEvent>,
     ) -> Result<()> {
         let req = ExecuteRequest {
@@ -83,6 +88,7 @@ impl WorkerClient {
             prompt,
             cwd: cwd.to_string_lossy().to_string(),
             agent_type: format!("{:?}", agent_type).to_lowercase(), // "opencode", etc.
+            args,
         };
 
         info!("Sending execution request to worker: {}/execute", self.url);
@@ -104,7 +110,8 @@ impl WorkerClient {
         let mut buffer = String::new();
         let mut final_result = Ok(());
         
-        let mut parser = create_parser(agent_type);
+        // The worker protocol carries no model, so the agent default is used
+        let mut parser = create_parser(&agent_type, None);
 
         while let Some(item) = stream.next().await {
             let chunk: bytes::Bytes = item.map_err(|e| ExecutorError::execution_failed(format!("Stream error: {}", e)))?;
@@ -186,9 +193,10 @@ impl WorkerClientApi for WorkerClient {
         prompt: String,
         cwd: PathBuf,
         agent_type: AgentType,
+        args: Vec<String>,
         event_tx: mpsc::Sender<AgentEvent>,
     ) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
-        Box::pin(self.execute(task_id, prompt, cwd, agent_type, event_tx))
+        Box::pin(self.execute(task_id, prompt, cwd, agent_type, args, event_tx))
     }
 
     fn stop(&self, task_id: String) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
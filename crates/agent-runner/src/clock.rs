@@ -0,0 +1,87 @@
+//! Time source for run timestamps and durations
+//!
+//! Sessions, the run store and run lifecycle code read the time from a
+//! `Clock` so tests can drive it with `MockClock` instead of waiting.
+
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+/// Source of the current time
+pub trait Clock: Debug + Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The system's wall clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock that only moves when told to
+///
+/// Clones share the same time.
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    now: Arc<Mutex<DateTime<Utc>>>,
+}
+
+impl MockClock {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            now: Arc::new(Mutex::new(now)),
+        }
+    }
+
+    /// Move the clock to `now`
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner()) = now;
+    }
+
+    /// Move the clock forward by `by`
+    pub fn advance(&self, by: Duration) {
+        let mut now = self.now.lock().unwrap_or_else(|e| e.into_inner());
+        *now += chrono::Duration::from_std(by).unwrap_or(chrono::Duration::MAX);
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Milliseconds from `start` to `end`, zero if `end` is earlier
+pub fn elapsed_ms(start: DateTime<Utc>, end: DateTime<Utc>) -> u64 {
+    end.signed_duration_since(start).num_milliseconds().max(0) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::AgentType;
+    use crate::run::Run;
+    use chrono::TimeZone;
+    use uuid::Uuid;
+
+    #[test]
+    fn mock_clock_drives_run_duration() {
+        let start = Utc.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap();
+        let clock = MockClock::new(start);
+        let mut run = Run::new(Uuid::new_v4(), AgentType::OpenCode, "prompt".to_string(), "main".to_string());
+
+        run.mark_started_at(clock.now());
+        clock.advance(Duration::from_millis(90_250));
+        run.mark_completed_at(0, None, clock.now());
+
+        assert_eq!(run.duration_ms, Some(90_250));
+        assert_eq!(run.started_at, Some(start));
+        assert_eq!(elapsed_ms(start, run.ended_at.unwrap()), 90_250);
+    }
+}
@@ -45,6 +45,14 @@ pub enum ExecutorError {
     #[error("Invalid agent type: {agent_type}")]
     InvalidAgentType { agent_type: String },
 
+    /// Agent type is known but not allowed on this server
+    #[error("Agent type not allowed: {agent_type}")]
+    AgentNotAllowed { agent_type: String },
+
+    /// Configured agent argument contains shell metacharacters
+    #[error("Invalid agent argument {arg:?}: contains shell metacharacters")]
+    InvalidAgentArg { arg: String },
+
     /// Execution timeout
     #[error("Execution timed out after {seconds} seconds")]
     Timeout { seconds: u64 },
@@ -72,6 +80,10 @@ pub enum ExecutorError {
     /// Execution failed
     #[error("Execution failed: {message}")]
     ExecutionFailed { message: String },
+
+    /// Agent process was killed for exceeding a resource limit
+    #[error("Resource limit exceeded: {message}")]
+    ResourceExceeded { message: String },
 }
 
 impl ExecutorError {
@@ -67,6 +67,12 @@ pub enum AgentEvent {
         path: String,
         action: FileAction,
         diff: Option<String>,
+        /// Lines added, when the agent reports it
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        additions: Option<u32>,
+        /// Lines removed, when the agent reports it
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        deletions: Option<u32>,
     },
 
     /// Agent called a tool
@@ -113,12 +119,24 @@ pub enum OutputStream {
     Stderr,
 }
 
+/// Schema version of newly written execution events
+///
+/// Bump it with a migration in `event_migration` whenever a change to the
+/// event enums would stop older persisted events from deserializing.
+pub const EVENT_SCHEMA_VERSION: u32 = 1;
+
 /// Execution event with metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecutionEvent {
     /// Unique event ID
     pub id: Uuid,
 
+    /// Schema the event was written with
+    ///
+    /// Events written before versioning lack it; `event_migration` stamps
+    /// them as version 1 on load.
+    pub schema_version: u32,
+
     /// Session ID this event belongs to
     pub session_id: Uuid,
 
@@ -128,6 +146,12 @@ pub struct ExecutionEvent {
     /// Timestamp
     pub timestamp: DateTime<Utc>,
 
+    /// Position in the run's event log, from 1, assigned when persisted
+    ///
+    /// Unlike the offset it survives compaction of the log.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seq: Option<u64>,
+
     /// The event payload
     #[serde(flatten)]
     pub event: ExecutionEventType,
@@ -176,6 +200,8 @@ impl ExecutionEvent {
             session_id,
             task_id,
             timestamp: Utc::now(),
+            schema_version: EVENT_SCHEMA_VERSION,
+            seq: None,
             event,
         }
     }
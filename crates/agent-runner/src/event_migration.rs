@@ -0,0 +1,100 @@
+//! Upgrades of persisted execution events to the current schema
+//!
+//! Every event carries the `schema_version` it was written with. On load,
+//! older JSON shapes are migrated one version at a time before
+//! deserializing, so a run's history survives changes to the event enums
+//! instead of being skipped line by line. Events from a newer schema than
+//! this build knows are rejected.
+
+use serde::de::Error as _;
+use serde_json::{Map, Value};
+
+use crate::event::{ExecutionEvent, EVENT_SCHEMA_VERSION};
+
+/// Parse one persisted event, migrating it from its schema version first
+pub fn parse_event(line: &str) -> serde_json::Result<ExecutionEvent> {
+    let mut value: Value = serde_json::from_str(line)?;
+    if let Value::Object(object) = &mut value {
+        migrate_event(object).map_err(serde_json::Error::custom)?;
+    }
+    serde_json::from_value(value)
+}
+
+/// Upgrade an event object in place to `EVENT_SCHEMA_VERSION`
+///
+/// Each schema bump adds one `if version < N` step here that rewrites the
+/// object and sets `schema_version` to N.
+pub fn migrate_event(object: &mut Map<String, Value>) -> Result<(), String> {
+    let version = match object.get("schema_version") {
+        None => 0,
+        Some(value) => value
+            .as_u64()
+            .ok_or_else(|| format!("Invalid event schema version {}", value))?,
+    };
+    if version > u64::from(EVENT_SCHEMA_VERSION) {
+        return Err(format!(
+            "Event schema version {} is newer than the supported version {}",
+            version, EVENT_SCHEMA_VERSION
+        ));
+    }
+
+    if version < 1 {
+        unversioned_to_v1(object);
+    }
+    Ok(())
+}
+
+/// Unversioned -> v1: events written before versioning have no
+/// `schema_version`, which v1 requires
+fn unversioned_to_v1(object: &mut Map<String, Value>) {
+    object.insert("schema_version".to_string(), Value::from(1));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::{AgentEvent, ExecutionEventType};
+
+    #[test]
+    fn current_events_parse_unchanged() {
+        let event = ExecutionEvent::agent_event(
+            uuid::Uuid::new_v4(),
+            uuid::Uuid::new_v4(),
+            AgentEvent::Message {
+                content: "hello".to_string(),
+            },
+        );
+        let parsed = parse_event(&serde_json::to_string(&event).unwrap()).unwrap();
+        assert_eq!(parsed.id, event.id);
+        assert_eq!(parsed.schema_version, EVENT_SCHEMA_VERSION);
+        assert!(matches!(
+            parsed.event,
+            ExecutionEventType::AgentEvent { event: AgentEvent::Message { content } } if content == "hello"
+        ));
+    }
+
+    #[test]
+    fn unversioned_events_are_migrated_to_v1() {
+        // As written before events carried a schema version
+        let legacy = r#"{"id":"7a1c9e52-0d3b-4c8e-9f61-2b5d8e4a7c10","session_id":"0e8f3b6a-5c2d-4f9e-a1b7-3d6c9e2f8a41","task_id":"c4d2a8e1-7b3f-4a6c-8e5d-1f9b2c7a3e60","timestamp":"2024-01-01T00:00:00Z","event_type":"agent_event","type":"error","message":"boom","recoverable":true}"#;
+        assert!(serde_json::from_str::<ExecutionEvent>(legacy).is_err());
+
+        let migrated = parse_event(legacy).unwrap();
+        assert_eq!(migrated.schema_version, 1);
+        assert_eq!(migrated.seq, None);
+        assert!(matches!(
+            migrated.event,
+            ExecutionEventType::AgentEvent { event: AgentEvent::Error { message, recoverable: true } } if message == "boom"
+        ));
+    }
+
+    #[test]
+    fn events_from_a_newer_schema_are_rejected() {
+        let event = ExecutionEvent::progress(uuid::Uuid::new_v4(), uuid::Uuid::new_v4(), "Later".to_string(), None);
+        let mut value = serde_json::to_value(&event).unwrap();
+        value["schema_version"] = Value::from(EVENT_SCHEMA_VERSION + 1);
+
+        let err = parse_event(&value.to_string()).unwrap_err();
+        assert!(err.to_string().contains("newer than the supported version"), "{}", err);
+    }
+}
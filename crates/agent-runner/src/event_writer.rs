@@ -0,0 +1,160 @@
+//! Background persistence of a run's events
+//!
+//! `RunStorage::append_event` writes synchronously, so a busy forwarding
+//! loop would wait on the disk for every event. `RunEventWriter` moves the
+//! writes onto a task per run, fed by a bounded channel: sending only waits
+//! once the writer is `capacity` events behind. Events are written in the
+//! order they were sent, batching whatever queued up during the last write.
+
+use std::sync::Arc;
+
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::event::ExecutionEvent;
+use crate::storage::RunStorage;
+
+/// Queued events per run when none is configured
+pub const DEFAULT_EVENT_QUEUE_CAPACITY: usize = 1024;
+
+enum WriterMessage {
+    Event(ExecutionEvent),
+    Unredacted(ExecutionEvent),
+    Flush(oneshot::Sender<()>),
+}
+
+/// Ordered, batched writer for one run's events
+pub struct RunEventWriter {
+    tx: mpsc::Sender<WriterMessage>,
+    handle: JoinHandle<()>,
+}
+
+impl RunEventWriter {
+    /// Start the writer task for a run
+    pub fn spawn(store: Arc<dyn RunStorage>, task_id: Uuid, run_id: Uuid, capacity: usize) -> Self {
+        let (tx, rx) = mpsc::channel(capacity.max(1));
+        let handle = tokio::spawn(write_loop(store, task_id, run_id, rx));
+        Self { tx, handle }
+    }
+
+    /// Queue an event for the run's event log
+    pub async fn append(&self, event: ExecutionEvent) {
+        self.send(WriterMessage::Event(event)).await;
+    }
+
+    /// Queue the unmasked original of a redacted event
+    pub async fn append_unredacted(&self, event: ExecutionEvent) {
+        self.send(WriterMessage::Unredacted(event)).await;
+    }
+
+    /// Wait until every event queued so far has been written
+    pub async fn flush(&self) {
+        let (done_tx, done_rx) = oneshot::channel();
+        self.send(WriterMessage::Flush(done_tx)).await;
+        let _ = done_rx.await;
+    }
+
+    /// Write the remaining events and stop the writer
+    pub async fn close(self) {
+        drop(self.tx);
+        if let Err(e) = self.handle.await {
+            warn!("Event writer task failed: {}", e);
+        }
+    }
+
+    async fn send(&self, message: WriterMessage) {
+        if self.tx.send(message).await.is_err() {
+            warn!("Event writer stopped; dropping event");
+        }
+    }
+}
+
+async fn write_loop(
+    store: Arc<dyn RunStorage>,
+    task_id: Uuid,
+    run_id: Uuid,
+    mut rx: mpsc::Receiver<WriterMessage>,
+) {
+    while let Some(first) = rx.recv().await {
+        let mut batch = vec![first];
+        while let Ok(message) = rx.try_recv() {
+            batch.push(message);
+        }
+
+        let store = Arc::clone(&store);
+        let written = tokio::task::spawn_blocking(move || {
+            let mut events = Vec::new();
+            let mut flushed = Vec::new();
+            for message in batch {
+                match message {
+                    WriterMessage::Event(event) => events.push(event),
+                    WriterMessage::Unredacted(event) => {
+                        if let Err(e) = store.append_unredacted_event(task_id, run_id, &event) {
+                            warn!("Failed to keep unredacted event for run {}: {}", run_id, e);
+                        }
+                    }
+                    WriterMessage::Flush(done) => {
+                        // Everything queued before the flush request is in this batch
+                        flushed.push(done);
+                    }
+                }
+            }
+            if let Err(e) = store.append_events(task_id, run_id, &events) {
+                warn!("Failed to persist {} events for run {}: {}", events.len(), run_id, e);
+            }
+            flushed
+        })
+        .await;
+
+        match written {
+            Ok(flushed) => flushed.into_iter().for_each(|done| {
+                let _ = done.send(());
+            }),
+            Err(e) => warn!("Event write for run {} panicked: {}", run_id, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::AgentEvent;
+    use crate::persistence::RunStore;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn events_are_written_in_order_and_flushed() {
+        let temp_dir = TempDir::new().unwrap();
+        let store: Arc<dyn RunStorage> = Arc::new(RunStore::new(temp_dir.path()));
+        let (task_id, run_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let writer = RunEventWriter::spawn(Arc::clone(&store), task_id, run_id, 4);
+
+        let message = |i: usize| {
+            ExecutionEvent::agent_event(run_id, task_id, AgentEvent::Message { content: format!("event {}", i) })
+        };
+        for i in 0..50 {
+            writer.append(message(i)).await;
+        }
+        writer.flush().await;
+
+        let contents = |events: Vec<ExecutionEvent>| -> Vec<String> {
+            events
+                .into_iter()
+                .map(|event| match event.event {
+                    crate::event::ExecutionEventType::AgentEvent {
+                        event: AgentEvent::Message { content },
+                    } => content,
+                    other => panic!("unexpected event {:?}", other),
+                })
+                .collect()
+        };
+        let expected: Vec<String> = (0..50).map(|i| format!("event {}", i)).collect();
+        assert_eq!(contents(store.load_events(task_id, run_id).unwrap()), expected);
+
+        writer.append(message(50)).await;
+        writer.close().await;
+        assert_eq!(store.load_events(task_id, run_id).unwrap().len(), 51);
+    }
+}
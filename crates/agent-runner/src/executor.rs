@@ -10,14 +10,19 @@ use tokio::sync::{mpsc, RwLock};
 use tracing::{error, info, warn};
 use uuid::Uuid;
 
-use git_worktree::{Worktree, WorktreeConfig, WorktreeManager};
+use git_worktree::{MergeStatus, Worktree, WorktreeConfig, WorktreeManager};
+use vk_core::project::WorktreeStrategy;
 
 use crate::client::{WorkerClient, WorkerClientApi};
+use crate::clock::{Clock, SystemClock};
 use crate::error::{ExecutorError, Result};
-use crate::event::{AgentEvent, ExecutionEvent, ExecutionEventType, ExecutionStatus};
-use crate::persistence::RunStore;
+use crate::event::{AgentEvent, ExecutionEvent, ExecutionEventType, ExecutionStatus, OutputStream};
+use crate::persistence::{EventFilter, EventPage, RunStore};
 use crate::process::AgentType;
-use crate::run::{Run, RunSummary};
+use crate::redact::Redactor;
+use crate::run::{extract_summary, Run, RunSummary};
+use crate::run_log::RunLogger;
+use crate::storage::RunStorage;
 use crate::session::{ExecutionSession, SessionState};
 
 pub trait WorktreeManagerApi: Send + Sync {
@@ -27,12 +32,27 @@ pub trait WorktreeManagerApi: Send + Sync {
         base_branch: String,
     ) -> Pin<Box<dyn Future<Output = Result<Worktree>> + Send + '_>>;
 
+    /// Reuse the task's worktree reset to `base_branch`, creating it if missing
+    fn reuse_or_create(
+        &self,
+        task_id: String,
+        base_branch: String,
+    ) -> Pin<Box<dyn Future<Output = Result<Worktree>> + Send + '_>> {
+        self.create(task_id, base_branch)
+    }
+
     fn remove(
         &self,
         path: PathBuf,
         force: bool,
         delete_branches: bool,
     ) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>>;
+
+    fn check_mergeable(
+        &self,
+        branch: String,
+        into_branch: String,
+    ) -> Pin<Box<dyn Future<Output = Result<MergeStatus>> + Send + '_>>;
 }
 
 impl WorktreeManagerApi for WorktreeManager {
@@ -48,6 +68,18 @@ impl WorktreeManagerApi for WorktreeManager {
         })
     }
 
+    fn reuse_or_create(
+        &self,
+        task_id: String,
+        base_branch: String,
+    ) -> Pin<Box<dyn Future<Output = Result<Worktree>> + Send + '_>> {
+        Box::pin(async move {
+            self.reuse_or_create(&task_id, &base_branch)
+                .await
+                .map_err(ExecutorError::from)
+        })
+    }
+
     fn remove(
         &self,
         path: PathBuf,
@@ -60,6 +92,18 @@ impl WorktreeManagerApi for WorktreeManager {
                 .map_err(ExecutorError::from)
         })
     }
+
+    fn check_mergeable(
+        &self,
+        branch: String,
+        into_branch: String,
+    ) -> Pin<Box<dyn Future<Output = Result<MergeStatus>> + Send + '_>> {
+        Box::pin(async move {
+            self.check_branch_mergeable(&branch, &into_branch)
+                .await
+                .map_err(ExecutorError::from)
+        })
+    }
 }
 
 /// Configuration for the task executor
@@ -75,6 +119,103 @@ pub struct ExecutorConfig {
     pub auto_cleanup: bool,
     /// Whether to delete branches on cleanup
     pub delete_branches: bool,
+    /// Roll run event logs to a new part past this size (bytes)
+    pub max_event_file_bytes: Option<u64>,
+    /// Which raw output events are written to the event log
+    pub raw_output_persistence: RawOutputPersistence,
+    /// Per-agent overrides for `raw_output_persistence`
+    pub raw_output_persistence_overrides: HashMap<AgentType, RawOutputPersistence>,
+    /// Masks secrets in events before they are logged, persisted or forwarded
+    pub redactor: Redactor,
+    /// Extra arguments appended to every agent command line
+    pub agent_args: Vec<String>,
+    /// Per-agent arguments appended after `agent_args`
+    pub per_agent_args: HashMap<AgentType, Vec<String>>,
+    /// Time source for run timestamps and durations
+    pub clock: Arc<dyn Clock>,
+}
+
+impl ExecutorConfig {
+    /// Extra command line arguments for an agent
+    pub fn agent_args(&self, agent_type: &AgentType) -> Vec<String> {
+        let mut args = self.agent_args.clone();
+        args.extend(self.per_agent_args.get(agent_type).into_iter().flatten().cloned());
+        args
+    }
+
+    /// Raw output persistence policy for an agent
+    pub fn raw_output_policy(&self, agent_type: &AgentType) -> RawOutputPersistence {
+        self.raw_output_persistence_overrides
+            .get(agent_type)
+            .copied()
+            .unwrap_or(self.raw_output_persistence)
+    }
+}
+
+/// Policy for persisting `RawOutput` events
+///
+/// Structured events are always persisted, and every event is still
+/// forwarded live regardless of the policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RawOutputPersistence {
+    /// Persist all raw output
+    #[default]
+    All,
+    /// Persist only stderr output
+    StderrOnly,
+    /// Persist one in every N raw output events
+    Sample(u32),
+    /// Persist no raw output
+    None,
+}
+
+impl RawOutputPersistence {
+    /// Parse `all`, `stderr`, `none` or `sample:N`
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "all" => Some(Self::All),
+            "stderr" => Some(Self::StderrOnly),
+            "none" => Some(Self::None),
+            other => other
+                .strip_prefix("sample:")
+                .and_then(|n| n.parse().ok())
+                .filter(|n| *n > 0)
+                .map(Self::Sample),
+        }
+    }
+}
+
+/// Applies a `RawOutputPersistence` policy to a run's event stream
+struct RawOutputFilter {
+    policy: RawOutputPersistence,
+    seen: u64,
+}
+
+impl RawOutputFilter {
+    fn new(policy: RawOutputPersistence) -> Self {
+        Self { policy, seen: 0 }
+    }
+
+    /// Whether an event should be written to the event log
+    fn should_persist(&mut self, event: &ExecutionEvent) -> bool {
+        let ExecutionEventType::AgentEvent {
+            event: AgentEvent::RawOutput { stream, .. },
+        } = &event.event
+        else {
+            return true;
+        };
+
+        match self.policy {
+            RawOutputPersistence::All => true,
+            RawOutputPersistence::StderrOnly => *stream == OutputStream::Stderr,
+            RawOutputPersistence::Sample(every) => {
+                let keep = self.seen.is_multiple_of(u64::from(every));
+                self.seen += 1;
+                keep
+            }
+            RawOutputPersistence::None => false,
+        }
+    }
 }
 
 impl Default for ExecutorConfig {
@@ -85,6 +226,13 @@ impl Default for ExecutorConfig {
             worktree_config: WorktreeConfig::default(),
             auto_cleanup: false, // Manual cleanup by default for safety
             delete_branches: false,
+            max_event_file_bytes: None,
+            raw_output_persistence: RawOutputPersistence::default(),
+            raw_output_persistence_overrides: HashMap::new(),
+            redactor: Redactor::default(),
+            agent_args: Vec::new(),
+            per_agent_args: HashMap::new(),
+            clock: Arc::new(SystemClock),
         }
     }
 }
@@ -100,6 +248,8 @@ pub struct ExecuteRequest {
     pub base_branch: String,
     /// The task prompt/description
     pub prompt: String,
+    /// Whether the run gets a fresh worktree or reuses the task's
+    pub worktree_strategy: WorktreeStrategy,
 }
 
 /// Task executor that manages execution sessions
@@ -110,8 +260,8 @@ pub struct TaskExecutor {
     worktree_manager: Arc<dyn WorktreeManagerApi>,
     /// Worker client
     worker_client: Arc<dyn WorkerClientApi>,
-    /// Run store for persistence
-    run_store: Arc<RunStore>,
+    /// Storage for runs and their events
+    run_store: Arc<dyn RunStorage>,
     /// Active sessions by session ID
     sessions: Arc<RwLock<HashMap<Uuid, Arc<RwLock<ExecutionSession>>>>>,
     /// Sessions by task ID (for lookup)
@@ -140,24 +290,39 @@ impl TaskExecutor {
         ))
     }
 
-    fn new_with_dependencies(
+    /// Create a task executor with the given worktree manager and worker client
+    pub fn new_with_dependencies(
         config: ExecutorConfig,
         worktree_manager: Arc<dyn WorktreeManagerApi>,
         worker_client: Arc<dyn WorkerClientApi>,
     ) -> Self {
-        let run_store = Arc::new(RunStore::new(&config.data_dir));
+        let mut run_store = RunStore::new(&config.data_dir).with_clock(Arc::clone(&config.clock));
+        if let Some(bytes) = config.max_event_file_bytes {
+            run_store = run_store.with_max_event_file_bytes(bytes);
+        }
 
         Self {
             config,
             worktree_manager,
             worker_client,
-            run_store,
+            run_store: Arc::new(run_store),
             sessions: Arc::new(RwLock::new(HashMap::new())),
             task_sessions: Arc::new(RwLock::new(HashMap::new())),
             active_runs: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
+    /// Time source for run timestamps and durations
+    pub fn clock(&self) -> &Arc<dyn Clock> {
+        &self.config.clock
+    }
+
+    /// Persist runs to `storage` instead of the filesystem store under `data_dir`
+    pub fn with_run_storage(mut self, storage: Arc<dyn RunStorage>) -> Self {
+        self.run_store = storage;
+        self
+    }
+
     /// Execute a task
     ///
     /// Returns a receiver for execution events
@@ -187,10 +352,11 @@ impl TaskExecutor {
         // Create session
         let mut session = ExecutionSession::new(
             request.task_id,
-            agent_type,
+            agent_type.clone(),
             request.prompt.clone(),
             request.base_branch.clone(),
-        );
+        )
+        .with_clock(Arc::clone(&self.config.clock));
 
         let session_id = session.id;
         info!("Creating execution session {} for task {}", session_id, request.task_id);
@@ -201,10 +367,18 @@ impl TaskExecutor {
 
         // Create worktree
         let task_id_str = request.task_id.to_string();
-        let worktree = self
-            .worktree_manager
-            .create(task_id_str.clone(), request.base_branch.clone())
-            .await?;
+        let worktree = match request.worktree_strategy {
+            WorktreeStrategy::PerRun => {
+                self.worktree_manager
+                    .create(task_id_str.clone(), request.base_branch.clone())
+                    .await?
+            }
+            WorktreeStrategy::PerTask => {
+                self.worktree_manager
+                    .reuse_or_create(task_id_str.clone(), request.base_branch.clone())
+                    .await?
+            }
+        };
 
         info!(
             "Created worktree at {:?} on branch {}",
@@ -232,7 +406,7 @@ impl TaskExecutor {
         let mut run = Run::with_id(
             session_id,
             task_id,
-            agent_type,
+            agent_type.clone(),
             request.prompt.clone(),
             request.base_branch.clone(),
         );
@@ -249,6 +423,14 @@ impl TaskExecutor {
 
         self.run_store.save_run(&run)?;
 
+        let run_logger = RunLogger::new(Arc::clone(&self.run_store), task_id, session_id);
+        run_logger.log(format!(
+            "Run created: {} agent on branch {} (base {})",
+            agent_type.as_str(),
+            worktree.branch,
+            request.base_branch
+        ));
+
         let run_handle = Arc::new(RwLock::new(run));
         {
             let mut active_runs = self.active_runs.write().await;
@@ -277,15 +459,18 @@ impl TaskExecutor {
         let session_clone = Arc::clone(&session);
         let worktree_manager = Arc::clone(&self.worktree_manager);
         let worker_client = Arc::clone(&self.worker_client);
-        let auto_cleanup = self.config.auto_cleanup;
+        let agent_args = self.config.agent_args(&agent_type);
+        // Per-task worktrees outlive their runs
+        let auto_cleanup = self.config.auto_cleanup && request.worktree_strategy == WorktreeStrategy::PerRun;
         let delete_branches = self.config.delete_branches;
 
         let run_store = Arc::clone(&self.run_store);
         let run_handle = Arc::clone(&run_handle);
         let active_runs = Arc::clone(&self.active_runs);
+        let mut raw_output_filter = RawOutputFilter::new(self.config.raw_output_policy(&agent_type));
 
         tokio::spawn(async move {
-            let result = run_session(session_clone.clone(), worker_client).await;
+            let result = run_session(session_clone.clone(), worker_client, agent_args).await;
 
             match result {
                 Ok(exit_code) => {
@@ -315,16 +500,30 @@ impl TaskExecutor {
         });
 
         let mut event_rx = event_rx;
+        let redactor = self.config.redactor.clone();
         tokio::spawn(async move {
-            while let Some(event) = event_rx.recv().await {
-                if let Err(e) = run_store.append_event(task_id, session_id, &event) {
-                    warn!("Failed to persist event for session {}: {}", session_id, e);
+            let mut last_message = None;
+            while let Some(mut event) = event_rx.recv().await {
+                let original = event.clone();
+                if redactor.redact_event(&mut event) {
+                    if let Err(e) = run_store.append_unredacted_event(task_id, session_id, &original) {
+                        warn!("Failed to keep unredacted event for session {}: {}", session_id, e);
+                    }
+                }
+                run_logger.log_event(&event);
+                let persist = raw_output_filter.should_persist(&event);
+                if persist {
+                    if let Err(e) = run_store.append_event(task_id, session_id, &event) {
+                        warn!("Failed to persist event for session {}: {}", session_id, e);
+                    }
                 }
 
                 let run_snapshot = {
                     let mut run = run_handle.write().await;
-                    update_run_from_event(&mut run, &event);
-                    run.increment_event_count();
+                    update_run_from_event(&mut run, &event, &mut last_message);
+                    if persist {
+                        run.increment_event_count();
+                    }
                     run.clone()
                 };
 
@@ -377,17 +576,11 @@ impl TaskExecutor {
         run_id: Uuid,
         offset: usize,
         limit: usize,
-        event_type: Option<String>,
-        agent_event_type: Option<String>,
-    ) -> Result<(Vec<ExecutionEvent>, bool)> {
-        self.run_store.load_events_filtered_paginated(
-            task_id,
-            run_id,
-            offset,
-            limit,
-            event_type.as_deref(),
-            agent_event_type.as_deref(),
-        )
+        filter: EventFilter<'_>,
+        include_total: bool,
+    ) -> Result<EventPage> {
+        self.run_store
+            .load_events_page(task_id, run_id, offset, limit, filter, include_total)
     }
 
     /// Cancel a session
@@ -458,8 +651,8 @@ impl TaskExecutor {
         self.worktree_manager.as_ref()
     }
 
-    /// Get the run store for persistence operations
-    pub fn run_store(&self) -> &RunStore {
+    /// Get the run storage for persistence operations
+    pub fn run_store(&self) -> &Arc<dyn RunStorage> {
         &self.run_store
     }
 
@@ -486,7 +679,8 @@ impl TaskExecutor {
     }
 }
 
-fn update_run_from_event(run: &mut Run, event: &ExecutionEvent) {
+/// Fold `event` into `run`; `last_message` tracks the agent's latest message
+fn update_run_from_event(run: &mut Run, event: &ExecutionEvent, last_message: &mut Option<String>) {
     match &event.event {
         ExecutionEventType::StatusChanged { new_status, .. } => {
             run.status = *new_status;
@@ -500,24 +694,21 @@ fn update_run_from_event(run: &mut Run, event: &ExecutionEvent) {
                     run.metadata.commands_executed =
                         run.metadata.commands_executed.saturating_add(1);
                 }
-                AgentEvent::FileChange { path, .. } => {
-                    if !run.metadata.files_modified.contains(path) {
-                        run.metadata.files_modified.push(path.clone());
-                    }
-                }
+                AgentEvent::FileChange { path, .. } => run.record_file_change(path),
                 AgentEvent::ToolCall { .. } => {
                     run.metadata.tools_called = run.metadata.tools_called.saturating_add(1);
                 }
-                AgentEvent::Message { .. } => {
+                AgentEvent::Message { content } => {
                     run.metadata.message_count = run.metadata.message_count.saturating_add(1);
+                    *last_message = Some(content.clone());
                 }
                 AgentEvent::Error { message, .. } => {
                     run.metadata.error_count = run.metadata.error_count.saturating_add(1);
                     run.error = Some(message.clone());
                 }
                 AgentEvent::Completed { summary, .. } => {
-                    if let Some(summary) = summary {
-                        run.summary = Some(summary.clone());
+                    if let Some(summary) = extract_summary(summary.as_deref(), last_message.as_deref()) {
+                        run.summary = Some(summary);
                     }
                 }
                 AgentEvent::RawOutput { .. } => {}
@@ -536,6 +727,7 @@ fn update_run_from_event(run: &mut Run, event: &ExecutionEvent) {
             run.ended_at = Some(event.timestamp);
             run.status = *status;
             run.duration_ms = Some(*duration_ms);
+            run.count_changed_files();
         }
         ExecutionEventType::Progress { .. } => {}
     }
@@ -545,6 +737,7 @@ fn update_run_from_event(run: &mut Run, event: &ExecutionEvent) {
 async fn run_session(
     session: Arc<RwLock<ExecutionSession>>,
     worker_client: Arc<dyn WorkerClientApi>,
+    agent_args: Vec<String>,
 ) -> Result<i32> {
     // Start session (updates state)
     {
@@ -562,14 +755,14 @@ async fn run_session(
             session.task_id,
             session.prompt.clone(),
             worktree_path,
-            session.agent_type,
+            session.agent_type.clone(),
             session.agent_event_sender(),
         )
     };
 
     // Execute via Worker
     match worker_client
-        .execute(task_id.to_string(), prompt, worktree_path, agent_type, event_tx)
+        .execute(task_id.to_string(), prompt, worktree_path, agent_type, agent_args, event_tx)
         .await
     {
         Ok(_) => Ok(0),
@@ -614,11 +807,25 @@ mod tests {
         ) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
             Box::pin(async move { Ok(()) })
         }
+
+        fn check_mergeable(
+            &self,
+            _branch: String,
+            _into_branch: String,
+        ) -> Pin<Box<dyn Future<Output = Result<MergeStatus>> + Send + '_>> {
+            Box::pin(async move {
+                Ok(MergeStatus {
+                    mergeable: true,
+                    conflicts: Vec::new(),
+                })
+            })
+        }
     }
 
     #[derive(Default)]
     struct MockWorkerClient {
         inputs: Arc<Mutex<Vec<(String, String)>>>,
+        events: Vec<AgentEvent>,
     }
 
     impl WorkerClientApi for MockWorkerClient {
@@ -628,9 +835,20 @@ mod tests {
             _prompt: String,
             _cwd: PathBuf,
             _agent_type: AgentType,
-            _event_tx: mpsc::Sender<AgentEvent>,
+            _args: Vec<String>,
+            event_tx: mpsc::Sender<AgentEvent>,
         ) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
-            Box::pin(async move { Ok(()) })
+            let events = self.events.clone();
+            Box::pin(async move {
+                if !events.is_empty() {
+                    for event in events {
+                        let _ = event_tx.send(event).await;
+                    }
+                    // Let the session forwarder drain before the run is completed
+                    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                }
+                Ok(())
+            })
         }
 
         fn stop(&self, _task_id: String) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
@@ -658,6 +876,201 @@ mod tests {
         assert!(!config.delete_branches);
     }
 
+    #[test]
+    fn test_raw_output_persistence_parse() {
+        assert_eq!(RawOutputPersistence::parse("all"), Some(RawOutputPersistence::All));
+        assert_eq!(RawOutputPersistence::parse("Stderr"), Some(RawOutputPersistence::StderrOnly));
+        assert_eq!(RawOutputPersistence::parse("sample:10"), Some(RawOutputPersistence::Sample(10)));
+        assert_eq!(RawOutputPersistence::parse("sample:0"), None);
+        assert_eq!(RawOutputPersistence::parse("verbose"), None);
+    }
+
+    #[tokio::test]
+    async fn filtered_raw_output_is_forwarded_but_not_persisted() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut config = ExecutorConfig {
+            data_dir: temp_dir.path().to_path_buf(),
+            ..ExecutorConfig::default()
+        };
+        config
+            .raw_output_persistence_overrides
+            .insert(AgentType::OpenCode, RawOutputPersistence::None);
+
+        let worker_client: Arc<dyn WorkerClientApi> = Arc::new(MockWorkerClient {
+            events: vec![
+                AgentEvent::RawOutput {
+                    stream: OutputStream::Stdout,
+                    content: "noisy line".to_string(),
+                },
+                AgentEvent::Message {
+                    content: "done".to_string(),
+                },
+            ],
+            ..MockWorkerClient::default()
+        });
+        let executor =
+            TaskExecutor::new_with_dependencies(config, Arc::new(MockWorktreeManager), worker_client);
+
+        let task_id = Uuid::new_v4();
+        let (session_id, mut rx) = executor
+            .execute(ExecuteRequest {
+                task_id,
+                agent_type: "opencode".to_string(),
+                base_branch: "main".to_string(),
+                prompt: "prompt".to_string(),
+                worktree_strategy: WorktreeStrategy::PerRun,
+            })
+            .await
+            .unwrap();
+
+        let mut forwarded_raw = false;
+        while let Ok(Some(event)) =
+            tokio::time::timeout(std::time::Duration::from_secs(5), rx.recv()).await
+        {
+            match event.event {
+                ExecutionEventType::AgentEvent {
+                    event: AgentEvent::RawOutput { .. },
+                } => forwarded_raw = true,
+                ExecutionEventType::SessionEnded { .. } => break,
+                _ => {}
+            }
+        }
+        assert!(forwarded_raw, "raw output should still be forwarded live");
+
+        let persisted = executor.run_store().load_events(task_id, session_id).unwrap();
+        assert!(persisted.iter().any(|e| matches!(
+            e.event,
+            ExecutionEventType::AgentEvent {
+                event: AgentEvent::Message { .. }
+            }
+        )));
+        assert!(!persisted.iter().any(|e| matches!(
+            e.event,
+            ExecutionEventType::AgentEvent {
+                event: AgentEvent::RawOutput { .. }
+            }
+        )));
+    }
+
+    #[tokio::test]
+    async fn secrets_are_masked_before_events_are_stored() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let config = ExecutorConfig {
+            data_dir: temp_dir.path().to_path_buf(),
+            ..ExecutorConfig::default()
+        };
+        let fake_key = "sk-ant-REDACTED";
+        let worker_client: Arc<dyn WorkerClientApi> = Arc::new(MockWorkerClient {
+            events: vec![AgentEvent::RawOutput {
+                stream: OutputStream::Stdout,
+                content: format!("ANTHROPIC_API_KEY={}", fake_key),
+            }],
+            ..MockWorkerClient::default()
+        });
+        let executor =
+            TaskExecutor::new_with_dependencies(config, Arc::new(MockWorktreeManager), worker_client);
+
+        let task_id = Uuid::new_v4();
+        let (session_id, mut rx) = executor
+            .execute(ExecuteRequest {
+                task_id,
+                agent_type: "opencode".to_string(),
+                base_branch: "main".to_string(),
+                prompt: "prompt".to_string(),
+                worktree_strategy: WorktreeStrategy::PerRun,
+            })
+            .await
+            .unwrap();
+
+        let mut forwarded = Vec::new();
+        while let Ok(Some(event)) =
+            tokio::time::timeout(std::time::Duration::from_secs(5), rx.recv()).await
+        {
+            let ended = matches!(event.event, ExecutionEventType::SessionEnded { .. });
+            forwarded.push(serde_json::to_string(&event).unwrap());
+            if ended {
+                break;
+            }
+        }
+        assert!(forwarded.iter().all(|json| !json.contains(fake_key)));
+
+        let stored = executor.run_store().load_events(task_id, session_id).unwrap();
+        let raw_output = stored
+            .iter()
+            .find_map(|e| match &e.event {
+                ExecutionEventType::AgentEvent {
+                    event: AgentEvent::RawOutput { content, .. },
+                } => Some(content.clone()),
+                _ => None,
+            })
+            .expect("raw output is stored");
+        assert_eq!(raw_output, "ANTHROPIC_API_KEY=[REDACTED]");
+
+        let originals = executor.run_store().load_unredacted_events(task_id, session_id).unwrap();
+        assert_eq!(originals.len(), 1);
+        assert!(serde_json::to_string(&originals[0]).unwrap().contains(fake_key));
+    }
+
+    #[tokio::test]
+    async fn run_log_records_lifecycle_lines() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let config = ExecutorConfig {
+            data_dir: temp_dir.path().to_path_buf(),
+            ..ExecutorConfig::default()
+        };
+        let worker_client: Arc<dyn WorkerClientApi> = Arc::new(MockWorkerClient {
+            events: vec![
+                AgentEvent::Error {
+                    message: "lint failed".to_string(),
+                    recoverable: true,
+                },
+                AgentEvent::Completed {
+                    success: true,
+                    summary: Some("all green".to_string()),
+                },
+            ],
+            ..MockWorkerClient::default()
+        });
+        let executor =
+            TaskExecutor::new_with_dependencies(config, Arc::new(MockWorktreeManager), worker_client);
+
+        let task_id = Uuid::new_v4();
+        let (session_id, mut rx) = executor
+            .execute(ExecuteRequest {
+                task_id,
+                agent_type: "opencode".to_string(),
+                base_branch: "main".to_string(),
+                prompt: "prompt".to_string(),
+                worktree_strategy: WorktreeStrategy::PerRun,
+            })
+            .await
+            .unwrap();
+
+        while let Ok(Some(event)) =
+            tokio::time::timeout(std::time::Duration::from_secs(5), rx.recv()).await
+        {
+            if matches!(event.event, ExecutionEventType::SessionEnded { .. }) {
+                break;
+            }
+        }
+
+        let log = executor.run_store().load_log(task_id, session_id).unwrap();
+        for expected in [
+            "Run created: opencode agent",
+            "Status changed: creating_worktree -> starting",
+            "Recoverable error: lint failed",
+            "Agent succeeded: all green",
+            "Session ended: completed",
+        ] {
+            assert!(log.contains(expected), "missing {:?} in run log:\n{}", expected, log);
+        }
+        assert!(!executor
+            .run_store()
+            .load_events(task_id, session_id)
+            .unwrap()
+            .is_empty());
+    }
+
     #[test]
     fn test_execute_request() {
         let request = ExecuteRequest {
@@ -665,6 +1078,7 @@ mod tests {
             agent_type: "opencode".to_string(),
             base_branch: "main".to_string(),
             prompt: "Test prompt".to_string(),
+            worktree_strategy: WorktreeStrategy::default(),
         };
 
         assert!(!request.task_id.is_nil());
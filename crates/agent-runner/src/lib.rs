@@ -4,20 +4,39 @@
 //! Git worktrees with agent processes (OpenCode, Claude Code, etc.)
 
 mod client;
+mod clock;
 mod error;
 mod event;
+mod event_migration;
+mod event_writer;
 mod executor;
 mod parser;
 mod process;
 mod persistence;
+mod redact;
 mod run;
+mod run_log;
 mod session;
+mod storage;
 
-pub use client::WorkerClient;
+pub use client::{WorkerClient, WorkerClientApi};
+pub use clock::{Clock, MockClock, SystemClock};
 pub use error::{ExecutorError, Result};
-pub use event::{AgentEvent, ExecutionEvent, ExecutionStatus, ExecutionEventType};
-pub use executor::{ExecuteRequest, ExecutorConfig, TaskExecutor};
-pub use process::{AgentProcess, AgentType};
-pub use persistence::RunStore;
-pub use run::{ChatMessage, MessageRole, Run, RunMetadata, RunSummary, ToolCallInfo, ToolResultInfo};
+pub use event::{
+    AgentEvent, ExecutionEvent, ExecutionStatus, ExecutionEventType, FileAction, OutputStream, EVENT_SCHEMA_VERSION,
+};
+pub use event_writer::{RunEventWriter, DEFAULT_EVENT_QUEUE_CAPACITY};
+pub use executor::{
+    ExecuteRequest, ExecutorConfig, RawOutputPersistence, TaskExecutor, WorktreeManagerApi,
+};
+pub use parser::{create_parser, register_parser, OutputParser, ParserConstructor};
+pub use process::{validate_agent_args, AgentConfig, AgentProcess, AgentType, ResourceLimits};
+pub use persistence::{EventFilter, EventHistogram, EventPage, RunDeleteFilter, RunLoadError, RunStore};
+pub use redact::Redactor;
+pub use run::{
+    extract_summary, ChatMessage, MessageContentType, MessageRole, Run, RunMetadata, RunSummary, ToolCallInfo,
+    ToolResultInfo,
+};
+pub use run_log::RunLogger;
 pub use session::{ExecutionSession, SessionState};
+pub use storage::RunStorage;
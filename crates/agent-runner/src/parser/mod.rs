@@ -1,6 +1,13 @@
 //! Output parsers for different agent types
+//!
+//! Each agent type has a default parser. Models whose output differs can
+//! register their own with `register_parser`, keyed by agent type and a
+//! model glob (`*` matches any run of characters, `?` a single one).
+
+use std::sync::RwLock;
 
 use crate::event::{AgentEvent, OutputStream};
+use crate::process::AgentType;
 
 pub mod opencode;
 
@@ -14,14 +21,85 @@ pub trait OutputParser: Send + Sync {
     fn parse(&mut self, line: &str, stream: OutputStream) -> AgentEvent;
 }
 
-/// Create a parser for the given agent type
-pub fn create_parser(agent_type: crate::process::AgentType) -> Box<dyn OutputParser> {
+/// Builds a fresh parser for one output stream
+pub type ParserConstructor = fn() -> Box<dyn OutputParser>;
+
+/// A parser registered for the models of one agent type
+struct ModelParser {
+    agent_type: AgentType,
+    model_glob: String,
+    constructor: ParserConstructor,
+}
+
+static MODEL_PARSERS: RwLock<Vec<ModelParser>> = RwLock::new(Vec::new());
+
+/// Use `constructor` for `agent_type` runs whose model matches `model_glob`
+///
+/// Later registrations take precedence over earlier ones.
+pub fn register_parser(agent_type: AgentType, model_glob: impl Into<String>, constructor: ParserConstructor) {
+    MODEL_PARSERS
+        .write()
+        .expect("parser registry poisoned")
+        .push(ModelParser {
+            agent_type,
+            model_glob: model_glob.into(),
+            constructor,
+        });
+}
+
+/// Create a parser for the given agent type and model
+///
+/// Falls back to the agent type's default parser when no registered glob
+/// matches the model.
+pub fn create_parser(agent_type: &AgentType, model: Option<&str>) -> Box<dyn OutputParser> {
+    if let Some(model) = model {
+        let registry = MODEL_PARSERS.read().expect("parser registry poisoned");
+        let registered = registry
+            .iter()
+            .rev()
+            .find(|entry| entry.agent_type == *agent_type && glob_matches(&entry.model_glob, model));
+        if let Some(entry) = registered {
+            return (entry.constructor)();
+        }
+    }
+
     match agent_type {
-        crate::process::AgentType::OpenCode => Box::new(opencode::OpenCodeParser::new()),
+        AgentType::OpenCode => Box::new(opencode::OpenCodeParser::new()),
         _ => Box::new(DefaultParser),
     }
 }
 
+/// Case-insensitive match of `text` against a `*`/`?` glob
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` and the text position it was tried at
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, tried)) => {
+                    p = star + 1;
+                    t = tried + 1;
+                    backtrack = Some((star, tried + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
 /// A default parser that just returns raw output
 pub struct DefaultParser;
 
@@ -33,3 +111,53 @@ impl OutputParser for DefaultParser {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct ShoutingParser;
+
+    impl OutputParser for ShoutingParser {
+        fn parse(&mut self, line: &str, _stream: OutputStream) -> AgentEvent {
+            AgentEvent::Message {
+                content: line.to_uppercase(),
+            }
+        }
+    }
+
+    fn parse_with(agent_type: AgentType, model: Option<&str>) -> AgentEvent {
+        create_parser(&agent_type, model).parse("Thinking: hello", OutputStream::Stdout)
+    }
+
+    #[test]
+    fn model_specific_parser_is_chosen_for_matching_models() {
+        register_parser(AgentType::OpenCode, "shout-*-v?", || Box::new(ShoutingParser));
+
+        assert!(matches!(
+            parse_with(AgentType::OpenCode, Some("Shout-large-v2")),
+            AgentEvent::Message { content } if content == "THINKING: HELLO"
+        ));
+
+        // Other models, no model and other agents keep the defaults
+        assert!(matches!(
+            parse_with(AgentType::OpenCode, Some("shout-large-v10")),
+            AgentEvent::Thinking { .. }
+        ));
+        assert!(matches!(parse_with(AgentType::OpenCode, None), AgentEvent::Thinking { .. }));
+        assert!(matches!(
+            parse_with(AgentType::Codex, Some("shout-large-v2")),
+            AgentEvent::RawOutput { .. }
+        ));
+    }
+
+    #[test]
+    fn glob_matches_wildcards() {
+        assert!(glob_matches("*", ""));
+        assert!(glob_matches("gpt-*", "gpt-5-codex"));
+        assert!(glob_matches("*sonnet*", "claude-sonnet-4"));
+        assert!(glob_matches("a*b*c", "aXbYbZc"));
+        assert!(!glob_matches("gpt-*", "claude-gpt-5"));
+        assert!(!glob_matches("a?c", "ac"));
+    }
+}
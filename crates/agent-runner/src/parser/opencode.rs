@@ -62,6 +62,8 @@ impl OutputParser for OpenCodeParser {
                 path: path.to_string(),
                 action: crate::event::FileAction::Created,
                 diff: None,
+                additions: None,
+                deletions: None,
             };
         }
 
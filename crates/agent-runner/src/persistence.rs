@@ -4,29 +4,128 @@
 //! ```text
 //! .vk-data/
 //!   runs/
+//!     index.json         # Run id -> task id, rebuilt from the directories when missing
 //!     {task_id}/
 //!       {run_id}/
 //!         run.json       # Run metadata
 //!         events.jsonl   # Event log (newline-delimited JSON)
+//!         events.1.jsonl # Rotated event log parts (when rotation is enabled)
 //!         messages.jsonl # Chat messages (newline-delimited JSON)
+//!         unredacted.jsonl # Originals of events that had secrets masked
+//!         run.log        # Human-readable lifecycle log
+//!         tool-results/  # Oversized tool call results, one `{index}.json` each
 //! ```
 
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
 use std::fs::{self, File, OpenOptions};
 use std::io::{BufRead, BufReader, BufWriter, Write};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
+use chrono::{DateTime, Utc};
+use serde::Serialize;
 use tracing::{debug, info, warn};
 use uuid::Uuid;
 
+use crate::clock::{Clock, SystemClock};
 use crate::error::{ExecutorError, Result};
-use crate::event::{AgentEvent, ExecutionEvent, ExecutionEventType};
+use crate::event::{AgentEvent, ExecutionEvent, ExecutionEventType, ExecutionStatus};
+use crate::event_migration::parse_event;
 use crate::run::{ChatMessage, Run, RunSummary};
 
+/// A run that exists on disk but could not be loaded, with the reason
+pub type RunLoadError = (Uuid, String);
+
+/// Tool call results larger than this are moved out of the event log (64 KiB)
+pub const DEFAULT_MAX_INLINE_TOOL_RESULT_BYTES: usize = 64 * 1024;
+
+/// A page of events from a run's event log
+#[derive(Debug, Clone)]
+pub struct EventPage {
+    pub events: Vec<ExecutionEvent>,
+    pub has_more: bool,
+    /// Number of events matching the filters; only counted on request
+    pub total: Option<usize>,
+}
+
+/// Filters on the events of a page, matched case-insensitively
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EventFilter<'a> {
+    /// Execution event type, e.g. `agent_event`
+    pub event_type: Option<&'a str>,
+    /// Agent event type, e.g. `message`
+    pub agent_event_type: Option<&'a str>,
+}
+
+/// Where a page of events starts
+#[derive(Debug, Clone, Copy)]
+enum PageStart {
+    /// Skip this many matching events
+    Offset(usize),
+    /// Skip events up to and including this seq
+    AfterSeq(u64),
+}
+
+/// Event counts of a run's event log, by type
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventHistogram {
+    /// Events in the log
+    pub total: usize,
+    /// Events per type, e.g. `agent_event`
+    pub event_types: BTreeMap<&'static str, usize>,
+    /// Agent events per agent event type, e.g. `tool_call`
+    pub agent_event_types: BTreeMap<&'static str, usize>,
+    /// Size of the event log
+    pub bytes: u64,
+}
+
+impl EventHistogram {
+    /// Count `event`
+    pub fn record(&mut self, event: &ExecutionEvent) {
+        self.total += 1;
+        *self.event_types.entry(event_type_name(event)).or_default() += 1;
+        if let ExecutionEventType::AgentEvent { event } = &event.event {
+            *self.agent_event_types.entry(agent_event_type_name(event)).or_default() += 1;
+        }
+    }
+}
+
+/// Criteria for deleting terminal runs of a task
+#[derive(Debug, Clone, Default)]
+pub struct RunDeleteFilter {
+    /// Only delete runs with this final status
+    pub status: Option<ExecutionStatus>,
+    /// Only delete runs created before this instant
+    pub older_than: Option<DateTime<Utc>>,
+}
+
+impl RunDeleteFilter {
+    /// Whether a run may be deleted under this filter; active runs never match
+    pub fn matches(&self, run: &RunSummary) -> bool {
+        run.status.is_terminal()
+            && self.status.is_none_or(|status| run.status == status)
+            && self.older_than.is_none_or(|cutoff| run.created_at < cutoff)
+    }
+}
+
 /// Run store for persisting runs and events
 #[derive(Debug, Clone)]
 pub struct RunStore {
     /// Base directory for run storage
     base_dir: PathBuf,
+    /// Size threshold after which the event log rolls to a new part
+    max_event_file_bytes: Option<u64>,
+    /// Tool call results above this size are written to a side file
+    max_inline_tool_result_bytes: usize,
+    /// Run id -> task id, loaded on first use and shared between clones
+    run_index: Arc<Mutex<Option<HashMap<Uuid, Uuid>>>>,
+    /// Run id -> `seq` of its last persisted event, shared between clones
+    last_seqs: Arc<Mutex<HashMap<Uuid, u64>>>,
+    /// Time source for log lines and event retention
+    clock: Arc<dyn Clock>,
 }
 
 impl RunStore {
@@ -34,9 +133,32 @@ impl RunStore {
     pub fn new(data_dir: impl AsRef<Path>) -> Self {
         Self {
             base_dir: data_dir.as_ref().join("runs"),
+            max_event_file_bytes: None,
+            max_inline_tool_result_bytes: DEFAULT_MAX_INLINE_TOOL_RESULT_BYTES,
+            run_index: Arc::new(Mutex::new(None)),
+            last_seqs: Arc::new(Mutex::new(HashMap::new())),
+            clock: Arc::new(SystemClock),
         }
     }
 
+    /// Read the time from `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Roll the event log to a new part once it exceeds `bytes`
+    pub fn with_max_event_file_bytes(mut self, bytes: u64) -> Self {
+        self.max_event_file_bytes = Some(bytes);
+        self
+    }
+
+    /// Offload tool call results larger than `bytes` to side files
+    pub fn with_max_inline_tool_result_bytes(mut self, bytes: usize) -> Self {
+        self.max_inline_tool_result_bytes = bytes;
+        self
+    }
+
     /// Get the directory path for a task's runs
     fn task_dir(&self, task_id: Uuid) -> PathBuf {
         self.base_dir.join(task_id.to_string())
@@ -57,11 +179,162 @@ impl RunStore {
         self.run_dir(task_id, run_id).join("events.jsonl")
     }
 
+    /// Get the path to a numbered event log part (part 0 is `events.jsonl`)
+    fn event_part_path(&self, task_id: Uuid, run_id: Uuid, part: usize) -> PathBuf {
+        if part == 0 {
+            self.events_path(task_id, run_id)
+        } else {
+            self.run_dir(task_id, run_id).join(format!("events.{}.jsonl", part))
+        }
+    }
+
+    /// List the existing event log parts in order
+    fn event_part_paths(&self, task_id: Uuid, run_id: Uuid) -> Vec<PathBuf> {
+        let mut parts = Vec::new();
+        let base = self.events_path(task_id, run_id);
+        if base.exists() {
+            parts.push(base);
+        }
+
+        let mut part = 1;
+        loop {
+            let path = self.event_part_path(task_id, run_id, part);
+            if !path.exists() {
+                break;
+            }
+            parts.push(path);
+            part += 1;
+        }
+
+        parts
+    }
+
+    /// Pick the event log part new events should be appended to
+    fn current_event_part(&self, task_id: Uuid, run_id: Uuid) -> PathBuf {
+        let mut part = 0;
+        while self.event_part_path(task_id, run_id, part + 1).exists() {
+            part += 1;
+        }
+
+        let path = self.event_part_path(task_id, run_id, part);
+        let Some(max_bytes) = self.max_event_file_bytes else {
+            return path;
+        };
+
+        match fs::metadata(&path) {
+            Ok(meta) if meta.len() >= max_bytes => {
+                let next = self.event_part_path(task_id, run_id, part + 1);
+                debug!("Rotating event log to {}", next.display());
+                next
+            }
+            _ => path,
+        }
+    }
+
+    /// Read the lines of all event log parts in order
+    fn event_lines(
+        &self,
+        task_id: Uuid,
+        run_id: Uuid,
+    ) -> Result<impl Iterator<Item = std::io::Result<String>>> {
+        let readers = self
+            .event_part_paths(task_id, run_id)
+            .iter()
+            .map(|path| File::open(path).map(BufReader::new))
+            .collect::<std::io::Result<Vec<_>>>()
+            .map_err(ExecutorError::from)?;
+
+        Ok(readers.into_iter().flat_map(|reader| reader.lines()))
+    }
+
     /// Get the path to a run's messages log file
     fn messages_path(&self, task_id: Uuid, run_id: Uuid) -> PathBuf {
         self.run_dir(task_id, run_id).join("messages.jsonl")
     }
 
+    /// Get the path to a run's unredacted event originals
+    fn unredacted_path(&self, task_id: Uuid, run_id: Uuid) -> PathBuf {
+        self.run_dir(task_id, run_id).join("unredacted.jsonl")
+    }
+
+    /// Get the path to a run's human-readable log file
+    fn log_path(&self, task_id: Uuid, run_id: Uuid) -> PathBuf {
+        self.run_dir(task_id, run_id).join("run.log")
+    }
+
+    /// Get the directory holding a run's offloaded tool results
+    fn tool_results_dir(&self, task_id: Uuid, run_id: Uuid) -> PathBuf {
+        self.run_dir(task_id, run_id).join("tool-results")
+    }
+
+    fn index_path(&self) -> PathBuf {
+        self.base_dir.join("index.json")
+    }
+
+    /// Task ids of every run on disk, found by walking the task directories
+    fn scan_run_index(&self) -> HashMap<Uuid, Uuid> {
+        let mut index = HashMap::new();
+        let Ok(tasks) = fs::read_dir(&self.base_dir) else {
+            return index;
+        };
+        for task in tasks.flatten() {
+            let Some(Ok(task_id)) = task.file_name().to_str().map(Uuid::parse_str) else {
+                continue;
+            };
+            let Ok(runs) = fs::read_dir(task.path()) else {
+                continue;
+            };
+            for run in runs.flatten() {
+                if let Some(Ok(run_id)) = run.file_name().to_str().map(Uuid::parse_str) {
+                    if self.run_metadata_path(task_id, run_id).exists() {
+                        index.insert(run_id, task_id);
+                    }
+                }
+            }
+        }
+        index
+    }
+
+    /// Write the index through a temporary file; failures only cost a rescan later
+    fn write_run_index(&self, index: &HashMap<Uuid, Uuid>) {
+        let path = self.index_path();
+        let tmp = path.with_extension("json.tmp");
+        let written = fs::create_dir_all(&self.base_dir)
+            .and_then(|_| fs::write(&tmp, serde_json::to_vec(index)?))
+            .and_then(|_| fs::rename(&tmp, &path));
+        if let Err(e) = written {
+            warn!("Failed to write run index {}: {}", path.display(), e);
+        }
+    }
+
+    /// Rebuild the index from the task directories
+    fn rebuild_run_index(&self, index: &mut HashMap<Uuid, Uuid>) {
+        *index = self.scan_run_index();
+        self.write_run_index(index);
+        debug!("Rebuilt run index with {} runs", index.len());
+    }
+
+    /// Run `f` on the index, loading it from disk or rebuilding it on first use
+    fn with_run_index<R>(&self, f: impl FnOnce(&mut HashMap<Uuid, Uuid>) -> R) -> R {
+        let mut guard = self.run_index.lock().unwrap_or_else(|e| e.into_inner());
+        let index = guard.get_or_insert_with(|| {
+            let loaded = fs::read(self.index_path())
+                .ok()
+                .and_then(|content| serde_json::from_slice(&content).ok());
+            loaded.unwrap_or_else(|| {
+                let mut index = HashMap::new();
+                self.rebuild_run_index(&mut index);
+                index
+            })
+        });
+        f(index)
+    }
+
+    /// Task of an indexed run
+    fn indexed_task(&self, run_id: Uuid) -> Option<Uuid> {
+        self.with_run_index(|index| index.get(&run_id).copied())
+    }
+
     /// Ensure the run directory exists
     fn ensure_run_dir(&self, task_id: Uuid, run_id: Uuid) -> Result<PathBuf> {
         let dir = self.run_dir(task_id, run_id);
@@ -84,6 +357,12 @@ impl RunStore {
         })?;
 
         debug!("Saved run metadata: {}", path.display());
+
+        self.with_run_index(|index| {
+            if index.insert(run.id, run.task_id) != Some(run.task_id) {
+                self.write_run_index(index);
+            }
+        });
         Ok(())
     }
 
@@ -101,15 +380,21 @@ impl RunStore {
         Ok(run)
     }
 
-    /// List all runs for a task
+    /// List all runs for a task, skipping runs that fail to load
     pub fn list_runs(&self, task_id: Uuid) -> Result<Vec<RunSummary>> {
+        self.list_runs_with_errors(task_id).map(|(runs, _)| runs)
+    }
+
+    /// List all runs for a task along with the ids and errors of runs that failed to load
+    pub fn list_runs_with_errors(&self, task_id: Uuid) -> Result<(Vec<RunSummary>, Vec<RunLoadError>)> {
         let task_dir = self.task_dir(task_id);
 
         if !task_dir.exists() {
-            return Ok(Vec::new());
+            return Ok((Vec::new(), Vec::new()));
         }
 
         let mut runs = Vec::new();
+        let mut errors = Vec::new();
 
         let entries = fs::read_dir(&task_dir).map_err(ExecutorError::from)?;
 
@@ -141,21 +426,83 @@ impl RunStore {
                 Ok(run) => runs.push(RunSummary::from(&run)),
                 Err(e) => {
                     warn!("Failed to load run {}: {}", run_id, e);
+                    errors.push((run_id, e.to_string()));
                     continue;
                 }
             }
         }
 
         // Sort by created_at descending (newest first)
-        runs.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        runs.sort_by_key(|r| std::cmp::Reverse(r.created_at));
 
-        Ok(runs)
+        Ok((runs, errors))
+    }
+
+    /// Find a run by ID without knowing its task
+    ///
+    /// Looks the task up in the run index. A stale entry rebuilds the index;
+    /// a run missing from it falls back to scanning every task directory.
+    pub fn find_run(&self, run_id: Uuid) -> Result<Option<Run>> {
+        if let Some(task_id) = self.indexed_task(run_id) {
+            if self.run_metadata_path(task_id, run_id).exists() {
+                return self.load_run(task_id, run_id).map(Some);
+            }
+            warn!("Run index entry for {} is stale, rebuilding", run_id);
+            self.with_run_index(|index| self.rebuild_run_index(index));
+            if let Some(task_id) = self.indexed_task(run_id) {
+                return self.load_run(task_id, run_id).map(Some);
+            }
+            return Ok(None);
+        }
+
+        let found = self.scan_for_run(run_id)?;
+        if let Some(run) = &found {
+            self.with_run_index(|index| {
+                index.insert(run.id, run.task_id);
+                self.write_run_index(index);
+            });
+        }
+        Ok(found)
+    }
+
+    /// Find a run by scanning every task directory
+    fn scan_for_run(&self, run_id: Uuid) -> Result<Option<Run>> {
+        if !self.base_dir.exists() {
+            return Ok(None);
+        }
+
+        let entries = fs::read_dir(&self.base_dir).map_err(ExecutorError::from)?;
+
+        for entry in entries.flatten() {
+            let task_id = match entry.file_name().to_str().map(Uuid::parse_str) {
+                Some(Ok(id)) => id,
+                _ => continue,
+            };
+
+            if self.run_metadata_path(task_id, run_id).exists() {
+                return self.load_run(task_id, run_id).map(Some);
+            }
+        }
+
+        Ok(None)
     }
 
     /// Append an event to a run's event log
     pub fn append_event(&self, task_id: Uuid, run_id: Uuid, event: &ExecutionEvent) -> Result<()> {
+        self.append_events(task_id, run_id, std::slice::from_ref(event))
+    }
+
+    /// Append events to a run's event log with a single write
+    ///
+    /// The log rolls over only between batches, so a part can exceed the
+    /// size limit by up to one batch.
+    pub fn append_events(&self, task_id: Uuid, run_id: Uuid, events: &[ExecutionEvent]) -> Result<()> {
+        if events.is_empty() {
+            return Ok(());
+        }
         self.ensure_run_dir(task_id, run_id)?;
-        let path = self.events_path(task_id, run_id);
+        let first_seq = self.reserve_seqs(task_id, run_id, events.len());
+        let path = self.current_event_part(task_id, run_id);
 
         let file = OpenOptions::new()
             .create(true)
@@ -164,31 +511,44 @@ impl RunStore {
             .map_err(ExecutorError::from)?;
 
         let mut writer = BufWriter::new(file);
-        let json = serde_json::to_string(event).map_err(|e| {
-            ExecutorError::execution_failed(format!("Failed to serialize event: {}", e))
-        })?;
-
-        writeln!(writer, "{}", json).map_err(ExecutorError::from)?;
+        for (seq, event) in (first_seq..).zip(events) {
+            let mut event = self.offload_tool_result(task_id, run_id, event)?.into_owned();
+            event.seq = Some(seq);
+            let json = serde_json::to_string(&event).map_err(|e| {
+                ExecutorError::execution_failed(format!("Failed to serialize event: {}", e))
+            })?;
+            writeln!(writer, "{}", json).map_err(ExecutorError::from)?;
+        }
 
         writer.flush().map_err(ExecutorError::from)?;
 
         Ok(())
     }
 
+    /// Take `count` consecutive seqs for new events of a run, returning the first
+    ///
+    /// The last seq of a run is read from its log once, then kept in memory.
+    fn reserve_seqs(&self, task_id: Uuid, run_id: Uuid, count: usize) -> u64 {
+        let mut last_seqs = self.last_seqs.lock().unwrap_or_else(|e| e.into_inner());
+        let last = match last_seqs.get(&run_id) {
+            Some(last) => *last,
+            None => self
+                .load_events(task_id, run_id)
+                .ok()
+                .and_then(|events| events.last().and_then(|event| event.seq))
+                .unwrap_or(0),
+        };
+        last_seqs.insert(run_id, last + count as u64);
+        last + 1
+    }
+
     /// Load all events for a run
+    ///
+    /// Events written before seqs were assigned get their position as `seq`.
     pub fn load_events(&self, task_id: Uuid, run_id: Uuid) -> Result<Vec<ExecutionEvent>> {
-        let path = self.events_path(task_id, run_id);
-
-        if !path.exists() {
-            return Ok(Vec::new());
-        }
-
-        let file = File::open(&path).map_err(ExecutorError::from)?;
-
-        let reader = BufReader::new(file);
         let mut events = Vec::new();
 
-        for (line_num, line) in reader.lines().enumerate() {
+        for (line_num, line) in self.event_lines(task_id, run_id)?.enumerate() {
             let line = match line {
                 Ok(l) => l,
                 Err(e) => {
@@ -201,14 +561,15 @@ impl RunStore {
                 continue;
             }
 
-            match serde_json::from_str::<ExecutionEvent>(&line) {
-                Ok(event) => events.push(event),
+            match parse_event(&line) {
+                Ok(event) => {
+                    let position = events.len() + 1;
+                    events.push(with_seq(event, position));
+                }
                 Err(e) => {
                     warn!(
-                        "Failed to parse event at line {} in {}: {}",
-                        line_num,
-                        path.display(),
-                        e
+                        "Failed to parse event at line {} in run {}: {}",
+                        line_num, run_id, e
                     );
                     continue;
                 }
@@ -218,6 +579,49 @@ impl RunStore {
         Ok(events)
     }
 
+    /// Keep the original of an event whose stored copy was redacted
+    pub fn append_unredacted_event(&self, task_id: Uuid, run_id: Uuid, event: &ExecutionEvent) -> Result<()> {
+        self.ensure_run_dir(task_id, run_id)?;
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.unredacted_path(task_id, run_id))
+            .map_err(ExecutorError::from)?;
+
+        let json = serde_json::to_string(event).map_err(|e| {
+            ExecutorError::execution_failed(format!("Failed to serialize event: {}", e))
+        })?;
+        let mut writer = BufWriter::new(file);
+        writeln!(writer, "{}", json).map_err(ExecutorError::from)?;
+        writer.flush().map_err(ExecutorError::from)?;
+
+        Ok(())
+    }
+
+    /// Load the originals of a run's redacted events
+    pub fn load_unredacted_events(&self, task_id: Uuid, run_id: Uuid) -> Result<Vec<ExecutionEvent>> {
+        let path = self.unredacted_path(task_id, run_id);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let file = File::open(&path).map_err(ExecutorError::from)?;
+        let events = BufReader::new(file)
+            .lines()
+            .map_while(std::io::Result::ok)
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| match parse_event(&line) {
+                Ok(event) => Some(event),
+                Err(e) => {
+                    warn!("Failed to parse unredacted event in run {}: {}", run_id, e);
+                    None
+                }
+            })
+            .collect();
+
+        Ok(events)
+    }
+
     /// Load events with pagination
     pub fn load_events_paginated(
         &self,
@@ -226,19 +630,10 @@ impl RunStore {
         offset: usize,
         limit: usize,
     ) -> Result<(Vec<ExecutionEvent>, bool)> {
-        let path = self.events_path(task_id, run_id);
-
-        if !path.exists() {
-            return Ok((Vec::new(), false));
-        }
-
-        let file = File::open(&path).map_err(ExecutorError::from)?;
-
-        let reader = BufReader::new(file);
         let mut events = Vec::new();
         let mut total_count = 0;
 
-        for (line_num, line) in reader.lines().enumerate() {
+        for (line_num, line) in self.event_lines(task_id, run_id)?.enumerate() {
             let line = match line {
                 Ok(l) => l,
                 Err(_) => continue,
@@ -260,7 +655,7 @@ impl RunStore {
                 continue; // Keep counting for has_more
             }
 
-            match serde_json::from_str::<ExecutionEvent>(&line) {
+            match parse_event(&line) {
                 Ok(event) => {
                     events.push(event);
                     total_count += 1;
@@ -286,21 +681,79 @@ impl RunStore {
         event_type: Option<&str>,
         agent_event_type: Option<&str>,
     ) -> Result<(Vec<ExecutionEvent>, bool)> {
-        let path = self.events_path(task_id, run_id);
+        let filter = EventFilter {
+            event_type,
+            agent_event_type,
+        };
+        let page = self.load_events_page(task_id, run_id, offset, limit, filter, false)?;
+        Ok((page.events, page.has_more))
+    }
 
-        if !path.exists() {
-            return Ok((Vec::new(), false));
-        }
+    /// Load a filtered page of events, optionally counting all matches
+    ///
+    /// Without `count_total` the scan stops once the page is full and one
+    /// more match is seen; counting reads the whole log in the same pass.
+    pub fn load_events_page(
+        &self,
+        task_id: Uuid,
+        run_id: Uuid,
+        offset: usize,
+        limit: usize,
+        filter: EventFilter<'_>,
+        count_total: bool,
+    ) -> Result<EventPage> {
+        self.scan_events_page(
+            task_id,
+            run_id,
+            PageStart::Offset(offset),
+            limit,
+            filter,
+            count_total,
+        )
+    }
 
-        let file = File::open(&path).map_err(ExecutorError::from)?;
-        let reader = BufReader::new(file);
+    /// Load a filtered page of the events after `after_seq`, optionally
+    /// counting all matches
+    ///
+    /// Pages keyed by seq stay in place when the log is compacted in between.
+    pub fn load_events_after(
+        &self,
+        task_id: Uuid,
+        run_id: Uuid,
+        after_seq: u64,
+        limit: usize,
+        filter: EventFilter<'_>,
+        count_total: bool,
+    ) -> Result<EventPage> {
+        self.scan_events_page(
+            task_id,
+            run_id,
+            PageStart::AfterSeq(after_seq),
+            limit,
+            filter,
+            count_total,
+        )
+    }
+
+    fn scan_events_page(
+        &self,
+        task_id: Uuid,
+        run_id: Uuid,
+        start: PageStart,
+        limit: usize,
+        filter: EventFilter<'_>,
+        count_total: bool,
+    ) -> Result<EventPage> {
         let mut events = Vec::new();
         let mut matched_count = 0;
+        // Matching events before the start of the page
+        let mut skipped = 0;
+        let mut position = 0;
 
-        let event_type = event_type.map(|t| t.to_lowercase());
-        let agent_event_type = agent_event_type.map(|t| t.to_lowercase());
+        let event_type = filter.event_type.map(|t| t.to_lowercase());
+        let agent_event_type = filter.agent_event_type.map(|t| t.to_lowercase());
 
-        for (line_num, line) in reader.lines().enumerate() {
+        for (line_num, line) in self.event_lines(task_id, run_id)?.enumerate() {
             let line = match line {
                 Ok(l) => l,
                 Err(_) => continue,
@@ -310,8 +763,11 @@ impl RunStore {
                 continue;
             }
 
-            let event = match serde_json::from_str::<ExecutionEvent>(&line) {
-                Ok(event) => event,
+            let event = match parse_event(&line) {
+                Ok(event) => {
+                    position += 1;
+                    with_seq(event, position)
+                }
                 Err(e) => {
                     warn!("Failed to parse event at line {}: {}", line_num, e);
                     continue;
@@ -330,7 +786,12 @@ impl RunStore {
                 }
             }
 
-            if matched_count < offset {
+            let before_start = match start {
+                PageStart::Offset(offset) => skipped < offset,
+                PageStart::AfterSeq(after_seq) => event.seq.is_some_and(|seq| seq <= after_seq),
+            };
+            if before_start {
+                skipped += 1;
                 matched_count += 1;
                 continue;
             }
@@ -339,10 +800,123 @@ impl RunStore {
                 events.push(event);
             }
             matched_count += 1;
+
+            if !count_total && matched_count > skipped + limit {
+                break;
+            }
         }
 
-        let has_more = matched_count > offset + events.len();
-        Ok((events, has_more))
+        let has_more = matched_count > skipped + events.len();
+        Ok(EventPage {
+            events,
+            has_more,
+            total: count_total.then_some(matched_count),
+        })
+    }
+
+    /// Drop the events `keep` rejects, merging rotated parts into one log
+    ///
+    /// Kept events hold on to their `seq`. Meant for runs that are no longer
+    /// written to; returns how many events were dropped.
+    pub fn compact_events(
+        &self,
+        task_id: Uuid,
+        run_id: Uuid,
+        keep: &dyn Fn(&ExecutionEvent) -> bool,
+    ) -> Result<usize> {
+        let parts = self.event_part_paths(task_id, run_id);
+        if parts.is_empty() {
+            return Ok(0);
+        }
+
+        let events = self.load_events(task_id, run_id)?;
+        let total = events.len();
+        let compacted = self.run_dir(task_id, run_id).join("events.jsonl.tmp");
+        let mut writer = BufWriter::new(File::create(&compacted).map_err(ExecutorError::from)?);
+        let mut kept = 0;
+        for event in events.iter().filter(|event| keep(event)) {
+            let json = serde_json::to_string(event).map_err(|e| {
+                ExecutorError::execution_failed(format!("Failed to serialize event: {}", e))
+            })?;
+            writeln!(writer, "{}", json).map_err(ExecutorError::from)?;
+            kept += 1;
+        }
+        writer.flush().map_err(ExecutorError::from)?;
+        drop(writer);
+
+        fs::rename(&compacted, self.events_path(task_id, run_id)).map_err(ExecutorError::from)?;
+        for part in parts.iter().skip(1) {
+            fs::remove_file(part).map_err(ExecutorError::from)?;
+        }
+
+        info!("Compacted event log of run {} from {} to {} events", run_id, total, kept);
+        Ok(total - kept)
+    }
+
+    /// Count a run's events by type in one pass over the event log
+    pub fn event_histogram(&self, task_id: Uuid, run_id: Uuid) -> Result<EventHistogram> {
+        let mut histogram = EventHistogram::default();
+
+        for (line_num, line) in self.event_lines(task_id, run_id)?.enumerate() {
+            let line = match line {
+                Ok(l) => l,
+                Err(_) => continue,
+            };
+            histogram.bytes += line.len() as u64 + 1;
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            match parse_event(&line) {
+                Ok(event) => histogram.record(&event),
+                Err(e) => warn!("Failed to parse event at line {}: {}", line_num, e),
+            }
+        }
+
+        Ok(histogram)
+    }
+
+    /// Search a run's events for a case-insensitive substring
+    ///
+    /// Streams the event log line by line and returns each match with its
+    /// offset, so callers can jump to it with the paginated loaders.
+    pub fn search_events(
+        &self,
+        task_id: Uuid,
+        run_id: Uuid,
+        query: &str,
+    ) -> Result<Vec<(usize, ExecutionEvent)>> {
+        let needle = query.to_lowercase();
+        let mut matches = Vec::new();
+        let mut offset = 0;
+
+        for (line_num, line) in self.event_lines(task_id, run_id)?.enumerate() {
+            let line = match line {
+                Ok(l) => l,
+                Err(_) => continue,
+            };
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let current = offset;
+            offset += 1;
+
+            match parse_event(&line) {
+                Ok(event) => {
+                    if event_text(&event).to_lowercase().contains(&needle) {
+                        matches.push((current, event));
+                    }
+                }
+                Err(e) => {
+                    warn!("Failed to parse event at line {}: {}", line_num, e);
+                }
+            }
+        }
+
+        Ok(matches)
     }
 
     /// Delete a run and all its data
@@ -354,6 +928,12 @@ impl RunStore {
             info!("Deleted run: {}", dir.display());
         }
 
+        self.with_run_index(|index| {
+            if index.remove(&run_id).is_some() {
+                self.write_run_index(index);
+            }
+        });
+
         Ok(())
     }
 
@@ -366,22 +946,76 @@ impl RunStore {
             info!("Deleted all runs for task: {}", task_id);
         }
 
+        self.with_run_index(|index| {
+            let before = index.len();
+            index.retain(|_, task| *task != task_id);
+            if index.len() != before {
+                self.write_run_index(index);
+            }
+        });
+
         Ok(())
     }
 
-    /// Get the event count for a run
-    pub fn get_event_count(&self, task_id: Uuid, run_id: Uuid) -> Result<u32> {
-        let path = self.events_path(task_id, run_id);
+    /// Delete the terminal runs of a task matching a filter
+    ///
+    /// Returns the number of runs deleted.
+    pub fn delete_task_runs_matching(&self, task_id: Uuid, filter: &RunDeleteFilter) -> Result<usize> {
+        let mut deleted = 0;
+        for run in self.list_runs(task_id)? {
+            if filter.matches(&run) {
+                self.delete_run(task_id, run.id)?;
+                deleted += 1;
+            }
+        }
+        Ok(deleted)
+    }
 
-        if !path.exists() {
-            return Ok(0);
+    /// Delete the events and messages of terminal runs that ended more than `max_age` ago
+    ///
+    /// `run.json` and the run log are kept and the run is marked
+    /// `events_purged`. Returns the number of runs purged.
+    pub fn purge_events(&self, max_age: Duration) -> Result<usize> {
+        let cutoff = self.clock.now() - chrono::Duration::from_std(max_age).unwrap_or(chrono::Duration::MAX);
+        let mut purged = 0;
+        for (run_id, task_id) in self.scan_run_index() {
+            let mut run = match self.load_run(task_id, run_id) {
+                Ok(run) => run,
+                Err(e) => {
+                    warn!("Skipping run {} during event purge: {}", run_id, e);
+                    continue;
+                }
+            };
+            if run.events_purged || !run.status.is_terminal() || run.ended_at.unwrap_or(run.created_at) >= cutoff {
+                continue;
+            }
+
+            let mut files = self.event_part_paths(task_id, run_id);
+            files.push(self.messages_path(task_id, run_id));
+            files.push(self.unredacted_path(task_id, run_id));
+            for path in files.iter().filter(|path| path.exists()) {
+                fs::remove_file(path).map_err(ExecutorError::from)?;
+            }
+            let tool_results = self.tool_results_dir(task_id, run_id);
+            if tool_results.exists() {
+                fs::remove_dir_all(&tool_results).map_err(ExecutorError::from)?;
+            }
+
+            run.events_purged = true;
+            self.save_run(&run)?;
+            purged += 1;
         }
 
-        let file = File::open(&path).map_err(ExecutorError::from)?;
+        if purged > 0 {
+            info!("Purged events of {} runs older than {:?}", purged, max_age);
+        }
+        Ok(purged)
+    }
 
-        let reader = BufReader::new(file);
-        let count = reader
-            .lines()
+    /// Get the event count for a run
+    pub fn get_event_count(&self, task_id: Uuid, run_id: Uuid) -> Result<u32> {
+        let count = self
+            .event_lines(task_id, run_id)?
             .filter(|l| l.as_ref().map(|s| !s.trim().is_empty()).unwrap_or(false))
             .count();
 
@@ -473,32 +1107,175 @@ impl RunStore {
 
         Ok(count as u32)
     }
-}
 
-fn matches_event_type(event: &ExecutionEvent, filter: &str) -> bool {
-    match (filter, &event.event) {
-        ("status_changed", ExecutionEventType::StatusChanged { .. }) => true,
-        ("agent_event", ExecutionEventType::AgentEvent { .. }) => true,
-        ("session_started", ExecutionEventType::SessionStarted { .. }) => true,
-        ("session_ended", ExecutionEventType::SessionEnded { .. }) => true,
-        ("progress", ExecutionEventType::Progress { .. }) => true,
-        _ => false,
+    // ============ Tool Results ============
+
+    /// Move an oversized tool call result to a side file
+    ///
+    /// The event keeps `{"offloaded": true, "index": N, "bytes": B}` in place
+    /// of the result; `load_tool_result` returns the original payload.
+    fn offload_tool_result<'a>(
+        &self,
+        task_id: Uuid,
+        run_id: Uuid,
+        event: &'a ExecutionEvent,
+    ) -> Result<Cow<'a, ExecutionEvent>> {
+        let ExecutionEventType::AgentEvent {
+            event: AgentEvent::ToolCall {
+                result: Some(result),
+                ..
+            },
+        } = &event.event
+        else {
+            return Ok(Cow::Borrowed(event));
+        };
+
+        let payload = serde_json::to_string(result).map_err(|e| {
+            ExecutorError::execution_failed(format!("Failed to serialize tool result: {}", e))
+        })?;
+        if payload.len() <= self.max_inline_tool_result_bytes {
+            return Ok(Cow::Borrowed(event));
+        }
+
+        let dir = self.tool_results_dir(task_id, run_id);
+        fs::create_dir_all(&dir).map_err(ExecutorError::from)?;
+        let index = fs::read_dir(&dir).map_err(ExecutorError::from)?.count();
+        fs::write(dir.join(format!("{}.json", index)), &payload).map_err(ExecutorError::from)?;
+        debug!(
+            "Offloaded {} byte tool result {} for run {}",
+            payload.len(),
+            index,
+            run_id
+        );
+
+        let mut event = event.clone();
+        if let ExecutionEventType::AgentEvent {
+            event: AgentEvent::ToolCall { result, .. },
+        } = &mut event.event
+        {
+            *result = Some(serde_json::json!({
+                "offloaded": true,
+                "index": index,
+                "bytes": payload.len(),
+            }));
+        }
+        Ok(Cow::Owned(event))
+    }
+
+    /// Load an offloaded tool result as JSON text, or None if there is none
+    pub fn load_tool_result(&self, task_id: Uuid, run_id: Uuid, index: usize) -> Result<Option<String>> {
+        let path = self
+            .tool_results_dir(task_id, run_id)
+            .join(format!("{}.json", index));
+
+        if !path.exists() {
+            return Ok(None);
+        }
+        fs::read_to_string(&path)
+            .map(Some)
+            .map_err(ExecutorError::from)
+    }
+
+    // ============ Run Log ============
+
+    /// Append a timestamped line to a run's human-readable log
+    pub fn append_log_line(&self, task_id: Uuid, run_id: Uuid, line: &str) -> Result<()> {
+        self.ensure_run_dir(task_id, run_id)?;
+        let path = self.log_path(task_id, run_id);
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(ExecutorError::from)?;
+
+        writeln!(file, "{} {}", self.clock.now().to_rfc3339(), line).map_err(ExecutorError::from)?;
+        Ok(())
+    }
+
+    /// Load a run's human-readable log (empty if nothing was logged)
+    pub fn load_log(&self, task_id: Uuid, run_id: Uuid) -> Result<String> {
+        let path = self.log_path(task_id, run_id);
+
+        if !path.exists() {
+            return Ok(String::new());
+        }
+
+        fs::read_to_string(&path).map_err(ExecutorError::from)
     }
 }
 
-fn matches_agent_event_type(event: &ExecutionEvent, filter: &str) -> bool {
+/// Human-readable text of an event, used for searching
+pub(crate) fn event_text(event: &ExecutionEvent) -> String {
     match &event.event {
-        ExecutionEventType::AgentEvent { event } => match (filter, event) {
-            ("thinking", AgentEvent::Thinking { .. }) => true,
-            ("command", AgentEvent::Command { .. }) => true,
-            ("file_change", AgentEvent::FileChange { .. }) => true,
-            ("tool_call", AgentEvent::ToolCall { .. }) => true,
-            ("message", AgentEvent::Message { .. }) => true,
-            ("error", AgentEvent::Error { .. }) => true,
-            ("completed", AgentEvent::Completed { .. }) => true,
-            ("raw_output", AgentEvent::RawOutput { .. }) => true,
-            _ => false,
+        ExecutionEventType::AgentEvent { event } => match event {
+            AgentEvent::Thinking { content }
+            | AgentEvent::Message { content }
+            | AgentEvent::RawOutput { content, .. } => content.clone(),
+            AgentEvent::Command {
+                command, output, ..
+            } => format!("{}\n{}", command, output),
+            AgentEvent::FileChange { path, diff, .. } => {
+                format!("{}\n{}", path, diff.as_deref().unwrap_or_default())
+            }
+            AgentEvent::ToolCall { tool, args, result } => format!(
+                "{} {} {}",
+                tool,
+                args,
+                result.as_ref().map(|r| r.to_string()).unwrap_or_default()
+            ),
+            AgentEvent::Error { message, .. } => message.clone(),
+            AgentEvent::Completed { summary, .. } => summary.clone().unwrap_or_default(),
         },
+        ExecutionEventType::SessionStarted {
+            worktree_path,
+            branch,
+        } => format!("{} {}", worktree_path, branch),
+        ExecutionEventType::Progress { message, .. } => message.clone(),
+        ExecutionEventType::StatusChanged { .. } | ExecutionEventType::SessionEnded { .. } => {
+            String::new()
+        }
+    }
+}
+
+/// Type name of an event, as used by the event type filter
+/// Give an event read from the log without a `seq` its 1-based position
+pub(crate) fn with_seq(mut event: ExecutionEvent, position: usize) -> ExecutionEvent {
+    event.seq.get_or_insert(position as u64);
+    event
+}
+
+pub(crate) fn event_type_name(event: &ExecutionEvent) -> &'static str {
+    match &event.event {
+        ExecutionEventType::StatusChanged { .. } => "status_changed",
+        ExecutionEventType::AgentEvent { .. } => "agent_event",
+        ExecutionEventType::SessionStarted { .. } => "session_started",
+        ExecutionEventType::SessionEnded { .. } => "session_ended",
+        ExecutionEventType::Progress { .. } => "progress",
+    }
+}
+
+/// Type name of an agent event, as used by the agent event type filter
+pub(crate) fn agent_event_type_name(event: &AgentEvent) -> &'static str {
+    match event {
+        AgentEvent::Thinking { .. } => "thinking",
+        AgentEvent::Command { .. } => "command",
+        AgentEvent::FileChange { .. } => "file_change",
+        AgentEvent::ToolCall { .. } => "tool_call",
+        AgentEvent::Message { .. } => "message",
+        AgentEvent::Error { .. } => "error",
+        AgentEvent::Completed { .. } => "completed",
+        AgentEvent::RawOutput { .. } => "raw_output",
+    }
+}
+
+pub(crate) fn matches_event_type(event: &ExecutionEvent, filter: &str) -> bool {
+    event_type_name(event) == filter
+}
+
+pub(crate) fn matches_agent_event_type(event: &ExecutionEvent, filter: &str) -> bool {
+    match &event.event {
+        ExecutionEventType::AgentEvent { event } => agent_event_type_name(event) == filter,
         _ => false,
     }
 }
@@ -508,6 +1285,7 @@ mod tests {
     use super::*;
     use crate::event::AgentEvent;
     use crate::process::AgentType;
+    use crate::run::MessageContentType;
     use tempfile::TempDir;
 
     fn create_test_store() -> (RunStore, TempDir) {
@@ -538,6 +1316,128 @@ mod tests {
         assert_eq!(loaded.prompt, run.prompt);
     }
 
+    #[test]
+    fn custom_agent_type_round_trips_through_save_and_load() {
+        let (store, _temp) = create_test_store();
+        let agent_type = AgentType::from_str("my-agent").unwrap();
+        let run = Run::new(Uuid::new_v4(), agent_type, "Test prompt".to_string(), "main".to_string());
+
+        store.save_run(&run).unwrap();
+        let raw: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(store.run_metadata_path(run.task_id, run.id)).unwrap())
+                .unwrap();
+        assert_eq!(raw["agent_type"], "my-agent");
+
+        let loaded = store.load_run(run.task_id, run.id).unwrap();
+        assert_eq!(loaded.agent_type, AgentType::Custom("my-agent".to_string()));
+        assert_eq!(loaded.agent_type.as_str(), "my-agent");
+
+        // Runs saved before custom agents existed still load
+        let legacy: AgentType = serde_json::from_str(r#""claude_code""#).unwrap();
+        assert_eq!(legacy, AgentType::ClaudeCode);
+    }
+
+    #[test]
+    fn test_find_run_across_tasks() {
+        let (store, _temp) = create_test_store();
+
+        let run = Run::new(
+            Uuid::new_v4(),
+            AgentType::OpenCode,
+            "Test prompt".to_string(),
+            "main".to_string(),
+        );
+        store.save_run(&run).unwrap();
+        store
+            .save_run(&Run::new(
+                Uuid::new_v4(),
+                AgentType::OpenCode,
+                "Other".to_string(),
+                "main".to_string(),
+            ))
+            .unwrap();
+
+        let found = store.find_run(run.id).unwrap().unwrap();
+        assert_eq!(found.task_id, run.task_id);
+        assert!(store.find_run(Uuid::new_v4()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_find_run_uses_index_and_rebuilds_it() {
+        let (store, temp) = create_test_store();
+        let run = Run::new(
+            Uuid::new_v4(),
+            AgentType::OpenCode,
+            "Test prompt".to_string(),
+            "main".to_string(),
+        );
+        store.save_run(&run).unwrap();
+
+        let index_path = temp.path().join("runs").join("index.json");
+        let index: HashMap<Uuid, Uuid> = serde_json::from_slice(&fs::read(&index_path).unwrap()).unwrap();
+        assert_eq!(index.get(&run.id), Some(&run.task_id));
+        assert_eq!(store.find_run(run.id).unwrap().unwrap().task_id, run.task_id);
+
+        // A missing index is rebuilt from the task directories
+        fs::remove_file(&index_path).unwrap();
+        let reopened = RunStore::new(temp.path());
+        assert_eq!(reopened.find_run(run.id).unwrap().unwrap().id, run.id);
+        assert!(index_path.exists());
+
+        // A stale entry is rebuilt and the run still found
+        let moved_task = Uuid::new_v4();
+        fs::create_dir_all(store.task_dir(moved_task)).unwrap();
+        fs::rename(store.run_dir(run.task_id, run.id), store.run_dir(moved_task, run.id)).unwrap();
+        assert_eq!(reopened.find_run(run.id).unwrap().unwrap().id, run.id);
+        assert_eq!(reopened.indexed_task(run.id), Some(moved_task));
+
+        reopened.delete_run(moved_task, run.id).unwrap();
+        assert!(reopened.indexed_task(run.id).is_none());
+        assert!(reopened.find_run(run.id).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_message_content_type_round_trips() {
+        let (store, _temp) = create_test_store();
+        let (task_id, run_id) = (Uuid::new_v4(), Uuid::new_v4());
+
+        store
+            .append_message(task_id, run_id, &ChatMessage::assistant("**done**".to_string()))
+            .unwrap();
+        store
+            .append_message(task_id, run_id, &ChatMessage::system("Failed".to_string()))
+            .unwrap();
+        store
+            .append_message(
+                task_id,
+                run_id,
+                &ChatMessage::assistant("{}".to_string()).with_content_type(MessageContentType::Json),
+            )
+            .unwrap();
+        // Written before messages recorded a content type
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(store.messages_path(task_id, run_id))
+            .unwrap();
+        writeln!(file, r#"{{"id":"legacy","role":"system","content":"old","timestamp":0}}"#).unwrap();
+
+        let content_types: Vec<_> = store
+            .load_messages(task_id, run_id)
+            .unwrap()
+            .iter()
+            .map(|message| message.content_type)
+            .collect();
+        assert_eq!(
+            content_types,
+            vec![
+                MessageContentType::Markdown,
+                MessageContentType::Plain,
+                MessageContentType::Json,
+                MessageContentType::Markdown,
+            ]
+        );
+    }
+
     #[test]
     fn test_list_runs() {
         let (store, _temp) = create_test_store();
@@ -600,6 +1500,47 @@ mod tests {
         assert_eq!(events.len(), 5);
     }
 
+    #[test]
+    fn unversioned_events_are_migrated_and_newer_ones_are_skipped() {
+        let (store, _temp) = create_test_store();
+        let (task_id, run_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let current = ExecutionEvent::progress(run_id, task_id, "Current".to_string(), None);
+        store.append_event(task_id, run_id, &current).unwrap();
+
+        // Written before events carried a schema version
+        let legacy_id = Uuid::new_v4();
+        let legacy = serde_json::json!({
+            "id": legacy_id,
+            "session_id": run_id,
+            "task_id": task_id,
+            "timestamp": "2024-01-01T00:00:00Z",
+            "event_type": "agent_event",
+            "type": "message",
+            "content": "unversioned",
+        });
+        // Written by a newer server
+        let mut newer = serde_json::to_value(&current).unwrap();
+        newer["id"] = serde_json::json!(Uuid::new_v4());
+        newer["schema_version"] = serde_json::json!(crate::event::EVENT_SCHEMA_VERSION + 1);
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(store.events_path(task_id, run_id))
+            .unwrap();
+        writeln!(file, "{}", legacy).unwrap();
+        writeln!(file, "{}", newer).unwrap();
+
+        let events = store.load_events(task_id, run_id).unwrap();
+        assert_eq!(events.len(), 2);
+        let loaded = &events[1];
+        assert_eq!(loaded.id, legacy_id);
+        assert_eq!(loaded.schema_version, 1);
+        assert_eq!(loaded.seq, Some(2));
+        assert!(matches!(
+            &loaded.event,
+            ExecutionEventType::AgentEvent { event: AgentEvent::Message { content } } if content == "unversioned"
+        ));
+    }
+
     #[test]
     fn test_load_events_paginated() {
         let (store, _temp) = create_test_store();
@@ -670,6 +1611,65 @@ mod tests {
         assert!(!has_more);
     }
 
+    #[test]
+    fn test_load_events_page_counts_total_on_request() {
+        let (store, _temp) = create_test_store();
+        let task_id = Uuid::new_v4();
+        let run_id = Uuid::new_v4();
+
+        for i in 0..7 {
+            let event = ExecutionEvent::progress(run_id, task_id, format!("Step {}", i), None);
+            store.append_event(task_id, run_id, &event).unwrap();
+        }
+
+        let page = store
+            .load_events_page(task_id, run_id, 2, 3, EventFilter::default(), true)
+            .unwrap();
+        assert_eq!(page.events.len(), 3);
+        assert!(page.has_more);
+        assert_eq!(page.total, Some(7));
+
+        let page = store
+            .load_events_page(task_id, run_id, 2, 3, EventFilter::default(), false)
+            .unwrap();
+        assert!(page.has_more);
+        assert_eq!(page.total, None);
+    }
+
+    #[test]
+    fn test_search_events_returns_matching_offsets() {
+        let (store, _temp) = create_test_store();
+        let task_id = Uuid::new_v4();
+        let run_id = Uuid::new_v4();
+
+        let events = [
+            AgentEvent::Message {
+                content: "Starting work".to_string(),
+            },
+            AgentEvent::Error {
+                message: "Connection REFUSED by host".to_string(),
+                recoverable: true,
+            },
+            AgentEvent::Thinking {
+                content: "Retrying after refused connection".to_string(),
+            },
+            AgentEvent::Message {
+                content: "All done".to_string(),
+            },
+        ];
+        for event in events {
+            store
+                .append_event(task_id, run_id, &ExecutionEvent::agent_event(run_id, task_id, event))
+                .unwrap();
+        }
+
+        let matches = store.search_events(task_id, run_id, "refused").unwrap();
+        let offsets: Vec<usize> = matches.iter().map(|(offset, _)| *offset).collect();
+        assert_eq!(offsets, vec![1, 2]);
+
+        assert!(store.search_events(task_id, run_id, "nothing like this").unwrap().is_empty());
+    }
+
     #[test]
     fn test_get_event_count() {
         let (store, _temp) = create_test_store();
@@ -688,6 +1688,40 @@ mod tests {
         assert_eq!(store.get_event_count(task_id, run_id).unwrap(), 7);
     }
 
+    #[test]
+    fn test_event_log_rotation() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = RunStore::new(temp_dir.path()).with_max_event_file_bytes(200);
+        let task_id = Uuid::new_v4();
+        let run_id = Uuid::new_v4();
+
+        for i in 0..10 {
+            let event = ExecutionEvent::progress(run_id, task_id, format!("Step {}", i), None);
+            store.append_event(task_id, run_id, &event).unwrap();
+        }
+
+        let run_dir = store.run_dir(task_id, run_id);
+        assert!(run_dir.join("events.1.jsonl").exists());
+        assert!(store.event_part_paths(task_id, run_id).len() > 2);
+
+        let messages: Vec<String> = store
+            .load_events(task_id, run_id)
+            .unwrap()
+            .into_iter()
+            .map(|event| match event.event {
+                ExecutionEventType::Progress { message, .. } => message,
+                other => panic!("unexpected event: {:?}", other),
+            })
+            .collect();
+        let expected: Vec<String> = (0..10).map(|i| format!("Step {}", i)).collect();
+        assert_eq!(messages, expected);
+
+        assert_eq!(store.get_event_count(task_id, run_id).unwrap(), 10);
+        let (page, has_more) = store.load_events_paginated(task_id, run_id, 4, 3).unwrap();
+        assert_eq!(page.len(), 3);
+        assert!(has_more);
+    }
+
     #[test]
     fn test_delete_run() {
         let (store, _temp) = create_test_store();
@@ -734,4 +1768,43 @@ mod tests {
 
         assert!(store.list_runs(task_id).unwrap().is_empty());
     }
+
+    #[test]
+    fn test_delete_task_runs_matching_status() {
+        let (store, _temp) = create_test_store();
+        let task_id = Uuid::new_v4();
+
+        for status in [
+            ExecutionStatus::Failed,
+            ExecutionStatus::Failed,
+            ExecutionStatus::Completed,
+            ExecutionStatus::Running,
+        ] {
+            let mut run = Run::new(
+                task_id,
+                AgentType::OpenCode,
+                "Test".to_string(),
+                "main".to_string(),
+            );
+            run.update_status(status);
+            store.save_run(&run).unwrap();
+        }
+
+        let filter = RunDeleteFilter {
+            status: Some(ExecutionStatus::Failed),
+            ..Default::default()
+        };
+        assert_eq!(store.delete_task_runs_matching(task_id, &filter).unwrap(), 2);
+
+        let remaining: Vec<_> = store.list_runs(task_id).unwrap().iter().map(|r| r.status).collect();
+        assert_eq!(remaining.len(), 2);
+        assert!(!remaining.contains(&ExecutionStatus::Failed));
+
+        // An unrestricted filter still leaves the active run alone
+        assert_eq!(
+            store.delete_task_runs_matching(task_id, &RunDeleteFilter::default()).unwrap(),
+            1
+        );
+        assert_eq!(store.list_runs(task_id).unwrap()[0].status, ExecutionStatus::Running);
+    }
 }
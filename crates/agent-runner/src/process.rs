@@ -1,7 +1,8 @@
 //! Agent process management
 
 use std::process::Stdio;
-use tokio::io::{AsyncBufReadExt, BufReader};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt};
 use tokio::process::{Child, Command};
 use tokio::sync::mpsc;
 use tracing::{debug, info, warn};
@@ -10,44 +11,87 @@ use serde::{Deserialize, Serialize};
 
 use crate::error::{ExecutorError, Result};
 use crate::event::{AgentEvent, OutputStream};
-use crate::parser::create_parser;
+use crate::parser::{create_parser, OutputParser};
 
 /// Supported agent types
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
+///
+/// Known agents serialize as their snake_case variant name, custom agents
+/// as the name they were given.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum AgentType {
     OpenCode,
     ClaudeCode,
     GeminiCli,
     Codex,
+    /// An agent the runner has no built-in support for, by its original name
+    Custom(String),
+}
+
+impl Serialize for AgentType {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(match self {
+            Self::OpenCode => "open_code",
+            Self::ClaudeCode => "claude_code",
+            Self::GeminiCli => "gemini_cli",
+            Self::Codex => "codex",
+            Self::Custom(name) => name,
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for AgentType {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let raw = String::deserialize(deserializer)?;
+        Self::from_str(&raw).map_err(serde::de::Error::custom)
+    }
 }
 
 impl AgentType {
+    /// Every agent type the runner knows how to launch
+    pub const ALL: [AgentType; 4] = [Self::OpenCode, Self::ClaudeCode, Self::GeminiCli, Self::Codex];
+
     /// Parse agent type from string
+    ///
+    /// Unknown names become `Custom`, keeping the name as given. Names must
+    /// be non-empty and made of letters, digits, `-`, `_` and `.`.
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Result<Self> {
         match s.to_lowercase().as_str() {
-            "opencode" => Ok(Self::OpenCode),
-            "claude-code" | "claudecode" => Ok(Self::ClaudeCode),
-            "gemini-cli" | "geminicli" | "gemini" => Ok(Self::GeminiCli),
+            "opencode" | "open_code" => Ok(Self::OpenCode),
+            "claude-code" | "claude_code" | "claudecode" => Ok(Self::ClaudeCode),
+            "gemini-cli" | "gemini_cli" | "geminicli" | "gemini" => Ok(Self::GeminiCli),
             "codex" => Ok(Self::Codex),
+            _ if is_custom_agent_name(s) => Ok(Self::Custom(s.to_string())),
             _ => Err(ExecutorError::InvalidAgentType {
                 agent_type: s.to_string(),
             }),
         }
     }
 
+    /// Parse agent type from string, rejecting types missing from `allowed`
+    pub fn from_str_allowed(s: &str, allowed: &[AgentType]) -> Result<Self> {
+        let agent_type = Self::from_str(s)?;
+        if !allowed.contains(&agent_type) {
+            return Err(ExecutorError::AgentNotAllowed {
+                agent_type: s.to_string(),
+            });
+        }
+        Ok(agent_type)
+    }
+
     /// Get the canonical string representation
-    pub fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &str {
         match self {
             Self::OpenCode => "opencode",
             Self::ClaudeCode => "claude-code",
             Self::GeminiCli => "gemini-cli",
             Self::Codex => "codex",
+            Self::Custom(name) => name,
         }
     }
 
-    /// Get the command to run this agent
-    pub fn command(&self) -> &'static str {
+    /// Get the command to run this agent; a custom agent runs its name
+    pub fn command(&self) -> &str {
         match self {
             Self::OpenCode => {
                 if cfg!(target_os = "windows") {
@@ -65,9 +109,20 @@ impl AgentType {
             },
             Self::GeminiCli => "gemini",
             Self::Codex => "codex",
+            Self::Custom(name) => name,
         }
     }
 
+    /// Whether the agent accepts follow-up input while running
+    pub fn supports_input(&self) -> bool {
+        matches!(self, Self::OpenCode | Self::ClaudeCode)
+    }
+
+    /// Whether the agent can be paused and resumed
+    pub fn supports_pause(&self) -> bool {
+        matches!(self, Self::OpenCode)
+    }
+
     /// Get default arguments for the agent
     pub fn default_args(&self) -> Vec<&'static str> {
         match self {
@@ -75,10 +130,19 @@ impl AgentType {
             Self::ClaudeCode => vec!["--yes"],
             Self::GeminiCli => vec![],
             Self::Codex => vec!["--yes"],
+            Self::Custom(_) => vec![],
         }
     }
 }
 
+/// Whether `name` can be used as a custom agent type
+fn is_custom_agent_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
 /// Configuration for an agent process
 #[derive(Debug, Clone)]
 pub struct AgentConfig {
@@ -92,14 +156,55 @@ pub struct AgentConfig {
     pub env: Vec<(String, String)>,
     /// Timeout in seconds (0 = no timeout)
     pub timeout_seconds: u64,
+    /// CPU and memory limits for the process
+    pub resource_limits: ResourceLimits,
+    /// Model the agent runs with; selects a model-specific output parser
+    pub model: Option<String>,
+    /// Arguments appended after the agent's default arguments
+    pub extra_args: Vec<String>,
 }
 
+/// Characters a shell would interpret, rejected in configured agent arguments
+const SHELL_METACHARACTERS: &[char] = &[
+    '|', '&', ';', '<', '>', '(', ')', '$', '`', '\\', '"', '\'', '*', '?', '\n', '\r',
+];
+
+/// Check configured agent arguments for shell metacharacters
+///
+/// Arguments are passed to the agent as-is, not through a shell, so quoting,
+/// globs or pipes would reach it literally; `.cmd` agents on Windows do run
+/// through `cmd /C`, where they would be interpreted.
+pub fn validate_agent_args(args: &[String]) -> Result<()> {
+    match args.iter().find(|arg| arg.contains(SHELL_METACHARACTERS)) {
+        Some(arg) => Err(ExecutorError::InvalidAgentArg { arg: arg.clone() }),
+        None => Ok(()),
+    }
+}
+
+/// Resource limits for an agent process
+///
+/// Unsupported limits are ignored on platforms that lack them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResourceLimits {
+    /// Scheduling priority adjustment (Unix only)
+    pub nice: Option<i32>,
+    /// Resident memory cap in bytes; the process is killed past it (Linux only)
+    pub max_memory_bytes: Option<u64>,
+}
+
+/// How often the memory cap is checked
+const MEMORY_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
 /// Represents a running agent process
 pub struct AgentProcess {
     /// The child process
     child: Child,
     /// Agent type
     agent_type: AgentType,
+    /// Limits enforced while the process runs
+    limits: ResourceLimits,
+    /// Model the agent runs with, if known
+    model: Option<String>,
     /// Event sender
     event_tx: mpsc::Sender<AgentEvent>,
 }
@@ -110,53 +215,88 @@ impl AgentProcess {
         config: AgentConfig,
         event_tx: mpsc::Sender<AgentEvent>,
     ) -> Result<Self> {
-        let command = config.agent_type.command();
-        let args = config.agent_type.default_args();
-
         info!(
             "Spawning {} in {:?} with prompt: {}",
-            command,
+            config.agent_type.command(),
             config.working_dir,
             config.prompt.chars().take(100).collect::<String>()
         );
 
-        // Build the command
+        let cmd = Self::build_command(&config)?;
+        let mut process = Self::spawn_command(cmd, config.agent_type, config.resource_limits, event_tx)?;
+        process.model = config.model;
+        Ok(process)
+    }
+
+    /// Command line for an agent: default args, extra args, then the prompt
+    fn build_command(config: &AgentConfig) -> Result<Command> {
+        validate_agent_args(&config.extra_args)?;
+
+        let command = config.agent_type.command();
+        let args = config.agent_type.default_args();
+
         let mut cmd = if cfg!(target_os = "windows") && command.ends_with(".cmd") {
             let mut c = Command::new("cmd");
             c.arg("/C").arg(command);
             for arg in &args {
                 c.arg(arg);
             }
-            c.arg(&config.prompt);
             c
         } else {
             let mut c = Command::new(command);
-            c.args(&args).arg(&config.prompt);
+            c.args(&args);
             c
         };
+        cmd.args(&config.extra_args).arg(&config.prompt);
 
-        cmd.current_dir(&config.working_dir)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .kill_on_drop(true);
+        cmd.current_dir(&config.working_dir);
 
         // Add environment variables
         for (key, value) in &config.env {
             cmd.env(key, value);
         }
 
-        // Spawn the process
+        Ok(cmd)
+    }
+
+    /// Spawn a prepared command with piped output and the given limits
+    fn spawn_command(
+        mut cmd: Command,
+        agent_type: AgentType,
+        limits: ResourceLimits,
+        event_tx: mpsc::Sender<AgentEvent>,
+    ) -> Result<Self> {
+        cmd.stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+
+        #[cfg(unix)]
+        if let Some(nice) = limits.nice {
+            // SAFETY: setpriority is async-signal-safe and touches no shared state
+            unsafe {
+                cmd.pre_exec(move || {
+                    if libc::setpriority(libc::PRIO_PROCESS, 0, nice) == -1 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                    Ok(())
+                });
+            }
+        }
+
+        let program = cmd.as_std().get_program().to_string_lossy().into_owned();
         let child = cmd.spawn().map_err(|e| {
             ExecutorError::spawn_failed_with_source(
-                format!("Failed to spawn {}: {}", command, e),
+                format!("Failed to spawn {}: {}", program, e),
                 e,
             )
         })?;
 
         Ok(Self {
             child,
-            agent_type: config.agent_type,
+            agent_type,
+            limits,
+            model: None,
             event_tx,
         })
     }
@@ -176,55 +316,104 @@ impl AgentProcess {
             .ok_or_else(|| ExecutorError::spawn_failed("Failed to capture stderr"))?;
 
         let event_tx = self.event_tx.clone();
-        let agent_type = self.agent_type;
+        let agent_type = &self.agent_type;
+        let model = self.model.as_deref();
 
         // Spawn stdout reader
-        let stdout_tx = event_tx.clone();
-        let stdout_handle = tokio::spawn(async move {
-            let reader = BufReader::new(stdout);
-            let mut lines = reader.lines();
-            let mut parser = create_parser(agent_type);
+        let stdout_handle = tokio::spawn(pump_output(
+            stdout,
+            OutputStream::Stdout,
+            create_parser(agent_type, model),
+            event_tx.clone(),
+        ));
+
+        // Spawn stderr reader; the parser handles raw output fallback
+        let stderr_handle = tokio::spawn(pump_output(
+            stderr,
+            OutputStream::Stderr,
+            create_parser(agent_type, model),
+            event_tx.clone(),
+        ));
 
-            while let Ok(Some(line)) = lines.next_line().await {
-                debug!("stdout: {}", line);
+        Ok(OutputReaderHandle {
+            child: self.child,
+            stdout_handle,
+            stderr_handle,
+            limits: self.limits,
+            event_tx,
+        })
+    }
+}
 
-                // Parse the line into an event
-                let event = parser.parse(&line, OutputStream::Stdout);
+/// Size of each read from the agent's output pipes
+const READ_CHUNK_SIZE: usize = 8 * 1024;
 
-                if stdout_tx.send(event).await.is_err() {
-                    warn!("Event channel closed, stopping stdout reader");
-                    break;
-                }
-            }
-        });
+/// Accumulates raw output bytes and yields complete newline-delimited lines
+#[derive(Debug, Default)]
+pub struct LineBuffer {
+    pending: Vec<u8>,
+}
 
-        // Spawn stderr reader
-        let stderr_tx = event_tx.clone();
-        let stderr_handle = tokio::spawn(async move {
-            let reader = BufReader::new(stderr);
-            let mut lines = reader.lines();
-            // Use same parser logic for stderr, or maybe a specific one?
-            // For now, use the same parser as it handles raw output fallback
-            let mut parser = create_parser(agent_type);
+impl LineBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
 
-            while let Ok(Some(line)) = lines.next_line().await {
-                debug!("stderr: {}", line);
+    /// Append a chunk and return every line it completed
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.pending.extend_from_slice(chunk);
 
-                let event = parser.parse(&line, OutputStream::Stderr);
+        let mut lines = Vec::new();
+        while let Some(pos) = self.pending.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=pos).collect();
+            lines.push(Self::decode(&line[..pos]));
+        }
+        lines
+    }
 
-                if stderr_tx.send(event).await.is_err() {
-                    warn!("Event channel closed, stopping stderr reader");
-                    break;
-                }
+    /// Take the trailing partial line, if any
+    pub fn finish(&mut self) -> Option<String> {
+        if self.pending.is_empty() {
+            return None;
+        }
+        let rest = std::mem::take(&mut self.pending);
+        Some(Self::decode(&rest))
+    }
+
+    fn decode(bytes: &[u8]) -> String {
+        let bytes = bytes.strip_suffix(b"\r").unwrap_or(bytes);
+        String::from_utf8_lossy(bytes).into_owned()
+    }
+}
+
+/// Read an output pipe, parsing only complete lines and flushing the remainder at EOF
+async fn pump_output<R: AsyncRead + Unpin>(
+    mut reader: R,
+    stream: OutputStream,
+    mut parser: Box<dyn OutputParser>,
+    tx: mpsc::Sender<AgentEvent>,
+) {
+    let mut buffer = LineBuffer::new();
+    let mut chunk = vec![0u8; READ_CHUNK_SIZE];
+
+    loop {
+        let lines = match reader.read(&mut chunk).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => buffer.push(&chunk[..n]),
+        };
+
+        for line in lines {
+            debug!("{:?}: {}", stream, line);
+            if tx.send(parser.parse(&line, stream)).await.is_err() {
+                warn!("Event channel closed, stopping {:?} reader", stream);
+                return;
             }
-        });
+        }
+    }
 
-        Ok(OutputReaderHandle {
-            child: self.child,
-            stdout_handle,
-            stderr_handle,
-            agent_type: self.agent_type,
-        })
+    if let Some(line) = buffer.finish() {
+        debug!("{:?}: {}", stream, line);
+        let _ = tx.send(parser.parse(&line, stream)).await;
     }
 }
 
@@ -233,14 +422,30 @@ pub struct OutputReaderHandle {
     child: Child,
     stdout_handle: tokio::task::JoinHandle<()>,
     stderr_handle: tokio::task::JoinHandle<()>,
-    #[allow(dead_code)]
-    agent_type: AgentType,
+    limits: ResourceLimits,
+    event_tx: mpsc::Sender<AgentEvent>,
 }
 
 impl OutputReaderHandle {
     /// Wait for the process to complete
+    ///
+    /// A process killed for exceeding its memory cap reports a failed
+    /// `Completed` event and returns `ResourceExceeded`.
     pub async fn wait(mut self) -> Result<i32> {
-        let status = self.child.wait().await?;
+        let memory_cap = self.limits.max_memory_bytes.filter(|_| cfg!(target_os = "linux"));
+        let status = match (memory_cap, self.child.id()) {
+            (Some(cap), Some(pid)) => {
+                let outcome = tokio::select! {
+                    status = self.child.wait() => Ok(status?),
+                    rss = watch_memory(pid, cap) => Err(rss),
+                };
+                match outcome {
+                    Ok(status) => status,
+                    Err(rss) => return Err(self.kill_for_memory(rss, cap).await),
+                }
+            }
+            _ => self.child.wait().await?,
+        };
 
         // Wait for readers to finish
         let _ = self.stdout_handle.await;
@@ -261,11 +466,60 @@ impl OutputReaderHandle {
     pub fn pid(&self) -> Option<u32> {
         self.child.id()
     }
+
+    /// Kill a process over its memory cap and report the run as failed
+    async fn kill_for_memory(mut self, rss: u64, cap: u64) -> ExecutorError {
+        let message = format!("memory usage {} bytes exceeded cap of {} bytes", rss, cap);
+        warn!("Killing agent process: {}", message);
+        if let Err(e) = self.child.kill().await {
+            warn!("Failed to kill agent process: {}", e);
+        }
+        self.stdout_handle.abort();
+        self.stderr_handle.abort();
+
+        let _ = self
+            .event_tx
+            .send(AgentEvent::Completed {
+                success: false,
+                summary: Some(format!("Resource limit exceeded: {}", message)),
+            })
+            .await;
+        ExecutorError::ResourceExceeded { message }
+    }
+}
+
+/// Poll a process's resident memory until it exceeds `cap`, returning the usage
+///
+/// Never resolves if the usage can't be read (e.g. the process already exited).
+async fn watch_memory(pid: u32, cap: u64) -> u64 {
+    loop {
+        if let Some(rss) = resident_memory_bytes(pid) {
+            if rss > cap {
+                return rss;
+            }
+        }
+        tokio::time::sleep(MEMORY_POLL_INTERVAL).await;
+    }
+}
+
+/// Resident memory of a process, read from `/proc/<pid>/status`
+fn resident_memory_bytes(pid: u32) -> Option<u64> {
+    let status = std::fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+    let kib = status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))?
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+    Some(kib * 1024)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use tokio::io::AsyncWriteExt;
 
     #[test]
     fn test_agent_type_from_str() {
@@ -273,7 +527,122 @@ mod tests {
         assert_eq!(AgentType::from_str("claude-code").unwrap(), AgentType::ClaudeCode);
         assert_eq!(AgentType::from_str("gemini-cli").unwrap(), AgentType::GeminiCli);
         assert_eq!(AgentType::from_str("codex").unwrap(), AgentType::Codex);
-        assert!(AgentType::from_str("unknown").is_err());
+        assert_eq!(AgentType::from_str("my-agent").unwrap(), AgentType::Custom("my-agent".to_string()));
+        assert_eq!(AgentType::from_str("my-agent").unwrap().as_str(), "my-agent");
+        assert!(AgentType::from_str("").is_err());
+        assert!(AgentType::from_str("rm -rf /").is_err());
+    }
+
+    #[test]
+    fn test_agent_type_from_str_allowed() {
+        let allowed = [AgentType::ClaudeCode];
+        assert_eq!(
+            AgentType::from_str_allowed("claude-code", &allowed).unwrap(),
+            AgentType::ClaudeCode
+        );
+        assert!(matches!(
+            AgentType::from_str_allowed("opencode", &allowed),
+            Err(ExecutorError::AgentNotAllowed { .. })
+        ));
+        assert!(matches!(
+            AgentType::from_str_allowed("my-agent", &allowed),
+            Err(ExecutorError::AgentNotAllowed { .. })
+        ));
+        assert!(matches!(
+            AgentType::from_str_allowed("not an agent", &allowed),
+            Err(ExecutorError::InvalidAgentType { .. })
+        ));
+    }
+
+    #[test]
+    fn extra_args_reach_the_command_line_before_the_prompt() {
+        let mut config = AgentConfig {
+            agent_type: AgentType::ClaudeCode,
+            working_dir: std::env::temp_dir(),
+            prompt: "fix the bug".to_string(),
+            env: Vec::new(),
+            timeout_seconds: 0,
+            resource_limits: ResourceLimits::default(),
+            model: None,
+            extra_args: vec!["--no-telemetry".to_string(), "--max-turns=5".to_string()],
+        };
+
+        let cmd = AgentProcess::build_command(&config).unwrap();
+        let args: Vec<_> = cmd.as_std().get_args().map(|arg| arg.to_string_lossy().into_owned()).collect();
+        let tail = &args[args.len() - 3..];
+        assert_eq!(tail, ["--no-telemetry", "--max-turns=5", "fix the bug"]);
+
+        config.extra_args.push("--out=$(whoami)".to_string());
+        assert!(matches!(
+            AgentProcess::build_command(&config),
+            Err(ExecutorError::InvalidAgentArg { arg }) if arg == "--out=$(whoami)"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_json_split_across_chunks_yields_one_event() {
+        let (mut writer, reader) = tokio::io::duplex(64);
+        let (tx, mut rx) = mpsc::channel(8);
+        let pump = tokio::spawn(pump_output(
+            reader,
+            OutputStream::Stdout,
+            create_parser(&AgentType::OpenCode, None),
+            tx,
+        ));
+
+        writer.write_all(br#"{"type":"message","#).await.unwrap();
+        writer.flush().await.unwrap();
+        tokio::task::yield_now().await;
+        writer.write_all(b"\"content\":\"Hello world\"}\n").await.unwrap();
+        drop(writer);
+        pump.await.unwrap();
+
+        let event = rx.recv().await.unwrap();
+        assert!(
+            matches!(&event, AgentEvent::Message { content } if content == "Hello world"),
+            "unexpected event: {:?}",
+            event
+        );
+        assert!(rx.recv().await.is_none());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn memory_hungry_process_is_killed_over_cap() {
+        // `tail` buffers /dev/zero looking for a newline that never comes
+        let mut cmd = Command::new("tail");
+        cmd.arg("/dev/zero");
+        let limits = ResourceLimits {
+            nice: Some(5),
+            max_memory_bytes: Some(64 * 1024 * 1024),
+        };
+        let (tx, mut rx) = mpsc::channel(64);
+        let process = AgentProcess::spawn_command(cmd, AgentType::OpenCode, limits, tx).unwrap();
+        let handle = process.start_output_reader().await.unwrap();
+
+        let result = tokio::time::timeout(Duration::from_secs(30), handle.wait())
+            .await
+            .expect("memory cap should stop the process");
+        assert!(matches!(result, Err(ExecutorError::ResourceExceeded { .. })));
+
+        let mut failed = false;
+        while let Some(event) = rx.recv().await {
+            if let AgentEvent::Completed { success, summary } = event {
+                assert!(!success);
+                assert!(summary.unwrap().starts_with("Resource limit exceeded"));
+                failed = true;
+            }
+        }
+        assert!(failed, "expected a failed completion event");
+    }
+
+    #[test]
+    fn test_line_buffer_flushes_trailing_partial() {
+        let mut buffer = LineBuffer::new();
+        assert_eq!(buffer.push(b"first\r\nsec"), vec!["first".to_string()]);
+        assert!(buffer.push(b"ond").is_empty());
+        assert_eq!(buffer.finish().as_deref(), Some("second"));
+        assert!(buffer.finish().is_none());
     }
 }
 
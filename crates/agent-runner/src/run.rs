@@ -6,6 +6,7 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
 use uuid::Uuid;
 
@@ -21,6 +22,27 @@ pub enum MessageRole {
     System,
 }
 
+/// How a message's content should be rendered
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageContentType {
+    /// Messages stored before the content type was recorded are markdown
+    #[default]
+    Markdown,
+    Plain,
+    Json,
+}
+
+impl MessageContentType {
+    /// Content type of a message from `role` unless set explicitly
+    pub fn for_role(role: MessageRole) -> Self {
+        match role {
+            MessageRole::Assistant => Self::Markdown,
+            MessageRole::User | MessageRole::System => Self::Plain,
+        }
+    }
+}
+
 /// A persisted chat message
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatMessage {
@@ -33,6 +55,10 @@ pub struct ChatMessage {
     /// Message content
     pub content: String,
 
+    /// How the content should be rendered
+    #[serde(default)]
+    pub content_type: MessageContentType,
+
     /// Timestamp in milliseconds
     pub timestamp: i64,
 
@@ -71,6 +97,7 @@ impl ChatMessage {
             id: Uuid::new_v4().to_string(),
             role: MessageRole::User,
             content,
+            content_type: MessageContentType::for_role(MessageRole::User),
             timestamp: Utc::now().timestamp_millis(),
             message_type: Some("text".to_string()),
             tool_call: None,
@@ -84,6 +111,7 @@ impl ChatMessage {
             id: Uuid::new_v4().to_string(),
             role: MessageRole::Assistant,
             content,
+            content_type: MessageContentType::for_role(MessageRole::Assistant),
             timestamp: Utc::now().timestamp_millis(),
             message_type: Some("text".to_string()),
             tool_call: None,
@@ -97,6 +125,7 @@ impl ChatMessage {
             id: Uuid::new_v4().to_string(),
             role: MessageRole::System,
             content,
+            content_type: MessageContentType::for_role(MessageRole::System),
             timestamp: Utc::now().timestamp_millis(),
             message_type: Some("system".to_string()),
             tool_call: None,
@@ -110,12 +139,19 @@ impl ChatMessage {
             id,
             role,
             content,
+            content_type: MessageContentType::for_role(role),
             timestamp: Utc::now().timestamp_millis(),
             message_type: Some("text".to_string()),
             tool_call: None,
             tool_result: None,
         }
     }
+
+    /// Override the content type derived from the role
+    pub fn with_content_type(mut self, content_type: MessageContentType) -> Self {
+        self.content_type = content_type;
+        self
+    }
 }
 
 /// A persistent execution record
@@ -172,6 +208,14 @@ pub struct Run {
     /// Number of events in the log
     pub event_count: u32,
 
+    /// Distinct files changed, counted when the run ended (`None` for older runs)
+    #[serde(default)]
+    pub changed_files: Option<usize>,
+
+    /// Events and messages were deleted by retention; the metadata remains
+    #[serde(default)]
+    pub events_purged: bool,
+
     /// Run metadata
     pub metadata: RunMetadata,
 }
@@ -206,6 +250,30 @@ pub struct RunMetadata {
     /// Custom tags
     #[serde(default)]
     pub tags: Vec<String>,
+
+    /// Role the agent played in this run (e.g. "planner", "reviewer")
+    #[serde(default)]
+    pub agent_role: Option<String>,
+
+    /// Shared ID linking all runs of a handoff chain
+    #[serde(default)]
+    pub handoff_id: Option<Uuid>,
+
+    /// Run this one was handed off from
+    #[serde(default)]
+    pub parent_execution_id: Option<Uuid>,
+
+    /// Operator-supplied labels (e.g. team, ticket)
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
+
+    /// Subject of the user who started the run (`None` for local runs)
+    #[serde(default)]
+    pub created_by: Option<String>,
+
+    /// Gateway host the run was dispatched to
+    #[serde(default)]
+    pub host_id: Option<String>,
 }
 
 impl Run {
@@ -229,6 +297,8 @@ impl Run {
             summary: None,
             events_path: None,
             event_count: 0,
+            changed_files: None,
+            events_purged: false,
             metadata: RunMetadata::default(),
         }
     }
@@ -248,13 +318,22 @@ impl Run {
 
     /// Mark the run as started
     pub fn mark_started(&mut self) {
-        self.started_at = Some(Utc::now());
+        self.mark_started_at(Utc::now());
+    }
+
+    /// Mark the run as started at `now`
+    pub fn mark_started_at(&mut self, now: DateTime<Utc>) {
+        self.started_at = Some(now);
         self.status = ExecutionStatus::Running;
     }
 
     /// Mark the run as completed
     pub fn mark_completed(&mut self, exit_code: i32, summary: Option<String>) {
-        let now = Utc::now();
+        self.mark_completed_at(exit_code, summary, Utc::now());
+    }
+
+    /// Mark the run as completed at `now`
+    pub fn mark_completed_at(&mut self, exit_code: i32, summary: Option<String>, now: DateTime<Utc>) {
         self.ended_at = Some(now);
         self.exit_code = Some(exit_code);
         self.summary = summary;
@@ -264,21 +343,34 @@ impl Run {
             ExecutionStatus::Failed
         };
         self.calculate_duration();
+        self.count_changed_files();
     }
 
     /// Mark the run as failed
     pub fn mark_failed(&mut self, error: String) {
-        self.ended_at = Some(Utc::now());
+        self.mark_failed_at(error, Utc::now());
+    }
+
+    /// Mark the run as failed at `now`
+    pub fn mark_failed_at(&mut self, error: String, now: DateTime<Utc>) {
+        self.ended_at = Some(now);
         self.error = Some(error);
         self.status = ExecutionStatus::Failed;
         self.calculate_duration();
+        self.count_changed_files();
     }
 
     /// Mark the run as cancelled
     pub fn mark_cancelled(&mut self) {
-        self.ended_at = Some(Utc::now());
+        self.mark_cancelled_at(Utc::now());
+    }
+
+    /// Mark the run as cancelled at `now`
+    pub fn mark_cancelled_at(&mut self, now: DateTime<Utc>) {
+        self.ended_at = Some(now);
         self.status = ExecutionStatus::Cancelled;
         self.calculate_duration();
+        self.count_changed_files();
     }
 
     /// Update status
@@ -286,6 +378,18 @@ impl Run {
         self.status = status;
     }
 
+    /// Record a changed file, once per path
+    pub fn record_file_change(&mut self, path: &str) {
+        if !self.metadata.files_modified.iter().any(|p| p == path) {
+            self.metadata.files_modified.push(path.to_string());
+        }
+    }
+
+    /// Fix the changed-file count from the files recorded so far
+    pub fn count_changed_files(&mut self) {
+        self.changed_files = Some(self.metadata.files_modified.len());
+    }
+
     /// Increment event count
     pub fn increment_event_count(&mut self) {
         self.event_count += 1;
@@ -304,12 +408,31 @@ impl Run {
     /// Calculate duration from started_at to ended_at
     fn calculate_duration(&mut self) {
         if let (Some(started), Some(ended)) = (self.started_at, self.ended_at) {
-            let duration = ended.signed_duration_since(started);
-            self.duration_ms = Some(duration.num_milliseconds().max(0) as u64);
+            self.duration_ms = Some(crate::clock::elapsed_ms(started, ended));
         }
     }
 }
 
+/// Longest run summary kept, in characters
+pub const MAX_SUMMARY_CHARS: usize = 500;
+
+/// Concise summary of a finished run
+///
+/// Prefers the summary reported with the agent's `Completed` event and falls
+/// back to its last assistant message. Blank text is skipped; longer text is
+/// cut to `MAX_SUMMARY_CHARS`.
+pub fn extract_summary(reported: Option<&str>, last_message: Option<&str>) -> Option<String> {
+    let text = [reported, last_message]
+        .into_iter()
+        .flatten()
+        .map(str::trim)
+        .find(|text| !text.is_empty())?;
+    Some(match text.char_indices().nth(MAX_SUMMARY_CHARS) {
+        Some((end, _)) => format!("{}...", text[..end].trim_end()),
+        None => text.to_string(),
+    })
+}
+
 /// Summary of a run for listing purposes
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RunSummary {
@@ -342,6 +465,30 @@ pub struct RunSummary {
 
     /// Event count
     pub event_count: u32,
+
+    /// Summary of what was accomplished
+    #[serde(default)]
+    pub summary: Option<String>,
+
+    /// Distinct files changed (`None` for older runs)
+    #[serde(default)]
+    pub changed_files: Option<usize>,
+
+    /// Events and messages were deleted by retention
+    #[serde(default)]
+    pub events_purged: bool,
+
+    /// Operator-supplied labels
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
+
+    /// Subject of the user who started the run
+    #[serde(default)]
+    pub created_by: Option<String>,
+
+    /// Gateway host the run was dispatched to
+    #[serde(default)]
+    pub host_id: Option<String>,
 }
 
 impl From<&Run> for RunSummary {
@@ -363,6 +510,12 @@ impl From<&Run> for RunSummary {
             duration_ms: run.duration_ms,
             status: run.status,
             event_count: run.event_count,
+            summary: run.summary.clone(),
+            changed_files: run.changed_files,
+            events_purged: run.events_purged,
+            labels: run.metadata.labels.clone(),
+            created_by: run.metadata.created_by.clone(),
+            host_id: run.metadata.host_id.clone(),
         }
     }
 }
@@ -439,6 +592,18 @@ mod tests {
         assert!(summary.prompt_preview.len() <= 103); // 100 + "..."
     }
 
+    #[test]
+    fn test_extract_summary() {
+        assert_eq!(extract_summary(Some(" done \n"), Some("last")).as_deref(), Some("done"));
+        assert_eq!(extract_summary(Some("  "), Some("last message")).as_deref(), Some("last message"));
+        assert_eq!(extract_summary(None, None), None);
+
+        let long = "é".repeat(MAX_SUMMARY_CHARS + 10);
+        let summary = extract_summary(Some(&long), None).unwrap();
+        assert_eq!(summary.chars().count(), MAX_SUMMARY_CHARS + 3);
+        assert!(summary.ends_with("..."));
+    }
+
     #[test]
     fn test_run_metadata() {
         let mut run = Run::new(
@@ -455,4 +620,28 @@ mod tests {
         assert_eq!(run.metadata.files_modified.len(), 1);
         assert_eq!(run.metadata.commands_executed, 5);
     }
+
+    #[test]
+    fn changed_files_are_counted_at_completion() {
+        let mut run = Run::new(
+            Uuid::new_v4(),
+            AgentType::OpenCode,
+            "Test".to_string(),
+            "main".to_string(),
+        );
+        run.mark_started();
+        run.record_file_change("src/main.rs");
+        run.record_file_change("src/lib.rs");
+        run.record_file_change("src/main.rs");
+        assert_eq!(run.changed_files, None);
+
+        run.mark_completed(0, None);
+        assert_eq!(RunSummary::from(&run).changed_files, Some(2));
+
+        // Runs saved before the count existed load without one
+        let mut legacy = serde_json::to_value(&run).unwrap();
+        legacy.as_object_mut().unwrap().remove("changed_files");
+        let legacy: Run = serde_json::from_value(legacy).unwrap();
+        assert_eq!(RunSummary::from(&legacy).changed_files, None);
+    }
 }
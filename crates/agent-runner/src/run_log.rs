@@ -0,0 +1,106 @@
+//! Run log - Human-readable lifecycle log for a run
+//!
+//! Writes `run.log` alongside `events.jsonl` with one line per notable
+//! lifecycle step (start, status changes, errors, completion). The log is
+//! meant for people tailing a run, not for replay; events stay the source
+//! of truth.
+
+use std::sync::Arc;
+
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::event::{AgentEvent, ExecutionEvent, ExecutionEventType, ExecutionStatus};
+use crate::storage::RunStorage;
+
+/// Writes human-readable lines to a single run's `run.log`
+#[derive(Debug, Clone)]
+pub struct RunLogger {
+    store: Arc<dyn RunStorage>,
+    task_id: Uuid,
+    run_id: Uuid,
+}
+
+impl RunLogger {
+    /// Create a logger for a run
+    pub fn new(store: Arc<dyn RunStorage>, task_id: Uuid, run_id: Uuid) -> Self {
+        Self {
+            store,
+            task_id,
+            run_id,
+        }
+    }
+
+    /// Append a line to the run log, warning if it can't be written
+    pub fn log(&self, line: impl AsRef<str>) {
+        if let Err(e) = self
+            .store
+            .append_log_line(self.task_id, self.run_id, line.as_ref())
+        {
+            warn!("Failed to write run log for run {}: {}", self.run_id, e);
+        }
+    }
+
+    /// Log an execution event if it marks a lifecycle step
+    pub fn log_event(&self, event: &ExecutionEvent) {
+        if let Some(line) = describe_event(event) {
+            self.log(line);
+        }
+    }
+}
+
+/// Log line for lifecycle events; streaming output is left to the event log
+fn describe_event(event: &ExecutionEvent) -> Option<String> {
+    match &event.event {
+        ExecutionEventType::SessionStarted {
+            worktree_path,
+            branch,
+        } => Some(format!(
+            "Session started on branch {} in {}",
+            branch, worktree_path
+        )),
+        ExecutionEventType::StatusChanged {
+            old_status,
+            new_status,
+        } => Some(format!(
+            "Status changed: {} -> {}",
+            status_name(*old_status),
+            status_name(*new_status)
+        )),
+        ExecutionEventType::AgentEvent {
+            event: AgentEvent::Error {
+                message,
+                recoverable,
+            },
+        } => Some(if *recoverable {
+            format!("Recoverable error: {}", message)
+        } else {
+            format!("Error: {}", message)
+        }),
+        ExecutionEventType::AgentEvent {
+            event: AgentEvent::Completed { success, summary },
+        } => {
+            let outcome = if *success { "succeeded" } else { "failed" };
+            Some(match summary {
+                Some(summary) => format!("Agent {}: {}", outcome, summary),
+                None => format!("Agent {}", outcome),
+            })
+        }
+        ExecutionEventType::SessionEnded {
+            status,
+            duration_ms,
+        } => Some(format!(
+            "Session ended: {} after {}ms",
+            status_name(*status),
+            duration_ms
+        )),
+        _ => None,
+    }
+}
+
+fn status_name(status: ExecutionStatus) -> String {
+    serde_json::to_value(status)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_else(|| format!("{:?}", status))
+}
@@ -2,7 +2,6 @@
 
 use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::Instant;
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -11,6 +10,7 @@ use uuid::Uuid;
 
 use git_worktree::Worktree;
 
+use crate::clock::{elapsed_ms, Clock, SystemClock};
 use crate::error::{ExecutorError, Result};
 use crate::event::{AgentEvent, ExecutionEvent, ExecutionStatus};
 use crate::process::AgentType;
@@ -82,7 +82,9 @@ pub struct ExecutionSession {
     /// When the session was created
     pub created_at: DateTime<Utc>,
     /// When execution started
-    pub started_at: Option<Instant>,
+    pub started_at: Option<DateTime<Utc>>,
+    /// Time source for timestamps and durations
+    clock: Arc<dyn Clock>,
     /// Event sender
     event_tx: mpsc::Sender<ExecutionEvent>,
     /// Event receiver (for external consumers)
@@ -115,6 +117,7 @@ impl ExecutionSession {
             status: Arc::new(RwLock::new(ExecutionStatus::Initializing)),
             created_at: Utc::now(),
             started_at: None,
+            clock: Arc::new(SystemClock),
             event_tx,
             event_rx: Some(event_rx),
             agent_event_tx,
@@ -122,6 +125,13 @@ impl ExecutionSession {
         }
     }
 
+    /// Read the time from `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.created_at = clock.now();
+        self.clock = clock;
+        self
+    }
+
     /// Get the current state
     pub async fn state(&self) -> SessionState {
         self.state.read().await.clone()
@@ -211,8 +221,8 @@ impl ExecutionSession {
         self.update_status(ExecutionStatus::Starting).await;
 
         // Update state to running
-        let started_at = Utc::now();
-        self.started_at = Some(Instant::now());
+        let started_at = self.clock.now();
+        self.started_at = Some(started_at);
         {
             let mut state = self.state.write().await;
             *state = SessionState::Running {
@@ -223,12 +233,13 @@ impl ExecutionSession {
         self.update_status(ExecutionStatus::Running).await;
 
         // Emit session started event
-        let event = ExecutionEvent::session_started(
+        let mut event = ExecutionEvent::session_started(
             self.id,
             self.task_id,
             worktree_path.to_string_lossy().to_string(),
             self.worktree.as_ref().map(|w| w.branch.clone()).unwrap_or_default(),
         );
+        event.timestamp = started_at;
         let _ = self.event_tx.send(event).await;
 
         // Start forwarding agent events
@@ -260,10 +271,7 @@ impl ExecutionSession {
 
     /// Mark the session as completed
     pub async fn complete(&self, exit_code: i32) {
-        let duration_ms = self
-            .started_at
-            .map(|s| s.elapsed().as_millis() as u64)
-            .unwrap_or(0);
+        let duration_ms = self.elapsed_ms();
 
         {
             let mut state = self.state.write().await;
@@ -282,15 +290,12 @@ impl ExecutionSession {
 
         // Emit session ended event
         let event = ExecutionEvent::session_ended(self.id, self.task_id, status, duration_ms);
-        let _ = self.event_tx.send(event).await;
+        let _ = self.event_tx.send(self.stamped(event)).await;
     }
 
     /// Mark the session as failed
     pub async fn fail(&self, error: String) {
-        let duration_ms = self
-            .started_at
-            .map(|s| s.elapsed().as_millis() as u64)
-            .unwrap_or(0);
+        let duration_ms = self.elapsed_ms();
 
         {
             let mut state = self.state.write().await;
@@ -309,15 +314,12 @@ impl ExecutionSession {
             ExecutionStatus::Failed,
             duration_ms,
         );
-        let _ = self.event_tx.send(event).await;
+        let _ = self.event_tx.send(self.stamped(event)).await;
     }
 
     /// Cancel the session
     pub async fn cancel(&self) {
-        let duration_ms = self
-            .started_at
-            .map(|s| s.elapsed().as_millis() as u64)
-            .unwrap_or(0);
+        let duration_ms = self.elapsed_ms();
 
         {
             let mut state = self.state.write().await;
@@ -333,7 +335,20 @@ impl ExecutionSession {
             ExecutionStatus::Cancelled,
             duration_ms,
         );
-        let _ = self.event_tx.send(event).await;
+        let _ = self.event_tx.send(self.stamped(event)).await;
+    }
+
+    /// Milliseconds since execution started, zero if it never did
+    fn elapsed_ms(&self) -> u64 {
+        self.started_at
+            .map(|started_at| elapsed_ms(started_at, self.clock.now()))
+            .unwrap_or(0)
+    }
+
+    /// `event` timestamped by the session's clock
+    fn stamped(&self, mut event: ExecutionEvent) -> ExecutionEvent {
+        event.timestamp = self.clock.now();
+        event
     }
 }
 
@@ -0,0 +1,596 @@
+//! Run storage backends
+//!
+//! `RunStorage` is what the executor and the API need from run persistence.
+//! `RunStore` keeps everything on the local filesystem and is the default;
+//! other backends (e.g. object storage) implement the trait and are handed
+//! to `TaskExecutor::with_run_storage`. Paging and search have provided
+//! implementations on top of `load_events`, which streaming backends can
+//! override.
+
+use std::fmt;
+use std::time::Duration;
+
+use uuid::Uuid;
+
+use crate::error::Result;
+use crate::event::ExecutionEvent;
+use crate::persistence::{
+    event_text, matches_agent_event_type, matches_event_type, with_seq, EventFilter, EventHistogram,
+    EventPage, RunDeleteFilter, RunLoadError, RunStore,
+};
+use crate::run::{ChatMessage, Run, RunSummary};
+
+/// Persistence for runs, their events, chat messages and logs
+pub trait RunStorage: Send + Sync + fmt::Debug {
+    // ============ Runs ============
+
+    /// Create or overwrite a run's metadata
+    fn save_run(&self, run: &Run) -> Result<()>;
+
+    /// Load a run's metadata
+    fn load_run(&self, task_id: Uuid, run_id: Uuid) -> Result<Run>;
+
+    /// List a task's runs, newest first, with the runs that failed to load
+    fn list_runs_with_errors(&self, task_id: Uuid) -> Result<(Vec<RunSummary>, Vec<RunLoadError>)>;
+
+    /// List a task's runs, newest first, skipping runs that fail to load
+    fn list_runs(&self, task_id: Uuid) -> Result<Vec<RunSummary>> {
+        self.list_runs_with_errors(task_id).map(|(runs, _)| runs)
+    }
+
+    /// Find a run by ID without knowing its task
+    fn find_run(&self, run_id: Uuid) -> Result<Option<Run>>;
+
+    /// Delete a run and all its data
+    fn delete_run(&self, task_id: Uuid, run_id: Uuid) -> Result<()>;
+
+    /// Delete all runs of a task
+    fn delete_task_runs(&self, task_id: Uuid) -> Result<()>;
+
+    /// Delete the runs of a task matching a filter, returning how many were deleted
+    fn delete_task_runs_matching(&self, task_id: Uuid, filter: &RunDeleteFilter) -> Result<usize> {
+        let mut deleted = 0;
+        for run in self.list_runs(task_id)? {
+            if filter.matches(&run) {
+                self.delete_run(task_id, run.id)?;
+                deleted += 1;
+            }
+        }
+        Ok(deleted)
+    }
+
+    // ============ Events ============
+
+    /// Append an event to a run's event log
+    fn append_event(&self, task_id: Uuid, run_id: Uuid, event: &ExecutionEvent) -> Result<()>;
+
+    /// Append events to a run's event log in order
+    fn append_events(&self, task_id: Uuid, run_id: Uuid, events: &[ExecutionEvent]) -> Result<()> {
+        events
+            .iter()
+            .try_for_each(|event| self.append_event(task_id, run_id, event))
+    }
+
+    /// Load all events of a run in order
+    fn load_events(&self, task_id: Uuid, run_id: Uuid) -> Result<Vec<ExecutionEvent>>;
+
+    /// Load a page of events and whether more follow
+    fn load_events_paginated(
+        &self,
+        task_id: Uuid,
+        run_id: Uuid,
+        offset: usize,
+        limit: usize,
+    ) -> Result<(Vec<ExecutionEvent>, bool)> {
+        let page = self.load_events_page(task_id, run_id, offset, limit, EventFilter::default(), false)?;
+        Ok((page.events, page.has_more))
+    }
+
+    /// Load a filtered page of events, optionally counting all matches
+    fn load_events_page(
+        &self,
+        task_id: Uuid,
+        run_id: Uuid,
+        offset: usize,
+        limit: usize,
+        filter: EventFilter<'_>,
+        count_total: bool,
+    ) -> Result<EventPage> {
+        let event_type = filter.event_type.map(str::to_lowercase);
+        let agent_event_type = filter.agent_event_type.map(str::to_lowercase);
+        let matched: Vec<_> = self
+            .load_events(task_id, run_id)?
+            .into_iter()
+            .filter(|event| event_type.as_deref().is_none_or(|f| matches_event_type(event, f)))
+            .filter(|event| {
+                agent_event_type
+                    .as_deref()
+                    .is_none_or(|f| matches_agent_event_type(event, f))
+            })
+            .collect();
+
+        let total = matched.len();
+        let events: Vec<_> = matched.into_iter().skip(offset).take(limit).collect();
+        Ok(EventPage {
+            has_more: total > offset + events.len(),
+            events,
+            total: count_total.then_some(total),
+        })
+    }
+
+    /// Load a filtered page of the events after `after_seq`, optionally
+    /// counting all matches
+    fn load_events_after(
+        &self,
+        task_id: Uuid,
+        run_id: Uuid,
+        after_seq: u64,
+        limit: usize,
+        filter: EventFilter<'_>,
+        count_total: bool,
+    ) -> Result<EventPage> {
+        let event_type = filter.event_type.map(str::to_lowercase);
+        let agent_event_type = filter.agent_event_type.map(str::to_lowercase);
+        let matched: Vec<_> = self
+            .load_events(task_id, run_id)?
+            .into_iter()
+            .enumerate()
+            .map(|(i, event)| with_seq(event, i + 1))
+            .filter(|event| event_type.as_deref().is_none_or(|f| matches_event_type(event, f)))
+            .filter(|event| {
+                agent_event_type
+                    .as_deref()
+                    .is_none_or(|f| matches_agent_event_type(event, f))
+            })
+            .collect();
+
+        let total = matched.len();
+        let skipped = matched
+            .iter()
+            .take_while(|event| event.seq.is_some_and(|seq| seq <= after_seq))
+            .count();
+        let events: Vec<_> = matched.into_iter().skip(skipped).take(limit).collect();
+        Ok(EventPage {
+            has_more: total > skipped + events.len(),
+            events,
+            total: count_total.then_some(total),
+        })
+    }
+
+    /// Events containing `query` (case-insensitive), with their offsets
+    fn search_events(&self, task_id: Uuid, run_id: Uuid, query: &str) -> Result<Vec<(usize, ExecutionEvent)>> {
+        let needle = query.to_lowercase();
+        Ok(self
+            .load_events(task_id, run_id)?
+            .into_iter()
+            .enumerate()
+            .filter(|(_, event)| event_text(event).to_lowercase().contains(&needle))
+            .collect())
+    }
+
+    /// Event counts by type, with the size of the serialized log
+    fn event_histogram(&self, task_id: Uuid, run_id: Uuid) -> Result<EventHistogram> {
+        let mut histogram = EventHistogram::default();
+        for event in self.load_events(task_id, run_id)? {
+            histogram.bytes += serde_json::to_string(&event).map_or(0, |json| json.len() as u64 + 1);
+            histogram.record(&event);
+        }
+        Ok(histogram)
+    }
+
+    /// Number of events in a run
+    fn get_event_count(&self, task_id: Uuid, run_id: Uuid) -> Result<u32> {
+        self.load_events(task_id, run_id).map(|events| events.len() as u32)
+    }
+
+    /// Keep the original of an event whose stored copy was redacted
+    fn append_unredacted_event(&self, task_id: Uuid, run_id: Uuid, event: &ExecutionEvent) -> Result<()>;
+
+    /// Load the originals of a run's redacted events
+    fn load_unredacted_events(&self, task_id: Uuid, run_id: Uuid) -> Result<Vec<ExecutionEvent>>;
+
+    // ============ Messages ============
+
+    /// Append a chat message to a run
+    fn append_message(&self, task_id: Uuid, run_id: Uuid, message: &ChatMessage) -> Result<()>;
+
+    /// Load all chat messages of a run
+    fn load_messages(&self, task_id: Uuid, run_id: Uuid) -> Result<Vec<ChatMessage>>;
+
+    // ============ Side files ============
+
+    /// Load an offloaded tool result as JSON text, or None if there is none
+    fn load_tool_result(&self, task_id: Uuid, run_id: Uuid, index: usize) -> Result<Option<String>>;
+
+    /// Append a timestamped line to a run's human-readable log
+    fn append_log_line(&self, task_id: Uuid, run_id: Uuid, line: &str) -> Result<()>;
+
+    /// Load a run's human-readable log (empty if nothing was logged)
+    fn load_log(&self, task_id: Uuid, run_id: Uuid) -> Result<String>;
+
+    // ============ Retention ============
+
+    /// Drop the events and messages of terminal runs older than `max_age`,
+    /// keeping their metadata; returns how many runs were purged
+    fn purge_events(&self, max_age: Duration) -> Result<usize>;
+
+    /// Drop the events of a run that `keep` rejects, returning how many were
+    /// dropped; the remaining events keep their `seq`
+    fn compact_events(&self, task_id: Uuid, run_id: Uuid, keep: &dyn Fn(&ExecutionEvent) -> bool) -> Result<usize>;
+}
+
+impl RunStorage for RunStore {
+    fn save_run(&self, run: &Run) -> Result<()> {
+        RunStore::save_run(self, run)
+    }
+
+    fn load_run(&self, task_id: Uuid, run_id: Uuid) -> Result<Run> {
+        RunStore::load_run(self, task_id, run_id)
+    }
+
+    fn list_runs_with_errors(&self, task_id: Uuid) -> Result<(Vec<RunSummary>, Vec<RunLoadError>)> {
+        RunStore::list_runs_with_errors(self, task_id)
+    }
+
+    fn find_run(&self, run_id: Uuid) -> Result<Option<Run>> {
+        RunStore::find_run(self, run_id)
+    }
+
+    fn delete_run(&self, task_id: Uuid, run_id: Uuid) -> Result<()> {
+        RunStore::delete_run(self, task_id, run_id)
+    }
+
+    fn delete_task_runs(&self, task_id: Uuid) -> Result<()> {
+        RunStore::delete_task_runs(self, task_id)
+    }
+
+    fn append_event(&self, task_id: Uuid, run_id: Uuid, event: &ExecutionEvent) -> Result<()> {
+        RunStore::append_event(self, task_id, run_id, event)
+    }
+
+    fn append_events(&self, task_id: Uuid, run_id: Uuid, events: &[ExecutionEvent]) -> Result<()> {
+        RunStore::append_events(self, task_id, run_id, events)
+    }
+
+    fn load_events(&self, task_id: Uuid, run_id: Uuid) -> Result<Vec<ExecutionEvent>> {
+        RunStore::load_events(self, task_id, run_id)
+    }
+
+    fn load_events_paginated(
+        &self,
+        task_id: Uuid,
+        run_id: Uuid,
+        offset: usize,
+        limit: usize,
+    ) -> Result<(Vec<ExecutionEvent>, bool)> {
+        RunStore::load_events_paginated(self, task_id, run_id, offset, limit)
+    }
+
+    fn load_events_page(
+        &self,
+        task_id: Uuid,
+        run_id: Uuid,
+        offset: usize,
+        limit: usize,
+        filter: EventFilter<'_>,
+        count_total: bool,
+    ) -> Result<EventPage> {
+        RunStore::load_events_page(
+            self,
+            task_id,
+            run_id,
+            offset,
+            limit,
+            filter,
+            count_total,
+        )
+    }
+
+    fn load_events_after(
+        &self,
+        task_id: Uuid,
+        run_id: Uuid,
+        after_seq: u64,
+        limit: usize,
+        filter: EventFilter<'_>,
+        count_total: bool,
+    ) -> Result<EventPage> {
+        RunStore::load_events_after(
+            self,
+            task_id,
+            run_id,
+            after_seq,
+            limit,
+            filter,
+            count_total,
+        )
+    }
+
+    fn search_events(&self, task_id: Uuid, run_id: Uuid, query: &str) -> Result<Vec<(usize, ExecutionEvent)>> {
+        RunStore::search_events(self, task_id, run_id, query)
+    }
+
+    fn event_histogram(&self, task_id: Uuid, run_id: Uuid) -> Result<EventHistogram> {
+        RunStore::event_histogram(self, task_id, run_id)
+    }
+
+    fn get_event_count(&self, task_id: Uuid, run_id: Uuid) -> Result<u32> {
+        RunStore::get_event_count(self, task_id, run_id)
+    }
+
+    fn append_unredacted_event(&self, task_id: Uuid, run_id: Uuid, event: &ExecutionEvent) -> Result<()> {
+        RunStore::append_unredacted_event(self, task_id, run_id, event)
+    }
+
+    fn load_unredacted_events(&self, task_id: Uuid, run_id: Uuid) -> Result<Vec<ExecutionEvent>> {
+        RunStore::load_unredacted_events(self, task_id, run_id)
+    }
+
+    fn append_message(&self, task_id: Uuid, run_id: Uuid, message: &ChatMessage) -> Result<()> {
+        RunStore::append_message(self, task_id, run_id, message)
+    }
+
+    fn load_messages(&self, task_id: Uuid, run_id: Uuid) -> Result<Vec<ChatMessage>> {
+        RunStore::load_messages(self, task_id, run_id)
+    }
+
+    fn load_tool_result(&self, task_id: Uuid, run_id: Uuid, index: usize) -> Result<Option<String>> {
+        RunStore::load_tool_result(self, task_id, run_id, index)
+    }
+
+    fn append_log_line(&self, task_id: Uuid, run_id: Uuid, line: &str) -> Result<()> {
+        RunStore::append_log_line(self, task_id, run_id, line)
+    }
+
+    fn load_log(&self, task_id: Uuid, run_id: Uuid) -> Result<String> {
+        RunStore::load_log(self, task_id, run_id)
+    }
+
+    fn purge_events(&self, max_age: Duration) -> Result<usize> {
+        RunStore::purge_events(self, max_age)
+    }
+
+    fn compact_events(&self, task_id: Uuid, run_id: Uuid, keep: &dyn Fn(&ExecutionEvent) -> bool) -> Result<usize> {
+        RunStore::compact_events(self, task_id, run_id, keep)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    use crate::error::ExecutorError;
+    use crate::event::{AgentEvent, ExecutionStatus};
+    use crate::process::AgentType;
+
+    #[derive(Debug, Default)]
+    struct RunData {
+        run: Option<Run>,
+        events: Vec<ExecutionEvent>,
+        unredacted: Vec<ExecutionEvent>,
+        messages: Vec<ChatMessage>,
+        log: String,
+    }
+
+    /// Storage that keeps everything in memory
+    #[derive(Debug, Default)]
+    struct MemoryRunStorage {
+        runs: Mutex<HashMap<(Uuid, Uuid), RunData>>,
+    }
+
+    impl MemoryRunStorage {
+        fn with_run<T>(&self, task_id: Uuid, run_id: Uuid, f: impl FnOnce(&mut RunData) -> T) -> T {
+            f(self.runs.lock().unwrap().entry((task_id, run_id)).or_default())
+        }
+
+        fn read<T: Default>(&self, task_id: Uuid, run_id: Uuid, f: impl FnOnce(&RunData) -> T) -> T {
+            self.runs.lock().unwrap().get(&(task_id, run_id)).map(f).unwrap_or_default()
+        }
+    }
+
+    impl RunStorage for MemoryRunStorage {
+        fn save_run(&self, run: &Run) -> Result<()> {
+            self.with_run(run.task_id, run.id, |data| data.run = Some(run.clone()));
+            Ok(())
+        }
+
+        fn load_run(&self, task_id: Uuid, run_id: Uuid) -> Result<Run> {
+            self.read(task_id, run_id, |data| data.run.clone())
+                .ok_or_else(|| ExecutorError::execution_failed(format!("Run not found: {}", run_id)))
+        }
+
+        fn list_runs_with_errors(&self, task_id: Uuid) -> Result<(Vec<RunSummary>, Vec<RunLoadError>)> {
+            let mut runs: Vec<_> = self
+                .runs
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|((task, _), _)| *task == task_id)
+                .filter_map(|(_, data)| data.run.as_ref().map(RunSummary::from))
+                .collect();
+            runs.sort_by_key(|r| std::cmp::Reverse(r.created_at));
+            Ok((runs, Vec::new()))
+        }
+
+        fn find_run(&self, run_id: Uuid) -> Result<Option<Run>> {
+            Ok(self
+                .runs
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|((_, run), _)| *run == run_id)
+                .and_then(|(_, data)| data.run.clone()))
+        }
+
+        fn delete_run(&self, task_id: Uuid, run_id: Uuid) -> Result<()> {
+            self.runs.lock().unwrap().remove(&(task_id, run_id));
+            Ok(())
+        }
+
+        fn delete_task_runs(&self, task_id: Uuid) -> Result<()> {
+            self.runs.lock().unwrap().retain(|(task, _), _| *task != task_id);
+            Ok(())
+        }
+
+        fn append_event(&self, task_id: Uuid, run_id: Uuid, event: &ExecutionEvent) -> Result<()> {
+            self.with_run(task_id, run_id, |data| data.events.push(event.clone()));
+            Ok(())
+        }
+
+        fn load_events(&self, task_id: Uuid, run_id: Uuid) -> Result<Vec<ExecutionEvent>> {
+            Ok(self.read(task_id, run_id, |data| data.events.clone()))
+        }
+
+        fn append_unredacted_event(&self, task_id: Uuid, run_id: Uuid, event: &ExecutionEvent) -> Result<()> {
+            self.with_run(task_id, run_id, |data| data.unredacted.push(event.clone()));
+            Ok(())
+        }
+
+        fn load_unredacted_events(&self, task_id: Uuid, run_id: Uuid) -> Result<Vec<ExecutionEvent>> {
+            Ok(self.read(task_id, run_id, |data| data.unredacted.clone()))
+        }
+
+        fn append_message(&self, task_id: Uuid, run_id: Uuid, message: &ChatMessage) -> Result<()> {
+            self.with_run(task_id, run_id, |data| data.messages.push(message.clone()));
+            Ok(())
+        }
+
+        fn load_messages(&self, task_id: Uuid, run_id: Uuid) -> Result<Vec<ChatMessage>> {
+            Ok(self.read(task_id, run_id, |data| data.messages.clone()))
+        }
+
+        fn load_tool_result(&self, _task_id: Uuid, _run_id: Uuid, _index: usize) -> Result<Option<String>> {
+            Ok(None)
+        }
+
+        fn append_log_line(&self, task_id: Uuid, run_id: Uuid, line: &str) -> Result<()> {
+            self.with_run(task_id, run_id, |data| {
+                data.log.push_str(line);
+                data.log.push('\n');
+            });
+            Ok(())
+        }
+
+        fn load_log(&self, task_id: Uuid, run_id: Uuid) -> Result<String> {
+            Ok(self.read(task_id, run_id, |data| data.log.clone()))
+        }
+
+        fn purge_events(&self, max_age: Duration) -> Result<usize> {
+            let cutoff = chrono::Utc::now() - chrono::Duration::from_std(max_age).unwrap();
+            let mut purged = 0;
+            for data in self.runs.lock().unwrap().values_mut() {
+                let Some(run) = data.run.as_mut() else { continue };
+                if !run.events_purged && run.status.is_terminal() && run.ended_at.unwrap_or(run.created_at) < cutoff {
+                    run.events_purged = true;
+                    data.events.clear();
+                    data.unredacted.clear();
+                    data.messages.clear();
+                    purged += 1;
+                }
+            }
+            Ok(purged)
+        }
+
+        fn compact_events(
+            &self,
+            task_id: Uuid,
+            run_id: Uuid,
+            keep: &dyn Fn(&ExecutionEvent) -> bool,
+        ) -> Result<usize> {
+            Ok(self.with_run(task_id, run_id, |data| {
+                let before = data.events.len();
+                data.events = std::mem::take(&mut data.events)
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, event)| with_seq(event, i + 1))
+                    .filter(|event| keep(event))
+                    .collect();
+                before - data.events.len()
+            }))
+        }
+    }
+
+    /// Behaviour every backend must share
+    fn check_contract(storage: &dyn RunStorage) {
+        let task_id = Uuid::new_v4();
+        let mut run = Run::new(task_id, AgentType::OpenCode, "Prompt".to_string(), "main".to_string());
+        run.status = ExecutionStatus::Completed;
+        storage.save_run(&run).unwrap();
+        assert_eq!(storage.load_run(task_id, run.id).unwrap().prompt, "Prompt");
+        assert_eq!(storage.find_run(run.id).unwrap().map(|r| r.task_id), Some(task_id));
+        assert!(storage.load_run(task_id, Uuid::new_v4()).is_err());
+
+        for i in 0..5 {
+            let event = ExecutionEvent::agent_event(
+                run.id,
+                task_id,
+                AgentEvent::Message {
+                    content: format!("Line {}", i),
+                },
+            );
+            storage.append_event(task_id, run.id, &event).unwrap();
+        }
+        storage
+            .append_event(task_id, run.id, &ExecutionEvent::progress(run.id, task_id, "Step".to_string(), None))
+            .unwrap();
+        assert_eq!(storage.get_event_count(task_id, run.id).unwrap(), 6);
+
+        let (page, has_more) = storage.load_events_paginated(task_id, run.id, 4, 3).unwrap();
+        assert_eq!(page.len(), 2);
+        assert!(!has_more);
+
+        let page = storage
+            .load_events_page(
+                task_id,
+                run.id,
+                1,
+                2,
+                EventFilter {
+                    agent_event_type: Some("message"),
+                    ..EventFilter::default()
+                },
+                true,
+            )
+            .unwrap();
+        assert_eq!(page.events.len(), 2);
+        assert!(page.has_more);
+        assert_eq!(page.total, Some(5));
+
+        let hits = storage.search_events(task_id, run.id, "line 3").unwrap();
+        assert_eq!(hits.iter().map(|(offset, _)| *offset).collect::<Vec<_>>(), vec![3]);
+
+        let seqs = |page: &EventPage| page.events.iter().map(|event| event.seq).collect::<Vec<_>>();
+        let page = storage.load_events_after(task_id, run.id, 2, 2, EventFilter::default(), false).unwrap();
+        assert_eq!(seqs(&page), vec![Some(3), Some(4)]);
+        assert!(page.has_more);
+        assert_eq!(storage.compact_events(task_id, run.id, &|event| event.seq != Some(4)).unwrap(), 1);
+        let page = storage.load_events_after(task_id, run.id, 3, 10, EventFilter::default(), true).unwrap();
+        assert_eq!(seqs(&page), vec![Some(5), Some(6)]);
+        assert!(!page.has_more);
+        assert_eq!(page.total, Some(5));
+
+        let message = ChatMessage::user("Hello".to_string());
+        storage.append_message(task_id, run.id, &message).unwrap();
+        assert_eq!(storage.load_messages(task_id, run.id).unwrap()[0].content, "Hello");
+
+        storage.append_log_line(task_id, run.id, "Run completed").unwrap();
+        assert!(storage.load_log(task_id, run.id).unwrap().contains("Run completed"));
+
+        let filter = RunDeleteFilter {
+            status: Some(ExecutionStatus::Completed),
+            ..RunDeleteFilter::default()
+        };
+        assert_eq!(storage.list_runs(task_id).unwrap().len(), 1);
+        assert_eq!(storage.delete_task_runs_matching(task_id, &filter).unwrap(), 1);
+        assert!(storage.list_runs(task_id).unwrap().is_empty());
+    }
+
+    #[test]
+    fn filesystem_and_memory_backends_share_the_contract() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        check_contract(&RunStore::new(temp_dir.path()));
+        check_contract(&MemoryRunStorage::default());
+    }
+}
@@ -0,0 +1,137 @@
+//! Files attached to a task as prompt context
+//!
+//! A task's `context_files` are paths relative to its project. At dispatch
+//! each file is read from the project checkout and prepended to the prompt
+//! between delimiter lines. When the checkout is not on this server, the
+//! file is only referenced, for the agent to read on its host. Paths must
+//! stay inside the project, and the attached contents are capped in total
+//! by `VK_CONTEXT_FILES_MAX_BYTES`.
+
+use std::path::{Path, PathBuf};
+
+use vk_core::project::is_path_within;
+
+use crate::env::positive_env_or_default;
+
+/// Environment variable capping the total size of a prompt's context files
+pub const CONTEXT_FILES_MAX_BYTES_ENV: &str = "VK_CONTEXT_FILES_MAX_BYTES";
+
+/// Default cap on the total size of a prompt's context files (256 KiB)
+pub const DEFAULT_CONTEXT_FILES_MAX_BYTES: usize = 256 * 1024;
+
+/// Load the context file cap from the process environment
+pub fn context_files_max_bytes_from_env() -> usize {
+    positive_env_or_default(CONTEXT_FILES_MAX_BYTES_ENV, DEFAULT_CONTEXT_FILES_MAX_BYTES)
+}
+
+/// Resolve `path` against `project_root`, rejecting paths that leave it
+fn resolve(project_root: &Path, path: &str) -> Result<PathBuf, String> {
+    if path.trim().is_empty() {
+        return Err("Context file path cannot be empty".to_string());
+    }
+    let full = project_root.join(path);
+    if !is_path_within(&full, project_root) {
+        return Err(format!("Context file {} is outside the project", path));
+    }
+    Ok(full)
+}
+
+/// Check that every context file path lies inside `project_root`
+pub fn check_context_paths(project_root: &Path, paths: &[String]) -> Result<(), String> {
+    paths.iter().try_for_each(|path| resolve(project_root, path).map(|_| ()))
+}
+
+/// Prepend the context files to `prompt`
+///
+/// Files are read when `project_root` exists on this server and referenced
+/// by path otherwise. A file's size is checked against the remaining budget
+/// before it is read.
+pub async fn prepend_context_files(
+    prompt: &str,
+    project_root: &Path,
+    paths: &[String],
+    max_bytes: usize,
+) -> Result<String, String> {
+    if paths.is_empty() {
+        return Ok(prompt.to_string());
+    }
+
+    let readable = tokio::fs::metadata(project_root)
+        .await
+        .is_ok_and(|metadata| metadata.is_dir());
+    let mut attached = String::new();
+    let mut total = 0usize;
+    for path in paths {
+        let full = resolve(project_root, path)?;
+        if !readable {
+            attached.push_str(&format!(
+                "--- CONTEXT FILE: {} (read it from the project checkout) ---\n\n",
+                path
+            ));
+            continue;
+        }
+
+        let read_error = |e: std::io::Error| format!("Failed to read context file {}: {}", path, e);
+        let over_limit = || format!("Context files exceed the limit of {} bytes", max_bytes);
+        let size = tokio::fs::metadata(&full).await.map_err(read_error)?.len();
+        if size > (max_bytes - total) as u64 {
+            return Err(over_limit());
+        }
+        let bytes = tokio::fs::read(&full).await.map_err(read_error)?;
+        // The file may have grown since its size was checked
+        total += bytes.len();
+        if total > max_bytes {
+            return Err(over_limit());
+        }
+        let content = String::from_utf8(bytes)
+            .map_err(|_| format!("Context file {} is not UTF-8 text", path))?;
+        attached.push_str(&format!("--- BEGIN CONTEXT FILE: {} ---\n", path));
+        attached.push_str(&content);
+        if !content.ends_with('\n') {
+            attached.push('\n');
+        }
+        attached.push_str(&format!("--- END CONTEXT FILE: {} ---\n\n", path));
+    }
+    attached.push_str(prompt);
+    Ok(attached)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn context_files_are_delimited_and_capped() {
+        let project = TempDir::new().unwrap();
+        std::fs::write(project.path().join("notes.md"), "Use the v2 API").unwrap();
+        let paths = vec!["notes.md".to_string()];
+
+        let prompt = prepend_context_files("Do the task", project.path(), &paths, 1024)
+            .await
+            .unwrap();
+        assert_eq!(
+            prompt,
+            "--- BEGIN CONTEXT FILE: notes.md ---\nUse the v2 API\n--- END CONTEXT FILE: notes.md ---\n\nDo the task"
+        );
+
+        let err = prepend_context_files("Do the task", project.path(), &paths, 4)
+            .await
+            .unwrap_err();
+        assert_eq!(err, "Context files exceed the limit of 4 bytes");
+
+        // The budget is shared by all files
+        std::fs::write(project.path().join("more.md"), "Use the v3 API").unwrap();
+        let both = vec!["notes.md".to_string(), "more.md".to_string()];
+        let err = prepend_context_files("Do the task", project.path(), &both, 20)
+            .await
+            .unwrap_err();
+        assert_eq!(err, "Context files exceed the limit of 20 bytes");
+
+        let escaping = vec!["../secrets.txt".to_string()];
+        assert!(check_context_paths(project.path(), &escaping).is_err());
+        assert!(prepend_context_files("Do the task", project.path(), &escaping, 1024)
+            .await
+            .is_err());
+    }
+}
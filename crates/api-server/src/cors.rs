@@ -0,0 +1,178 @@
+//! CORS configuration
+//!
+//! Allowed origins come from `VK_CORS_ORIGINS` (comma-separated). When unset,
+//! debug builds stay permissive for local development while release builds
+//! reject all cross-origin requests. A `*` entry allows any origin, but
+//! without credentials, since browsers reject credentialed wildcard responses.
+
+use axum::http::{HeaderValue, Method};
+use tower_http::cors::{AllowHeaders, AllowOrigin, Any, CorsLayer};
+
+/// Environment variable holding the comma-separated origin allowlist
+pub const CORS_ORIGINS_ENV: &str = "VK_CORS_ORIGINS";
+
+/// Build the CORS layer from the process environment
+pub fn cors_layer() -> CorsLayer {
+    let origins = std::env::var(CORS_ORIGINS_ENV).ok();
+    cors_layer_from(origins.as_deref(), cfg!(debug_assertions))
+}
+
+/// Build the CORS layer from an optional origin list
+///
+/// `permissive_default` controls the fallback when no origins are configured.
+pub fn cors_layer_from(origins: Option<&str>, permissive_default: bool) -> CorsLayer {
+    let configured = origins.is_some();
+    let origins = origins.map(parse_origins).unwrap_or_default();
+
+    if origins.is_empty() {
+        if permissive_default {
+            return CorsLayer::new()
+                .allow_origin(Any)
+                .allow_methods(Any)
+                .allow_headers(Any);
+        }
+        if configured {
+            tracing::warn!(
+                "{} has no valid origins, cross-origin requests will be rejected",
+                CORS_ORIGINS_ENV
+            );
+        } else {
+            tracing::warn!("{} not set, cross-origin requests will be rejected", CORS_ORIGINS_ENV);
+        }
+        return CorsLayer::new();
+    }
+
+    if origins.iter().any(|origin| origin == "*") {
+        if origins.len() > 1 {
+            tracing::warn!("{} contains `*`, other origins are redundant", CORS_ORIGINS_ENV);
+        }
+        return CorsLayer::new()
+            .allow_origin(Any)
+            .allow_methods(allowed_methods())
+            .allow_headers(Any);
+    }
+
+    CorsLayer::new()
+        .allow_origin(AllowOrigin::list(origins))
+        .allow_methods(allowed_methods())
+        .allow_headers(AllowHeaders::mirror_request())
+        .allow_credentials(true)
+}
+
+fn allowed_methods() -> [Method; 6] {
+    [
+        Method::GET,
+        Method::POST,
+        Method::PUT,
+        Method::PATCH,
+        Method::DELETE,
+        Method::OPTIONS,
+    ]
+}
+
+/// Parse a comma-separated origin list, skipping blank or invalid entries
+fn parse_origins(raw: &str) -> Vec<HeaderValue> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|origin| !origin.is_empty())
+        .filter_map(|origin| match HeaderValue::from_str(origin) {
+            Ok(value) => Some(value),
+            Err(_) => {
+                tracing::warn!("Ignoring invalid CORS origin: {}", origin);
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::{header, Request, StatusCode};
+    use axum::routing::get;
+    use axum::Router;
+    use tower::ServiceExt;
+
+    async fn allowed_origin(layer: CorsLayer, origin: &str) -> Option<HeaderValue> {
+        let app = Router::new().route("/", get(|| async { "ok" })).layer(layer);
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/")
+                    .header(header::ORIGIN, origin)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        response
+            .headers()
+            .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+            .cloned()
+    }
+
+    #[tokio::test]
+    async fn allowlist_accepts_listed_origins_and_rejects_others() {
+        let raw = "http://localhost:5173, https://kanban.example.com";
+
+        let allowed = allowed_origin(cors_layer_from(Some(raw), false), "https://kanban.example.com").await;
+        assert_eq!(allowed.unwrap(), "https://kanban.example.com");
+
+        let rejected = allowed_origin(cors_layer_from(Some(raw), false), "https://evil.example.com").await;
+        assert!(rejected.is_none());
+    }
+
+    #[tokio::test]
+    async fn allowlist_allows_credentials() {
+        let app = Router::new()
+            .route("/", get(|| async { "ok" }))
+            .layer(cors_layer_from(Some("http://localhost:5173"), false));
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/")
+                    .header(header::ORIGIN, "http://localhost:5173")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            response.headers().get(header::ACCESS_CONTROL_ALLOW_CREDENTIALS).unwrap(),
+            "true"
+        );
+    }
+
+    #[tokio::test]
+    async fn wildcard_allows_any_origin_without_credentials() {
+        let app = Router::new()
+            .route("/", get(|| async { "ok" }))
+            .layer(cors_layer_from(Some("*, http://localhost:5173"), false));
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/")
+                    .header(header::ORIGIN, "https://any.example.com")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers().get(header::ACCESS_CONTROL_ALLOW_ORIGIN).unwrap(), "*");
+        assert!(response.headers().get(header::ACCESS_CONTROL_ALLOW_CREDENTIALS).is_none());
+    }
+
+    #[tokio::test]
+    async fn unset_origins_fall_back_by_build_mode() {
+        let permissive = allowed_origin(cors_layer_from(None, true), "https://any.example.com").await;
+        assert_eq!(permissive.unwrap(), "*");
+
+        let restrictive = allowed_origin(cors_layer_from(Some(" , "), false), "https://any.example.com").await;
+        assert!(restrictive.is_none());
+    }
+}
@@ -0,0 +1,161 @@
+//! Deprecation and sunset handling for legacy routes
+//!
+//! Legacy `/api/tasks/*` routes carry `Deprecation` and `Sunset` headers and
+//! link their successor. The sunset date comes from `VK_API_SUNSET` (RFC 1123);
+//! once it has passed and `VK_ENFORCE_SUNSET=true`, those routes answer
+//! `410 Gone`.
+
+use axum::{
+    extract::{Request, State},
+    http::{HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+
+/// Environment variable holding the RFC 1123 sunset date
+pub const SUNSET_ENV: &str = "VK_API_SUNSET";
+
+/// Environment variable enabling `410 Gone` after the sunset date
+pub const ENFORCE_SUNSET_ENV: &str = "VK_ENFORCE_SUNSET";
+
+/// Legacy routes superseded by `/api/v1/executions` and `/api/v1/tasks`
+pub const DEPRECATED_ROUTES: &[&str] = &[
+    "POST /api/tasks/{id}/execute",
+    "GET /api/tasks/{id}/status",
+    "POST /api/tasks/{id}/stop",
+    "POST /api/tasks/{id}/input",
+    "DELETE /api/tasks/{id}/worktree",
+    "* /api/tasks[/...] task and run routes",
+];
+
+/// Sunset policy applied to deprecated routes
+#[derive(Debug, Clone, Default)]
+pub struct SunsetConfig {
+    pub sunset: Option<DateTime<Utc>>,
+    pub enforce: bool,
+}
+
+impl SunsetConfig {
+    /// Load the sunset policy from the process environment
+    pub fn from_env() -> Self {
+        let sunset = std::env::var(SUNSET_ENV).ok().and_then(|raw| {
+            match DateTime::parse_from_rfc2822(raw.trim()) {
+                Ok(date) => Some(date.with_timezone(&Utc)),
+                Err(e) => {
+                    tracing::warn!("Ignoring invalid {} value {:?}: {}", SUNSET_ENV, raw, e);
+                    None
+                }
+            }
+        });
+        let enforce = std::env::var(ENFORCE_SUNSET_ENV)
+            .map(|v| v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
+
+        Self { sunset, enforce }
+    }
+
+    /// Whether deprecated routes should answer `410 Gone` at `now`
+    pub fn is_gone(&self, now: DateTime<Utc>) -> bool {
+        self.enforce && self.sunset.is_some_and(|sunset| now >= sunset)
+    }
+
+    /// Sunset date formatted as an HTTP date
+    fn sunset_header(&self) -> Option<HeaderValue> {
+        self.sunset.and_then(|sunset| {
+            HeaderValue::from_str(&sunset.format("%a, %d %b %Y %H:%M:%S GMT").to_string()).ok()
+        })
+    }
+}
+
+/// Log the deprecated routes and their sunset policy at startup
+pub fn log_deprecated_routes(config: &SunsetConfig) {
+    let sunset = config
+        .sunset
+        .map(|date| date.to_rfc2822())
+        .unwrap_or_else(|| "not scheduled".to_string());
+    tracing::info!(
+        "Deprecated routes (sunset: {}, enforced: {}): {}",
+        sunset,
+        config.enforce,
+        DEPRECATED_ROUTES.join(", ")
+    );
+}
+
+/// Middleware marking responses as deprecated, or rejecting them after sunset
+///
+/// The state pairs the sunset policy with the path prefix replacing the route.
+pub async fn deprecated_route(
+    State((config, successor)): State<(SunsetConfig, &'static str)>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if config.is_gone(Utc::now()) {
+        return (
+            StatusCode::GONE,
+            Json(serde_json::json!({
+                "error": format!("This endpoint has been retired; use {}", successor),
+            })),
+        )
+            .into_response();
+    }
+
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+    headers.insert("deprecation", HeaderValue::from_static("true"));
+    if let Some(sunset) = config.sunset_header() {
+        headers.insert("sunset", sunset);
+    }
+    if let Ok(link) = HeaderValue::from_str(&format!("<{}>; rel=\"successor-version\"", successor)) {
+        headers.insert("link", link);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use axum::middleware;
+    use axum::routing::get;
+    use axum::Router;
+    use chrono::TimeZone;
+    use tower::ServiceExt;
+
+    async fn call(config: SunsetConfig) -> Response {
+        Router::new()
+            .route("/legacy", get(|| async { "ok" }))
+            .route_layer(middleware::from_fn_with_state((config, "/api/v1/legacy"), deprecated_route))
+            .oneshot(Request::builder().uri("/legacy").body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn deprecated_route_sets_deprecation_and_sunset_headers() {
+        let config = SunsetConfig {
+            sunset: Some(Utc.with_ymd_and_hms(2099, 1, 6, 8, 30, 0).unwrap()),
+            enforce: true,
+        };
+
+        let response = call(config).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["deprecation"], "true");
+        assert_eq!(response.headers()["sunset"], "Tue, 06 Jan 2099 08:30:00 GMT");
+        assert_eq!(response.headers()["link"], "</api/v1/legacy>; rel=\"successor-version\"");
+    }
+
+    #[tokio::test]
+    async fn past_sunset_returns_gone_only_when_enforced() {
+        let sunset = Some(Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap());
+
+        let enforced = call(SunsetConfig { sunset, enforce: true }).await;
+        assert_eq!(enforced.status(), StatusCode::GONE);
+
+        let lenient = call(SunsetConfig { sunset, enforce: false }).await;
+        assert_eq!(lenient.status(), StatusCode::OK);
+        assert!(lenient.headers().contains_key("sunset"));
+    }
+}
@@ -0,0 +1,277 @@
+//! Periodic digests of run outcomes for task watchers
+//!
+//! Terminal run events of watched tasks are collected per watcher and sent
+//! as one digest per watcher every `VK_DIGEST_INTERVAL_SECS`. Delivery goes
+//! through a `DigestNotifier`; with `VK_DIGEST_WEBHOOK_URL` set, digests are
+//! posted to that webhook as JSON.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use agent_runner::ExecutionStatus;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+use vk_core::task::{Task, TaskRepository};
+
+use crate::env::positive_env_or_default;
+use crate::state::{AppState, RunLifecycleEvent};
+
+/// Environment variable with the webhook digests are posted to
+pub const DIGEST_WEBHOOK_ENV: &str = "VK_DIGEST_WEBHOOK_URL";
+
+/// Environment variable with the seconds between digests
+pub const DIGEST_INTERVAL_ENV: &str = "VK_DIGEST_INTERVAL_SECS";
+
+/// Time between digests when none is configured
+const DEFAULT_DIGEST_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// One run outcome of a watched task
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DigestEntry {
+    pub task_id: Uuid,
+    pub task_title: String,
+    pub execution_id: Uuid,
+    pub status: ExecutionStatus,
+    pub at: DateTime<Utc>,
+}
+
+/// Run outcomes collected for one watcher since the last digest
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Digest {
+    pub watcher: String,
+    pub entries: Vec<DigestEntry>,
+}
+
+/// Pending digest entries, per watcher
+#[derive(Debug, Clone, Default)]
+pub struct DigestCollector {
+    pending: Arc<Mutex<BTreeMap<String, Vec<DigestEntry>>>>,
+}
+
+impl DigestCollector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `event` to the pending digest of every watcher of `task`
+    pub fn record(&self, task: &Task, event: &RunLifecycleEvent) {
+        if task.watchers.is_empty() {
+            return;
+        }
+        let entry = DigestEntry {
+            task_id: task.id,
+            task_title: task.title.clone(),
+            execution_id: event.execution_id,
+            status: event.status,
+            at: Utc::now(),
+        };
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        for watcher in &task.watchers {
+            pending.entry(watcher.clone()).or_default().push(entry.clone());
+        }
+    }
+
+    /// Take the pending digests, leaving the collector empty
+    pub fn drain(&self) -> Vec<Digest> {
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        std::mem::take(&mut *pending)
+            .into_iter()
+            .map(|(watcher, entries)| Digest { watcher, entries })
+            .collect()
+    }
+}
+
+/// Delivers digests to watchers
+#[async_trait]
+pub trait DigestNotifier: Send + Sync {
+    async fn send(&self, digest: &Digest) -> Result<(), String>;
+}
+
+/// Posts each digest as JSON to a webhook
+pub struct WebhookNotifier {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl WebhookNotifier {
+    pub fn new(url: String) -> Self {
+        Self {
+            url,
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl DigestNotifier for WebhookNotifier {
+    async fn send(&self, digest: &Digest) -> Result<(), String> {
+        self.client
+            .post(&self.url)
+            .json(digest)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Load the digest webhook from the process environment
+pub fn webhook_notifier_from_env() -> Option<WebhookNotifier> {
+    let url = std::env::var(DIGEST_WEBHOOK_ENV).ok()?;
+    let url = url.trim();
+    (!url.is_empty()).then(|| WebhookNotifier::new(url.to_string()))
+}
+
+/// Load the time between digests from the process environment
+pub fn digest_interval_from_env() -> Duration {
+    Duration::from_secs(positive_env_or_default(DIGEST_INTERVAL_ENV, DEFAULT_DIGEST_INTERVAL.as_secs()))
+}
+
+/// Record the run events of watched tasks until the broadcaster closes
+pub async fn collect_run_events(
+    state: AppState,
+    collector: DigestCollector,
+    mut events: broadcast::Receiver<RunLifecycleEvent>,
+) {
+    loop {
+        match events.recv().await {
+            Ok(event) => match state.task_store().get(event.task_id).await {
+                Ok(Some(task)) => collector.record(&task, &event),
+                Ok(None) => {}
+                Err(e) => tracing::warn!("Failed to load task {} for digest: {}", event.task_id, e),
+            },
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                tracing::warn!("Digest collector skipped {} run events", skipped);
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
+
+/// Start collecting watched-task events and sending a digest every `interval`
+pub fn start_digests(state: AppState, notifier: Arc<dyn DigestNotifier>, interval: Duration) {
+    let collector = DigestCollector::new();
+    let events = state.subscribe_run_events();
+    tokio::spawn(collect_run_events(state, collector.clone(), events));
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        // The first tick completes immediately
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            for digest in collector.drain() {
+                if let Err(e) = notifier.send(&digest).await {
+                    tracing::warn!("Failed to send digest to {}: {}", digest.watcher, e);
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use axum::{body::Body, http::Request, http::StatusCode};
+    use tempfile::TempDir;
+    use tower::ServiceExt;
+    use vk_core::kanban::KanbanStore;
+    use vk_core::task::FileTaskStore;
+
+    use crate::gateway::GatewayManager;
+
+    async fn build_state() -> (AppState, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path().to_path_buf();
+        let task_store = Arc::new(FileTaskStore::new(data_dir.join("tasks.json")).await.unwrap());
+        let kanban_store = Arc::new(
+            KanbanStore::with_task_store(data_dir.join("kanban.json"), Arc::clone(&task_store))
+                .await
+                .unwrap(),
+        );
+        let gateway_manager = Arc::new(GatewayManager::with_stores(
+            Arc::clone(&task_store),
+            Arc::clone(&kanban_store),
+        ));
+        let state = AppState::with_stores(data_dir, task_store, kanban_store, gateway_manager)
+            .await
+            .unwrap();
+        (state, temp_dir)
+    }
+
+    #[tokio::test]
+    async fn digest_collects_terminal_events_of_watched_tasks() {
+        let (mut state, _temp_dir) = build_state().await;
+        state.set_trust_user_header(true);
+        let watched = state
+            .task_store()
+            .create(Task::new("Watched task".to_string()))
+            .await
+            .unwrap();
+        let unwatched = state
+            .task_store()
+            .create(Task::new("Unwatched task".to_string()))
+            .await
+            .unwrap();
+
+        let response = crate::routes::task::router()
+            .with_state(state.clone())
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/tasks/{}/watch", watched.id))
+                    .header("X-VK-User", "alice")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let collector = DigestCollector::new();
+        tokio::spawn(collect_run_events(
+            state.clone(),
+            collector.clone(),
+            state.subscribe_run_events(),
+        ));
+        let execution_id = Uuid::new_v4();
+        for (task_id, execution_id) in [(unwatched.id, Uuid::new_v4()), (watched.id, execution_id)] {
+            state
+                .publish_run_event(RunLifecycleEvent {
+                    execution_id,
+                    task_id,
+                    status: ExecutionStatus::Completed,
+                })
+                .await;
+        }
+
+        let digests = tokio::time::timeout(Duration::from_secs(2), async {
+            loop {
+                let digests = collector.drain();
+                if !digests.is_empty() {
+                    return digests;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(digests.len(), 1);
+        assert_eq!(digests[0].watcher, "alice");
+        let entries = &digests[0].entries;
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].task_id, watched.id);
+        assert_eq!(entries[0].execution_id, execution_id);
+        assert_eq!(entries[0].status, ExecutionStatus::Completed);
+        assert!(collector.drain().is_empty());
+    }
+}
@@ -0,0 +1,57 @@
+//! Settings read from the process environment
+//!
+//! Unset variables fall back to their defaults. Values that fail to parse
+//! are logged and fall back too, so a typo in an optional setting does not
+//! stop the server.
+
+use std::str::FromStr;
+
+/// Value of `name` parsed as `T`, or `None` when unset or invalid
+pub fn env_parse<T: FromStr>(name: &str) -> Option<T> {
+    parse_env(name, |_| true)
+}
+
+/// Value of `name` parsed as `T`, or `default` when unset or invalid
+pub fn env_or_default<T: FromStr>(name: &str, default: T) -> T {
+    env_parse(name).unwrap_or(default)
+}
+
+/// Like `env_or_default`, for counts and sizes that must be positive
+pub fn positive_env_or_default<T: FromStr + PartialOrd + Default>(name: &str, default: T) -> T {
+    parse_env(name, |value| *value > T::default()).unwrap_or(default)
+}
+
+fn parse_env<T: FromStr>(name: &str, valid: impl Fn(&T) -> bool) -> Option<T> {
+    let raw = std::env::var(name).ok()?;
+    match raw.trim().parse() {
+        Ok(value) if valid(&value) => Some(value),
+        _ => {
+            tracing::warn!("Ignoring invalid {} value {:?}", name, raw);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_values_fall_back_to_the_default() {
+        const NAME: &str = "VK_TEST_ENV_OR_DEFAULT";
+        assert_eq!(env_or_default(NAME, 7usize), 7);
+
+        std::env::set_var(NAME, " 42 ");
+        assert_eq!(env_or_default(NAME, 7usize), 42);
+        assert_eq!(env_parse::<u64>(NAME), Some(42));
+
+        std::env::set_var(NAME, "0");
+        assert_eq!(env_or_default(NAME, 7usize), 0);
+        assert_eq!(positive_env_or_default(NAME, 7usize), 7);
+
+        std::env::set_var(NAME, "lots");
+        assert_eq!(env_or_default(NAME, 7usize), 7);
+        assert_eq!(env_parse::<u64>(NAME), None);
+        std::env::remove_var(NAME);
+    }
+}
@@ -0,0 +1,154 @@
+//! JSON body extractor with structured deserialization errors
+//!
+//! `axum::Json` rejects bad payloads with an opaque plain-text message.
+//! `JsonBody` answers `422 Unprocessable Entity` instead, naming the field
+//! that failed to deserialize and why, so clients can point at the input.
+
+use axum::{
+    body::Bytes,
+    extract::{FromRequest, Request},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// JSON request body that reports deserialization failures per field
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonBody<T>(pub T);
+
+/// Body of a rejected JSON payload
+#[derive(Debug, Serialize)]
+pub struct ValidationErrorResponse {
+    pub error: String,
+    /// Path of the offending field, e.g. `labels.env` or `tags[1]`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    pub reason: String,
+}
+
+fn reject(status: StatusCode, error: &str, field: Option<String>, reason: String) -> Response {
+    (
+        status,
+        Json(ValidationErrorResponse {
+            error: error.to_string(),
+            field,
+            reason,
+        }),
+    )
+        .into_response()
+}
+
+fn is_json_content_type(req: &Request) -> bool {
+    req.headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .map(|mime| {
+            let mime = mime.trim();
+            mime == "application/json" || (mime.starts_with("application/") && mime.ends_with("+json"))
+        })
+        .unwrap_or(false)
+}
+
+impl<T, S> FromRequest<S> for JsonBody<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        if !is_json_content_type(&req) {
+            return Err(reject(
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                "Unsupported content type",
+                None,
+                "Expected request with `Content-Type: application/json`".to_string(),
+            ));
+        }
+
+        let bytes = Bytes::from_request(req, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+
+        let deserializer = &mut serde_json::Deserializer::from_slice(&bytes);
+        match serde_path_to_error::deserialize(deserializer) {
+            Ok(value) => Ok(Self(value)),
+            Err(e) => {
+                let path = e.path().to_string();
+                let field = (path != ".").then_some(path);
+                let inner = e.into_inner();
+                if inner.is_data() {
+                    Err(reject(
+                        StatusCode::UNPROCESSABLE_ENTITY,
+                        "Invalid request body",
+                        field,
+                        inner.to_string(),
+                    ))
+                } else {
+                    Err(reject(
+                        StatusCode::BAD_REQUEST,
+                        "Malformed JSON body",
+                        None,
+                        inner.to_string(),
+                    ))
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::{to_bytes, Body},
+        routing::post,
+        Router,
+    };
+    use serde::Deserialize;
+    use tower::ServiceExt;
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Payload {
+        title: String,
+        max_retries: Option<u32>,
+    }
+
+    async fn post_json(body: &str) -> (StatusCode, serde_json::Value) {
+        let app = Router::new().route("/", post(|JsonBody(payload): JsonBody<Payload>| async move {
+            format!("{} {:?}", payload.title, payload.max_retries)
+        }));
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/")
+                    .header("Content-Type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap_or_default())
+    }
+
+    #[tokio::test]
+    async fn wrong_typed_field_is_named_in_the_error() {
+        let (status, body) = post_json(r#"{"title":"t","maxRetries":"three"}"#).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["field"], "maxRetries");
+        assert!(body["reason"].as_str().unwrap().contains("invalid type"));
+
+        let (status, body) = post_json(r#"{"title":"t""#).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body.get("field").is_none());
+
+        let (status, _) = post_json(r#"{"title":"t"}"#).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+}
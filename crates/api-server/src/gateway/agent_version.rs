@@ -0,0 +1,101 @@
+//! Minimum agent CLI versions for gateway hosts
+//!
+//! Hosts report the version of each agent CLI at handshake. When
+//! `VK_MIN_AGENT_VERSIONS` is set (e.g. `opencode=0.5.0,claude-code=1.2`),
+//! the server warns about hosts running an older CLI. Hosts are still
+//! registered; the warning is a debugging aid.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// Environment variable listing minimum agent versions as `agent=version` pairs
+pub const MIN_AGENT_VERSIONS_ENV: &str = "VK_MIN_AGENT_VERSIONS";
+
+/// Parse a comma-separated list of `agent=version` pairs, skipping invalid entries
+pub fn parse_min_versions(spec: &str) -> HashMap<String, String> {
+    spec.split(',')
+        .filter_map(|entry| {
+            let (agent, version) = entry.split_once('=')?;
+            let (agent, version) = (agent.trim(), version.trim());
+            (!agent.is_empty() && !version.is_empty())
+                .then(|| (agent.to_string(), version.to_string()))
+        })
+        .collect()
+}
+
+/// Load the minimum agent versions from the process environment
+pub fn min_versions_from_env() -> HashMap<String, String> {
+    std::env::var(MIN_AGENT_VERSIONS_ENV)
+        .map(|spec| parse_min_versions(&spec))
+        .unwrap_or_default()
+}
+
+/// Numeric components of a version such as `v1.2.3-beta`
+fn components(version: &str) -> Vec<u64> {
+    let version = version.trim().trim_start_matches('v');
+    let release = version.split(['-', '+', ' ']).next().unwrap_or_default();
+    release
+        .split('.')
+        .map(|part| {
+            let digits: String = part.chars().take_while(char::is_ascii_digit).collect();
+            digits.parse().unwrap_or(0)
+        })
+        .collect()
+}
+
+/// Compare two dotted versions numerically, treating missing parts as zero
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let (a, b) = (components(a), components(b));
+    let len = a.len().max(b.len());
+    (0..len)
+        .map(|i| {
+            let x = a.get(i).copied().unwrap_or(0);
+            let y = b.get(i).copied().unwrap_or(0);
+            x.cmp(&y)
+        })
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+/// Agents whose reported version is below the configured minimum
+///
+/// Returns `(agent, reported, minimum)` for each outdated agent.
+pub fn outdated_agents(
+    reported: &HashMap<String, String>,
+    minimums: &HashMap<String, String>,
+) -> Vec<(String, String, String)> {
+    let mut outdated: Vec<_> = reported
+        .iter()
+        .filter_map(|(agent, version)| {
+            let minimum = minimums.get(agent)?;
+            (compare_versions(version, minimum) == Ordering::Less)
+                .then(|| (agent.clone(), version.clone(), minimum.clone()))
+        })
+        .collect();
+    outdated.sort();
+    outdated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_compare_numerically() {
+        assert_eq!(compare_versions("0.10.0", "0.9.9"), Ordering::Greater);
+        assert_eq!(compare_versions("v1.2", "1.2.0"), Ordering::Equal);
+        assert_eq!(compare_versions("1.2.3-beta", "1.2.4"), Ordering::Less);
+
+        let minimums = parse_min_versions("opencode=0.5.0, claude-code=1.2 ,broken");
+        assert_eq!(minimums.len(), 2);
+        let reported = HashMap::from([
+            ("opencode".to_string(), "0.4.9".to_string()),
+            ("claude-code".to_string(), "1.3.0".to_string()),
+            ("gemini".to_string(), "0.1.0".to_string()),
+        ]);
+        assert_eq!(
+            outdated_agents(&reported, &minimums),
+            vec![("opencode".to_string(), "0.4.9".to_string(), "0.5.0".to_string())]
+        );
+    }
+}
@@ -0,0 +1,109 @@
+//! Dead-lettered gateway tasks
+//!
+//! A queued task whose delivery to its host fails is parked here instead of
+//! being dropped. Redelivery is attempted with capped exponential backoff
+//! until it succeeds or an operator forces a retry.
+
+use std::time::Duration;
+
+use serde::Serialize;
+use vk_core::task::TaskPriority;
+
+use super::protocol::GatewayTaskRequest;
+
+/// Delay before the first redelivery
+pub const BASE_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Longest delay between redeliveries
+pub const MAX_RETRY_DELAY: Duration = Duration::from_secs(5 * 60);
+
+/// Delay before the next redelivery after `attempts` failed deliveries
+pub fn retry_delay(attempts: u32) -> Duration {
+    let doublings = attempts.saturating_sub(1).min(16);
+    BASE_RETRY_DELAY
+        .saturating_mul(1 << doublings)
+        .min(MAX_RETRY_DELAY)
+}
+
+/// A task that could not be delivered to its host
+pub struct DeadLetter {
+    pub task: GatewayTaskRequest,
+    pub priority: TaskPriority,
+    pub host_id: String,
+    /// Failed deliveries so far
+    pub attempts: u32,
+    pub last_error: String,
+    /// Unix timestamp (ms) when the task was dead-lettered
+    pub dead_lettered_at: u64,
+    /// Unix timestamp (ms) of the next redelivery
+    pub next_retry_at: u64,
+}
+
+impl DeadLetter {
+    pub fn new(task: GatewayTaskRequest, priority: TaskPriority, host_id: String, error: String, now: u64) -> Self {
+        let mut letter = Self {
+            task,
+            priority,
+            host_id,
+            attempts: 0,
+            last_error: String::new(),
+            dead_lettered_at: now,
+            next_retry_at: now,
+        };
+        letter.record_failure(error, now);
+        letter
+    }
+
+    /// Count a failed delivery and schedule the next one
+    pub fn record_failure(&mut self, error: String, now: u64) {
+        self.attempts += 1;
+        self.last_error = error;
+        self.next_retry_at = now + retry_delay(self.attempts).as_millis() as u64;
+    }
+
+    /// Whether the next redelivery is due at `now`
+    pub fn is_due(&self, now: u64) -> bool {
+        self.next_retry_at <= now
+    }
+
+    pub fn status(&self) -> DeadLetterStatus {
+        DeadLetterStatus {
+            task_id: self.task.task_id.clone(),
+            host_id: self.host_id.clone(),
+            agent_type: self.task.agent_type.clone(),
+            attempts: self.attempts,
+            last_error: self.last_error.clone(),
+            dead_lettered_at: self.dead_lettered_at,
+            next_retry_at: self.next_retry_at,
+        }
+    }
+}
+
+/// Redelivery state of a dead-lettered task
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeadLetterStatus {
+    pub task_id: String,
+    pub host_id: String,
+    pub agent_type: String,
+    pub attempts: u32,
+    pub last_error: String,
+    /// Unix timestamp (ms) when the task was dead-lettered
+    pub dead_lettered_at: u64,
+    /// Unix timestamp (ms) of the next redelivery
+    pub next_retry_at: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_delay_doubles_up_to_the_cap() {
+        assert_eq!(retry_delay(1), Duration::from_secs(5));
+        assert_eq!(retry_delay(2), Duration::from_secs(10));
+        assert_eq!(retry_delay(4), Duration::from_secs(40));
+        assert_eq!(retry_delay(7), MAX_RETRY_DELAY);
+        assert_eq!(retry_delay(u32::MAX), MAX_RETRY_DELAY);
+    }
+}
@@ -0,0 +1,98 @@
+//! Retries of gateway dispatches that failed transiently
+//!
+//! A host that briefly drops its connection or has a full outbound channel
+//! fails a dispatch without receiving anything, so the dispatch is retried a
+//! few times with doubling backoff. Permanent failures, such as a host that
+//! does not run the requested agent, are returned at once.
+
+use std::time::Duration;
+
+use crate::env::{env_parse, positive_env_or_default};
+
+/// Environment variable with the dispatch attempts, including the first
+pub const DISPATCH_RETRY_ATTEMPTS_ENV: &str = "VK_DISPATCH_RETRY_ATTEMPTS";
+
+/// Environment variable with the delay (ms) before the first retry
+pub const DISPATCH_RETRY_BACKOFF_ENV: &str = "VK_DISPATCH_RETRY_BACKOFF_MS";
+
+const DEFAULT_ATTEMPTS: u32 = 3;
+const DEFAULT_BACKOFF: Duration = Duration::from_millis(100);
+
+/// How often and how patiently a transiently failed dispatch is retried
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DispatchRetry {
+    /// Dispatch attempts, including the first; at least 1
+    pub attempts: u32,
+    /// Delay before the first retry, doubled for each further retry
+    pub backoff: Duration,
+}
+
+impl Default for DispatchRetry {
+    fn default() -> Self {
+        Self {
+            attempts: DEFAULT_ATTEMPTS,
+            backoff: DEFAULT_BACKOFF,
+        }
+    }
+}
+
+impl DispatchRetry {
+    /// Delay before retry number `retry` (1-based)
+    pub fn delay(&self, retry: u32) -> Duration {
+        self.backoff.saturating_mul(1 << retry.saturating_sub(1).min(16))
+    }
+}
+
+/// Why a dispatch failed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DispatchError {
+    /// The host is missing or busy, and a later attempt may succeed
+    Transient(String),
+    /// Sending to the host failed, so nothing reached it; worth retrying
+    Undelivered(String),
+    /// The host has not sent a heartbeat recently but may still send one
+    Stale(String),
+    /// Retrying cannot help
+    Permanent(String),
+}
+
+impl DispatchError {
+    pub fn is_transient(&self) -> bool {
+        !matches!(self, Self::Permanent(_))
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Self::Transient(message)
+            | Self::Undelivered(message)
+            | Self::Stale(message)
+            | Self::Permanent(message) => message,
+        }
+    }
+}
+
+impl std::fmt::Display for DispatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl From<DispatchError> for String {
+    fn from(error: DispatchError) -> Self {
+        match error {
+            DispatchError::Transient(message)
+            | DispatchError::Undelivered(message)
+            | DispatchError::Stale(message)
+            | DispatchError::Permanent(message) => message,
+        }
+    }
+}
+
+/// Load the dispatch retry settings from the process environment
+pub fn dispatch_retry_from_env() -> DispatchRetry {
+    let defaults = DispatchRetry::default();
+    DispatchRetry {
+        attempts: positive_env_or_default(DISPATCH_RETRY_ATTEMPTS_ENV, defaults.attempts),
+        backoff: env_parse(DISPATCH_RETRY_BACKOFF_ENV).map_or(defaults.backoff, Duration::from_millis),
+    }
+}
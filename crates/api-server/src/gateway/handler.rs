@@ -3,11 +3,12 @@
 use axum::{
     extract::{
         ws::{Message, WebSocket, WebSocketUpgrade},
-        Query, State,
+        ConnectInfo, Query, State,
     },
     http::{header::AUTHORIZATION, HeaderMap, StatusCode},
     response::Response,
     response::IntoResponse,
+    Extension,
 };
 use futures::{SinkExt, StreamExt};
 use std::sync::Arc;
@@ -15,7 +16,9 @@ use tokio::sync::mpsc;
 use tracing::{debug, error, info, warn};
 
 use super::manager::GatewayManager;
+use super::mtls::GatewayPeer;
 use super::protocol::*;
+use crate::env::env_or_default;
 
 const DEFAULT_GATEWAY_AUTH_TOKEN: &str = "dev-token";
 
@@ -51,14 +54,36 @@ pub async fn gateway_ws_handler(
     ws: WebSocketUpgrade,
     Query(query): Query<WsQuery>,
     State(manager): State<Arc<GatewayManager>>,
+    peer: Option<Extension<ConnectInfo<GatewayPeer>>>,
     headers: HeaderMap,
 ) -> Response {
-    if !is_gateway_authorized(&headers, &expected_gateway_auth_token()) {
-        warn!(
-            "Rejected gateway connection due to invalid token: {}",
-            query.host_id
-        );
-        return (StatusCode::UNAUTHORIZED, "Unauthorized").into_response();
+    match peer {
+        // Connected over mTLS: the verified certificate must name this host
+        Some(Extension(ConnectInfo(peer))) => {
+            if peer.subject_cn.as_deref() != Some(query.host_id.as_str()) {
+                warn!(
+                    "Rejected gateway connection from {}: certificate subject {:?} does not match host {}",
+                    peer.addr, peer.subject_cn, query.host_id
+                );
+                return (StatusCode::FORBIDDEN, "Certificate does not match host").into_response();
+            }
+        }
+        None if manager.mtls_required() => {
+            warn!(
+                "Rejected gateway connection without client certificate: {}",
+                query.host_id
+            );
+            return (StatusCode::UNAUTHORIZED, "Client certificate required").into_response();
+        }
+        None => {
+            if !is_gateway_authorized(&headers, &expected_gateway_auth_token()) {
+                warn!(
+                    "Rejected gateway connection due to invalid token: {}",
+                    query.host_id
+                );
+                return (StatusCode::UNAUTHORIZED, "Unauthorized").into_response();
+            }
+        }
     }
 
     info!("New gateway connection request from host: {}", query.host_id);
@@ -151,6 +176,13 @@ async fn handle_gateway_message(
     msg: GatewayToServerMessage,
     tx: mpsc::Sender<ServerToGatewayMessage>,
 ) {
+    // A host may only report on tasks dispatched to it
+    if let Some((task_id, message_type)) = msg.task_report() {
+        if !manager.accept_task_message(host_id, task_id, message_type).await {
+            return;
+        }
+    }
+
     match msg {
         GatewayToServerMessage::Register {
             host_id: msg_host_id,
@@ -210,6 +242,13 @@ async fn handle_gateway_message(
         } => {
             manager.handle_models_response(&request_id, providers).await;
         }
+
+        GatewayToServerMessage::TasksQuery { task_ids } => {
+            let (active, abandoned) = manager.reconcile_host_tasks(host_id, &task_ids).await;
+            let _ = tx
+                .send(ServerToGatewayMessage::TasksStatus { active, abandoned })
+                .await;
+        }
     }
 }
 
@@ -220,12 +259,52 @@ pub fn start_heartbeat_checker(manager: Arc<GatewayManager>) {
         loop {
             interval.tick().await;
             manager
-                .cleanup_stale_connections(std::time::Duration::from_secs(90))
+                .cleanup_stale_connections(super::manager::HEARTBEAT_TIMEOUT)
                 .await;
         }
     });
 }
 
+/// Environment variable setting how long a run may go without events (seconds)
+pub const STALL_TIMEOUT_ENV: &str = "VK_RUN_STALL_TIMEOUT_SECS";
+
+/// Default window after which a silent run is considered stalled
+const DEFAULT_STALL_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(15 * 60);
+
+/// Load the stall window from the process environment; `0` disables detection
+pub fn stall_timeout_from_env() -> Option<std::time::Duration> {
+    match env_or_default(STALL_TIMEOUT_ENV, DEFAULT_STALL_TIMEOUT.as_secs()) {
+        0 => None,
+        secs => Some(std::time::Duration::from_secs(secs)),
+    }
+}
+
+/// Start the background sweep that fails runs silent for longer than `window`
+pub fn start_stall_detector(manager: Arc<GatewayManager>, window: std::time::Duration) {
+    let period = (window / 2).clamp(
+        std::time::Duration::from_secs(1),
+        std::time::Duration::from_secs(30),
+    );
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+            manager.sweep_stalled_tasks(window).await;
+        }
+    });
+}
+
+/// Start the background task redelivering dead-lettered tasks once due
+pub fn start_dead_letter_redelivery(manager: Arc<GatewayManager>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(1));
+        loop {
+            interval.tick().await;
+            manager.redeliver_dead_letters().await;
+        }
+    });
+}
+
 /// List all connected hosts (for REST API)
 pub async fn list_hosts_handler(
     State(manager): State<Arc<GatewayManager>>,
@@ -287,4 +366,156 @@ mod tests {
 
         assert!(!is_gateway_authorized(&headers, "secret-token"));
     }
+
+    fn create_test_capabilities() -> HostCapabilities {
+        HostCapabilities {
+            name: "Test Host".to_string(),
+            agents: vec!["opencode".to_string()],
+            max_concurrent: 2,
+            cwd: "/tmp".to_string(),
+            labels: Default::default(),
+            agent_versions: Default::default(),
+        }
+    }
+
+    fn create_test_task(task_id: &str) -> GatewayTaskRequest {
+        GatewayTaskRequest {
+            task_id: task_id.to_string(),
+            prompt: "prompt".to_string(),
+            cwd: "/tmp".to_string(),
+            agent_type: "opencode".to_string(),
+            model: None,
+            env: Default::default(),
+            timeout: None,
+            metadata: serde_json::Value::Null,
+        }
+    }
+
+    #[tokio::test]
+    async fn task_event_from_another_host_is_rejected_and_audited() {
+        let manager = GatewayManager::new();
+        let capabilities = create_test_capabilities();
+        let (tx1, _rx1) = mpsc::channel(10);
+        let (tx2, _rx2) = mpsc::channel(10);
+        manager.register_host("host-1".to_string(), capabilities.clone(), tx1.clone()).await;
+        manager.register_host("host-2".to_string(), capabilities, tx2.clone()).await;
+        manager
+            .dispatch_task_to_host("host-1", create_test_task("task-1"))
+            .await
+            .unwrap();
+        let mut events = manager.subscribe();
+
+        let event = |content: &str| GatewayToServerMessage::TaskEvent {
+            task_id: "task-1".to_string(),
+            event: GatewayAgentEvent {
+                event_type: GatewayAgentEventType::Message,
+                content: Some(content.to_string()),
+                data: serde_json::Value::Null,
+                timestamp: 0,
+            },
+        };
+        handle_gateway_message(&manager, "host-2", event("spoofed"), tx2).await;
+        handle_gateway_message(&manager, "host-1", event("genuine"), tx1).await;
+
+        let received = events.recv().await.unwrap();
+        assert_eq!(received.host_id, "host-1");
+        assert_eq!(received.event.content.as_deref(), Some("genuine"));
+        assert!(events.try_recv().is_err());
+
+        let rejected = manager.rejected_task_messages();
+        assert_eq!(rejected.len(), 1);
+        assert_eq!(rejected[0].host_id, "host-2");
+        assert_eq!(rejected[0].task_id, "task-1");
+        assert_eq!(rejected[0].message_type, "task:event");
+    }
+
+    #[tokio::test]
+    async fn task_query_after_reconnect_reports_active_tasks() {
+        let manager = GatewayManager::new();
+        let capabilities = create_test_capabilities();
+        let (tx, _rx) = mpsc::channel(10);
+        manager
+            .register_host("host-1".to_string(), capabilities.clone(), tx)
+            .await;
+        for task_id in ["task-1", "task-2"] {
+            manager.dispatch_task(create_test_task(task_id)).await.unwrap();
+        }
+
+        // The host drops and reconnects, still running task-1 plus an unknown task
+        manager.unregister_host("host-1").await;
+        let (tx, mut rx) = mpsc::channel(10);
+        manager
+            .register_host("host-1".to_string(), capabilities, tx.clone())
+            .await;
+        let mut events = manager.subscribe();
+
+        let query = GatewayToServerMessage::TasksQuery {
+            task_ids: vec!["task-1".to_string(), "task-9".to_string()],
+        };
+        handle_gateway_message(&manager, "host-1", query, tx).await;
+
+        match rx.recv().await {
+            Some(ServerToGatewayMessage::TasksStatus { active, abandoned }) => {
+                assert_eq!(active, vec!["task-1".to_string()]);
+                assert_eq!(abandoned, vec!["task-9".to_string()]);
+            }
+            other => panic!("expected tasks:status, got {:?}", other),
+        }
+        let hosts = manager.list_hosts().await;
+        assert_eq!(hosts[0].active_tasks, vec!["task-1".to_string()]);
+
+        // The task the host no longer runs is failed
+        let failed = events.recv().await.unwrap();
+        assert_eq!(failed.task_id, "task-2");
+        assert!(matches!(failed.event.event_type, GatewayAgentEventType::Failed));
+    }
+
+    #[tokio::test]
+    async fn tasks_of_a_timed_out_host_are_adopted_on_reconnect() {
+        let manager = GatewayManager::new();
+        let capabilities = create_test_capabilities();
+        let (tx, _rx) = mpsc::channel(10);
+        manager
+            .register_host("host-1".to_string(), capabilities.clone(), tx)
+            .await;
+        manager.dispatch_task(create_test_task("task-1")).await.unwrap();
+
+        // The heartbeat checker drops the silent host, which then reconnects
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        manager
+            .cleanup_stale_connections(std::time::Duration::from_millis(1))
+            .await;
+        assert_eq!(manager.host_count().await, 0);
+        let (tx, mut rx) = mpsc::channel(10);
+        manager
+            .register_host("host-1".to_string(), capabilities, tx.clone())
+            .await;
+
+        let query = GatewayToServerMessage::TasksQuery {
+            task_ids: vec!["task-1".to_string()],
+        };
+        handle_gateway_message(&manager, "host-1", query, tx.clone()).await;
+        match rx.recv().await {
+            Some(ServerToGatewayMessage::TasksStatus { active, abandoned }) => {
+                assert_eq!(active, vec!["task-1".to_string()]);
+                assert!(abandoned.is_empty());
+            }
+            other => panic!("expected tasks:status, got {:?}", other),
+        }
+
+        // Events of the adopted task are accepted again
+        let mut events = manager.subscribe();
+        let event = GatewayToServerMessage::TaskEvent {
+            task_id: "task-1".to_string(),
+            event: GatewayAgentEvent {
+                event_type: GatewayAgentEventType::Message,
+                content: Some("still running".to_string()),
+                data: serde_json::Value::Null,
+                timestamp: 0,
+            },
+        };
+        handle_gateway_message(&manager, "host-1", event, tx).await;
+        assert_eq!(events.recv().await.unwrap().task_id, "task-1");
+        assert!(manager.rejected_task_messages().is_empty());
+    }
 }
@@ -0,0 +1,174 @@
+//! Journal of published gateway events
+//!
+//! Recent events of each task are kept so a subscriber that lagged behind
+//! the broadcast channel can replay what it missed. Each task's journal is
+//! capped by event count and by bytes. It is dropped shortly after the task
+//! completes or fails, or once no event arrived for a long while, so tasks
+//! whose subscriber never forgets them do not leak.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use super::manager::BroadcastTaskEvent;
+use super::protocol::{GatewayAgentEvent, GatewayAgentEventType};
+
+/// Events kept per task
+const MAX_EVENTS_PER_TASK: usize = 10_000;
+
+/// Event bytes kept per task
+const MAX_BYTES_PER_TASK: usize = 8 * 1024 * 1024;
+
+/// How long a finished task's journal stays for subscribers still catching up
+const FINISHED_GRACE: Duration = Duration::from_secs(60);
+
+/// Journals without events for this long are dropped
+const IDLE_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Time between sweeps for expired journals
+const PRUNE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Approximate bytes an event holds in the journal
+fn event_size(event: &BroadcastTaskEvent) -> usize {
+    let size = |event: &GatewayAgentEvent| {
+        event.content.as_ref().map_or(0, String::len)
+            + serde_json::to_string(&event.data).map_or(0, |data| data.len())
+    };
+    size(&event.event) + event.original.as_ref().map_or(0, size)
+}
+
+#[derive(Default)]
+struct TaskJournal {
+    events: VecDeque<(BroadcastTaskEvent, usize)>,
+    bytes: usize,
+    last_published: Option<Instant>,
+    finished_at: Option<Instant>,
+}
+
+impl TaskJournal {
+    fn is_expired(&self, now: Instant) -> bool {
+        let since = |at: Option<Instant>| at.map(|at| now.saturating_duration_since(at));
+        since(self.finished_at).is_some_and(|age| age > FINISHED_GRACE)
+            || since(self.last_published).is_some_and(|age| age > IDLE_TTL)
+    }
+}
+
+/// Recent events of each task, for subscribers that lagged behind
+#[derive(Default)]
+pub struct EventJournal {
+    pub last_seq: u64,
+    tasks: HashMap<String, TaskJournal>,
+    last_pruned: Option<Instant>,
+}
+
+impl EventJournal {
+    /// Number `event` and keep it, returning it with its sequence number
+    pub fn push(&mut self, mut event: BroadcastTaskEvent, now: Instant) -> BroadcastTaskEvent {
+        self.prune(now);
+        self.last_seq += 1;
+        event.seq = self.last_seq;
+
+        let size = event_size(&event);
+        let journal = self.tasks.entry(event.task_id.clone()).or_default();
+        while !journal.events.is_empty()
+            && (journal.events.len() >= MAX_EVENTS_PER_TASK || journal.bytes + size > MAX_BYTES_PER_TASK)
+        {
+            if let Some((_, dropped)) = journal.events.pop_front() {
+                journal.bytes -= dropped;
+            }
+        }
+        journal.last_published = Some(now);
+        if matches!(
+            event.event.event_type,
+            GatewayAgentEventType::Completed | GatewayAgentEventType::Failed
+        ) {
+            journal.finished_at = Some(now);
+        }
+        journal.bytes += size;
+        journal.events.push_back((event.clone(), size));
+        event
+    }
+
+    /// Journaled events of a task after `seq`
+    pub fn events_since(&self, task_id: &str, seq: u64) -> Vec<BroadcastTaskEvent> {
+        self.tasks
+            .get(task_id)
+            .map(|journal| {
+                journal
+                    .events
+                    .iter()
+                    .filter(|(event, _)| event.seq > seq)
+                    .map(|(event, _)| event.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Drop a task's journal
+    pub fn forget(&mut self, task_id: &str) {
+        self.tasks.remove(task_id);
+    }
+
+    /// Drop expired journals, at most once per `PRUNE_INTERVAL`
+    fn prune(&mut self, now: Instant) {
+        if self
+            .last_pruned
+            .is_some_and(|at| now.saturating_duration_since(at) < PRUNE_INTERVAL)
+        {
+            return;
+        }
+        self.last_pruned = Some(now);
+        self.tasks.retain(|_, journal| !journal.is_expired(now));
+    }
+
+    #[cfg(test)]
+    fn task_bytes(&self, task_id: &str) -> Option<usize> {
+        self.tasks.get(task_id).map(|journal| journal.bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(task_id: &str, event_type: GatewayAgentEventType, content: String) -> BroadcastTaskEvent {
+        BroadcastTaskEvent {
+            task_id: task_id.to_string(),
+            host_id: "host-1".to_string(),
+            event: GatewayAgentEvent {
+                event_type,
+                content: Some(content),
+                data: serde_json::Value::Null,
+                timestamp: 0,
+            },
+            original: None,
+            seq: 0,
+        }
+    }
+
+    #[test]
+    fn journal_is_capped_by_bytes_and_dropped_after_the_task_ends() {
+        let mut journal = EventJournal::default();
+        let start = Instant::now();
+        let chunk = "x".repeat(1024 * 1024);
+        for _ in 0..12 {
+            journal.push(event("task-1", GatewayAgentEventType::Stdout, chunk.clone()), start);
+        }
+        assert!(journal.task_bytes("task-1").unwrap() <= MAX_BYTES_PER_TASK);
+        assert_eq!(journal.events_since("task-1", 0).len(), 7);
+        assert_eq!(journal.events_since("task-1", 0).last().unwrap().seq, 12);
+
+        journal.push(event("task-1", GatewayAgentEventType::Completed, String::new()), start);
+        let later = start + FINISHED_GRACE / 2;
+        journal.push(event("task-2", GatewayAgentEventType::Stdout, String::new()), later);
+        assert!(!journal.events_since("task-1", 0).is_empty());
+
+        let after_grace = start + FINISHED_GRACE + PRUNE_INTERVAL * 2;
+        journal.push(event("task-2", GatewayAgentEventType::Stdout, String::new()), after_grace);
+        assert!(journal.events_since("task-1", 0).is_empty());
+        assert_eq!(journal.events_since("task-2", 0).len(), 2);
+
+        // Journals without events for long are dropped too
+        journal.push(event("task-3", GatewayAgentEventType::Stdout, String::new()), after_grace + IDLE_TTL * 2);
+        assert!(journal.events_since("task-2", 0).is_empty());
+    }
+}
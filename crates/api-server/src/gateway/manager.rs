@@ -1,14 +1,22 @@
 //! Gateway Manager - manages connections to remote Agent Gateways
 
-use std::collections::HashMap;
-use std::sync::Arc;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use tokio::sync::{broadcast, mpsc, oneshot, RwLock};
 use tracing::{debug, error, info, warn};
 
+use super::agent_version::{min_versions_from_env, outdated_agents};
+use super::dead_letter::{DeadLetter, DeadLetterStatus};
+use super::dispatch_retry::{dispatch_retry_from_env, DispatchError, DispatchRetry};
+use super::journal::EventJournal;
+use super::output::{event_max_bytes_from_env, truncate_content};
 use super::protocol::*;
-use vk_core::kanban::{KanbanStore, KanbanTaskStatus};
-use vk_core::task::{FileTaskStore, TaskRepository, TaskStatus};
+use crate::env::positive_env_or_default;
+use crate::redaction::redactor_from_env;
+use agent_runner::Redactor;
+use vk_core::kanban::{KanbanAutoMovePolicy, KanbanStore, RunTransition};
+use vk_core::task::{FileTaskStore, TaskPriority, TaskRepository};
 
 /// Host connection state
 pub struct HostConnection {
@@ -16,16 +24,181 @@ pub struct HostConnection {
     pub capabilities: HostCapabilities,
     pub tx: mpsc::Sender<ServerToGatewayMessage>,
     pub active_tasks: Vec<String>,
+    pub in_flight: HashMap<String, InFlightTask>,
+    /// Tasks waiting for capacity, highest priority first
+    pub pending: Vec<QueuedTask>,
     pub last_heartbeat: Instant,
     pub connected_at: Instant,
 }
 
+/// A task waiting for a slot on a saturated host
+pub struct QueuedTask {
+    pub task: GatewayTaskRequest,
+    pub priority: TaskPriority,
+}
+
+/// Result of dispatching a task that may be queued
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DispatchOutcome {
+    /// Sent to the host immediately
+    Dispatched(String),
+    /// Host is saturated; the task waits at `position` in its queue
+    Queued { host_id: String, position: usize },
+}
+
 impl HostConnection {
     /// Check if host is available for the given agent type
+    #[allow(dead_code)]
     pub fn is_available(&self, agent_type: &str) -> bool {
         self.capabilities.agents.contains(&agent_type.to_string())
             && (self.active_tasks.len() as u32) < self.capabilities.max_concurrent
     }
+
+    /// Record a task as dispatched to this host
+    fn track_task(&mut self, task_id: &str) {
+        self.active_tasks.push(task_id.to_string());
+        self.in_flight.insert(
+            task_id.to_string(),
+            InFlightTask {
+                task_id: task_id.to_string(),
+                dispatched_at: unix_millis(),
+                started_at: None,
+                session_id: None,
+                last_event_at: None,
+            },
+        );
+    }
+
+    /// Forget a task once it has finished or failed to dispatch
+    fn release_task(&mut self, task_id: &str) {
+        self.active_tasks.retain(|id| id != task_id);
+        self.in_flight.remove(task_id);
+    }
+
+    /// Check if the host has a free execution slot
+    fn has_capacity(&self) -> bool {
+        (self.active_tasks.len() as u32) < self.capabilities.max_concurrent
+    }
+
+    /// Queue position a task of this priority would take
+    fn queue_position(&self, priority: TaskPriority) -> usize {
+        self.pending
+            .iter()
+            .position(|queued| queued.priority < priority)
+            .unwrap_or(self.pending.len())
+    }
+
+    /// Queue a task behind all pending tasks of equal or higher priority
+    fn enqueue(&mut self, task: GatewayTaskRequest, priority: TaskPriority) -> usize {
+        let position = self.queue_position(priority);
+        self.pending.insert(position, QueuedTask { task, priority });
+        position
+    }
+
+    /// Reject agent types the host did not advertise
+    fn check_agent(&self, agent_type: &str) -> Result<(), String> {
+        if self.capabilities.agents.iter().any(|agent| agent == agent_type) {
+            Ok(())
+        } else {
+            Err(format!(
+                "Host {} does not support agent type: {}",
+                self.host_id, agent_type
+            ))
+        }
+    }
+
+    /// Reject hosts that have not sent a heartbeat within `freshness`
+    fn check_heartbeat(&self, freshness: Duration) -> Result<(), DispatchError> {
+        let silent = self.last_heartbeat.elapsed();
+        if silent > freshness {
+            Err(DispatchError::Stale(format!(
+                "Stale heartbeat from host {}: last seen {}s ago",
+                self.host_id,
+                silent.as_secs()
+            )))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Whether the host carries every required label with the required value
+    fn matches_labels(&self, required: &HashMap<String, String>) -> bool {
+        required
+            .iter()
+            .all(|(key, value)| self.capabilities.labels.get(key) == Some(value))
+    }
+
+    /// Dispatch queued tasks while the host has free slots
+    ///
+    /// Never waits on the host's channel, as callers hold the connections
+    /// lock; a full channel leaves the rest queued. Returns the tasks that
+    /// could not be sent, with the error.
+    fn drain_pending(&mut self) -> Vec<(QueuedTask, String)> {
+        let mut undelivered = Vec::new();
+        while self.has_capacity() && !self.pending.is_empty() {
+            let queued = self.pending.remove(0);
+            let task_id = queued.task.task_id.clone();
+            self.track_task(&task_id);
+
+            match self
+                .tx
+                .try_send(ServerToGatewayMessage::TaskExecute { task: queued.task })
+            {
+                Ok(()) => info!("Queued task {} dispatched to host {}", task_id, self.host_id),
+                Err(mpsc::error::TrySendError::Full(message)) => {
+                    warn!("Host {} channel is full, keeping task {} queued", self.host_id, task_id);
+                    self.release_task(&task_id);
+                    if let ServerToGatewayMessage::TaskExecute { task } = message {
+                        self.pending.insert(0, QueuedTask { task, priority: queued.priority });
+                    }
+                    break;
+                }
+                Err(mpsc::error::TrySendError::Closed(message)) => {
+                    error!("Failed to send queued task {} to host {}", task_id, self.host_id);
+                    self.release_task(&task_id);
+                    if let ServerToGatewayMessage::TaskExecute { task } = message {
+                        let error = format!("Host {} connection closed", self.host_id);
+                        undelivered.push((QueuedTask { task, priority: queued.priority }, error));
+                    }
+                }
+            }
+        }
+        undelivered
+    }
+}
+
+/// Rejected task messages kept for auditing
+const MAX_REJECTED_MESSAGES: usize = 1000;
+
+/// A task message from a host the task was not dispatched to
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RejectedTaskMessage {
+    pub host_id: String,
+    pub task_id: String,
+    /// Wire type of the message, e.g. `task:event`
+    pub message_type: String,
+    /// Unix timestamp (ms) when the message was rejected
+    pub rejected_at: u64,
+}
+
+/// Current Unix time in milliseconds
+fn unix_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Silence after which the heartbeat checker drops a host
+pub const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// Environment variable with how recent (seconds) a host's heartbeat must be to dispatch to it
+pub const HEARTBEAT_FRESHNESS_ENV: &str = "VK_GATEWAY_HEARTBEAT_FRESHNESS_SECS";
+
+/// Load the dispatch heartbeat window; defaults to the checker's timeout
+fn heartbeat_freshness_from_env() -> Duration {
+    Duration::from_secs(positive_env_or_default(HEARTBEAT_FRESHNESS_ENV, HEARTBEAT_TIMEOUT.as_secs()))
 }
 
 /// Task event for broadcasting (includes host info)
@@ -34,6 +207,23 @@ pub struct BroadcastTaskEvent {
     pub task_id: String,
     pub host_id: String,
     pub event: GatewayAgentEvent,
+    /// The event before secrets were masked; never sent to clients
+    #[serde(skip)]
+    pub original: Option<GatewayAgentEvent>,
+    /// Position in the broadcast order, assigned when published
+    #[serde(skip)]
+    pub seq: u64,
+}
+
+/// Environment variable setting the gateway event broadcast capacity
+pub const EVENT_BUFFER_ENV: &str = "VK_GATEWAY_EVENT_BUFFER";
+
+/// Default number of events a subscriber may fall behind before lagging
+const DEFAULT_EVENT_BUFFER: usize = 4096;
+
+/// Load the broadcast capacity from the process environment
+fn event_buffer_from_env() -> usize {
+    positive_env_or_default(EVENT_BUFFER_ENV, DEFAULT_EVENT_BUFFER)
 }
 
 /// Gateway Manager - central hub for gateway connections
@@ -46,33 +236,130 @@ pub struct GatewayManager {
     task_store: Option<Arc<FileTaskStore>>,
     /// Kanban store for updating kanban board
     kanban_store: Option<Arc<KanbanStore>>,
+    /// Cap on the content of a single ingested event
+    max_event_content_bytes: usize,
+    /// Kanban auto-move policy of each dispatched task
+    auto_move_policies: Arc<RwLock<HashMap<String, KanbanAutoMovePolicy>>>,
+    /// Minimum agent CLI versions; older hosts are logged with a warning
+    min_agent_versions: HashMap<String, String>,
+    /// Masks secrets in ingested events
+    redactor: Redactor,
+    /// In-flight tasks of disconnected hosts, kept until the host reconnects
+    detached: Arc<RwLock<HashMap<String, Vec<InFlightTask>>>>,
+    /// Published events, replayed to subscribers that lag
+    journal: Arc<Mutex<EventJournal>>,
+    /// Queued tasks that could not be delivered, awaiting redelivery
+    dead_letters: Arc<RwLock<Vec<DeadLetter>>>,
+    /// Retries of dispatches that failed transiently
+    dispatch_retry: DispatchRetry,
+    /// Most recent task messages rejected for coming from the wrong host
+    rejected: Arc<Mutex<VecDeque<RejectedTaskMessage>>>,
+    /// Hosts silent for longer are not dispatched to
+    heartbeat_freshness: Duration,
+    /// Hosts must connect over mTLS rather than with the bearer token
+    mtls_required: bool,
 }
 
 impl GatewayManager {
     /// Create a new Gateway Manager
     pub fn new() -> Self {
-        let (event_tx, _) = broadcast::channel(1000);
+        let (event_tx, _) = broadcast::channel(event_buffer_from_env());
         Self {
             connections: Arc::new(RwLock::new(HashMap::new())),
             event_tx,
             pending_model_requests: Arc::new(RwLock::new(HashMap::new())),
             task_store: None,
             kanban_store: None,
+            max_event_content_bytes: event_max_bytes_from_env(),
+            auto_move_policies: Arc::new(RwLock::new(HashMap::new())),
+            min_agent_versions: min_versions_from_env(),
+            redactor: redactor_from_env(),
+            detached: Arc::new(RwLock::new(HashMap::new())),
+            journal: Arc::new(Mutex::new(EventJournal::default())),
+            dead_letters: Arc::new(RwLock::new(Vec::new())),
+            dispatch_retry: dispatch_retry_from_env(),
+            rejected: Arc::new(Mutex::new(VecDeque::new())),
+            heartbeat_freshness: heartbeat_freshness_from_env(),
+            mtls_required: false,
         }
     }
 
     /// Create a new Gateway Manager with both task store and kanban store
     pub fn with_stores(task_store: Arc<FileTaskStore>, kanban_store: Arc<KanbanStore>) -> Self {
-        let (event_tx, _) = broadcast::channel(1000);
+        let (event_tx, _) = broadcast::channel(event_buffer_from_env());
         Self {
             connections: Arc::new(RwLock::new(HashMap::new())),
             event_tx,
             pending_model_requests: Arc::new(RwLock::new(HashMap::new())),
             task_store: Some(task_store),
             kanban_store: Some(kanban_store),
+            max_event_content_bytes: event_max_bytes_from_env(),
+            auto_move_policies: Arc::new(RwLock::new(HashMap::new())),
+            min_agent_versions: min_versions_from_env(),
+            redactor: redactor_from_env(),
+            detached: Arc::new(RwLock::new(HashMap::new())),
+            journal: Arc::new(Mutex::new(EventJournal::default())),
+            dead_letters: Arc::new(RwLock::new(Vec::new())),
+            dispatch_retry: dispatch_retry_from_env(),
+            rejected: Arc::new(Mutex::new(VecDeque::new())),
+            heartbeat_freshness: heartbeat_freshness_from_env(),
+            mtls_required: false,
         }
     }
 
+    /// Cap the content of each ingested event at `bytes`
+    #[cfg(test)]
+    pub fn with_max_event_content_bytes(mut self, bytes: usize) -> Self {
+        self.max_event_content_bytes = bytes;
+        self
+    }
+
+    /// Let subscribers fall up to `capacity` events behind before lagging
+    #[cfg(test)]
+    pub fn with_event_buffer(mut self, capacity: usize) -> Self {
+        self.event_tx = broadcast::channel(capacity.max(1)).0;
+        self
+    }
+
+    /// Mask ingested event content with `redactor`
+    #[cfg(test)]
+    pub fn with_redactor(mut self, redactor: Redactor) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
+    /// Warn about hosts whose agent CLIs are older than `versions`
+    #[cfg(test)]
+    pub fn with_min_agent_versions(mut self, versions: HashMap<String, String>) -> Self {
+        self.min_agent_versions = versions;
+        self
+    }
+
+    /// Retry transiently failed dispatches as `retry` says
+    #[cfg(test)]
+    pub fn with_dispatch_retry(mut self, retry: DispatchRetry) -> Self {
+        self.dispatch_retry = retry;
+        self
+    }
+
+    /// Only dispatch to hosts that sent a heartbeat within `freshness`
+    #[cfg(test)]
+    pub fn with_heartbeat_freshness(mut self, freshness: Duration) -> Self {
+        self.heartbeat_freshness = freshness;
+        self
+    }
+
+    /// Require hosts to connect over mTLS
+    pub fn with_mtls_required(mut self, required: bool) -> Self {
+        self.mtls_required = required;
+        self
+    }
+
+    /// Whether hosts must connect over mTLS
+    pub fn mtls_required(&self) -> bool {
+        self.mtls_required
+    }
+
     /// Set the task store (for use after construction)
     #[allow(dead_code)]
     pub fn set_task_store(&mut self, task_store: Arc<FileTaskStore>) {
@@ -86,10 +373,74 @@ impl GatewayManager {
     }
 
     /// Subscribe to task events (for forwarding to frontend)
+    #[allow(dead_code)]
     pub fn subscribe(&self) -> broadcast::Receiver<BroadcastTaskEvent> {
         self.event_tx.subscribe()
     }
 
+    /// Subscribe, also returning the sequence number of the last event published before
+    pub fn subscribe_from(&self) -> (broadcast::Receiver<BroadcastTaskEvent>, u64) {
+        let journal = self.journal.lock().expect("event journal poisoned");
+        (self.event_tx.subscribe(), journal.last_seq)
+    }
+
+    /// Journaled events of a task published after `seq`
+    ///
+    /// Lets a subscriber that lagged recover the events it missed.
+    pub fn task_events_since(&self, task_id: &str, seq: u64) -> Vec<BroadcastTaskEvent> {
+        self.journal
+            .lock()
+            .expect("event journal poisoned")
+            .events_since(task_id, seq)
+    }
+
+    /// Drop a task's journaled events once its subscriber is done
+    pub fn forget_task_events(&self, task_id: &str) {
+        self.journal
+            .lock()
+            .expect("event journal poisoned")
+            .forget(task_id);
+    }
+
+    /// Mask and cap an event, then number, journal and broadcast it
+    ///
+    /// Every event leaving the manager goes through here, so synthetic
+    /// completion and failure events are redacted like streamed ones.
+    fn publish(&self, task_id: &str, host_id: &str, mut event: GatewayAgentEvent) {
+        // Mask secrets before truncating so a cut cannot split one
+        let mut original = event.clone();
+        let content_redacted = event
+            .content
+            .as_mut()
+            .is_some_and(|content| self.redactor.redact_in_place(content));
+        let data_redacted = self.redactor.redact_value(&mut event.data);
+
+        if let Some(content) = event.content.as_mut() {
+            let dropped = truncate_content(content, self.max_event_content_bytes);
+            if dropped > 0 {
+                warn!(
+                    "Truncated {} bytes of oversized event content for task {} from host {}",
+                    dropped, task_id, host_id
+                );
+            }
+        }
+        if let Some(content) = original.content.as_mut() {
+            truncate_content(content, self.max_event_content_bytes);
+        }
+
+        let event = BroadcastTaskEvent {
+            task_id: task_id.to_string(),
+            host_id: host_id.to_string(),
+            event,
+            original: (content_redacted || data_redacted).then_some(original),
+            seq: 0,
+        };
+        let mut journal = self.journal.lock().expect("event journal poisoned");
+        let event = journal.push(event, Instant::now());
+        // Sent under the lock so the channel order matches the sequence
+        let _ = self.event_tx.send(event);
+    }
+
     /// Register a new host connection
     pub async fn register_host(
         &self,
@@ -104,28 +455,122 @@ impl GatewayManager {
         }
 
         info!("Registering host: {} ({})", host_id, capabilities.name);
+        for (agent, version, minimum) in
+            outdated_agents(&capabilities.agent_versions, &self.min_agent_versions)
+        {
+            warn!(
+                "Host {} runs {} {}, below the minimum version {}",
+                host_id, agent, version, minimum
+            );
+        }
         
-        connections.insert(
+        let replaced = connections.insert(
             host_id.clone(),
             HostConnection {
-                host_id,
+                host_id: host_id.clone(),
                 capabilities,
                 tx,
                 active_tasks: Vec::new(),
+                in_flight: HashMap::new(),
+                pending: Vec::new(),
                 last_heartbeat: Instant::now(),
                 connected_at: Instant::now(),
             },
         );
+        drop(connections);
+        if let Some(old) = replaced {
+            self.detach_tasks(&host_id, old).await;
+        }
 
         true
     }
 
     /// Unregister a host (on disconnect)
     pub async fn unregister_host(&self, host_id: &str) {
-        let mut connections = self.connections.write().await;
-        if connections.remove(host_id).is_some() {
+        let removed = self.connections.write().await.remove(host_id);
+        if let Some(conn) = removed {
             info!("Host {} unregistered", host_id);
+            self.detach_tasks(host_id, conn).await;
+        }
+    }
+
+    /// Dead-letter the tasks still queued on a removed connection
+    ///
+    /// They are redelivered once the host is back, instead of leaving their
+    /// runs pending forever.
+    async fn dead_letter_pending(&self, host_id: &str, conn: &mut HostConnection) {
+        let error = format!("Host {} disconnected before the task was dispatched", host_id);
+        let pending = std::mem::take(&mut conn.pending)
+            .into_iter()
+            .map(|queued| (queued, error.clone()))
+            .collect();
+        self.dead_letter(host_id, pending).await;
+    }
+
+    /// Keep a dropped connection's in-flight tasks for `reconcile_host_tasks`
+    /// and dead-letter its queued ones
+    async fn detach_tasks(&self, host_id: &str, mut conn: HostConnection) {
+        self.dead_letter_pending(host_id, &mut conn).await;
+        if conn.in_flight.is_empty() {
+            return;
+        }
+        self.detached
+            .write()
+            .await
+            .entry(host_id.to_string())
+            .or_default()
+            .extend(conn.in_flight.into_values());
+    }
+
+    /// Reconcile the tasks a reconnected host reports as running
+    ///
+    /// Reported tasks the server still considers in flight on this host are
+    /// adopted by the new connection; the rest are returned as abandoned.
+    /// Tasks the host held before disconnecting but no longer reports are
+    /// failed. Returns `(active, abandoned)`.
+    pub async fn reconcile_host_tasks(
+        &self,
+        host_id: &str,
+        reported: &[String],
+    ) -> (Vec<String>, Vec<String>) {
+        let mut detached = self.detached.write().await.remove(host_id).unwrap_or_default();
+        let mut active = Vec::new();
+        let mut abandoned = Vec::new();
+        {
+            let mut connections = self.connections.write().await;
+            let Some(conn) = connections.get_mut(host_id) else {
+                warn!("Task query from unregistered host {}", host_id);
+                if !detached.is_empty() {
+                    self.detached.write().await.insert(host_id.to_string(), detached);
+                }
+                return (active, reported.to_vec());
+            };
+            for task_id in reported {
+                if conn.in_flight.contains_key(task_id) {
+                    active.push(task_id.clone());
+                } else if let Some(index) = detached.iter().position(|t| &t.task_id == task_id) {
+                    let task = detached.remove(index);
+                    conn.active_tasks.push(task_id.clone());
+                    conn.in_flight.insert(task_id.clone(), task);
+                    active.push(task_id.clone());
+                } else {
+                    abandoned.push(task_id.clone());
+                }
+            }
+        }
+
+        info!(
+            "Host {} reconciled tasks: {} active, {} abandoned, {} lost",
+            host_id,
+            active.len(),
+            abandoned.len(),
+            detached.len()
+        );
+        for lost in detached {
+            self.handle_task_failed(host_id, &lost.task_id, "Host lost the task while reconnecting")
+                .await;
         }
+        (active, abandoned)
     }
 
     /// Update heartbeat timestamp for a host
@@ -138,6 +583,7 @@ impl GatewayManager {
     }
 
     /// Dispatch a task to an available host
+    #[allow(dead_code)]
     pub async fn dispatch_task(&self, task: GatewayTaskRequest) -> Result<String, String> {
         let mut connections = self.connections.write().await;
         let task_id = task.task_id.clone();
@@ -145,18 +591,18 @@ impl GatewayManager {
         // Find a suitable host
         let host = connections
             .values_mut()
-            .find(|c| c.is_available(&task.agent_type))
+            .find(|c| c.is_available(&task.agent_type) && c.check_heartbeat(self.heartbeat_freshness).is_ok())
             .ok_or_else(|| format!("No available host for agent type: {}", task.agent_type))?;
 
         let host_id = host.host_id.clone();
-        host.active_tasks.push(task_id.clone());
+        host.track_task(&task_id);
 
         // Send task to gateway
         if let Err(e) = host.tx.send(ServerToGatewayMessage::TaskExecute { task }).await {
             error!("Failed to send task to host {}: {}", host_id, e);
             // Remove from active tasks since send failed
             if let Some(conn) = connections.get_mut(&host_id) {
-                conn.active_tasks.retain(|id| id != &task_id);
+                conn.release_task(&task_id);
             }
             return Err(format!("Failed to dispatch task: {}", e));
         }
@@ -166,102 +612,302 @@ impl GatewayManager {
     }
 
     /// Dispatch a task to a specific host.
+    #[allow(dead_code)]
     pub async fn dispatch_task_to_host(
         &self,
         host_id: &str,
         task: GatewayTaskRequest,
     ) -> Result<String, String> {
+        self.try_dispatch_task_to_host(host_id, task)
+            .await
+            .map_err(String::from)
+    }
+
+    async fn try_dispatch_task_to_host(
+        &self,
+        host_id: &str,
+        task: GatewayTaskRequest,
+    ) -> Result<String, DispatchError> {
         let mut connections = self.connections.write().await;
         let task_id = task.task_id.clone();
 
+        // A host that just dropped may be back shortly
         let conn = connections
             .get_mut(host_id)
-            .ok_or_else(|| format!("Host {} not found", host_id))?;
+            .ok_or_else(|| DispatchError::Transient(format!("Host {} not found", host_id)))?;
 
         if !conn.capabilities.agents.contains(&task.agent_type) {
-            return Err(format!(
+            return Err(DispatchError::Permanent(format!(
                 "Host {} does not support agent type: {}",
                 host_id, task.agent_type
-            ));
+            )));
         }
 
-        if (conn.active_tasks.len() as u32) >= conn.capabilities.max_concurrent {
-            return Err(format!("Host {} is offline or busy", host_id));
+        // The host may still heartbeat again, so this is worth retrying
+        conn.check_heartbeat(self.heartbeat_freshness)?;
+
+        if !conn.has_capacity() {
+            return Err(DispatchError::Transient(format!("Host {} is offline or busy", host_id)));
         }
 
-        conn.active_tasks.push(task_id.clone());
+        conn.track_task(&task_id);
 
-        if let Err(e) = conn.tx.send(ServerToGatewayMessage::TaskExecute { task }).await {
+        // Never wait on a full channel while holding the connections lock;
+        // a failed send delivered nothing, so it is safe to retry
+        if let Err(e) = conn.tx.try_send(ServerToGatewayMessage::TaskExecute { task }) {
             error!("Failed to send task to host {}: {}", host_id, e);
-            conn.active_tasks.retain(|id| id != &task_id);
-            return Err(format!("Failed to dispatch task: {}", e));
+            conn.release_task(&task_id);
+            return Err(DispatchError::Undelivered(format!("Failed to dispatch task: {}", e)));
         }
 
         info!("Task dispatched to host {}", host_id);
         Ok(host_id.to_string())
     }
 
+    /// Pick a host whose labels satisfy `required_labels`
+    ///
+    /// Keeps `preferred` when it matches; otherwise takes the matching host
+    /// running `agent_type` with the most free slots. Errors when no
+    /// connected host matches.
+    pub async fn select_host(
+        &self,
+        preferred: &str,
+        agent_type: &str,
+        required_labels: &HashMap<String, String>,
+    ) -> Result<String, String> {
+        let connections = self.connections.read().await;
+
+        if let Some(conn) = connections.get(preferred) {
+            if conn.matches_labels(required_labels) {
+                return Ok(preferred.to_string());
+            }
+        }
+
+        connections
+            .values()
+            .filter(|conn| conn.matches_labels(required_labels))
+            .filter(|conn| conn.check_agent(agent_type).is_ok())
+            .max_by_key(|conn| {
+                i64::from(conn.capabilities.max_concurrent) - conn.active_tasks.len() as i64
+            })
+            .map(|conn| conn.host_id.clone())
+            .ok_or_else(|| {
+                let mut labels: Vec<String> = required_labels
+                    .iter()
+                    .map(|(key, value)| format!("{}={}", key, value))
+                    .collect();
+                labels.sort();
+                format!(
+                    "No connected host for agent type {} has labels {}",
+                    agent_type,
+                    labels.join(", ")
+                )
+            })
+    }
+
+    /// Dispatch a task to a specific host, queueing it if the host is saturated.
+    pub async fn dispatch_or_queue(
+        &self,
+        host_id: &str,
+        task: GatewayTaskRequest,
+        priority: TaskPriority,
+    ) -> Result<DispatchOutcome, String> {
+        self.try_dispatch_or_queue(host_id, task, priority)
+            .await
+            .map_err(String::from)
+    }
+
+    /// `dispatch_or_queue`, retrying transient failures with backoff
+    pub async fn dispatch_or_queue_with_retry(
+        &self,
+        host_id: &str,
+        task: GatewayTaskRequest,
+        priority: TaskPriority,
+    ) -> Result<DispatchOutcome, DispatchError> {
+        let mut attempt = 1;
+        loop {
+            match self.try_dispatch_or_queue(host_id, task.clone(), priority).await {
+                Err(e) if e.is_transient() && attempt < self.dispatch_retry.attempts => {
+                    let delay = self.dispatch_retry.delay(attempt);
+                    warn!(
+                        "Dispatch of task {} to host {} failed ({}), retrying in {:?}",
+                        task.task_id, host_id, e, delay
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    async fn try_dispatch_or_queue(
+        &self,
+        host_id: &str,
+        task: GatewayTaskRequest,
+        priority: TaskPriority,
+    ) -> Result<DispatchOutcome, DispatchError> {
+        {
+            let mut connections = self.connections.write().await;
+            let conn = connections
+                .get_mut(host_id)
+                .ok_or_else(|| DispatchError::Transient(format!("Host {} not found", host_id)))?;
+
+            conn.check_agent(&task.agent_type).map_err(DispatchError::Permanent)?;
+            conn.check_heartbeat(self.heartbeat_freshness)?;
+
+            if !conn.has_capacity() {
+                let task_id = task.task_id.clone();
+                let position = conn.enqueue(task, priority);
+                info!(
+                    "Host {} is saturated, queued task {} at position {}",
+                    host_id, task_id, position
+                );
+                return Ok(DispatchOutcome::Queued {
+                    host_id: host_id.to_string(),
+                    position,
+                });
+            }
+        }
+
+        self.try_dispatch_task_to_host(host_id, task)
+            .await
+            .map(DispatchOutcome::Dispatched)
+    }
+
+    /// Run the checks of `dispatch_or_queue` without dispatching anything
+    ///
+    /// Returns the queue position the task would take, or None if the host
+    /// has a free slot.
+    pub async fn preview_dispatch(
+        &self,
+        host_id: &str,
+        agent_type: &str,
+        priority: TaskPriority,
+    ) -> Result<Option<usize>, String> {
+        let connections = self.connections.read().await;
+        let conn = connections
+            .get(host_id)
+            .ok_or_else(|| format!("Host {} not found", host_id))?;
+        conn.check_agent(agent_type)?;
+        conn.check_heartbeat(self.heartbeat_freshness)
+            .map_err(String::from)?;
+
+        Ok((!conn.has_capacity()).then(|| conn.queue_position(priority)))
+    }
+
+    /// Remove a task that is still waiting in a host queue
+    ///
+    /// Returns the host it was queued on, or None if the task is not queued
+    /// (e.g. already dispatched).
+    pub async fn cancel_queued(&self, task_id: &str) -> Option<String> {
+        let mut connections = self.connections.write().await;
+        for conn in connections.values_mut() {
+            if let Some(index) = conn.pending.iter().position(|q| q.task.task_id == task_id) {
+                conn.pending.remove(index);
+                info!("Cancelled queued task {} on host {}", task_id, conn.host_id);
+                return Some(conn.host_id.clone());
+            }
+        }
+        None
+    }
+
     /// Handle task started event from gateway
     pub async fn handle_task_started(&self, host_id: &str, task_id: &str, session_id: &str) {
         debug!(
             "Task {} started on host {} (session: {})",
             task_id, host_id, session_id
         );
+
+        let mut connections = self.connections.write().await;
+        if let Some(task) = connections
+            .get_mut(host_id)
+            .and_then(|conn| conn.in_flight.get_mut(task_id))
+        {
+            task.started_at = Some(unix_millis());
+            task.session_id = Some(session_id.to_string());
+        }
+    }
+
+    /// Whether `task_id` is dispatched to `host_id`
+    ///
+    /// Task messages from any other host are to be dropped; they are
+    /// recorded here for auditing.
+    pub async fn accept_task_message(&self, host_id: &str, task_id: &str, message_type: &str) -> bool {
+        let assigned = self
+            .connections
+            .read()
+            .await
+            .get(host_id)
+            .is_some_and(|conn| conn.in_flight.contains_key(task_id));
+        if !assigned {
+            warn!(
+                "Rejected {} message from host {} for task {} it was not dispatched",
+                message_type, host_id, task_id
+            );
+            let mut rejected = self.rejected.lock().expect("rejected messages poisoned");
+            if rejected.len() == MAX_REJECTED_MESSAGES {
+                rejected.pop_front();
+            }
+            rejected.push_back(RejectedTaskMessage {
+                host_id: host_id.to_string(),
+                task_id: task_id.to_string(),
+                message_type: message_type.to_string(),
+                rejected_at: unix_millis(),
+            });
+        }
+        assigned
+    }
+
+    /// Task messages rejected for coming from the wrong host, oldest first
+    pub fn rejected_task_messages(&self) -> Vec<RejectedTaskMessage> {
+        self.rejected
+            .lock()
+            .expect("rejected messages poisoned")
+            .iter()
+            .cloned()
+            .collect()
     }
 
     /// Handle task event from gateway
     pub async fn handle_task_event(&self, host_id: &str, task_id: &str, event: GatewayAgentEvent) {
-        let _ = self.event_tx.send(BroadcastTaskEvent {
-            task_id: task_id.to_string(),
-            host_id: host_id.to_string(),
-            event,
-        });
+        if let Some(task) = self
+            .connections
+            .write()
+            .await
+            .get_mut(host_id)
+            .and_then(|conn| conn.in_flight.get_mut(task_id))
+        {
+            task.last_event_at = Some(unix_millis());
+        }
+
+        self.publish(task_id, host_id, event);
     }
 
     /// Handle task completed event from gateway
     pub async fn handle_task_completed(&self, host_id: &str, task_id: &str, result: TaskResult) {
         let mut connections = self.connections.write().await;
+        let mut undelivered = Vec::new();
         if let Some(conn) = connections.get_mut(host_id) {
-            conn.active_tasks.retain(|id| id != task_id);
+            conn.release_task(task_id);
+            undelivered = conn.drain_pending();
         }
         drop(connections); // Release lock before async task store operations
+        self.dead_letter(host_id, undelivered).await;
 
         info!(
             "Task {} completed on host {}: success={}",
             task_id, host_id, result.success
         );
         
-        // Update task status in TaskStore
-        if let Some(task_store) = &self.task_store {
-            if let Ok(task_uuid) = uuid::Uuid::parse_str(task_id) {
-                match task_store.get(task_uuid).await {
-                    Ok(Some(mut task)) => {
-                        task.status = TaskStatus::Done;
-                        if let Err(e) = task_store.update(task).await {
-                            warn!("Failed to update task {} status to Done: {}", task_id, e);
-                        } else {
-                            info!("Task {} status updated to Done", task_id);
-                        }
-                    }
-                    Ok(None) => {
-                        warn!("Task {} not found in task store", task_id);
-                    }
-                    Err(e) => {
-                        warn!("Failed to get task {} from store: {}", task_id, e);
-                    }
-                }
-            }
-        }
-        
-        // Update KanbanStore (this is what the UI actually reads)
-        if let Some(kanban_store) = &self.kanban_store {
-            if let Err(e) = kanban_store.move_task(task_id, KanbanTaskStatus::Done, None).await {
-                warn!("Failed to move kanban task {} to Done: {}", task_id, e);
-            } else {
-                info!("Kanban task {} moved to Done", task_id);
-            }
-        }
-        
+        let transition = if result.success {
+            RunTransition::Succeeded
+        } else {
+            RunTransition::Failed
+        };
+        self.apply_auto_move(task_id, transition).await;
+        self.clear_auto_move_policy(task_id).await;
+
         // Broadcast a synthetic "Completed" event so the event forwarder in executor.rs
         // can detect task completion and emit kanban:sync
         let completed_event = GatewayAgentEvent {
@@ -273,34 +919,77 @@ impl GatewayManager {
                 .unwrap_or_default()
                 .as_millis() as u64,
         };
-        let _ = self.event_tx.send(BroadcastTaskEvent {
-            task_id: task_id.to_string(),
-            host_id: host_id.to_string(),
-            event: completed_event,
-        });
+        self.publish(task_id, host_id, completed_event);
     }
 
     /// Handle task failed event from gateway
     pub async fn handle_task_failed(&self, host_id: &str, task_id: &str, error: &str) {
         let mut connections = self.connections.write().await;
+        let mut undelivered = Vec::new();
         if let Some(conn) = connections.get_mut(host_id) {
-            conn.active_tasks.retain(|id| id != task_id);
+            conn.release_task(task_id);
+            undelivered = conn.drain_pending();
         }
         drop(connections); // Release lock before async task store operations
+        self.dead_letter(host_id, undelivered).await;
 
         error!("Task {} failed on host {}: {}", task_id, host_id, error);
         
-        // Update task status in TaskStore (mark as Todo so user can retry)
+        self.apply_auto_move(task_id, RunTransition::Failed).await;
+        self.clear_auto_move_policy(task_id).await;
+
+        // Broadcast a synthetic "Failed" event so the event forwarder in executor.rs
+        // can detect task failure and emit kanban:sync
+        let failed_event = GatewayAgentEvent {
+            event_type: GatewayAgentEventType::Failed,
+            content: Some(format!("Task failed: {}", error)),
+            data: serde_json::json!({ "error": error }),
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+        };
+        self.publish(task_id, host_id, failed_event);
+    }
+
+    /// Use `policy` for the kanban moves of a dispatched task
+    pub async fn set_auto_move_policy(&self, task_id: &str, policy: KanbanAutoMovePolicy) {
+        self.auto_move_policies
+            .write()
+            .await
+            .insert(task_id.to_string(), policy);
+    }
+
+    /// Forget the auto-move policy of a finished or undispatched task
+    pub async fn clear_auto_move_policy(&self, task_id: &str) {
+        self.auto_move_policies.write().await.remove(task_id);
+    }
+
+    /// Move a task's card as its auto-move policy dictates for `transition`
+    ///
+    /// Tasks without a recorded policy use the default one. Returns whether
+    /// the board changed.
+    pub async fn apply_auto_move(&self, task_id: &str, transition: RunTransition) -> bool {
+        let policy = self
+            .auto_move_policies
+            .read()
+            .await
+            .get(task_id)
+            .copied()
+            .unwrap_or_default();
+        let Some(target) = policy.target(transition) else {
+            debug!("Task {} stays in place after {:?}", task_id, transition);
+            return false;
+        };
+
+        // Update task status in TaskStore
         if let Some(task_store) = &self.task_store {
             if let Ok(task_uuid) = uuid::Uuid::parse_str(task_id) {
                 match task_store.get(task_uuid).await {
                     Ok(Some(mut task)) => {
-                        // Move back to Todo on failure so user can retry
-                        task.status = TaskStatus::Todo;
+                        task.status = target.into();
                         if let Err(e) = task_store.update(task).await {
-                            warn!("Failed to update task {} status to Todo: {}", task_id, e);
-                        } else {
-                            info!("Task {} status updated to Todo (failed)", task_id);
+                            warn!("Failed to update task {} status to {:?}: {}", task_id, target, e);
                         }
                     }
                     Ok(None) => {
@@ -312,36 +1001,24 @@ impl GatewayManager {
                 }
             }
         }
-        
-        // Update KanbanStore - move back to Todo on failure
-        if let Some(kanban_store) = &self.kanban_store {
-            if let Err(e) = kanban_store.move_task(task_id, KanbanTaskStatus::Todo, None).await {
-                warn!("Failed to move kanban task {} to Todo: {}", task_id, e);
-            } else {
-                info!("Kanban task {} moved to Todo (failed)", task_id);
+
+        // Update KanbanStore (this is what the UI actually reads)
+        let Some(kanban_store) = &self.kanban_store else {
+            return false;
+        };
+        match kanban_store.move_task(task_id, target, None).await {
+            Ok(moved) => {
+                info!("Kanban task {} moved to {:?} after {:?}", task_id, target, transition);
+                moved
+            }
+            Err(e) => {
+                warn!("Failed to move kanban task {} to {:?}: {}", task_id, target, e);
+                false
             }
         }
-        
-        // Broadcast a synthetic "Failed" event so the event forwarder in executor.rs
-        // can detect task failure and emit kanban:sync
-        let failed_event = GatewayAgentEvent {
-            event_type: GatewayAgentEventType::Failed,
-            content: Some(format!("Task failed: {}", error)),
-            data: serde_json::json!({ "error": error }),
-            timestamp: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_millis() as u64,
-        };
-        let _ = self.event_tx.send(BroadcastTaskEvent {
-            task_id: task_id.to_string(),
-            host_id: host_id.to_string(),
-            event: failed_event,
-        });
     }
 
     /// Abort a running task
-    #[allow(dead_code)]
     pub async fn abort_task(&self, task_id: &str) -> Result<(), String> {
         let connections = self.connections.read().await;
 
@@ -457,12 +1134,17 @@ impl GatewayManager {
                     HostConnectionStatus::Busy
                 };
 
+                let mut in_flight: Vec<InFlightTask> = conn.in_flight.values().cloned().collect();
+                in_flight.sort_by_key(|t| t.dispatched_at);
+
                 HostStatus {
                     host_id: conn.host_id.clone(),
                     name: conn.capabilities.name.clone(),
                     status,
                     capabilities: conn.capabilities.clone(),
                     active_tasks: conn.active_tasks.clone(),
+                    in_flight,
+                    queue_depth: conn.pending.len(),
                     last_heartbeat: now.duration_since(conn.last_heartbeat).as_secs(),
                     connected_at: now.duration_since(conn.connected_at).as_secs(),
                 }
@@ -470,19 +1152,180 @@ impl GatewayManager {
             .collect()
     }
 
+    /// Map each advertised agent to the connected hosts that can run it
+    pub async fn agent_hosts(&self) -> HashMap<String, Vec<String>> {
+        let connections = self.connections.read().await;
+        let mut agents: HashMap<String, Vec<String>> = HashMap::new();
+
+        for conn in connections.values() {
+            for agent in &conn.capabilities.agents {
+                agents.entry(agent.clone()).or_default().push(conn.host_id.clone());
+            }
+        }
+        for hosts in agents.values_mut() {
+            hosts.sort();
+        }
+        agents
+    }
+
+    /// Pretend a host's last heartbeat was `by` earlier
+    #[cfg(test)]
+    pub async fn age_heartbeat(&self, host_id: &str, by: Duration) {
+        if let Some(conn) = self.connections.write().await.get_mut(host_id) {
+            conn.last_heartbeat = conn.last_heartbeat.checked_sub(by).expect("heartbeat aged past the clock");
+        }
+    }
+
     /// Clean up stale connections (heartbeat timeout)
     pub async fn cleanup_stale_connections(&self, timeout: Duration) {
-        let mut connections = self.connections.write().await;
         let now = Instant::now();
+        let stale: Vec<HostConnection> = {
+            let mut connections = self.connections.write().await;
+            let stale_ids: Vec<String> = connections
+                .values()
+                .filter(|conn| now.duration_since(conn.last_heartbeat) > timeout)
+                .map(|conn| conn.host_id.clone())
+                .collect();
+            stale_ids
+                .iter()
+                .filter_map(|host_id| connections.remove(host_id))
+                .collect()
+        };
 
-        connections.retain(|host_id, conn| {
-            if now.duration_since(conn.last_heartbeat) > timeout {
-                warn!("Host {} heartbeat timeout, removing", host_id);
-                false
-            } else {
-                true
+        // Keep their tasks for adoption, as on a disconnect
+        for conn in stale {
+            warn!("Host {} heartbeat timeout, removing", conn.host_id);
+            let host_id = conn.host_id.clone();
+            self.detach_tasks(&host_id, conn).await;
+        }
+    }
+
+    /// Fail in-flight tasks that have sent no events for longer than `window`
+    ///
+    /// Each stalled task is aborted on its host and then failed like a host
+    /// reported failure, so its run ends as `Failed`. Returns the task IDs.
+    pub async fn sweep_stalled_tasks(&self, window: Duration) -> Vec<String> {
+        let now = unix_millis();
+        let window_ms = window.as_millis() as u64;
+        let stalled: Vec<(String, String, u64)> = {
+            let connections = self.connections.read().await;
+            connections
+                .values()
+                .flat_map(|conn| {
+                    conn.in_flight.values().filter_map(move |task| {
+                        let idle_ms = now.saturating_sub(task.last_activity_at());
+                        (idle_ms > window_ms)
+                            .then(|| (conn.host_id.clone(), task.task_id.clone(), idle_ms))
+                    })
+                })
+                .collect()
+        };
+
+        for (host_id, task_id, idle_ms) in &stalled {
+            warn!(
+                "Task {} on host {} sent no events for {}ms, marking it stalled",
+                task_id, host_id, idle_ms
+            );
+            let tx = self
+                .connections
+                .read()
+                .await
+                .get(host_id)
+                .map(|conn| conn.tx.clone());
+            if let Some(tx) = tx {
+                let abort = ServerToGatewayMessage::TaskAbort {
+                    task_id: task_id.clone(),
+                };
+                if let Err(e) = tx.send(abort).await {
+                    warn!("Failed to abort stalled task {} on host {}: {}", task_id, host_id, e);
+                }
             }
-        });
+            let error = format!("Run stalled: no events for {}s", idle_ms / 1000);
+            self.handle_task_failed(host_id, task_id, &error).await;
+        }
+
+        stalled.into_iter().map(|(_, task_id, _)| task_id).collect()
+    }
+
+    /// Park tasks that could not be delivered for later redelivery
+    async fn dead_letter(&self, host_id: &str, undelivered: Vec<(QueuedTask, String)>) {
+        if undelivered.is_empty() {
+            return;
+        }
+        let now = unix_millis();
+        let mut dead_letters = self.dead_letters.write().await;
+        for (queued, error) in undelivered {
+            warn!("Dead-lettered task {} for host {}: {}", queued.task.task_id, host_id, error);
+            dead_letters.push(DeadLetter::new(queued.task, queued.priority, host_id.to_string(), error, now));
+        }
+    }
+
+    /// Dead-lettered tasks with their redelivery state
+    pub async fn dead_letters(&self) -> Vec<DeadLetterStatus> {
+        self.dead_letters.read().await.iter().map(DeadLetter::status).collect()
+    }
+
+    /// Redeliver the dead-lettered tasks that are due; returns how many were delivered
+    pub async fn redeliver_dead_letters(&self) -> usize {
+        self.redeliver_due(unix_millis()).await
+    }
+
+    async fn redeliver_due(&self, now: u64) -> usize {
+        let due: Vec<DeadLetter> = {
+            let mut dead_letters = self.dead_letters.write().await;
+            let (due, waiting) = std::mem::take(&mut *dead_letters)
+                .into_iter()
+                .partition(|letter| letter.is_due(now));
+            *dead_letters = waiting;
+            due
+        };
+
+        let mut delivered = 0;
+        for letter in due {
+            if self.redeliver(letter, now).await.is_ok() {
+                delivered += 1;
+            }
+        }
+        delivered
+    }
+
+    /// Try one delivery; a failure puts the task back with its backoff advanced
+    async fn redeliver(&self, mut letter: DeadLetter, now: u64) -> Result<DispatchOutcome, String> {
+        match self
+            .dispatch_or_queue(&letter.host_id, letter.task.clone(), letter.priority)
+            .await
+        {
+            Ok(outcome) => {
+                info!("Redelivered dead-lettered task {} after {} attempts", letter.task.task_id, letter.attempts);
+                Ok(outcome)
+            }
+            Err(e) => {
+                letter.record_failure(e.clone(), now);
+                self.dead_letters.write().await.push(letter);
+                Err(e)
+            }
+        }
+    }
+
+    /// Redeliver a dead-lettered task now, resetting its backoff
+    ///
+    /// Returns None if the task is not dead-lettered.
+    pub async fn retry_dead_letter(&self, task_id: &str) -> Option<Result<DispatchOutcome, String>> {
+        let mut letter = {
+            let mut dead_letters = self.dead_letters.write().await;
+            let index = dead_letters.iter().position(|letter| letter.task.task_id == task_id)?;
+            dead_letters.remove(index)
+        };
+        letter.attempts = 0;
+        Some(self.redeliver(letter, unix_millis()).await)
+    }
+
+    /// Whether any host has a task in flight or queued
+    pub async fn has_active_tasks(&self) -> bool {
+        let connections = self.connections.read().await;
+        connections
+            .values()
+            .any(|conn| !conn.in_flight.is_empty() || !conn.pending.is_empty())
     }
 
     /// Get the number of connected hosts
@@ -510,6 +1353,7 @@ mod tests {
             max_concurrent: 2,
             cwd: "/home/user".to_string(),
             labels: HashMap::new(),
+            agent_versions: HashMap::new(),
         }
     }
 
@@ -529,6 +1373,125 @@ mod tests {
         assert_eq!(hosts[0].status, HostConnectionStatus::Online);
     }
 
+    #[tokio::test]
+    async fn select_host_honors_required_labels() {
+        let manager = GatewayManager::new();
+        let (tx1, _rx1) = mpsc::channel(10);
+        let (tx2, _rx2) = mpsc::channel(10);
+        manager
+            .register_host("host-1".to_string(), create_test_capabilities(), tx1)
+            .await;
+        let mut gpu = create_test_capabilities();
+        gpu.labels.insert("gpu".to_string(), "a100".to_string());
+        manager.register_host("host-2".to_string(), gpu, tx2).await;
+
+        let required = HashMap::from([("gpu".to_string(), "a100".to_string())]);
+        assert_eq!(
+            manager.select_host("host-1", "opencode", &required).await.unwrap(),
+            "host-2"
+        );
+        assert_eq!(
+            manager.select_host("host-2", "opencode", &required).await.unwrap(),
+            "host-2"
+        );
+        assert_eq!(
+            manager.select_host("host-1", "opencode", &HashMap::new()).await.unwrap(),
+            "host-1"
+        );
+
+        let required = HashMap::from([("gpu".to_string(), "h100".to_string())]);
+        let error = manager.select_host("host-1", "opencode", &required).await.unwrap_err();
+        assert!(error.contains("gpu=h100"));
+    }
+
+    #[tokio::test]
+    async fn list_hosts_reports_agent_versions() {
+        let manager = GatewayManager::new().with_min_agent_versions(HashMap::from([(
+            "opencode".to_string(),
+            "0.5.0".to_string(),
+        )]));
+        let (tx, _rx) = mpsc::channel(10);
+        let mut capabilities = create_test_capabilities();
+        capabilities
+            .agent_versions
+            .insert("opencode".to_string(), "0.4.2".to_string());
+
+        // Outdated hosts are only warned about, not rejected
+        assert!(manager.register_host("host-1".to_string(), capabilities, tx).await);
+
+        let hosts = manager.list_hosts().await;
+        assert_eq!(
+            hosts[0].capabilities.agent_versions.get("opencode").map(String::as_str),
+            Some("0.4.2")
+        );
+        let json = serde_json::to_value(&hosts[0]).unwrap();
+        assert_eq!(json["capabilities"]["agentVersions"]["opencode"], "0.4.2");
+    }
+
+    #[tokio::test]
+    async fn oversized_event_content_is_truncated_on_ingest() {
+        let manager = GatewayManager::new().with_max_event_content_bytes(16);
+        let mut events = manager.subscribe();
+
+        let event = GatewayAgentEvent {
+            event_type: GatewayAgentEventType::Stdout,
+            content: Some("x".repeat(1024)),
+            data: serde_json::Value::Null,
+            timestamp: 1,
+        };
+        manager.handle_task_event("host-1", "task-1", event).await;
+
+        let received = events.recv().await.unwrap();
+        let content = received.event.content.unwrap();
+        assert!(content.starts_with(&"x".repeat(16)));
+        assert!(content.ends_with("[... 1008 bytes truncated ...]"));
+    }
+
+    #[tokio::test]
+    async fn secrets_in_event_content_are_masked_on_ingest() {
+        let manager = GatewayManager::new().with_redactor(Redactor::default());
+        let mut events = manager.subscribe();
+
+        let event = GatewayAgentEvent {
+            event_type: GatewayAgentEventType::Stdout,
+            content: Some("token: ghp_FAKEFAKEFAKEFAKEFAKE1234".to_string()),
+            data: serde_json::Value::Null,
+            timestamp: 1,
+        };
+        manager.handle_task_event("host-1", "task-1", event).await;
+
+        let received = events.recv().await.unwrap();
+        assert_eq!(received.event.content.as_deref(), Some("token: [REDACTED]"));
+        let original = received.original.as_ref().unwrap();
+        assert!(original.content.as_deref().unwrap().contains("ghp_FAKE"));
+        assert!(!serde_json::to_string(&received).unwrap().contains("ghp_FAKE"));
+    }
+
+    #[tokio::test]
+    async fn secrets_in_task_results_are_masked() {
+        let manager = GatewayManager::new().with_redactor(Redactor::default());
+        let mut events = manager.subscribe();
+
+        let result = TaskResult {
+            success: true,
+            exit_code: Some(0),
+            output: None,
+            summary: Some("pushed with ghp_FAKEFAKEFAKEFAKEFAKE1234".to_string()),
+            duration: None,
+            files_changed: Vec::new(),
+        };
+        manager.handle_task_completed("host-1", "task-1", result).await;
+        manager
+            .handle_task_failed("host-1", "task-2", "auth failed for ghp_FAKEFAKEFAKEFAKEFAKE1234")
+            .await;
+
+        for _ in 0..2 {
+            let received = events.recv().await.unwrap();
+            assert!(received.original.is_some());
+            assert!(!serde_json::to_string(&received.event).unwrap().contains("ghp_FAKE"));
+        }
+    }
+
     #[tokio::test]
     async fn test_unregister_host() {
         let manager = GatewayManager::new();
@@ -723,6 +1686,7 @@ mod tests {
                     success: true,
                     exit_code: Some(0),
                     output: None,
+                    summary: None,
                     duration: Some(100),
                     files_changed: vec![],
                 },
@@ -735,6 +1699,366 @@ mod tests {
         assert_eq!(hosts[0].status, HostConnectionStatus::Online);
     }
 
+    #[tokio::test]
+    async fn silent_task_is_marked_stalled() {
+        let manager = GatewayManager::new();
+        let (tx, mut rx) = mpsc::channel(10);
+        manager
+            .register_host("host-1".to_string(), create_test_capabilities(), tx)
+            .await;
+        let mut events = manager.subscribe();
+
+        for task_id in ["silent", "chatty"] {
+            let task = GatewayTaskRequest {
+                task_id: task_id.to_string(),
+                prompt: "test".to_string(),
+                cwd: "/tmp".to_string(),
+                agent_type: "opencode".to_string(),
+                model: None,
+                env: HashMap::new(),
+                timeout: None,
+                metadata: serde_json::Value::Null,
+            };
+            manager.dispatch_task_to_host("host-1", task).await.unwrap();
+        }
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        let chatter = GatewayAgentEvent {
+            event_type: GatewayAgentEventType::Stdout,
+            content: Some("still here".to_string()),
+            data: serde_json::Value::Null,
+            timestamp: 1,
+        };
+        manager.handle_task_event("host-1", "chatty", chatter).await;
+        let _ = events.recv().await.unwrap();
+
+        let stalled = manager.sweep_stalled_tasks(Duration::from_millis(30)).await;
+        assert_eq!(stalled, vec!["silent".to_string()]);
+
+        let failed = events.recv().await.unwrap();
+        assert_eq!(failed.task_id, "silent");
+        assert!(matches!(failed.event.event_type, GatewayAgentEventType::Failed));
+        assert!(failed.event.content.unwrap().contains("stalled"));
+
+        let mut aborted = false;
+        while let Ok(msg) = rx.try_recv() {
+            if let ServerToGatewayMessage::TaskAbort { task_id } = msg {
+                assert_eq!(task_id, "silent");
+                aborted = true;
+            }
+        }
+        assert!(aborted, "stalled task should be aborted on its host");
+
+        let hosts = manager.list_hosts().await;
+        assert_eq!(hosts[0].active_tasks, vec!["chatty".to_string()]);
+        assert!(hosts[0].in_flight[0].last_event_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_host_status_lists_in_flight_tasks() {
+        let manager = GatewayManager::new();
+        let (tx, _rx) = mpsc::channel(10);
+
+        manager
+            .register_host("host-1".to_string(), create_test_capabilities(), tx)
+            .await;
+
+        let task = GatewayTaskRequest {
+            task_id: "task-1".to_string(),
+            prompt: "test".to_string(),
+            cwd: "/tmp".to_string(),
+            agent_type: "opencode".to_string(),
+            model: None,
+            env: HashMap::new(),
+            timeout: None,
+            metadata: serde_json::Value::Null,
+        };
+        manager.dispatch_task_to_host("host-1", task).await.unwrap();
+
+        let hosts = manager.list_hosts().await;
+        assert_eq!(hosts[0].in_flight.len(), 1);
+        assert_eq!(hosts[0].in_flight[0].task_id, "task-1");
+        assert!(hosts[0].in_flight[0].dispatched_at > 0);
+        assert!(hosts[0].in_flight[0].started_at.is_none());
+
+        manager.handle_task_started("host-1", "task-1", "session-1").await;
+        let hosts = manager.list_hosts().await;
+        assert!(hosts[0].in_flight[0].started_at.is_some());
+        assert_eq!(hosts[0].in_flight[0].session_id.as_deref(), Some("session-1"));
+
+        manager.handle_task_failed("host-1", "task-1", "boom").await;
+        let hosts = manager.list_hosts().await;
+        assert!(hosts[0].in_flight.is_empty());
+    }
+
+    #[tokio::test]
+    async fn transient_dispatch_failure_is_retried_until_the_send_succeeds() {
+        let manager = GatewayManager::new().with_dispatch_retry(DispatchRetry {
+            attempts: 3,
+            backoff: Duration::from_millis(50),
+        });
+        let (tx, mut rx) = mpsc::channel(1);
+        manager.register_host("host-1".to_string(), create_test_capabilities(), tx.clone()).await;
+
+        let task = |id: &str, agent_type: &str| GatewayTaskRequest {
+            task_id: id.to_string(),
+            prompt: "test".to_string(),
+            cwd: "/tmp".to_string(),
+            agent_type: agent_type.to_string(),
+            model: None,
+            env: HashMap::new(),
+            timeout: None,
+            metadata: serde_json::Value::Null,
+        };
+
+        // The host's channel is full, so the first send fails
+        tx.try_send(ServerToGatewayMessage::TaskExecute { task: task("earlier", "opencode") })
+            .unwrap();
+        let host = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            let mut received = Vec::new();
+            while let Some(ServerToGatewayMessage::TaskExecute { task }) = rx.recv().await {
+                received.push(task.task_id);
+                if received.len() == 2 {
+                    break;
+                }
+            }
+            received
+        });
+
+        let outcome = manager
+            .dispatch_or_queue_with_retry("host-1", task("task-1", "opencode"), TaskPriority::Medium)
+            .await
+            .unwrap();
+        assert_eq!(outcome, DispatchOutcome::Dispatched("host-1".to_string()));
+        assert_eq!(host.await.unwrap(), ["earlier", "task-1"]);
+        assert_eq!(manager.list_hosts().await[0].active_tasks, ["task-1"]);
+
+        // Permanent failures are not retried
+        let slow = GatewayManager::new().with_dispatch_retry(DispatchRetry {
+            attempts: 3,
+            backoff: Duration::from_secs(60),
+        });
+        let (tx, _rx) = mpsc::channel(10);
+        slow.register_host("host-1".to_string(), create_test_capabilities(), tx).await;
+        let result = tokio::time::timeout(
+            Duration::from_secs(1),
+            slow.dispatch_or_queue_with_retry("host-1", task("task-2", "codex"), TaskPriority::Medium),
+        )
+        .await
+        .unwrap();
+        assert!(matches!(
+            result,
+            Err(DispatchError::Permanent(message)) if message.contains("does not support agent type")
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_saturated_host_dispatches_queued_tasks_by_priority() {
+        let manager = GatewayManager::new();
+        let (tx, mut rx) = mpsc::channel(10);
+        let mut caps = create_test_capabilities();
+        caps.max_concurrent = 1;
+
+        manager.register_host("host-1".to_string(), caps, tx).await;
+
+        let task = |id: &str| GatewayTaskRequest {
+            task_id: id.to_string(),
+            prompt: "test".to_string(),
+            cwd: "/tmp".to_string(),
+            agent_type: "opencode".to_string(),
+            model: None,
+            env: HashMap::new(),
+            timeout: None,
+            metadata: serde_json::Value::Null,
+        };
+
+        let outcome = manager
+            .dispatch_or_queue("host-1", task("running"), TaskPriority::Medium)
+            .await
+            .unwrap();
+        assert_eq!(outcome, DispatchOutcome::Dispatched("host-1".to_string()));
+        assert!(matches!(rx.recv().await, Some(ServerToGatewayMessage::TaskExecute { .. })));
+
+        let low = manager
+            .dispatch_or_queue("host-1", task("low"), TaskPriority::Low)
+            .await
+            .unwrap();
+        assert!(matches!(low, DispatchOutcome::Queued { position: 0, .. }));
+        let high = manager
+            .dispatch_or_queue("host-1", task("high"), TaskPriority::High)
+            .await
+            .unwrap();
+        assert!(matches!(high, DispatchOutcome::Queued { position: 0, .. }));
+        assert_eq!(manager.list_hosts().await[0].queue_depth, 2);
+
+        manager
+            .handle_task_completed(
+                "host-1",
+                "running",
+                TaskResult {
+                    success: true,
+                    exit_code: Some(0),
+                    output: None,
+                    summary: None,
+                    duration: None,
+                    files_changed: vec![],
+                },
+            )
+            .await;
+
+        match rx.recv().await {
+            Some(ServerToGatewayMessage::TaskExecute { task }) => assert_eq!(task.task_id, "high"),
+            other => panic!("Expected queued task dispatch, got {:?}", other),
+        }
+        let hosts = manager.list_hosts().await;
+        assert_eq!(hosts[0].active_tasks, vec!["high".to_string()]);
+        assert_eq!(hosts[0].queue_depth, 1);
+
+        assert_eq!(manager.cancel_queued("low").await.as_deref(), Some("host-1"));
+        assert!(manager.cancel_queued("high").await.is_none());
+        assert_eq!(manager.list_hosts().await[0].queue_depth, 0);
+    }
+
+    #[tokio::test]
+    async fn undeliverable_queued_task_is_dead_lettered_with_backoff() {
+        let manager = GatewayManager::new();
+        let (tx, mut rx) = mpsc::channel(10);
+        let mut caps = create_test_capabilities();
+        caps.max_concurrent = 1;
+        manager.register_host("host-1".to_string(), caps.clone(), tx).await;
+
+        let task = |id: &str| GatewayTaskRequest {
+            task_id: id.to_string(),
+            prompt: "test".to_string(),
+            cwd: "/tmp".to_string(),
+            agent_type: "opencode".to_string(),
+            model: None,
+            env: HashMap::new(),
+            timeout: None,
+            metadata: serde_json::Value::Null,
+        };
+        manager
+            .dispatch_or_queue("host-1", task("running"), TaskPriority::Medium)
+            .await
+            .unwrap();
+        manager
+            .dispatch_or_queue("host-1", task("queued"), TaskPriority::Medium)
+            .await
+            .unwrap();
+        assert!(rx.recv().await.is_some());
+        drop(rx);
+
+        manager.handle_task_failed("host-1", "running", "boom").await;
+        let letters = manager.dead_letters().await;
+        assert_eq!(letters.len(), 1);
+        assert_eq!((letters[0].task_id.as_str(), letters[0].attempts), ("queued", 1));
+        let first = letters[0].clone();
+        assert_eq!(first.next_retry_at - first.dead_lettered_at, 5_000);
+
+        // Not due yet
+        assert_eq!(manager.redeliver_due(first.next_retry_at - 1).await, 0);
+        assert_eq!(manager.dead_letters().await[0], first);
+
+        // Each failed redelivery advances the attempt count and doubles the delay
+        assert_eq!(manager.redeliver_due(first.next_retry_at).await, 0);
+        let second = manager.dead_letters().await[0].clone();
+        assert_eq!(second.attempts, 2);
+        assert_eq!(second.next_retry_at, first.next_retry_at + 10_000);
+
+        assert_eq!(manager.redeliver_due(second.next_retry_at).await, 0);
+        let third = manager.dead_letters().await[0].clone();
+        assert_eq!(third.attempts, 3);
+        assert_eq!(third.next_retry_at, second.next_retry_at + 20_000);
+
+        // A forced retry against the reconnected host delivers it right away
+        let (tx, mut rx) = mpsc::channel(10);
+        manager.register_host("host-1".to_string(), caps, tx).await;
+        let outcome = manager.retry_dead_letter("queued").await.unwrap().unwrap();
+        assert_eq!(outcome, DispatchOutcome::Dispatched("host-1".to_string()));
+        match rx.recv().await {
+            Some(ServerToGatewayMessage::TaskExecute { task }) => assert_eq!(task.task_id, "queued"),
+            other => panic!("Expected redelivered task, got {:?}", other),
+        }
+        assert!(manager.dead_letters().await.is_empty());
+        assert!(manager.retry_dead_letter("queued").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn queued_tasks_of_a_removed_host_are_dead_lettered() {
+        let manager = GatewayManager::new();
+        let mut caps = create_test_capabilities();
+        caps.max_concurrent = 1;
+        let task = |id: &str| GatewayTaskRequest {
+            task_id: id.to_string(),
+            prompt: "test".to_string(),
+            cwd: "/tmp".to_string(),
+            agent_type: "opencode".to_string(),
+            model: None,
+            env: HashMap::new(),
+            timeout: None,
+            metadata: serde_json::Value::Null,
+        };
+
+        for (host_id, stale) in [("host-1", false), ("host-2", true)] {
+            let (tx, _rx) = mpsc::channel(10);
+            manager.register_host(host_id.to_string(), caps.clone(), tx).await;
+            for suffix in ["running", "queued"] {
+                manager
+                    .dispatch_or_queue(host_id, task(&format!("{}-{}", host_id, suffix)), TaskPriority::Medium)
+                    .await
+                    .unwrap();
+            }
+            if stale {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                manager.cleanup_stale_connections(Duration::from_millis(1)).await;
+            } else {
+                manager.unregister_host(host_id).await;
+            }
+        }
+
+        let mut dead: Vec<String> = manager
+            .dead_letters()
+            .await
+            .into_iter()
+            .map(|letter| letter.task_id)
+            .collect();
+        dead.sort();
+        assert_eq!(dead, ["host-1-queued", "host-2-queued"]);
+        assert!(!manager.has_active_tasks().await);
+    }
+
+    #[tokio::test]
+    async fn forced_retry_resets_dead_letter_backoff() {
+        let manager = GatewayManager::new();
+        let (tx, rx) = mpsc::channel(10);
+        manager.register_host("host-1".to_string(), create_test_capabilities(), tx).await;
+        drop(rx);
+
+        let task = GatewayTaskRequest {
+            task_id: "stuck".to_string(),
+            prompt: "test".to_string(),
+            cwd: "/tmp".to_string(),
+            agent_type: "opencode".to_string(),
+            model: None,
+            env: HashMap::new(),
+            timeout: None,
+            metadata: serde_json::Value::Null,
+        };
+        let mut letter = DeadLetter::new(task, TaskPriority::Medium, "host-1".to_string(), "closed".to_string(), 0);
+        for _ in 0..5 {
+            letter.record_failure("closed".to_string(), 0);
+        }
+        manager.dead_letters.write().await.push(letter);
+
+        let before = unix_millis();
+        assert!(manager.retry_dead_letter("stuck").await.unwrap().is_err());
+        let status = &manager.dead_letters().await[0];
+        assert_eq!(status.attempts, 1);
+        assert!(status.next_retry_at >= before + 5_000);
+        assert!(status.next_retry_at <= unix_millis() + 5_000);
+    }
+
     #[tokio::test]
     async fn test_heartbeat_update() {
         let manager = GatewayManager::new();
@@ -760,6 +2084,53 @@ mod tests {
         assert!(last_hb2 <= last_hb1);
     }
 
+    #[tokio::test]
+    async fn dispatch_skips_hosts_with_a_stale_heartbeat() {
+        let manager = GatewayManager::new()
+            .with_heartbeat_freshness(Duration::from_secs(30))
+            .with_dispatch_retry(DispatchRetry {
+                attempts: 1,
+                backoff: Duration::ZERO,
+            });
+        let (tx, mut rx) = mpsc::channel(10);
+        manager
+            .register_host("host-1".to_string(), create_test_capabilities(), tx)
+            .await;
+        manager.age_heartbeat("host-1", Duration::from_secs(60)).await;
+
+        let task = GatewayTaskRequest {
+            task_id: "task-1".to_string(),
+            prompt: "test".to_string(),
+            cwd: "/tmp".to_string(),
+            agent_type: "opencode".to_string(),
+            model: None,
+            env: HashMap::new(),
+            timeout: None,
+            metadata: serde_json::Value::Null,
+        };
+        let error = manager
+            .dispatch_task_to_host("host-1", task.clone())
+            .await
+            .unwrap_err();
+        assert!(error.starts_with("Stale heartbeat from host host-1"), "{}", error);
+        assert!(matches!(
+            manager
+                .dispatch_or_queue_with_retry("host-1", task.clone(), TaskPriority::Medium)
+                .await,
+            Err(DispatchError::Stale(_))
+        ));
+        assert!(rx.try_recv().is_err());
+        assert!(manager.list_hosts().await[0].active_tasks.is_empty());
+        let error = manager
+            .preview_dispatch("host-1", "opencode", TaskPriority::Medium)
+            .await
+            .unwrap_err();
+        assert!(error.starts_with("Stale heartbeat from host host-1"), "{}", error);
+
+        manager.update_heartbeat("host-1").await;
+        manager.dispatch_task_to_host("host-1", task).await.unwrap();
+    }
+
     #[tokio::test]
     async fn test_cleanup_stale_connections() {
         let manager = GatewayManager::new();
@@ -808,4 +2179,69 @@ mod tests {
         assert_eq!(broadcast.task_id, "task-1");
         assert_eq!(broadcast.host_id, "host-1");
     }
+
+    #[tokio::test]
+    async fn run_outcomes_move_cards_by_auto_move_policy() {
+        use vk_core::kanban::KanbanTaskStatus;
+        use vk_core::task::{Task, TaskStatus};
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let task_store = Arc::new(
+            FileTaskStore::new(temp_dir.path().join("tasks.json"))
+                .await
+                .unwrap(),
+        );
+        let kanban_store = Arc::new(
+            KanbanStore::with_task_store(temp_dir.path().join("kanban.json"), Arc::clone(&task_store))
+                .await
+                .unwrap(),
+        );
+        let manager = GatewayManager::with_stores(Arc::clone(&task_store), Arc::clone(&kanban_store));
+        let (tx, _rx) = mpsc::channel(10);
+        manager
+            .register_host("host-1".to_string(), create_test_capabilities(), tx)
+            .await;
+
+        let succeeded = task_store.create(Task::new("Succeeds")).await.unwrap().id.to_string();
+        let failed = task_store.create(Task::new("Fails")).await.unwrap().id.to_string();
+        kanban_store.get_state_synced().await.unwrap();
+
+        // Default policy: start -> Doing, success -> Done
+        manager.apply_auto_move(&succeeded, RunTransition::Started).await;
+        manager
+            .handle_task_completed(
+                "host-1",
+                &succeeded,
+                TaskResult {
+                    success: true,
+                    exit_code: Some(0),
+                    output: None,
+                    summary: None,
+                    duration: None,
+                    files_changed: vec![],
+                },
+            )
+            .await;
+        assert_eq!(kanban_store.get_task(&succeeded).await.unwrap().status, KanbanTaskStatus::Done);
+
+        // Failure policy that leaves the card in Doing
+        manager
+            .set_auto_move_policy(
+                &failed,
+                KanbanAutoMovePolicy {
+                    on_failure: None,
+                    ..KanbanAutoMovePolicy::default()
+                },
+            )
+            .await;
+        manager.apply_auto_move(&failed, RunTransition::Started).await;
+        manager.handle_task_failed("host-1", &failed, "boom").await;
+        assert_eq!(kanban_store.get_task(&failed).await.unwrap().status, KanbanTaskStatus::Doing);
+        let task = task_store.get(uuid::Uuid::parse_str(&failed).unwrap()).await.unwrap().unwrap();
+        assert_eq!(task.status, TaskStatus::InProgress);
+
+        // Default failure policy sends the card back to Todo
+        manager.apply_auto_move(&failed, RunTransition::Failed).await;
+        assert_eq!(kanban_store.get_task(&failed).await.unwrap().status, KanbanTaskStatus::Todo);
+    }
 }
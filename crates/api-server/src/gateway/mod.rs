@@ -3,9 +3,19 @@
 //! This module provides WebSocket-based communication with remote Agent Gateways
 //! that execute tasks on behalf of the server.
 
+pub mod agent_version;
+pub mod dead_letter;
+pub mod dispatch_retry;
+pub mod journal;
 pub mod protocol;
 pub mod manager;
 pub mod handler;
+pub mod mtls;
+pub mod output;
 
 pub use manager::GatewayManager;
-pub use handler::{gateway_ws_handler, list_hosts_handler, get_host_models_handler, start_heartbeat_checker};
+pub use mtls::MtlsConfig;
+pub use handler::{
+    gateway_ws_handler, get_host_models_handler, list_hosts_handler, stall_timeout_from_env,
+    start_dead_letter_redelivery, start_heartbeat_checker, start_stall_detector,
+};
@@ -0,0 +1,385 @@
+//! Mutual TLS for gateway host connections
+//!
+//! When `VK_GATEWAY_TLS_CERT`, `VK_GATEWAY_TLS_KEY` and `VK_GATEWAY_TLS_CA` are
+//! all set, the gateway WebSocket is served on `VK_GATEWAY_TLS_ADDR` and hosts
+//! must present a certificate issued by the CA whose subject common name
+//! matches their `hostId`. Without them, hosts authenticate with the bearer
+//! token as before. Setting only some of the three is a startup error.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::connect_info::Connected;
+use axum::serve::{IncomingStream, Listener};
+use axum::Router;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::server::WebPkiClientVerifier;
+use rustls::{RootCertStore, ServerConfig};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_rustls::server::TlsStream;
+use tokio_rustls::TlsAcceptor;
+use tracing::{info, warn};
+
+/// Environment variable holding the server certificate chain (PEM)
+pub const TLS_CERT_ENV: &str = "VK_GATEWAY_TLS_CERT";
+/// Environment variable holding the server private key (PEM)
+pub const TLS_KEY_ENV: &str = "VK_GATEWAY_TLS_KEY";
+/// Environment variable holding the CA that issues host certificates (PEM)
+pub const TLS_CA_ENV: &str = "VK_GATEWAY_TLS_CA";
+/// Environment variable holding the mTLS listen address
+pub const TLS_ADDR_ENV: &str = "VK_GATEWAY_TLS_ADDR";
+
+const DEFAULT_TLS_ADDR: &str = "0.0.0.0:8443";
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+const HANDSHAKE_QUEUE: usize = 64;
+
+/// Paths and address for the mTLS gateway listener
+#[derive(Debug, Clone)]
+pub struct MtlsConfig {
+    pub cert_path: String,
+    pub key_path: String,
+    pub ca_path: String,
+    pub addr: SocketAddr,
+}
+
+impl MtlsConfig {
+    /// Load the mTLS settings, or None when none of them are set
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let (cert_path, key_path, ca_path) = match (
+            std::env::var(TLS_CERT_ENV).ok(),
+            std::env::var(TLS_KEY_ENV).ok(),
+            std::env::var(TLS_CA_ENV).ok(),
+        ) {
+            (Some(cert), Some(key), Some(ca)) => (cert, key, ca),
+            (None, None, None) => return Ok(None),
+            (cert, key, ca) => {
+                let missing: Vec<_> = [(TLS_CERT_ENV, cert), (TLS_KEY_ENV, key), (TLS_CA_ENV, ca)]
+                    .into_iter()
+                    .filter(|(_, value)| value.is_none())
+                    .map(|(name, _)| name)
+                    .collect();
+                return Err(format!(
+                    "Incomplete gateway mTLS config, missing {}",
+                    missing.join(", ")
+                ));
+            }
+        };
+        let addr = std::env::var(TLS_ADDR_ENV).unwrap_or_else(|_| DEFAULT_TLS_ADDR.to_string());
+        let addr = addr
+            .parse()
+            .map_err(|e| format!("Invalid {} value {:?}: {}", TLS_ADDR_ENV, addr, e))?;
+
+        Ok(Some(Self {
+            cert_path,
+            key_path,
+            ca_path,
+            addr,
+        }))
+    }
+
+    /// Build a TLS server config that requires CA-issued client certificates
+    pub fn server_config(&self) -> Result<Arc<ServerConfig>, String> {
+        let read = |path: &str| std::fs::read(path).map_err(|e| format!("{}: {}", path, e));
+        server_config(
+            &read(&self.cert_path)?,
+            &read(&self.key_path)?,
+            &read(&self.ca_path)?,
+        )
+    }
+}
+
+/// Build a TLS server config from PEM-encoded cert chain, key and client CA
+pub fn server_config(cert_pem: &[u8], key_pem: &[u8], ca_pem: &[u8]) -> Result<Arc<ServerConfig>, String> {
+    let certs = CertificateDer::pem_slice_iter(cert_pem)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Invalid server certificate: {}", e))?;
+    let key = PrivateKeyDer::from_pem_slice(key_pem)
+        .map_err(|e| format!("Invalid server key: {}", e))?;
+
+    let mut roots = RootCertStore::empty();
+    for ca in CertificateDer::pem_slice_iter(ca_pem) {
+        let ca = ca.map_err(|e| format!("Invalid CA certificate: {}", e))?;
+        roots
+            .add(ca)
+            .map_err(|e| format!("Invalid CA certificate: {}", e))?;
+    }
+
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), Arc::clone(&provider))
+        .build()
+        .map_err(|e| format!("Invalid client CA: {}", e))?;
+
+    let config = ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(|e| e.to_string())?
+        .with_client_cert_verifier(verifier)
+        .with_single_cert(certs, key)
+        .map_err(|e| format!("Invalid server certificate: {}", e))?;
+
+    Ok(Arc::new(config))
+}
+
+/// Identity of a host connected over mTLS
+#[derive(Debug, Clone)]
+pub struct GatewayPeer {
+    pub addr: SocketAddr,
+    /// Subject common name of the verified client certificate
+    pub subject_cn: Option<String>,
+}
+
+impl Connected<IncomingStream<'_, MtlsListener>> for GatewayPeer {
+    fn connect_info(stream: IncomingStream<'_, MtlsListener>) -> Self {
+        stream.remote_addr().clone()
+    }
+}
+
+type Handshaken = (TlsStream<TcpStream>, GatewayPeer);
+
+/// TCP listener that completes a verified TLS handshake before yielding a connection
+///
+/// Each handshake runs in its own task, so a client that stalls mid-handshake
+/// doesn't hold up the others.
+pub struct MtlsListener {
+    addr: SocketAddr,
+    handshaken: mpsc::Receiver<Handshaken>,
+    accept_loop: JoinHandle<()>,
+}
+
+impl MtlsListener {
+    pub fn new(tcp: TcpListener, config: Arc<ServerConfig>) -> std::io::Result<Self> {
+        let addr = tcp.local_addr()?;
+        let (tx, handshaken) = mpsc::channel(HANDSHAKE_QUEUE);
+        let accept_loop = tokio::spawn(accept_loop(tcp, TlsAcceptor::from(config), tx));
+        Ok(Self {
+            addr,
+            handshaken,
+            accept_loop,
+        })
+    }
+}
+
+impl Drop for MtlsListener {
+    fn drop(&mut self) {
+        self.accept_loop.abort();
+    }
+}
+
+impl Listener for MtlsListener {
+    type Io = TlsStream<TcpStream>;
+    type Addr = GatewayPeer;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        match self.handshaken.recv().await {
+            Some(conn) => conn,
+            // The accept loop only stops when this listener is dropped
+            None => std::future::pending().await,
+        }
+    }
+
+    fn local_addr(&self) -> std::io::Result<Self::Addr> {
+        Ok(GatewayPeer {
+            addr: self.addr,
+            subject_cn: None,
+        })
+    }
+}
+
+/// Accept TCP connections and hand each one to its own handshake task
+async fn accept_loop(tcp: TcpListener, acceptor: TlsAcceptor, tx: mpsc::Sender<Handshaken>) {
+    loop {
+        let (stream, addr) = tokio::select! {
+            accepted = tcp.accept() => match accepted {
+                Ok(conn) => conn,
+                Err(e) => {
+                    warn!("Failed to accept gateway TLS connection: {}", e);
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    continue;
+                }
+            },
+            _ = tx.closed() => return,
+        };
+
+        let acceptor = acceptor.clone();
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            match handshake(&acceptor, stream, addr).await {
+                Ok(conn) => {
+                    let _ = tx.send(conn).await;
+                }
+                Err(e) => warn!("Rejected gateway TLS connection from {}: {}", addr, e),
+            }
+        });
+    }
+}
+
+/// Run the TLS handshake and extract the client's certificate identity
+async fn handshake(
+    acceptor: &TlsAcceptor,
+    stream: TcpStream,
+    addr: SocketAddr,
+) -> Result<(TlsStream<TcpStream>, GatewayPeer), String> {
+    let tls = tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream))
+        .await
+        .map_err(|_| "TLS handshake timed out".to_string())?
+        .map_err(|e| e.to_string())?;
+
+    let subject_cn = tls
+        .get_ref()
+        .1
+        .peer_certificates()
+        .and_then(|certs| certs.first())
+        .and_then(|cert| subject_common_name(cert));
+
+    Ok((tls, GatewayPeer { addr, subject_cn }))
+}
+
+/// Subject common name of a DER-encoded certificate
+pub fn subject_common_name(cert_der: &[u8]) -> Option<String> {
+    let (_, cert) = x509_parser::parse_x509_certificate(cert_der).ok()?;
+    let cn = cert.subject().iter_common_name().next()?;
+    cn.as_str().ok().map(str::to_string)
+}
+
+/// Load the TLS config and bind the mTLS listener
+pub async fn bind(config: &MtlsConfig) -> Result<MtlsListener, String> {
+    let tls_config = config.server_config()?;
+    let tcp = TcpListener::bind(config.addr)
+        .await
+        .map_err(|e| format!("Failed to bind {}: {}", config.addr, e))?;
+    info!("Gateway mTLS listener on {}", config.addr);
+    MtlsListener::new(tcp, tls_config).map_err(|e| format!("Failed to bind {}: {}", config.addr, e))
+}
+
+/// Serve the gateway router on the mTLS listener until `shutdown` resolves
+pub async fn serve(
+    listener: MtlsListener,
+    router: Router,
+    shutdown: impl std::future::Future<Output = ()> + Send + 'static,
+) -> std::io::Result<()> {
+    axum::serve(
+        listener,
+        router.into_make_service_with_connect_info::<GatewayPeer>(),
+    )
+    .with_graceful_shutdown(shutdown)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustls::ClientConfig;
+    use rustls::pki_types::ServerName;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio_rustls::TlsConnector;
+
+    macro_rules! fixture {
+        ($name:literal) => {
+            include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/mtls/", $name))
+        };
+    }
+
+    fn test_server_config() -> Arc<ServerConfig> {
+        server_config(
+            fixture!("host-1.pem"),
+            fixture!("host-1-key.pem"),
+            fixture!("ca.pem"),
+        )
+        .unwrap()
+    }
+
+    fn client_config(cert_pem: &[u8], key_pem: &[u8]) -> Arc<ClientConfig> {
+        let mut roots = RootCertStore::empty();
+        roots
+            .add(CertificateDer::from_pem_slice(fixture!("ca.pem")).unwrap())
+            .unwrap();
+        let certs = CertificateDer::pem_slice_iter(cert_pem)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let key = PrivateKeyDer::from_pem_slice(key_pem).unwrap();
+
+        let config = ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_root_certificates(roots)
+            .with_client_auth_cert(certs, key)
+            .unwrap();
+        Arc::new(config)
+    }
+
+    /// Connect with the given client cert and return the server-side handshake result
+    async fn connect(cert_pem: &[u8], key_pem: &[u8]) -> Result<GatewayPeer, String> {
+        let acceptor = TlsAcceptor::from(test_server_config());
+        let tcp = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = tcp.local_addr().unwrap();
+
+        let connector = TlsConnector::from(client_config(cert_pem, key_pem));
+        let client = tokio::spawn(async move {
+            let stream = TcpStream::connect(addr).await.unwrap();
+            let name = ServerName::try_from("host-1").unwrap();
+            if let Ok(mut tls) = connector.connect(name, stream).await {
+                let _ = tls.write_all(b"ping").await;
+                let _ = tls.read(&mut [0u8; 4]).await;
+            }
+        });
+
+        let (stream, peer_addr) = tcp.accept().await.unwrap();
+        let result = handshake(&acceptor, stream, peer_addr).await.map(|(_, peer)| peer);
+        client.abort();
+        result
+    }
+
+    #[tokio::test]
+    async fn trusted_client_cert_binds_subject_to_host_id() {
+        let peer = connect(fixture!("host-1.pem"), fixture!("host-1-key.pem"))
+            .await
+            .expect("trusted certificate should be accepted");
+        assert_eq!(peer.subject_cn.as_deref(), Some("host-1"));
+    }
+
+    #[tokio::test]
+    async fn untrusted_client_cert_is_rejected() {
+        let result = connect(fixture!("rogue-host-1.pem"), fixture!("rogue-host-1-key.pem")).await;
+        assert!(result.is_err(), "certificate from an unknown CA must be rejected");
+    }
+
+    #[tokio::test]
+    async fn stalled_handshake_does_not_block_other_hosts() {
+        let tcp = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = tcp.local_addr().unwrap();
+        let mut listener = MtlsListener::new(tcp, test_server_config()).unwrap();
+
+        // Opens a TCP connection but never starts the handshake
+        let _stalled = TcpStream::connect(addr).await.unwrap();
+
+        let connector = TlsConnector::from(client_config(fixture!("host-1.pem"), fixture!("host-1-key.pem")));
+        let client = tokio::spawn(async move {
+            let stream = TcpStream::connect(addr).await.unwrap();
+            let name = ServerName::try_from("host-1").unwrap();
+            let mut tls = connector.connect(name, stream).await.unwrap();
+            let _ = tls.read(&mut [0u8; 4]).await;
+        });
+
+        let (_, peer) = tokio::time::timeout(Duration::from_secs(5), listener.accept())
+            .await
+            .expect("handshake should not wait on the stalled connection");
+        assert_eq!(peer.subject_cn.as_deref(), Some("host-1"));
+        client.abort();
+    }
+
+    #[test]
+    fn partial_env_config_is_rejected() {
+        std::env::set_var(TLS_CERT_ENV, "/etc/vk/gateway.pem");
+        std::env::remove_var(TLS_KEY_ENV);
+        std::env::remove_var(TLS_CA_ENV);
+        let result = MtlsConfig::from_env();
+        std::env::remove_var(TLS_CERT_ENV);
+
+        let err = result.unwrap_err();
+        assert!(err.contains(TLS_KEY_ENV) && err.contains(TLS_CA_ENV), "{}", err);
+        assert!(MtlsConfig::from_env().unwrap().is_none());
+    }
+}
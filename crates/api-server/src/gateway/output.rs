@@ -0,0 +1,165 @@
+//! Bounded buffer for streamed gateway output
+//!
+//! Gateway runs stream their stdout into a single chat message. Chatty runs
+//! can produce far more than is useful to keep in memory, so the buffer keeps
+//! only the head and tail once it exceeds `VK_GATEWAY_OUTPUT_MAX_BYTES` and
+//! marks the omitted middle.
+//!
+//! Single event contents are capped on ingest as well
+//! (`VK_GATEWAY_EVENT_MAX_BYTES`), so a misbehaving host can't push huge
+//! payloads into the broadcast channel or the run log.
+
+use crate::env::positive_env_or_default;
+
+/// Environment variable capping the accumulated output of a gateway run
+pub const OUTPUT_MAX_BYTES_ENV: &str = "VK_GATEWAY_OUTPUT_MAX_BYTES";
+
+/// Default cap on the accumulated output of a gateway run (256 KiB)
+pub const DEFAULT_OUTPUT_MAX_BYTES: usize = 256 * 1024;
+
+/// Load the output cap from the process environment
+pub fn output_max_bytes_from_env() -> usize {
+    positive_env_or_default(OUTPUT_MAX_BYTES_ENV, DEFAULT_OUTPUT_MAX_BYTES)
+}
+
+/// Environment variable capping the content of a single gateway event
+pub const EVENT_MAX_BYTES_ENV: &str = "VK_GATEWAY_EVENT_MAX_BYTES";
+
+/// Default cap on the content of a single gateway event (64 KiB)
+pub const DEFAULT_EVENT_MAX_BYTES: usize = 64 * 1024;
+
+/// Load the event content cap from the process environment
+pub fn event_max_bytes_from_env() -> usize {
+    positive_env_or_default(EVENT_MAX_BYTES_ENV, DEFAULT_EVENT_MAX_BYTES)
+}
+
+/// Cut `content` to at most `max_bytes`, appending a truncation marker
+///
+/// Returns the number of bytes dropped.
+pub fn truncate_content(content: &mut String, max_bytes: usize) -> usize {
+    if content.len() <= max_bytes {
+        return 0;
+    }
+    let keep = floor_char_boundary(content, max_bytes);
+    let dropped = content.len() - keep;
+    content.truncate(keep);
+    content.push_str(&format!("\n[... {} bytes truncated ...]", dropped));
+    dropped
+}
+
+/// Output buffer that keeps the head and tail once it exceeds its cap
+#[derive(Debug, Clone)]
+pub struct OutputAccumulator {
+    max_bytes: usize,
+    head: String,
+    tail: String,
+    omitted_bytes: usize,
+}
+
+impl OutputAccumulator {
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            max_bytes,
+            head: String::new(),
+            tail: String::new(),
+            omitted_bytes: 0,
+        }
+    }
+
+    /// Append a chunk, dropping the oldest tail bytes past the cap
+    pub fn push_str(&mut self, chunk: &str) {
+        let head_budget = self.max_bytes / 2;
+        let mut rest = chunk;
+        if self.tail.is_empty() && self.head.len() < head_budget {
+            let take = floor_char_boundary(rest, head_budget - self.head.len());
+            self.head.push_str(&rest[..take]);
+            rest = &rest[take..];
+        }
+        if rest.is_empty() {
+            return;
+        }
+
+        self.tail.push_str(rest);
+        let tail_budget = self.max_bytes - head_budget;
+        if self.tail.len() > tail_budget {
+            let cut = ceil_char_boundary(&self.tail, self.tail.len() - tail_budget);
+            self.tail.drain(..cut);
+            self.omitted_bytes += cut;
+        }
+    }
+
+    /// Whether any output was dropped
+    pub fn is_truncated(&self) -> bool {
+        self.omitted_bytes > 0
+    }
+
+    /// The kept output, with a marker where the middle was dropped
+    pub fn to_message(&self) -> String {
+        if self.is_truncated() {
+            format!(
+                "{}\n\n[... {} bytes of output truncated ...]\n\n{}",
+                self.head, self.omitted_bytes, self.tail
+            )
+        } else {
+            format!("{}{}", self.head, self.tail)
+        }
+    }
+}
+
+fn floor_char_boundary(s: &str, index: usize) -> usize {
+    let mut index = index.min(s.len());
+    while !s.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+fn ceil_char_boundary(s: &str, index: usize) -> usize {
+    let mut index = index.min(s.len());
+    while !s.is_char_boundary(index) {
+        index += 1;
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_output_is_kept_verbatim() {
+        let mut output = OutputAccumulator::new(64);
+        output.push_str("hello ");
+        output.push_str("world");
+        assert!(!output.is_truncated());
+        assert_eq!(output.to_message(), "hello world");
+    }
+
+    #[test]
+    fn oversized_output_keeps_head_and_tail_with_marker() {
+        let mut output = OutputAccumulator::new(1024);
+        output.push_str("BEGIN");
+        for _ in 0..10_000 {
+            output.push_str("中间输出 chunk\n");
+        }
+        output.push_str("END");
+
+        let message = output.to_message();
+        assert!(output.is_truncated());
+        assert!(message.starts_with("BEGIN"));
+        assert!(message.ends_with("END"));
+        assert!(message.contains("bytes of output truncated"));
+        assert!(message.len() <= 1024 + 64, "message is {} bytes", message.len());
+    }
+
+    #[test]
+    fn truncate_content_cuts_on_char_boundary() {
+        let mut content = "短".repeat(10);
+        assert_eq!(truncate_content(&mut content, 7), 24);
+        assert!(content.starts_with("短短\n[... 24 bytes truncated ...]"));
+
+        let mut small = "ok".to_string();
+        assert_eq!(truncate_content(&mut small, 7), 0);
+        assert_eq!(small, "ok");
+    }
+}
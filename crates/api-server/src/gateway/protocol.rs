@@ -1,5 +1,6 @@
 //! Gateway protocol types for Agent Gateway communication
 
+use agent_runner::{AgentEvent, FileAction};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -13,6 +14,9 @@ pub struct HostCapabilities {
     pub cwd: String,
     #[serde(default)]
     pub labels: HashMap<String, String>,
+    /// Installed CLI version of each agent, keyed by agent type
+    #[serde(default)]
+    pub agent_versions: HashMap<String, String>,
 }
 
 /// Task request sent from server to gateway
@@ -66,6 +70,47 @@ pub struct GatewayAgentEvent {
     pub timestamp: u64,
 }
 
+/// Payload of a `file_change` gateway event
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GatewayFileChange {
+    path: String,
+    #[serde(default, alias = "action")]
+    change_type: Option<String>,
+    #[serde(default)]
+    diff: Option<String>,
+    #[serde(default)]
+    additions: Option<u32>,
+    #[serde(default)]
+    deletions: Option<u32>,
+}
+
+impl GatewayAgentEvent {
+    /// Structured form of a `file_change` event, parsed from `data`
+    ///
+    /// Returns None for other event types or when `data` has no path.
+    pub fn to_file_change(&self) -> Option<AgentEvent> {
+        if !matches!(self.event_type, GatewayAgentEventType::FileChange) {
+            return None;
+        }
+        let change: GatewayFileChange = serde_json::from_value(self.data.clone()).ok()?;
+        let action = match change.change_type.as_deref().map(str::to_ascii_lowercase).as_deref() {
+            Some("created" | "create" | "added" | "add") => FileAction::Created,
+            Some("deleted" | "delete" | "removed" | "remove") => FileAction::Deleted,
+            Some("renamed" | "rename" | "moved") => FileAction::Renamed,
+            _ => FileAction::Modified,
+        };
+
+        Some(AgentEvent::FileChange {
+            path: change.path,
+            action,
+            diff: change.diff,
+            additions: change.additions,
+            deletions: change.deletions,
+        })
+    }
+}
+
 /// Task result - returned when task completes
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -75,6 +120,9 @@ pub struct TaskResult {
     pub exit_code: Option<i32>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub output: Option<String>,
+    /// Concise summary reported by the agent, separate from its output
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub duration: Option<u64>,
     #[serde(default)]
@@ -126,6 +174,25 @@ pub enum GatewayToServerMessage {
         request_id: String,
         providers: Vec<ProviderInfo>,
     },
+    /// Sent after a reconnect with the tasks the host is still running
+    #[serde(rename = "tasks:query")]
+    TasksQuery {
+        #[serde(rename = "taskIds")]
+        task_ids: Vec<String>,
+    },
+}
+
+impl GatewayToServerMessage {
+    /// The task a task message reports on, with the message's wire type
+    pub fn task_report(&self) -> Option<(&str, &'static str)> {
+        match self {
+            Self::TaskStarted { task_id, .. } => Some((task_id, "task:started")),
+            Self::TaskEvent { task_id, .. } => Some((task_id, "task:event")),
+            Self::TaskCompleted { task_id, .. } => Some((task_id, "task:completed")),
+            Self::TaskFailed { task_id, .. } => Some((task_id, "task:failed")),
+            _ => None,
+        }
+    }
 }
 
 /// Server -> Gateway messages
@@ -158,6 +225,12 @@ pub enum ServerToGatewayMessage {
         #[serde(rename = "requestId")]
         request_id: String,
     },
+    /// Answer to `tasks:query`; the host should abandon `abandoned` tasks
+    #[serde(rename = "tasks:status")]
+    TasksStatus {
+        active: Vec<String>,
+        abandoned: Vec<String>,
+    },
 }
 
 /// Host status - used for API responses
@@ -169,10 +242,40 @@ pub struct HostStatus {
     pub status: HostConnectionStatus,
     pub capabilities: HostCapabilities,
     pub active_tasks: Vec<String>,
+    /// Tasks currently dispatched to this host, oldest first
+    #[serde(default)]
+    pub in_flight: Vec<InFlightTask>,
+    /// Tasks waiting for a free slot on this host
+    #[serde(default)]
+    pub queue_depth: usize,
     pub last_heartbeat: u64,
     pub connected_at: u64,
 }
 
+/// A task dispatched to a host that has not finished yet
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InFlightTask {
+    pub task_id: String,
+    /// Unix timestamp (ms) when the task was dispatched
+    pub dispatched_at: u64,
+    /// Unix timestamp (ms) when the host reported the task as started
+    pub started_at: Option<u64>,
+    pub session_id: Option<String>,
+    /// Unix timestamp (ms) of the last event the host sent for the task
+    #[serde(default)]
+    pub last_event_at: Option<u64>,
+}
+
+impl InFlightTask {
+    /// Unix timestamp (ms) of the last sign of life from the task
+    pub fn last_activity_at(&self) -> u64 {
+        self.last_event_at
+            .or(self.started_at)
+            .unwrap_or(self.dispatched_at)
+    }
+}
+
 /// Host connection status
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -226,6 +329,7 @@ mod tests {
                 max_concurrent: 2,
                 cwd: "/home/user".to_string(),
                 labels: HashMap::new(),
+                agent_versions: HashMap::new(),
             },
         };
 
@@ -234,6 +338,45 @@ mod tests {
         assert!(json.contains("\"hostId\":\"host-1\""));
     }
 
+    #[test]
+    fn file_change_event_maps_to_structured_agent_event() {
+        let event: GatewayAgentEvent = serde_json::from_value(serde_json::json!({
+            "type": "file_change",
+            "data": {
+                "path": "src/main.rs",
+                "changeType": "added",
+                "additions": 12,
+                "deletions": 3
+            },
+            "timestamp": 1
+        }))
+        .unwrap();
+
+        match event.to_file_change() {
+            Some(AgentEvent::FileChange {
+                path,
+                action,
+                additions,
+                deletions,
+                ..
+            }) => {
+                assert_eq!(path, "src/main.rs");
+                assert_eq!(action, FileAction::Created);
+                assert_eq!(additions, Some(12));
+                assert_eq!(deletions, Some(3));
+            }
+            other => panic!("expected file change, got {:?}", other),
+        }
+
+        let log = GatewayAgentEvent {
+            event_type: GatewayAgentEventType::Log,
+            content: Some("src/main.rs".to_string()),
+            data: serde_json::json!({ "path": "src/main.rs" }),
+            timestamp: 2,
+        };
+        assert!(log.to_file_change().is_none());
+    }
+
     #[test]
     fn test_server_to_gateway_message_serialization() {
         let msg = ServerToGatewayMessage::Registered {
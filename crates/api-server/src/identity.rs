@@ -0,0 +1,84 @@
+//! Identity of the caller behind a request
+//!
+//! The server keeps no user accounts. A proxy in front of it that
+//! authenticates users forwards the subject in `X-VK-User`; runs started
+//! through it record that subject as their creator. Any client can send the
+//! header, so it is only honoured when `VK_TRUST_USER_HEADER=true` says such
+//! a proxy sits in front. Otherwise, and for requests without the header
+//! (local use), there is no identity.
+
+use std::convert::Infallible;
+
+use axum::{
+    extract::OptionalFromRequestParts,
+    http::{request::Parts, HeaderMap},
+};
+
+use crate::state::AppState;
+
+/// Header carrying the authenticated subject
+pub const USER_HEADER: &str = "x-vk-user";
+
+/// Environment variable that enables trusting `USER_HEADER`
+pub const TRUST_USER_HEADER_ENV: &str = "VK_TRUST_USER_HEADER";
+
+/// Whether `VK_TRUST_USER_HEADER` says a proxy sets the user header
+pub fn trust_user_header_from_env() -> bool {
+    std::env::var(TRUST_USER_HEADER_ENV)
+        .map(|v| v.trim().eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+/// Authenticated caller
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserIdentity {
+    /// Subject identifier, e.g. a user name or id
+    pub sub: String,
+}
+
+impl UserIdentity {
+    /// Identity from the request headers, if one was forwarded by a trusted proxy
+    pub fn from_headers(headers: &HeaderMap, trust_header: bool) -> Option<Self> {
+        if !trust_header {
+            return None;
+        }
+        let sub = headers.get(USER_HEADER)?.to_str().ok()?.trim();
+        (!sub.is_empty()).then(|| Self { sub: sub.to_string() })
+    }
+}
+
+impl OptionalFromRequestParts<AppState> for UserIdentity {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Option<Self>, Self::Rejection> {
+        Ok(Self::from_headers(&parts.headers, state.trust_user_header()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn identity_requires_a_non_empty_header() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(UserIdentity::from_headers(&headers, true), None);
+
+        headers.insert(USER_HEADER, HeaderValue::from_static("  "));
+        assert_eq!(UserIdentity::from_headers(&headers, true), None);
+
+        headers.insert(USER_HEADER, HeaderValue::from_static(" alice "));
+        assert_eq!(
+            UserIdentity::from_headers(&headers, true),
+            Some(UserIdentity { sub: "alice".to_string() })
+        );
+    }
+
+    #[test]
+    fn header_is_ignored_unless_trusted() {
+        let mut headers = HeaderMap::new();
+        headers.insert(USER_HEADER, HeaderValue::from_static("alice"));
+        assert_eq!(UserIdentity::from_headers(&headers, false), None);
+    }
+}
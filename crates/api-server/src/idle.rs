@@ -0,0 +1,156 @@
+//! Shutdown-on-idle for ephemeral deployments
+//!
+//! With `VK_IDLE_SHUTDOWN_SECS` set, the server shuts down gracefully once
+//! it has gone that long without an HTTP request and without an active
+//! execution, local or on a gateway host. Any request or running execution
+//! resets the timer.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::env::env_parse;
+use crate::state::AppState;
+
+/// Environment variable with the idle window in seconds; unset or `0` disables it
+pub const IDLE_SHUTDOWN_ENV: &str = "VK_IDLE_SHUTDOWN_SECS";
+
+/// Load the idle window from the process environment
+pub fn idle_timeout_from_env() -> Option<Duration> {
+    env_parse::<u64>(IDLE_SHUTDOWN_ENV)
+        .filter(|&secs| secs > 0)
+        .map(Duration::from_secs)
+}
+
+/// Time of the last observed activity
+#[derive(Debug, Clone)]
+pub struct ActivityTracker {
+    started: Instant,
+    last_ms: Arc<AtomicU64>,
+}
+
+impl Default for ActivityTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ActivityTracker {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            last_ms: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Record activity now
+    pub fn touch(&self) {
+        let now = self.started.elapsed().as_millis() as u64;
+        self.last_ms.fetch_max(now, Ordering::Relaxed);
+    }
+
+    /// Time since the last activity
+    pub fn idle_for(&self) -> Duration {
+        let last = Duration::from_millis(self.last_ms.load(Ordering::Relaxed));
+        self.started.elapsed().saturating_sub(last)
+    }
+}
+
+/// Middleware recording every request as activity
+pub async fn track_activity(
+    State(tracker): State<ActivityTracker>,
+    request: Request,
+    next: Next,
+) -> Response {
+    tracker.touch();
+    let response = next.run(request).await;
+    // Count long requests up to the moment they finish
+    tracker.touch();
+    response
+}
+
+/// Whether any execution is still running, locally or on a gateway host
+pub async fn has_active_executions(state: &AppState) -> bool {
+    let local = state
+        .executor()
+        .list_sessions()
+        .await
+        .iter()
+        .any(|(_, _, session)| !session.is_terminal());
+    local || state.gateway_manager().has_active_tasks().await
+}
+
+/// Wait until `window` passes with no activity while `is_busy` reports
+/// nothing running, then call `shutdown`
+pub async fn shutdown_when_idle<B, F>(
+    tracker: ActivityTracker,
+    window: Duration,
+    mut is_busy: B,
+    shutdown: impl FnOnce(),
+) where
+    B: FnMut() -> F,
+    F: Future<Output = bool>,
+{
+    let period = (window / 4).clamp(Duration::from_millis(10), Duration::from_secs(30));
+    let mut interval = tokio::time::interval(period);
+    loop {
+        interval.tick().await;
+        if is_busy().await {
+            tracker.touch();
+        } else if tracker.idle_for() >= window {
+            break;
+        }
+    }
+    tracing::info!("No activity for {:?}, shutting down", window);
+    shutdown();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicBool;
+
+    #[tokio::test]
+    async fn shutdown_runs_after_idle_window_and_activity_resets_it() {
+        let tracker = ActivityTracker::new();
+        let busy = Arc::new(AtomicBool::new(true));
+        let fired = Arc::new(AtomicBool::new(false));
+        let window = Duration::from_millis(80);
+
+        let watcher = tokio::spawn({
+            let tracker = tracker.clone();
+            let busy = Arc::clone(&busy);
+            let fired = Arc::clone(&fired);
+            async move {
+                let is_busy = move || {
+                    let busy = busy.load(Ordering::SeqCst);
+                    async move { busy }
+                };
+                shutdown_when_idle(tracker, window, is_busy, move || fired.store(true, Ordering::SeqCst)).await;
+            }
+        });
+
+        // A running execution keeps the server up past the window
+        tokio::time::sleep(window * 3).await;
+        assert!(!fired.load(Ordering::SeqCst));
+
+        // Requests keep resetting the timer once nothing runs
+        busy.store(false, Ordering::SeqCst);
+        for _ in 0..6 {
+            tracker.touch();
+            tokio::time::sleep(window / 4).await;
+        }
+        assert!(!fired.load(Ordering::SeqCst));
+
+        tokio::time::timeout(window * 10, watcher).await.unwrap().unwrap();
+        assert!(fired.load(Ordering::SeqCst));
+        assert!(tracker.idle_for() >= window);
+    }
+}
@@ -0,0 +1,81 @@
+//! Localized status text for task chat messages
+//!
+//! Gateway executions post short status messages (working, completed,
+//! failed) into the task chat. `VK_LOCALE` selects their language; English
+//! is the default.
+
+/// Environment variable selecting the status message locale
+pub const LOCALE_ENV: &str = "VK_LOCALE";
+
+/// Status messages posted to the task chat
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusMessage {
+    /// Placeholder shown while the agent is running
+    Working,
+    /// Fallback when a run completes without output
+    Completed,
+    /// Fallback when a run fails without an error message
+    Failed,
+}
+
+/// Language of the status messages
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Locale {
+    #[default]
+    En,
+    Zh,
+}
+
+impl Locale {
+    /// Parse a locale tag such as `en`, `zh-CN` or `zh_CN.UTF-8`
+    pub fn parse(value: &str) -> Option<Self> {
+        let language = value
+            .trim()
+            .split(['-', '_', '.'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match language.as_str() {
+            "en" => Some(Self::En),
+            "zh" => Some(Self::Zh),
+            _ => None,
+        }
+    }
+
+    /// Load the locale from the process environment
+    pub fn from_env() -> Self {
+        match std::env::var(LOCALE_ENV) {
+            Ok(raw) => Self::parse(&raw).unwrap_or_else(|| {
+                tracing::warn!("Ignoring unsupported {} value {:?}", LOCALE_ENV, raw);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Text of a status message in this locale
+    pub fn text(self, message: StatusMessage) -> &'static str {
+        match (self, message) {
+            (Self::En, StatusMessage::Working) => "Working...",
+            (Self::En, StatusMessage::Completed) => "Task completed",
+            (Self::En, StatusMessage::Failed) => "Task failed",
+            (Self::Zh, StatusMessage::Working) => "正在工作中...",
+            (Self::Zh, StatusMessage::Completed) => "任务完成",
+            (Self::Zh, StatusMessage::Failed) => "任务执行失败",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_accepts_language_tags() {
+        assert_eq!(Locale::parse("en"), Some(Locale::En));
+        assert_eq!(Locale::parse("zh-CN"), Some(Locale::Zh));
+        assert_eq!(Locale::parse("ZH_cn.UTF-8"), Some(Locale::Zh));
+        assert_eq!(Locale::parse("fr"), None);
+        assert_eq!(Locale::default().text(StatusMessage::Working), "Working...");
+    }
+}
@@ -3,27 +3,47 @@
 //! This is the main entry point for the Rust backend.
 //! It provides REST API on port 8081 and Socket.IO on port 8080.
 
+mod context_files;
+mod cors;
+mod cursor;
+mod deprecation;
+mod digest;
+mod env;
+mod extract;
 mod gateway;
+mod identity;
+mod idle;
+mod locale;
+mod range;
+mod redaction;
+mod retention;
+mod rollup;
 mod routes;
+mod run_compare;
 mod socket;
 mod state;
 
-use axum::Router;
+use anyhow::Context;
+use axum::{extract::DefaultBodyLimit, middleware, Router};
 use std::net::SocketAddr;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 use std::sync::Arc;
-use tower_http::cors::{Any, CorsLayer};
 use tower_http::trace::TraceLayer;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-use crate::gateway::{GatewayManager, start_heartbeat_checker};
+use crate::env::env_or_default;
+use crate::gateway::{
+    stall_timeout_from_env, start_dead_letter_redelivery, start_heartbeat_checker, start_stall_detector,
+    GatewayManager, MtlsConfig,
+};
 use crate::socket::{create_socket_layer, SocketState};
 use crate::state::AppState;
-use vk_core::kanban::KanbanStore;
-use vk_core::task::FileTaskStore;
+use vk_core::kanban::{KanbanReconcilePolicy, KanbanStore};
+use vk_core::task::{FileTaskStore, StatusTransitions, TaskLimits};
 
 #[tokio::main]
-async fn main() {
+async fn main() -> ExitCode {
     // Initialize tracing
     tracing_subscriber::registry()
         .with(
@@ -33,6 +53,17 @@ async fn main() {
         .with(tracing_subscriber::fmt::layer())
         .init();
 
+    match run().await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            tracing::error!("Fatal: {:#}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Initialize state and serve until a server stops
+async fn run() -> anyhow::Result<()> {
     // Determine data directory
     let data_dir = std::env::var("VK_DATA_DIR")
         .map(PathBuf::from)
@@ -40,22 +71,22 @@ async fn main() {
 
     tracing::info!("Using data directory: {:?}", data_dir);
 
-    // Create TaskStore first (needed by both GatewayManager, KanbanStore and AppState)
-    let tasks_path = data_dir.join("tasks.json");
-    let task_store = Arc::new(FileTaskStore::new(tasks_path).await
-        .expect("Failed to initialize task store"));
+    let (task_store, kanban_store) = init_stores(&data_dir).await?;
 
-    // Create KanbanStore synced with TaskStore
-    let kanban_path = data_dir.join("kanban.json");
-    let kanban_store = Arc::new(KanbanStore::with_task_store(kanban_path, Arc::clone(&task_store)).await
-        .expect("Failed to initialize kanban store"));
+    let mtls_config = MtlsConfig::from_env()
+        .map_err(anyhow::Error::msg)
+        .context("Invalid gateway mTLS configuration")?;
 
     // Create Gateway Manager with TaskStore and KanbanStore for Agent Gateway connections
-    let gateway_manager = Arc::new(GatewayManager::with_stores(
-        Arc::clone(&task_store),
-        Arc::clone(&kanban_store),
-    ));
+    let gateway_manager = Arc::new(
+        GatewayManager::with_stores(Arc::clone(&task_store), Arc::clone(&kanban_store))
+            .with_mtls_required(mtls_config.is_some()),
+    );
     start_heartbeat_checker(Arc::clone(&gateway_manager));
+    start_dead_letter_redelivery(Arc::clone(&gateway_manager));
+    if let Some(window) = stall_timeout_from_env() {
+        start_stall_detector(Arc::clone(&gateway_manager), window);
+    }
     tracing::info!("Gateway Manager initialized with TaskStore and KanbanStore");
 
     // Create application state for REST API (uses shared stores)
@@ -66,65 +97,316 @@ async fn main() {
         Arc::clone(&gateway_manager),
     )
         .await
-        .expect("Failed to initialize application state");
+        .context("Failed to initialize application state")?;
+
+    if let Some(max_age) = retention::events_retention_from_env() {
+        tracing::info!("Purging run events after {:?}", max_age);
+        retention::start_events_retention(Arc::clone(app_state.executor().run_store()), max_age);
+    }
+
+    if let Some(notifier) = digest::webhook_notifier_from_env() {
+        let interval = digest::digest_interval_from_env();
+        tracing::info!("Sending watcher digests every {:?}", interval);
+        digest::start_digests(app_state.clone(), Arc::new(notifier), interval);
+    }
 
     // Create Socket.IO layer with the shared KanbanStore
     let socket_state = SocketState::new(
         Arc::clone(&kanban_store),
         Arc::clone(&task_store),
         data_dir.clone(),
+        Arc::clone(app_state.executor().run_store()),
     );
     let (socket_layer, io) = create_socket_layer(socket_state);
 
     // Set Socket.IO instance in AppState
     app_state.set_socket_io(io.clone()).await;
 
+    deprecation::log_deprecated_routes(&deprecation::SunsetConfig::from_env());
+
 // REST API server (port 8081)
-    let rest_app = Router::new()
-        .merge(routes::health::router())
-        .merge(routes::task::router())
-        .merge(routes::project::router())
-        .merge(routes::executor::router())
-        .with_state(app_state.clone())
-        .merge(routes::gateway::router(app_state.gateway_manager_arc()))
-        .layer(
-            CorsLayer::new()
-                .allow_origin(Any)
-                .allow_methods(Any)
-                .allow_headers(Any),
-        )
-        .layer(TraceLayer::new_for_http());
+    let activity = idle::ActivityTracker::new();
+    let rest_app = rest_router(app_state.clone(), max_body_bytes_from_env())
+        .layer(middleware::from_fn_with_state(activity.clone(), idle::track_activity));
 
     // Socket.IO server (port 8080)
     // Layers are applied bottom-to-top, so CorsLayer is added last to be applied first
     let socket_app = Router::new()
-        .layer(
-            CorsLayer::new()
-                .allow_origin(Any)
-                .allow_methods(Any)
-                .allow_headers(Any),
-        )
-        .layer(socket_layer);
+        .layer(cors::cors_layer())
+        .layer(socket_layer)
+        .layer(middleware::from_fn_with_state(activity.clone(), idle::track_activity));
 
-    // Start both servers - bind to 0.0.0.0 for localhost/127.0.0.1 compatibility
-    let rest_addr = SocketAddr::from(([0, 0, 0, 0], 8081));
-    let socket_addr = SocketAddr::from(([0, 0, 0, 0], 8080));
+    // Both servers drain and stop once the idle watcher fires
+    let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    if let Some(window) = idle::idle_timeout_from_env() {
+        tracing::info!("Shutting down after {:?} without activity", window);
+        let state = app_state.clone();
+        tokio::spawn(idle::shutdown_when_idle(
+            activity,
+            window,
+            move || {
+                let state = state.clone();
+                async move { idle::has_active_executions(&state).await }
+            },
+            move || {
+                let _ = shutdown_tx.send(true);
+            },
+        ));
+    }
+
+    // Start both servers - bind to 0.0.0.0 by default for localhost/127.0.0.1 compatibility
+    let rest_addr = listen_addr_from_env(REST_ADDR_ENV, DEFAULT_REST_ADDR).await?;
+    let socket_addr = listen_addr_from_env(SOCKET_ADDR_ENV, DEFAULT_SOCKET_ADDR).await?;
+
+    // Bind both listeners before serving so a port in use fails startup cleanly
+    let rest_listener = tokio::net::TcpListener::bind(rest_addr)
+        .await
+        .with_context(|| format!("Failed to bind REST API on {}", rest_addr))?;
+    let socket_listener = tokio::net::TcpListener::bind(socket_addr)
+        .await
+        .with_context(|| format!("Failed to bind Socket.IO on {}", socket_addr))?;
+    let mtls_listener = match &mtls_config {
+        Some(config) => Some(
+            gateway::mtls::bind(config)
+                .await
+                .map_err(anyhow::Error::msg)
+                .context("Failed to start gateway mTLS listener")?,
+        ),
+        None => None,
+    };
 
     tracing::info!("REST API listening on {}", rest_addr);
     tracing::info!("Socket.IO listening on {}", socket_addr);
 
     // Spawn REST server
-    let rest_listener = tokio::net::TcpListener::bind(rest_addr).await.unwrap();
+    let rest_shutdown = shutdown_signal(shutdown_rx.clone());
     let rest_handle = tokio::spawn(async move {
-        axum::serve(rest_listener, rest_app).await.unwrap();
+        axum::serve(rest_listener, rest_app)
+            .with_graceful_shutdown(rest_shutdown)
+            .await
+    });
+
+    // Serve the gateway WebSocket over mTLS as well when configured
+    let gateway_app = routes::gateway::router(app_state.gateway_manager_arc());
+    let mtls_shutdown = shutdown_signal(shutdown_rx.clone());
+    let mtls_handle = tokio::spawn(async move {
+        match mtls_listener {
+            Some(listener) => gateway::mtls::serve(listener, gateway_app, mtls_shutdown).await,
+            None => Ok(()),
+        }
     });
 
     // Spawn Socket.IO server
-    let socket_listener = tokio::net::TcpListener::bind(socket_addr).await.unwrap();
+    let socket_shutdown = shutdown_signal(shutdown_rx);
     let socket_handle = tokio::spawn(async move {
-        axum::serve(socket_listener, socket_app).await.unwrap();
+        axum::serve(socket_listener, socket_app)
+            .with_graceful_shutdown(socket_shutdown)
+            .await
     });
 
-    // Wait for both
-    tokio::try_join!(rest_handle, socket_handle).unwrap();
+    // Wait for all servers
+    let (rest_result, socket_result, mtls_result) = tokio::try_join!(rest_handle, socket_handle, mtls_handle)?;
+    rest_result.context("REST API server failed")?;
+    socket_result.context("Socket.IO server failed")?;
+    mtls_result.context("Gateway mTLS server failed")?;
+    Ok(())
+}
+
+/// Resolve once the shutdown flag is set
+async fn shutdown_signal(mut rx: tokio::sync::watch::Receiver<bool>) {
+    // A dropped sender means no idle watcher; serve until the process ends
+    if rx.wait_for(|stop| *stop).await.is_err() {
+        std::future::pending::<()>().await;
+    }
+}
+
+/// REST API routes with CORS, tracing and the request body limit
+fn rest_router(app_state: AppState, max_body_bytes: usize) -> Router {
+    Router::new()
+        .merge(routes::health::router())
+        .merge(routes::task::router())
+        .merge(routes::kanban::router())
+        .merge(routes::ops::router())
+        .merge(routes::project::router())
+        .merge(routes::openapi::router())
+        .merge(routes::template::router())
+        .merge(routes::executor::router())
+        .with_state(app_state.clone())
+        .merge(routes::agent::router(app_state.gateway_manager_arc()))
+        .merge(routes::gateway::router(app_state.gateway_manager_arc()))
+        .layer(DefaultBodyLimit::max(max_body_bytes))
+        .layer(cors::cors_layer())
+        .layer(TraceLayer::new_for_http())
+}
+
+/// Open the task store and the kanban store synced with it
+async fn init_stores(data_dir: &Path) -> anyhow::Result<(Arc<FileTaskStore>, Arc<KanbanStore>)> {
+    // Create TaskStore first (needed by both GatewayManager, KanbanStore and AppState)
+    let tasks_path = data_dir.join("tasks.json");
+    let task_store = FileTaskStore::new(&tasks_path)
+        .await
+        .with_context(|| format!("Failed to initialize task store at {}", tasks_path.display()))?
+        .with_limits(task_limits_from_env())
+        .with_transitions(status_transitions_from_env());
+    let task_store = Arc::new(task_store);
+
+    // Create KanbanStore synced with TaskStore
+    let kanban_path = data_dir.join("kanban.json");
+    let kanban_store = KanbanStore::with_task_store(kanban_path.clone(), Arc::clone(&task_store))
+        .await
+        .with_context(|| format!("Failed to initialize kanban store at {}", kanban_path.display()))?
+        .with_reconcile_policy(kanban_reconcile_policy_from_env());
+
+    // Resolve columns that drifted from task statuses while the server was down
+    let mismatches = kanban_store
+        .reconcile_with_tasks(kanban_store.reconcile_policy())
+        .await
+        .context("Failed to reconcile kanban board with tasks")?;
+    for mismatch in &mismatches {
+        tracing::warn!(
+            "Kanban card {} was in {:?} but its task is {:?}; resolved by {:?}",
+            mismatch.task_id,
+            mismatch.column,
+            mismatch.task_status,
+            kanban_store.reconcile_policy()
+        );
+    }
+
+    Ok((task_store, Arc::new(kanban_store)))
+}
+
+const KANBAN_RECONCILE_ENV: &str = "VK_KANBAN_RECONCILE_POLICY";
+
+/// Read which side wins when a card's column and task status disagree
+fn kanban_reconcile_policy_from_env() -> KanbanReconcilePolicy {
+    let Ok(value) = std::env::var(KANBAN_RECONCILE_ENV) else {
+        return KanbanReconcilePolicy::default();
+    };
+    KanbanReconcilePolicy::parse(&value).unwrap_or_else(|| {
+        tracing::warn!("Ignoring invalid {}={:?}", KANBAN_RECONCILE_ENV, value);
+        KanbanReconcilePolicy::default()
+    })
+}
+
+const MAX_BODY_BYTES_ENV: &str = "VK_MAX_BODY_BYTES";
+const DEFAULT_MAX_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+/// Read the REST request body limit; larger bodies are answered with 413
+fn max_body_bytes_from_env() -> usize {
+    env_or_default(MAX_BODY_BYTES_ENV, DEFAULT_MAX_BODY_BYTES)
+}
+
+const REST_ADDR_ENV: &str = "VK_REST_ADDR";
+const SOCKET_ADDR_ENV: &str = "VK_SOCKET_ADDR";
+const DEFAULT_REST_ADDR: &str = "0.0.0.0:8081";
+const DEFAULT_SOCKET_ADDR: &str = "0.0.0.0:8080";
+
+/// Read a `host:port` listen address from the environment
+async fn listen_addr_from_env(name: &str, default: &str) -> anyhow::Result<SocketAddr> {
+    let value = std::env::var(name).unwrap_or_else(|_| default.to_string());
+    parse_listen_addr(&value)
+        .await
+        .map_err(|e| anyhow::anyhow!("Invalid {}: {}", name, e))
+}
+
+/// Resolve a `host:port` listen address; the host may be a name such as `localhost`
+async fn parse_listen_addr(value: &str) -> Result<SocketAddr, String> {
+    let invalid = || format!("expected host:port such as 127.0.0.1:8081, got {:?}", value);
+    tokio::net::lookup_host(value.trim())
+        .await
+        .map_err(|_| invalid())?
+        .next()
+        .ok_or_else(invalid)
+}
+
+/// Read forbidden task status transitions from the environment
+fn status_transitions_from_env() -> StatusTransitions {
+    let Ok(spec) = std::env::var("VK_FORBIDDEN_TRANSITIONS") else {
+        return StatusTransitions::permissive();
+    };
+    StatusTransitions::parse(&spec).unwrap_or_else(|e| {
+        tracing::warn!("Ignoring VK_FORBIDDEN_TRANSITIONS: {}", e);
+        StatusTransitions::permissive()
+    })
+}
+
+/// Read task field length limits from the environment, falling back to defaults
+fn task_limits_from_env() -> TaskLimits {
+    let defaults = TaskLimits::default();
+    TaskLimits {
+        max_title_len: env_or_default("VK_TASK_MAX_TITLE_LEN", defaults.max_title_len),
+        max_description_len: env_or_default("VK_TASK_MAX_DESCRIPTION_LEN", defaults.max_description_len),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn parse_listen_addr_accepts_host_port_only() {
+        assert_eq!(
+            parse_listen_addr("127.0.0.1:9081").await.unwrap(),
+            SocketAddr::from(([127, 0, 0, 1], 9081))
+        );
+        assert_eq!(parse_listen_addr(DEFAULT_SOCKET_ADDR).await.unwrap().port(), 8080);
+        assert_eq!(parse_listen_addr("[::1]:8081").await.unwrap().port(), 8081);
+
+        let localhost = parse_listen_addr("localhost:8081").await.unwrap();
+        assert!(localhost.ip().is_loopback());
+        assert_eq!(localhost.port(), 8081);
+
+        assert!(parse_listen_addr("8081").await.is_err());
+        assert!(parse_listen_addr("127.0.0.1:99999").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn init_stores_reports_corrupt_task_store() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("tasks.json"), "not json").unwrap();
+
+        let Err(err) = init_stores(temp_dir.path()).await else {
+            panic!("corrupt tasks.json should fail startup");
+        };
+        assert!(
+            format!("{:#}", err).contains("Failed to initialize task store"),
+            "unexpected error: {:#}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    async fn oversized_request_body_is_rejected() {
+        use axum::{body::Body, http::{Request, StatusCode}};
+        use tower::ServiceExt;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let (task_store, kanban_store) = init_stores(temp_dir.path()).await.unwrap();
+        let gateway_manager = Arc::new(GatewayManager::with_stores(
+            Arc::clone(&task_store),
+            Arc::clone(&kanban_store),
+        ));
+        let state = AppState::with_stores(temp_dir.path().to_path_buf(), task_store, kanban_store, gateway_manager)
+            .await
+            .unwrap();
+        let app = rest_router(state, 1024);
+
+        let post = |description: String| {
+            Request::builder()
+                .method("POST")
+                .uri("/api/tasks")
+                .header("Content-Type", "application/json")
+                .body(Body::from(
+                    serde_json::json!({ "title": "Big", "description": description }).to_string(),
+                ))
+                .unwrap()
+        };
+
+        let response = app.clone().oneshot(post("x".repeat(4096))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        // Within the limit the body reaches the handler's own validation
+        let response = app.oneshot(post("small".to_string())).await.unwrap();
+        assert_ne!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
 }
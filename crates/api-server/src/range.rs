@@ -0,0 +1,116 @@
+//! HTTP byte range support for run artifact downloads
+//!
+//! Run logs and offloaded tool results can be large, so their endpoints honor
+//! a single `Range: bytes=...` request and answer `206 Partial Content`,
+//! letting clients resume interrupted downloads. Multi-range and malformed
+//! headers are ignored and the full body is served.
+
+use std::ops::Range;
+
+use axum::{
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+
+/// Outcome of matching a `Range` header against a body length
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RangeRequest {
+    /// No usable range; serve the whole body
+    Full,
+    /// Serve the given byte range
+    Partial(Range<usize>),
+    /// The range lies outside the body
+    Unsatisfiable,
+}
+
+/// Parse a `Range` header value for a body of `len` bytes
+pub fn parse_range(value: Option<&str>, len: usize) -> RangeRequest {
+    let Some(spec) = value.and_then(|v| v.trim().strip_prefix("bytes=")) else {
+        return RangeRequest::Full;
+    };
+    if spec.contains(',') {
+        return RangeRequest::Full;
+    }
+    let Some((start, end)) = spec.trim().split_once('-') else {
+        return RangeRequest::Full;
+    };
+    let (start, end) = (start.trim(), end.trim());
+
+    let range = match (start.is_empty(), end.is_empty()) {
+        // bytes=-N: the last N bytes
+        (true, false) => match end.parse::<usize>() {
+            Ok(0) => return RangeRequest::Unsatisfiable,
+            Ok(suffix) => len.saturating_sub(suffix)..len,
+            Err(_) => return RangeRequest::Full,
+        },
+        // bytes=N-: from N to the end
+        (false, true) => match start.parse::<usize>() {
+            Ok(start) => start..len,
+            Err(_) => return RangeRequest::Full,
+        },
+        // bytes=N-M: inclusive, clamped to the body
+        (false, false) => match (start.parse::<usize>(), end.parse::<usize>()) {
+            (Ok(start), Ok(end)) if start <= end => start..end.saturating_add(1).min(len),
+            _ => return RangeRequest::Full,
+        },
+        (true, true) => return RangeRequest::Full,
+    };
+
+    if range.start >= len {
+        RangeRequest::Unsatisfiable
+    } else {
+        RangeRequest::Partial(range)
+    }
+}
+
+/// Serve `body`, honoring the request's `Range` header
+pub fn ranged_response(request_headers: &HeaderMap, content_type: &'static str, body: Vec<u8>) -> Response {
+    let len = body.len();
+    let range = request_headers
+        .get(header::RANGE)
+        .and_then(|value| value.to_str().ok());
+
+    let mut response = match parse_range(range, len) {
+        RangeRequest::Full => body.into_response(),
+        RangeRequest::Partial(range) => {
+            let content_range = format!("bytes {}-{}/{}", range.start, range.end - 1, len);
+            let mut response = (StatusCode::PARTIAL_CONTENT, body[range].to_vec()).into_response();
+            response.headers_mut().insert(
+                header::CONTENT_RANGE,
+                HeaderValue::from_str(&content_range).expect("valid content range"),
+            );
+            response
+        }
+        RangeRequest::Unsatisfiable => {
+            let mut response = StatusCode::RANGE_NOT_SATISFIABLE.into_response();
+            response.headers_mut().insert(
+                header::CONTENT_RANGE,
+                HeaderValue::from_str(&format!("bytes */{}", len)).expect("valid content range"),
+            );
+            return response;
+        }
+    };
+
+    let headers = response.headers_mut();
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+    headers.insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_range_handles_open_suffix_and_bounded_ranges() {
+        assert_eq!(parse_range(None, 10), RangeRequest::Full);
+        assert_eq!(parse_range(Some("bytes=2-4"), 10), RangeRequest::Partial(2..5));
+        assert_eq!(parse_range(Some("bytes=2-99"), 10), RangeRequest::Partial(2..10));
+        assert_eq!(parse_range(Some("bytes=7-"), 10), RangeRequest::Partial(7..10));
+        assert_eq!(parse_range(Some("bytes=-3"), 10), RangeRequest::Partial(7..10));
+        assert_eq!(parse_range(Some("bytes=10-"), 10), RangeRequest::Unsatisfiable);
+        assert_eq!(parse_range(Some("bytes=0-1,4-5"), 10), RangeRequest::Full);
+        assert_eq!(parse_range(Some("items=0-1"), 10), RangeRequest::Full);
+        assert_eq!(parse_range(Some("bytes=5-2"), 10), RangeRequest::Full);
+    }
+}
@@ -0,0 +1,70 @@
+//! Secret redaction settings and admin access to unredacted events
+//!
+//! Agent output is masked with the default patterns plus any listed in
+//! `VK_REDACT_PATTERNS` (a JSON array of regexes). The originals of masked
+//! events are kept next to the run and served only to requests bearing
+//! `VK_ADMIN_TOKEN`; without that variable the raw endpoint is disabled.
+
+use agent_runner::Redactor;
+use axum::http::{header, HeaderMap};
+
+/// Environment variable with extra redaction patterns, as a JSON array
+pub const REDACT_PATTERNS_ENV: &str = "VK_REDACT_PATTERNS";
+
+/// Environment variable holding the bearer token for admin-only endpoints
+pub const ADMIN_TOKEN_ENV: &str = "VK_ADMIN_TOKEN";
+
+/// Redactor with the default patterns plus those from the environment
+pub fn redactor_from_env() -> Redactor {
+    let Ok(raw) = std::env::var(REDACT_PATTERNS_ENV) else {
+        return Redactor::default();
+    };
+    let extra = match serde_json::from_str::<Vec<String>>(&raw) {
+        Ok(extra) => extra,
+        Err(e) => {
+            tracing::warn!("Ignoring invalid {} value {:?}: {}", REDACT_PATTERNS_ENV, raw, e);
+            return Redactor::default();
+        }
+    };
+    Redactor::with_extra(extra.iter().map(String::as_str)).unwrap_or_else(|e| {
+        tracing::warn!("Ignoring invalid {} pattern: {}", REDACT_PATTERNS_ENV, e);
+        Redactor::default()
+    })
+}
+
+/// Outcome of checking a request for admin access
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdminAccess {
+    Granted,
+    /// No admin token is configured
+    Disabled,
+    Denied,
+}
+
+/// Compare two byte strings in time independent of where they differ
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Check the request's bearer token against `admin_token`
+pub fn check_admin(headers: &HeaderMap, admin_token: Option<&str>) -> AdminAccess {
+    let Some(expected) = admin_token.filter(|token| !token.is_empty()) else {
+        return AdminAccess::Disabled;
+    };
+    let presented = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    match presented {
+        Some(token) if constant_time_eq(token.as_bytes(), expected.as_bytes()) => AdminAccess::Granted,
+        _ => AdminAccess::Denied,
+    }
+}
+
+/// Admin token from the process environment
+pub fn admin_token_from_env() -> Option<String> {
+    std::env::var(ADMIN_TOKEN_ENV).ok().filter(|token| !token.is_empty())
+}
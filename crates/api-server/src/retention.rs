@@ -0,0 +1,42 @@
+//! Retention of run event logs
+//!
+//! With `VK_RUN_EVENTS_RETENTION_DAYS` set, the events and messages of
+//! finished runs are deleted once they are that many days old. Run metadata
+//! and summaries are kept; the events endpoints answer `410 Gone` for purged
+//! runs.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use agent_runner::RunStorage;
+
+use crate::env::env_parse;
+
+/// Environment variable with the event retention in days; unset or `0` keeps events forever
+pub const EVENTS_RETENTION_ENV: &str = "VK_RUN_EVENTS_RETENTION_DAYS";
+
+/// How often expired events are looked for
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Load the event retention from the process environment
+pub fn events_retention_from_env() -> Option<Duration> {
+    env_parse::<u64>(EVENTS_RETENTION_ENV)
+        .filter(|&days| days > 0)
+        .map(|days| Duration::from_secs(days.saturating_mul(24 * 60 * 60)))
+}
+
+/// Start the background task purging events older than `max_age`
+pub fn start_events_retention(run_store: Arc<dyn RunStorage>, max_age: Duration) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(PURGE_INTERVAL);
+        loop {
+            interval.tick().await;
+            let store = Arc::clone(&run_store);
+            match tokio::task::spawn_blocking(move || store.purge_events(max_age)).await {
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => tracing::warn!("Failed to purge run events: {}", e),
+                Err(e) => tracing::warn!("Run event purge panicked: {}", e),
+            }
+        }
+    });
+}
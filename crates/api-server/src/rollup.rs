@@ -0,0 +1,171 @@
+//! Daily rollups of run metrics for reporting
+//!
+//! Runs are grouped by the gateway of their task's project. A rollup for a
+//! past day can no longer change, so it is computed once and cached on disk.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::PathBuf;
+
+use agent_runner::{ExecutionStatus, RunSummary};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Aggregates for the runs of one gateway on one day
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GatewayRollup {
+    /// `None` groups runs of tasks without a project
+    pub gateway_id: Option<Uuid>,
+    /// Executions created that day
+    pub created: usize,
+    /// Executions that completed that day
+    pub completed: usize,
+    /// Executions that failed that day
+    pub failed: usize,
+    /// Mean duration of the executions that ended that day
+    pub avg_duration_ms: Option<u64>,
+    /// Distinct hosts running an execution created or ended that day
+    pub active_hosts: usize,
+}
+
+/// Run metrics for one UTC day
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyRollup {
+    pub date: NaiveDate,
+    pub gateways: Vec<GatewayRollup>,
+}
+
+/// Aggregate `runs`, each tagged with its gateway, for `date`
+pub fn compute_rollup(date: NaiveDate, runs: &[(Option<Uuid>, RunSummary)]) -> DailyRollup {
+    #[derive(Default)]
+    struct Totals {
+        rollup: GatewayRollup,
+        durations: Vec<u64>,
+        hosts: BTreeSet<String>,
+    }
+
+    let mut groups: BTreeMap<Option<Uuid>, Totals> = BTreeMap::new();
+    for (gateway_id, run) in runs {
+        let created = run.created_at.date_naive() == date;
+        let ended = run.ended_at.is_some_and(|at| at.date_naive() == date);
+        if !created && !ended {
+            continue;
+        }
+
+        let totals = groups.entry(*gateway_id).or_default();
+        if created {
+            totals.rollup.created += 1;
+        }
+        if ended {
+            match run.status {
+                ExecutionStatus::Completed => totals.rollup.completed += 1,
+                ExecutionStatus::Failed => totals.rollup.failed += 1,
+                _ => {}
+            }
+            totals.durations.extend(run.duration_ms);
+        }
+        totals.hosts.extend(run.host_id.clone());
+    }
+
+    let gateways = groups
+        .into_iter()
+        .map(|(gateway_id, totals)| {
+            let count = totals.durations.len() as u64;
+            GatewayRollup {
+                gateway_id,
+                avg_duration_ms: (count > 0).then(|| totals.durations.iter().sum::<u64>() / count),
+                active_hosts: totals.hosts.len(),
+                ..totals.rollup
+            }
+        })
+        .collect();
+
+    DailyRollup { date, gateways }
+}
+
+/// Rollups of past days, one JSON file per day
+#[derive(Debug, Clone)]
+pub struct RollupCache {
+    dir: PathBuf,
+}
+
+impl RollupCache {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    fn path(&self, date: NaiveDate) -> PathBuf {
+        self.dir.join(format!("{}.json", date))
+    }
+
+    /// Cached rollup for `date`, if any
+    pub fn load(&self, date: NaiveDate) -> Option<DailyRollup> {
+        let content = fs::read_to_string(self.path(date)).ok()?;
+        serde_json::from_str(&content)
+            .inspect_err(|e| tracing::warn!("Ignoring corrupt rollup cache for {}: {}", date, e))
+            .ok()
+    }
+
+    /// Cache `rollup`; only call for days that are over
+    pub fn store(&self, rollup: &DailyRollup) -> std::io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let content = serde_json::to_string_pretty(rollup)?;
+        fs::write(self.path(rollup.date), content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use agent_runner::{AgentType, Run};
+    use chrono::{TimeZone, Utc};
+
+    fn run_on(day: u32, ended_day: Option<u32>, status: ExecutionStatus, host: &str) -> RunSummary {
+        let mut run = Run::new(Uuid::new_v4(), AgentType::OpenCode, "prompt".to_string(), "main".to_string());
+        run.created_at = Utc.with_ymd_and_hms(2024, 3, day, 23, 0, 0).unwrap();
+        run.ended_at = ended_day.map(|day| Utc.with_ymd_and_hms(2024, 3, day, 1, 0, 0).unwrap());
+        run.duration_ms = ended_day.map(|_| 1_000 * u64::from(day));
+        run.status = status;
+        run.metadata.host_id = Some(host.to_string());
+        RunSummary::from(&run)
+    }
+
+    #[test]
+    fn rollup_counts_created_and_ended_runs_per_day() {
+        let gateway = Some(Uuid::new_v4());
+        let runs = vec![
+            (gateway, run_on(1, Some(2), ExecutionStatus::Completed, "host-a")),
+            (gateway, run_on(1, Some(1), ExecutionStatus::Failed, "host-b")),
+            (gateway, run_on(2, None, ExecutionStatus::Running, "host-a")),
+            (None, run_on(2, Some(2), ExecutionStatus::Completed, "local")),
+        ];
+
+        let day_one = compute_rollup(NaiveDate::from_ymd_opt(2024, 3, 1).unwrap(), &runs);
+        assert_eq!(
+            day_one.gateways,
+            vec![GatewayRollup {
+                gateway_id: gateway,
+                created: 2,
+                completed: 0,
+                failed: 1,
+                avg_duration_ms: Some(1_000),
+                active_hosts: 2,
+            }]
+        );
+
+        let day_two = compute_rollup(NaiveDate::from_ymd_opt(2024, 3, 2).unwrap(), &runs);
+        assert_eq!(day_two.gateways.len(), 2);
+        let unassigned = &day_two.gateways[0];
+        assert_eq!((unassigned.gateway_id, unassigned.created, unassigned.completed), (None, 1, 1));
+        let assigned = &day_two.gateways[1];
+        assert_eq!((assigned.created, assigned.completed, assigned.failed), (1, 1, 0));
+        assert_eq!(assigned.avg_duration_ms, Some(1_000));
+        assert_eq!(assigned.active_hosts, 1);
+
+        let day_three = compute_rollup(NaiveDate::from_ymd_opt(2024, 3, 3).unwrap(), &runs);
+        assert!(day_three.gateways.is_empty());
+    }
+}
@@ -0,0 +1,127 @@
+//! Agent discovery routes
+//!
+//! Lists the agent types the server knows about and which of them connected
+//! gateway hosts can run right now.
+
+use axum::{extract::State, routing::get, Json, Router};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use agent_runner::AgentType;
+
+use crate::gateway::GatewayManager;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentInfo {
+    pub agent_type: String,
+    /// Whether at least one connected host advertises this agent
+    pub available: bool,
+    /// Hosts advertising this agent
+    pub hosts: Vec<String>,
+    /// Supported features, e.g. `input` and `pause`
+    pub capabilities: BTreeMap<&'static str, bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AgentsResponse {
+    pub agents: Vec<AgentInfo>,
+}
+
+fn capabilities(agent_type: Option<&AgentType>) -> BTreeMap<&'static str, bool> {
+    BTreeMap::from([
+        ("input", agent_type.is_some_and(|a| a.supports_input())),
+        ("pause", agent_type.is_some_and(|a| a.supports_pause())),
+    ])
+}
+
+/// GET /api/agents - List known agents and their current availability
+async fn list_agents(State(manager): State<Arc<GatewayManager>>) -> Json<AgentsResponse> {
+    let mut advertised = manager.agent_hosts().await;
+
+    let mut agents: Vec<AgentInfo> = AgentType::ALL
+        .iter()
+        .map(|agent_type| {
+            let hosts = advertised.remove(agent_type.as_str()).unwrap_or_default();
+            AgentInfo {
+                agent_type: agent_type.as_str().to_string(),
+                available: !hosts.is_empty(),
+                hosts,
+                capabilities: capabilities(Some(agent_type)),
+            }
+        })
+        .collect();
+
+    // Agents advertised by hosts under names the server doesn't know
+    let mut extra: Vec<AgentInfo> = advertised
+        .into_iter()
+        .map(|(name, hosts)| AgentInfo {
+            capabilities: capabilities(AgentType::from_str(&name).ok().as_ref()),
+            agent_type: name,
+            available: true,
+            hosts,
+        })
+        .collect();
+    extra.sort_by(|a, b| a.agent_type.cmp(&b.agent_type));
+    agents.extend(extra);
+
+    Json(AgentsResponse { agents })
+}
+
+/// Create the agent router
+pub fn router(manager: Arc<GatewayManager>) -> Router<()> {
+    Router::new()
+        .route("/api/agents", get(list_agents))
+        .with_state(manager)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::{to_bytes, Body};
+    use axum::http::{Request, StatusCode};
+    use std::collections::HashMap;
+    use tower::ServiceExt;
+
+    use crate::gateway::protocol::HostCapabilities;
+
+    #[tokio::test]
+    async fn list_agents_marks_host_advertised_agents_available() {
+        let manager = Arc::new(GatewayManager::new());
+        let (tx, _rx) = tokio::sync::mpsc::channel(10);
+        manager
+            .register_host(
+                "host-1".to_string(),
+                HostCapabilities {
+                    name: "Agent host".to_string(),
+                    agents: vec!["opencode".to_string()],
+                    max_concurrent: 1,
+                    cwd: "/tmp".to_string(),
+                    labels: HashMap::new(),
+                    agent_versions: HashMap::new(),
+                },
+                tx,
+            )
+            .await;
+
+        let response = router(manager)
+            .oneshot(Request::builder().uri("/api/agents").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let payload: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let agents = payload["agents"].as_array().unwrap();
+        assert_eq!(agents.len(), AgentType::ALL.len());
+
+        let opencode = agents.iter().find(|a| a["agentType"] == "opencode").unwrap();
+        assert_eq!(opencode["available"], true);
+        assert_eq!(opencode["hosts"], serde_json::json!(["host-1"]));
+        assert_eq!(opencode["capabilities"]["input"], true);
+
+        let codex = agents.iter().find(|a| a["agentType"] == "codex").unwrap();
+        assert_eq!(codex["available"], false);
+    }
+}
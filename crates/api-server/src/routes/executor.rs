@@ -3,35 +3,91 @@
 //! RESTful API for task execution operations.
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
+    middleware,
     routing::{delete, get, post},
     Json, Router,
 };
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
 use uuid::Uuid;
 
-use agent_runner::{AgentType, ChatMessage, MessageRole, Run, SessionState};
-use vk_core::kanban::KanbanTaskStatus;
-use vk_core::task::TaskRepository;
-
+use agent_runner::{
+    extract_summary, AgentEvent, AgentType, ChatMessage, ExecutionEvent, ExecutionEventType, ExecutionStatus,
+    MessageContentType, MessageRole, Run, RunEventWriter, RunLogger, RunMetadata, SessionState,
+};
+use vk_core::kanban::RunTransition;
+use vk_core::project::{is_path_within, Project};
+use vk_core::task::{Task, TaskPriority, TaskRepository};
+
+use crate::context_files::prepend_context_files;
+use crate::deprecation::{deprecated_route, SunsetConfig};
+use crate::extract::JsonBody;
+use crate::identity::UserIdentity;
+use crate::gateway::dispatch_retry::DispatchError;
+use crate::gateway::manager::DispatchOutcome;
+use crate::gateway::output::OutputAccumulator;
+use crate::locale::{Locale, StatusMessage};
 use crate::gateway::protocol::GatewayTaskRequest;
-use crate::state::AppState;
+use crate::state::{AppState, RunLifecycleEvent};
 
 // ============================================================================
 // Request/Response types
 // ============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
+#[schemars(rename = "CreateExecutionRequest")]
 pub struct StartExecutionRequest {
     pub agent_type: String,
-    pub base_branch: String,
+    /// Overrides the task/project base branch when set
+    #[serde(default)]
+    pub base_branch: Option<String>,
     /// Optional target host for remote execution
     pub target_host: Option<String>,
     /// Optional model to use (format: provider/model)
     pub model: Option<String>,
+    /// Labels stored on the run and passed to the gateway
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
+    /// Host labels required for this execution, on top of the project's
+    #[serde(default)]
+    pub required_labels: HashMap<String, String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct StartExecutionQuery {
+    /// Start even if the task already has an active run
+    #[serde(default)]
+    pub force: bool,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidateExecutionRequest {
+    pub task_id: Uuid,
+    pub agent_type: String,
+    /// Overrides the task/project base branch when set
+    #[serde(default)]
+    pub base_branch: Option<String>,
+    /// Optional model to use (format: provider/model)
+    pub model: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct HandoffRequest {
+    /// Role the next agent takes over with (must differ from the parent's)
+    pub agent_role: String,
+    /// Agent type for the new execution (defaults to the parent's)
+    pub agent_type: Option<String>,
+    /// Optional model to use (format: provider/model)
+    pub model: Option<String>,
+    /// Extra instructions appended after the handoff context
+    pub instructions: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -40,7 +96,7 @@ pub struct SendInputRequest {
     pub content: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ExecutionResponse {
     pub session_id: Uuid,
@@ -49,6 +105,74 @@ pub struct ExecutionResponse {
     pub message: String,
 }
 
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionDetailResponse {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub agent_type: String,
+    #[schemars(with = "String")]
+    pub status: ExecutionStatus,
+    /// Full prompt text dispatched to the agent
+    pub prompt: String,
+    pub base_branch: String,
+    pub worktree_branch: Option<String>,
+    pub created_at: String,
+    pub started_at: Option<String>,
+    pub ended_at: Option<String>,
+    pub duration_ms: Option<u64>,
+    pub exit_code: Option<i32>,
+    pub error: Option<String>,
+    pub summary: Option<String>,
+    pub agent_role: Option<String>,
+    pub parent_execution_id: Option<Uuid>,
+    pub labels: HashMap<String, String>,
+    /// Subject of the user who started the execution
+    pub created_by: Option<String>,
+    /// Events and messages were deleted by retention
+    pub events_purged: bool,
+}
+
+impl From<Run> for ExecutionDetailResponse {
+    fn from(run: Run) -> Self {
+        Self {
+            id: run.id,
+            task_id: run.task_id,
+            agent_type: run.agent_type.as_str().to_string(),
+            status: run.status,
+            prompt: run.prompt,
+            base_branch: run.base_branch,
+            worktree_branch: run.worktree_branch,
+            created_at: run.created_at.to_rfc3339(),
+            started_at: run.started_at.map(|t| t.to_rfc3339()),
+            ended_at: run.ended_at.map(|t| t.to_rfc3339()),
+            duration_ms: run.duration_ms,
+            exit_code: run.exit_code,
+            error: run.error,
+            summary: run.summary,
+            agent_role: run.metadata.agent_role,
+            parent_execution_id: run.metadata.parent_execution_id,
+            labels: run.metadata.labels,
+            created_by: run.metadata.created_by,
+            events_purged: run.events_purged,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidateExecutionResponse {
+    /// Whether a real dispatch would be accepted
+    pub valid: bool,
+    pub prompt: Option<String>,
+    pub host_id: Option<String>,
+    pub base_branch: Option<String>,
+    /// Queue position the task would take on a saturated host
+    pub queue_position: Option<usize>,
+    /// Errors that would block the dispatch
+    pub errors: Vec<String>,
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SessionResponse {
@@ -74,7 +198,7 @@ pub struct SessionSummary {
     pub state: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct ErrorResponse {
     pub error: String,
 }
@@ -87,7 +211,9 @@ pub struct ErrorResponse {
 async fn start_execution(
     State(state): State<AppState>,
     Path(task_id): Path<Uuid>,
-    Json(req): Json<StartExecutionRequest>,
+    Query(query): Query<StartExecutionQuery>,
+    identity: Option<UserIdentity>,
+    JsonBody(req): JsonBody<StartExecutionRequest>,
 ) -> Result<(StatusCode, Json<ExecutionResponse>), (StatusCode, Json<ErrorResponse>)> {
     // Log received request for debugging
     tracing::info!(
@@ -95,11 +221,136 @@ async fn start_execution(
         task_id, req.agent_type, req.target_host, req.model
     );
 
-    // Verify task exists
-    let task = state
-        .task_store()
-        .get(task_id)
+    let agent = resolve_agent_type(&state, &req.agent_type)?;
+    let (task, mut project) = load_task_with_project(&state, task_id).await?;
+    project.required_labels.extend(req.required_labels);
+
+    if !query.force {
+        ensure_no_active_run(&state, task_id)?;
+    }
+
+    ensure_project_checkout(&state, &project).await?;
+
+    let prompt = render_task_prompt(&state, &task, &project).await?;
+
+    let base_branch = resolve_base_branch(req.base_branch.as_deref(), &task, &project).await;
+
+    if let Some(target_host) = &req.target_host {
+        tracing::info!(
+            "Ignoring requested target_host={} for task {} because execution is project-bound",
+            target_host,
+            task_id
+        );
+    }
+
+    dispatch_to_gateway(
+        &state,
+        GatewayDispatch {
+            task_id,
+            prompt: &prompt,
+            agent,
+            project: &project,
+            model: req.model.as_deref(),
+            base_branch: &base_branch,
+            priority: task.priority,
+            metadata: RunMetadata {
+                labels: req.labels,
+                created_by: identity.map(|identity| identity.sub),
+                ..RunMetadata::default()
+            },
+        },
+    )
+    .await
+}
+
+/// POST /api/v1/executions/validate - Check a dispatch without running it
+///
+/// Resolves the prompt, host and branch `start_execution` would use and
+/// reports the errors a real dispatch would raise. A missing checkout is
+/// cloned as a dispatch would, but no run is created.
+async fn validate_execution(
+    State(state): State<AppState>,
+    JsonBody(req): JsonBody<ValidateExecutionRequest>,
+) -> Json<ValidateExecutionResponse> {
+    let mut response = ValidateExecutionResponse {
+        valid: false,
+        prompt: None,
+        host_id: None,
+        base_branch: None,
+        queue_position: None,
+        errors: Vec::new(),
+    };
+
+    let (task, project) = match load_task_with_project(&state, req.task_id).await {
+        Ok(loaded) => loaded,
+        Err((_, Json(error))) => {
+            response.errors.push(error.error);
+            return Json(response);
+        }
+    };
+
+    if let Err((_, Json(error))) = ensure_no_active_run(&state, task.id) {
+        response.errors.push(error.error);
+    }
+    // Check against the agent name a dispatch sends, not the requested alias
+    let agent_type = match resolve_agent_type(&state, &req.agent_type) {
+        Ok(agent) => agent.as_str().to_string(),
+        Err((_, Json(error))) => {
+            response.errors.push(error.error);
+            req.agent_type.clone()
+        }
+    };
+    if let Err((_, Json(error))) = ensure_project_checkout(&state, &project).await {
+        response.errors.push(error.error);
+    }
+
+    let mut host_id = project.gateway_id.to_string();
+    if !project.required_labels.is_empty() {
+        match state
+            .gateway_manager()
+            .select_host(&host_id, &agent_type, &project.required_labels)
+            .await
+        {
+            Ok(selected) => host_id = selected,
+            Err(e) => response.errors.push(e),
+        }
+    }
+    match render_task_prompt(&state, &task, &project).await {
+        Ok(prompt) => response.prompt = Some(prompt),
+        Err((_, Json(error))) => response.errors.push(error.error),
+    }
+    response.base_branch =
+        Some(resolve_base_branch(req.base_branch.as_deref(), &task, &project).await);
+
+    match state
+        .gateway_manager()
+        .preview_dispatch(&host_id, &agent_type, task.priority)
         .await
+    {
+        Ok(queue_position) => response.queue_position = queue_position,
+        Err(e) => response.errors.push(format!("Gateway dispatch failed: {}", e)),
+    }
+    response.host_id = Some(host_id);
+    response.valid = response.errors.is_empty();
+
+    tracing::debug!(
+        "Validated execution for task {} (model={:?}): valid={}",
+        req.task_id,
+        req.model,
+        response.valid
+    );
+    Json(response)
+}
+
+/// GET /api/v1/executions/:id - Get an execution, including the full dispatched prompt
+async fn get_execution(
+    State(state): State<AppState>,
+    Path(execution_id): Path<Uuid>,
+) -> Result<Json<ExecutionDetailResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let run = state
+        .executor()
+        .run_store()
+        .find_run(execution_id)
         .map_err(|e| {
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
@@ -112,106 +363,338 @@ async fn start_execution(
             (
                 StatusCode::NOT_FOUND,
                 Json(ErrorResponse {
-                    error: format!("Task {} not found", task_id),
+                    error: format!("Execution {} not found", execution_id),
                 }),
             )
         })?;
 
-    let project_id = task.project_id.ok_or_else(|| {
-        (
+    Ok(Json(run.into()))
+}
+
+/// POST /api/v1/executions/:id/handoff - Hand a finished execution over to another agent role
+async fn handoff_execution(
+    State(state): State<AppState>,
+    Path(execution_id): Path<Uuid>,
+    identity: Option<UserIdentity>,
+    JsonBody(req): JsonBody<HandoffRequest>,
+) -> Result<(StatusCode, Json<ExecutionResponse>), (StatusCode, Json<ErrorResponse>)> {
+    let agent_role = req.agent_role.trim();
+    if agent_role.is_empty() {
+        return Err((
             StatusCode::UNPROCESSABLE_ENTITY,
             Json(ErrorResponse {
-                error: "Project is required".to_string(),
+                error: "Agent role is required".to_string(),
             }),
-        )
-    })?;
+        ));
+    }
 
-    let project = state.project_store().get(project_id).await.ok_or_else(|| {
-        (
-            StatusCode::NOT_FOUND,
+    let parent = state
+        .executor()
+        .run_store()
+        .find_run(execution_id)
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: e.to_string(),
+                }),
+            )
+        })?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Execution {} not found", execution_id),
+                }),
+            )
+        })?;
+
+    if !parent.is_terminal() {
+        return Err((
+            StatusCode::CONFLICT,
             Json(ErrorResponse {
-                error: format!("Project {} not found", project_id),
+                error: format!("Execution {} has not finished", execution_id),
             }),
-        )
-    })?;
+        ));
+    }
 
-    // Build prompt from task
-    let prompt = if let Some(desc) = &task.description {
-        format!("{}\n\n{}", task.title, desc)
-    } else {
-        task.title.clone()
-    };
+    if parent.metadata.agent_role.as_deref() == Some(agent_role) {
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(ErrorResponse {
+                error: format!("Execution {} already ran as {}", execution_id, agent_role),
+            }),
+        ));
+    }
 
-    let base_branch = task
-        .base_branch
-        .clone()
-        .unwrap_or_else(|| project.default_branch.clone());
+    // Another run of the task may have started since the parent finished
+    ensure_no_active_run(&state, parent.task_id)?;
 
-    if let Some(target_host) = &req.target_host {
-        tracing::info!(
-            "Ignoring requested target_host={} for task {} because execution is project-bound",
-            target_host,
-            task_id
-        );
-    }
+    let (task, project) = load_task_with_project(&state, parent.task_id).await?;
+    ensure_project_checkout(&state, &project).await?;
+
+    let agent_type = req
+        .agent_type
+        .clone()
+        .unwrap_or_else(|| parent.agent_type.as_str().to_string());
+    let agent = resolve_agent_type(&state, &agent_type)?;
+    let prompt = build_handoff_prompt(&parent, req.instructions.as_deref());
+    let metadata = RunMetadata {
+        agent_role: Some(agent_role.to_string()),
+        handoff_id: Some(parent.metadata.handoff_id.unwrap_or(parent.id)),
+        parent_execution_id: Some(parent.id),
+        created_by: identity.map(|identity| identity.sub),
+        ..RunMetadata::default()
+    };
 
     dispatch_to_gateway(
         &state,
-        task_id,
-        &prompt,
-        &req.agent_type,
-        &project.gateway_id.to_string(),
-        &project.local_path,
-        req.model.as_deref(),
-        &base_branch,
+        GatewayDispatch {
+            task_id: parent.task_id,
+            prompt: &prompt,
+            agent,
+            project: &project,
+            model: req.model.as_deref(),
+            base_branch: &parent.base_branch,
+            priority: task.priority,
+            metadata,
+        },
     )
     .await
 }
 
+/// Final chat message for a completed gateway run
+fn completion_message(locale: Locale, accumulated_output: &str, content: Option<&str>) -> String {
+    if !accumulated_output.is_empty() {
+        return accumulated_output.to_string();
+    }
+    content
+        .unwrap_or_else(|| locale.text(StatusMessage::Completed))
+        .to_string()
+}
+
+/// Chat message for a failed gateway run
+fn failure_message(locale: Locale, content: Option<&str>) -> String {
+    format!("❌ {}", content.unwrap_or_else(|| locale.text(StatusMessage::Failed)))
+}
+
+/// How a gateway run ended
+#[derive(Debug, Clone)]
+enum GatewayRunOutcome {
+    Completed { summary: Option<String> },
+    Failed { error: String, exit_code: Option<i32> },
+}
+
+/// Outcome of a Completed event, failed when its result reports no success
+///
+/// Matches the board, which moves such tasks as failed.
+fn completed_outcome(data: &serde_json::Value, last_message: Option<&str>) -> GatewayRunOutcome {
+    let result = &data["result"];
+    if result["success"].as_bool() == Some(false) {
+        let exit_code = result["exitCode"].as_i64().and_then(|code| i32::try_from(code).ok());
+        let error = match exit_code {
+            Some(code) => format!("Task completed without success (exit code {})", code),
+            None => "Task completed without success".to_string(),
+        };
+        return GatewayRunOutcome::Failed { error, exit_code };
+    }
+    GatewayRunOutcome::Completed {
+        summary: extract_summary(result["summary"].as_str(), last_message),
+    }
+}
+
+/// Mark a gateway run terminal and persist it
+///
+/// Updates the stored run so fields set while it was running survive, falling
+/// back to the initial record if it can't be loaded. Publishes the run
+/// lifecycle event once the record is saved.
+async fn finalize_gateway_run(
+    state: &AppState,
+    initial_run: &Run,
+    outcome: GatewayRunOutcome,
+    event_count: u32,
+) -> Run {
+    let mut run = match state
+        .executor()
+        .run_store()
+        .load_run(initial_run.task_id, initial_run.id)
+    {
+        Ok(run) => run,
+        Err(e) => {
+            tracing::warn!(
+                "Failed to load run {} for task {}, rebuilding it: {}",
+                initial_run.id,
+                initial_run.task_id,
+                e
+            );
+            initial_run.clone()
+        }
+    };
+    let now = state.executor().clock().now();
+    if run.started_at.is_none() {
+        run.mark_started_at(now);
+    }
+    run.event_count = event_count;
+    record_gateway_file_changes(state, &mut run);
+
+    let run_logger = RunLogger::new(state.executor().run_store().clone(), run.task_id, run.id);
+    match outcome {
+        GatewayRunOutcome::Completed { summary } => {
+            run.mark_completed_at(0, summary, now);
+            run_logger.log("Run completed");
+        }
+        GatewayRunOutcome::Failed { error, exit_code } => {
+            run_logger.log(format!("Run failed: {}", error));
+            run.mark_failed_at(error, now);
+            run.exit_code = exit_code;
+        }
+    }
+
+    if let Err(e) = state.executor().run_store().save_run(&run) {
+        tracing::warn!("Failed to save terminal run {} for task {}: {}", run.id, run.task_id, e);
+    } else {
+        tracing::info!("Run {} {:?} for gateway task {}", run.id, run.status, run.task_id);
+        state
+            .publish_run_event(RunLifecycleEvent {
+                execution_id: run.id,
+                task_id: run.task_id,
+                status: run.status,
+            })
+            .await;
+    }
+
+    run
+}
+
+/// Fold the run's persisted file-change events into its changed files
+fn record_gateway_file_changes(state: &AppState, run: &mut Run) {
+    let events = match state.executor().run_store().load_events(run.task_id, run.id) {
+        Ok(events) => events,
+        Err(e) => {
+            tracing::warn!("Failed to load events for run {}: {}", run.id, e);
+            return;
+        }
+    };
+    for event in &events {
+        if let ExecutionEventType::AgentEvent {
+            event: AgentEvent::FileChange { path, .. },
+        } = &event.event
+        {
+            run.record_file_change(path);
+        }
+    }
+}
+
+/// A run to dispatch to the project's gateway host
+struct GatewayDispatch<'a> {
+    task_id: Uuid,
+    prompt: &'a str,
+    agent: AgentType,
+    project: &'a Project,
+    model: Option<&'a str>,
+    base_branch: &'a str,
+    priority: TaskPriority,
+    metadata: RunMetadata,
+}
+
 /// Dispatch task to a remote Gateway host
 async fn dispatch_to_gateway(
     state: &AppState,
-    task_id: Uuid,
-    prompt: &str,
-    agent_type: &str,
-    target_host: &str,
-    cwd: &str,
-    model: Option<&str>,
-    base_branch: &str,
+    dispatch: GatewayDispatch<'_>,
 ) -> Result<(StatusCode, Json<ExecutionResponse>), (StatusCode, Json<ErrorResponse>)> {
+    let GatewayDispatch {
+        task_id,
+        prompt,
+        agent,
+        project,
+        model,
+        base_branch,
+        priority,
+        metadata,
+    } = dispatch;
+    // Known agents go out under their canonical name, custom ones unchanged
+    let agent_type = agent.as_str().to_string();
     let gateway_manager = state.gateway_manager();
+    let target_host = if project.required_labels.is_empty() {
+        project.gateway_id.to_string()
+    } else {
+        gateway_manager
+            .select_host(&project.gateway_id.to_string(), &agent_type, &project.required_labels)
+            .await
+            .map_err(|e| (StatusCode::CONFLICT, Json(ErrorResponse { error: e })))?
+    };
+
+    let env = state.project_store().dispatch_env(project).map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: format!("Failed to prepare project environment: {}", e),
+            }),
+        )
+    })?;
 
     let gateway_task = GatewayTaskRequest {
         task_id: task_id.to_string(),
         prompt: prompt.to_string(),
-        cwd: cwd.to_string(),
-        agent_type: agent_type.to_string(),
+        cwd: project.local_path.clone(),
+        agent_type: agent_type.clone(),
         model: model.map(String::from),
-        env: HashMap::new(),
+        env,
         timeout: None,
-        metadata: serde_json::Value::Null,
+        metadata: if metadata.labels.is_empty() {
+            serde_json::Value::Null
+        } else {
+            serde_json::json!({ "labels": metadata.labels })
+        },
     };
 
+    // Record the policy first so a fast host can't finish before it is known
+    gateway_manager
+        .set_auto_move_policy(&task_id.to_string(), project.kanban_policy)
+        .await;
+
+    // Subscribe before dispatching so events from a fast host aren't missed
+    let (mut event_rx, mut last_seq) = gateway_manager.subscribe_from();
+
     match gateway_manager
-        .dispatch_task_to_host(target_host, gateway_task)
+        .dispatch_or_queue_with_retry(&target_host, gateway_task, priority)
         .await
     {
-        Ok(host_id) => {
-            tracing::info!("Task {} dispatched to gateway host {}", task_id, host_id);
+        Ok(outcome) => {
+            let moved_on_start = gateway_manager
+                .apply_auto_move(&task_id.to_string(), RunTransition::Started)
+                .await;
+
+            let (host_id, queue_position) = match outcome {
+                DispatchOutcome::Dispatched(host_id) => {
+                    tracing::info!("Task {} dispatched to gateway host {}", task_id, host_id);
+                    (host_id, None)
+                }
+                DispatchOutcome::Queued { host_id, position } => {
+                    tracing::info!(
+                        "Task {} queued on gateway host {} at position {}",
+                        task_id, host_id, position
+                    );
+                    (host_id, Some(position))
+                }
+            };
             
             // Create a Run record for this gateway execution
             let run_id = Uuid::new_v4();
-            let parsed_agent_type = AgentType::from_str(agent_type)
-                .unwrap_or(AgentType::OpenCode);
             let mut run = Run::new(
                 task_id,
-                parsed_agent_type,
+                agent,
                 prompt.to_string(),
                 base_branch.to_string(),
             );
             // Override the generated ID to use our run_id
             run.id = run_id;
-            run.mark_started();
+            run.created_at = state.executor().clock().now();
+            run.metadata = metadata;
+            run.metadata.host_id = Some(host_id.clone());
+            if queue_position.is_none() {
+                run.mark_started_at(run.created_at);
+            }
             
             // Save the initial run record
             if let Err(e) = state.executor().run_store().save_run(&run) {
@@ -219,34 +702,42 @@ async fn dispatch_to_gateway(
             } else {
                 tracing::info!("Created run {} for gateway task {}", run_id, task_id);
             }
+            let run_logger = RunLogger::new(state.executor().run_store().clone(), task_id, run_id);
+            run_logger.log(match queue_position {
+                None => format!("Run created: {} agent dispatched to gateway host {}", agent_type, host_id),
+                Some(position) => format!(
+                    "Run created: {} agent queued on gateway host {} at position {}",
+                    agent_type, host_id, position
+                ),
+            });
             
             // Set up event forwarding from Gateway to Socket.IO
             let state_clone = state.clone();
             let task_id_str = task_id.to_string();
-            let prompt_clone = prompt.to_string();
-            let agent_type_clone = parsed_agent_type;
-            let base_branch = base_branch.to_string();
+            let initial_run = run.clone();
             tokio::spawn(async move {
-                let mut event_rx = state_clone.gateway_manager().subscribe();
                 let io = state_clone.get_socket_io().await;
                 let mut event_count: u32 = 0;
-                // Accumulate stdout content for final message
-                let mut accumulated_output = String::new();
+                // Accumulate stdout content for final message, bounded to head and tail
+                let mut accumulated_output =
+                    OutputAccumulator::new(state_clone.gateway_output_max_bytes());
+                // Latest agent message, the summary when none is reported
+                let mut last_message: Option<String> = None;
                 // Fixed message ID for streaming updates
                 let message_id = uuid::Uuid::new_v4().to_string();
+                let locale = state_clone.locale();
+                // Persist events off the forwarding loop, in order
+                let event_writer = RunEventWriter::spawn(
+                    Arc::clone(state_clone.executor().run_store()),
+                    task_id,
+                    run_id,
+                    state_clone.event_queue_capacity(),
+                );
                 
                 if let Some(io) = io {
-                    // Move task to Doing when execution starts
-                    {
-                        let kanban_store = state_clone.kanban_store();
-                        if let Err(e) = kanban_store.move_task(&task_id_str, KanbanTaskStatus::Doing, None).await {
-                            tracing::warn!("Failed to move kanban task {} to Doing: {}", task_id_str, e);
-                        } else {
-                            tracing::info!("Kanban task {} moved to Doing", task_id_str);
-                            // Broadcast initial kanban sync
-                            let board_state = kanban_store.get_state().await;
-                            let _ = io.emit("kanban:sync", &board_state);
-                        }
+                    // Broadcast the move made by the project's start policy
+                    if moved_on_start {
+                        state_clone.request_kanban_sync();
                     }
                     
                     // Send initial "working" message
@@ -262,6 +753,8 @@ async fn dispatch_to_gateway(
                             id: String,
                             role: String,
                             content: String,
+                            #[serde(rename = "contentType")]
+                            content_type: MessageContentType,
                             timestamp: i64,
                             #[serde(rename = "isStreaming")]
                             is_streaming: bool,
@@ -272,7 +765,8 @@ async fn dispatch_to_gateway(
                             message: TaskMessage {
                                 id: message_id.clone(),
                                 role: "assistant".to_string(),
-                                content: "正在工作中...".to_string(),
+                                content: locale.text(StatusMessage::Working).to_string(),
+                                content_type: MessageContentType::Markdown,
                                 timestamp: std::time::SystemTime::now()
                                     .duration_since(std::time::UNIX_EPOCH)
                                     .unwrap_or_default()
@@ -282,25 +776,65 @@ async fn dispatch_to_gateway(
                         });
                     }
                     
-                    while let Ok(event) = event_rx.recv().await {
-                        if event.task_id == task_id_str {
+                    let mut backlog = std::collections::VecDeque::new();
+                    loop {
+                        let event = match backlog.pop_front() {
+                            Some(event) => event,
+                            None => match event_rx.recv().await {
+                                Ok(event) => event,
+                                Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                                    // Recover this task's missed events from the journal
+                                    let missed = state_clone
+                                        .gateway_manager()
+                                        .task_events_since(&task_id_str, last_seq);
+                                    tracing::warn!(
+                                        "Event forwarder for task {} lagged by {} events, replaying {} from the journal",
+                                        task_id_str,
+                                        skipped,
+                                        missed.len()
+                                    );
+                                    backlog.extend(missed);
+                                    continue;
+                                }
+                                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                            },
+                        };
+                        if event.task_id == task_id_str && event.seq > last_seq {
+                            last_seq = event.seq;
                             event_count += 1;
                             
                             // Forward gateway event to Socket.IO for Logs panel
                             let _ = io.emit("task:gateway_event", &event);
+
+                            // Keep the unmasked original for admins
+                            if let Some(original) = &event.original {
+                                let agent_event = original.to_file_change().unwrap_or_else(|| AgentEvent::Message {
+                                    content: original.content.clone().unwrap_or_default(),
+                                });
+                                let execution_event = ExecutionEvent::agent_event(run_id, task_id, agent_event);
+                                event_writer.append_unredacted(execution_event).await;
+                            }
+
+                            // Keep file changes in structured form for the run's file tree
+                            if let Some(file_change) = event.event.to_file_change() {
+                                let execution_event =
+                                    ExecutionEvent::agent_event(run_id, task_id, file_change);
+                                event_writer.append(execution_event).await;
+                            }
                             
                             // Accumulate stdout content
                             if let Some(content) = &event.event.content {
                                 match event.event.event_type {
-                                    crate::gateway::protocol::GatewayAgentEventType::Stdout => {
-                                        if !content.starts_with("[executor]") && 
-                                           !content.starts_with("[Gateway]") &&
-                                           !content.is_empty() {
-                                            accumulated_output.push_str(content);
-                                        }
+                                    crate::gateway::protocol::GatewayAgentEventType::Stdout
+                                        if !content.starts_with("[executor]")
+                                            && !content.starts_with("[Gateway]")
+                                            && !content.is_empty() =>
+                                    {
+                                        accumulated_output.push_str(content);
                                     }
                                     crate::gateway::protocol::GatewayAgentEventType::Message => {
                                         accumulated_output.push_str(content);
+                                        last_message = Some(content.clone());
                                     }
                                     _ => {}
                                 }
@@ -309,23 +843,14 @@ async fn dispatch_to_gateway(
                             // Check for Completed/Failed events and update Run record
                             match event.event.event_type {
                                 crate::gateway::protocol::GatewayAgentEventType::Completed => {
-                                    // Update Run record
-                                    let mut run = Run::new(
-                                        task_id,
-                                        agent_type_clone,
-                                        prompt_clone.clone(),
-                                        base_branch.clone(),
-                                    );
-                                    run.id = run_id;
-                                    run.mark_started();
-                                    run.mark_completed(0, event.event.content.clone());
-                                    run.event_count = event_count;
-                                    
-                                    if let Err(e) = state_clone.executor().run_store().save_run(&run) {
-                                        tracing::warn!("Failed to save completed run for task {}: {}", task_id_str, e);
-                                    } else {
-                                        tracing::info!("Run {} completed for gateway task {}", run_id, task_id_str);
-                                    }
+                                    // The run's file changes are read back when it is finalized
+                                    event_writer.flush().await;
+                                    finalize_gateway_run(
+                                        &state_clone,
+                                        &initial_run,
+                                        completed_outcome(&event.event.data, last_message.as_deref()),
+                                        event_count,
+                                    ).await;
                                     
                                     // Send final complete message (replacing the streaming one)
                                     {
@@ -340,16 +865,18 @@ async fn dispatch_to_gateway(
                                             id: String,
                                             role: String,
                                             content: String,
+                                            #[serde(rename = "contentType")]
+                                            content_type: MessageContentType,
                                             timestamp: i64,
                                             #[serde(rename = "isStreaming")]
                                             is_streaming: bool,
                                         }
                                         
-                                        let final_content = if accumulated_output.is_empty() {
-                                            event.event.content.clone().unwrap_or_else(|| "任务完成".to_string())
-                                        } else {
-                                            accumulated_output.clone()
-                                        };
+                                        let final_content = completion_message(
+                                            locale,
+                                            &accumulated_output.to_message(),
+                                            event.event.content.as_deref(),
+                                        );
                                         
                                         let msg_timestamp = std::time::SystemTime::now()
                                             .duration_since(std::time::UNIX_EPOCH)
@@ -362,6 +889,7 @@ async fn dispatch_to_gateway(
                                                 id: message_id.clone(),
                                                 role: "assistant".to_string(),
                                                 content: final_content.clone(),
+                                                content_type: MessageContentType::Markdown,
                                                 timestamp: msg_timestamp,
                                                 is_streaming: false,
                                             },
@@ -379,30 +907,25 @@ async fn dispatch_to_gateway(
                                     }
                                     
                                     // Broadcast kanban sync
-                                    let kanban_store = state_clone.kanban_store();
-                                    let board_state = kanban_store.get_state().await;
-                                    let _ = io.emit("kanban:sync", &board_state);
-                                    tracing::info!("Broadcasted kanban:sync after task {} completed", task_id_str);
+                                    state_clone.request_kanban_sync();
+                                    tracing::info!("Requested kanban:sync after task {} completed", task_id_str);
                                     break;
                                 }
                                 crate::gateway::protocol::GatewayAgentEventType::Failed => {
-                                    // Update Run record
-                                    let mut run = Run::new(
-                                        task_id,
-                                        agent_type_clone,
-                                        prompt_clone.clone(),
-                                        base_branch.clone(),
-                                    );
-                                    run.id = run_id;
-                                    run.mark_started();
-                                    run.mark_failed(event.event.content.clone().unwrap_or_else(|| "Unknown error".to_string()));
-                                    run.event_count = event_count;
-                                    
-                                    if let Err(e) = state_clone.executor().run_store().save_run(&run) {
-                                        tracing::warn!("Failed to save failed run for task {}: {}", task_id_str, e);
-                                    } else {
-                                        tracing::info!("Run {} failed for gateway task {}", run_id, task_id_str);
-                                    }
+                                    event_writer.flush().await;
+                                    finalize_gateway_run(
+                                        &state_clone,
+                                        &initial_run,
+                                        GatewayRunOutcome::Failed {
+                                            error: event
+                                                .event
+                                                .content
+                                                .clone()
+                                                .unwrap_or_else(|| "Unknown error".to_string()),
+                                            exit_code: None,
+                                        },
+                                        event_count,
+                                    ).await;
                                     
                                     // Send error message
                                     {
@@ -417,15 +940,15 @@ async fn dispatch_to_gateway(
                                             id: String,
                                             role: String,
                                             content: String,
+                                            #[serde(rename = "contentType")]
+                                            content_type: MessageContentType,
                                             timestamp: i64,
                                             #[serde(rename = "isStreaming")]
                                             is_streaming: bool,
                                         }
                                         
-                                        let error_content = event.event.content.clone()
-                                            .unwrap_or_else(|| "任务执行失败".to_string());
-                                        
-                                        let error_msg_content = format!("❌ {}", error_content);
+                                        let error_msg_content =
+                                            failure_message(locale, event.event.content.as_deref());
                                         let msg_timestamp = std::time::SystemTime::now()
                                             .duration_since(std::time::UNIX_EPOCH)
                                             .unwrap_or_default()
@@ -437,6 +960,7 @@ async fn dispatch_to_gateway(
                                                 id: message_id.clone(),
                                                 role: "system".to_string(),
                                                 content: error_msg_content.clone(),
+                                                content_type: MessageContentType::Plain,
                                                 timestamp: msg_timestamp,
                                                 is_streaming: false,
                                             },
@@ -454,10 +978,8 @@ async fn dispatch_to_gateway(
                                     }
                                     
                                     // Broadcast kanban sync
-                                    let kanban_store = state_clone.kanban_store();
-                                    let board_state = kanban_store.get_state().await;
-                                    let _ = io.emit("kanban:sync", &board_state);
-                                    tracing::info!("Broadcasted kanban:sync after task {} failed", task_id_str);
+                                    state_clone.request_kanban_sync();
+                                    tracing::info!("Requested kanban:sync after task {} failed", task_id_str);
                                     break;
                                 }
                                 _ => {}
@@ -480,27 +1002,33 @@ async fn dispatch_to_gateway(
                         }
                     }
                 }
+                event_writer.close().await;
+                state_clone.gateway_manager().forget_task_events(&task_id_str);
             });
 
+            let (status, message) = match queue_position {
+                Some(position) => (
+                    "queued",
+                    format!("Task queued on gateway host: {} (position {})", host_id, position),
+                ),
+                None => ("dispatched", format!("Task dispatched to gateway host: {}", host_id)),
+            };
+
             Ok((
                 StatusCode::ACCEPTED,
                 Json(ExecutionResponse {
                     session_id: run_id, // Use the run_id as session_id for consistency
                     task_id,
-                    status: "dispatched".to_string(),
-                    message: format!("Task dispatched to gateway host: {}", host_id),
+                    status: status.to_string(),
+                    message,
                 }),
             ))
         }
         Err(e) => {
             tracing::error!("Failed to dispatch task to gateway: {}", e);
-            let status = if e.starts_with("Host") {
-                StatusCode::CONFLICT
-            } else {
-                StatusCode::SERVICE_UNAVAILABLE
-            };
+            gateway_manager.clear_auto_move_policy(&task_id.to_string()).await;
             Err((
-                status,
+                dispatch_error_status(&e),
                 Json(ErrorResponse {
                     error: format!("Gateway dispatch failed: {}", e),
                 }),
@@ -509,6 +1037,14 @@ async fn dispatch_to_gateway(
     }
 }
 
+/// Status for a failed gateway dispatch
+fn dispatch_error_status(error: &DispatchError) -> StatusCode {
+    match error {
+        DispatchError::Transient(_) | DispatchError::Permanent(_) => StatusCode::CONFLICT,
+        DispatchError::Undelivered(_) | DispatchError::Stale(_) => StatusCode::SERVICE_UNAVAILABLE,
+    }
+}
+
 /// GET /api/tasks/:id/status - Get execution status
 async fn get_execution_status(
     State(state): State<AppState>,
@@ -546,36 +1082,123 @@ async fn stop_execution(
     State(state): State<AppState>,
     Path(task_id): Path<Uuid>,
 ) -> Result<Json<ExecutionResponse>, (StatusCode, Json<ErrorResponse>)> {
-    let session = state
+    stop_run(&state, task_id, None).await.map(Json)
+}
+
+/// POST /api/v1/executions/:id/stop - Stop a running execution
+async fn stop_execution_v1(
+    State(state): State<AppState>,
+    Path(execution_id): Path<Uuid>,
+) -> Result<Json<ExecutionResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let run = state
         .executor()
-        .get_session_by_task(task_id)
-        .await
+        .run_store()
+        .find_run(execution_id)
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: e.to_string(),
+                }),
+            )
+        })?
         .ok_or_else(|| {
             (
                 StatusCode::NOT_FOUND,
                 Json(ErrorResponse {
-                    error: format!("No active session for task {}", task_id),
+                    error: format!("Execution {} not found", execution_id),
                 }),
             )
         })?;
 
-    let session_id = session.read().await.id;
-
-    state.executor().cancel_session(session_id).await.map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
+    if run.is_terminal() {
+        return Err((
+            StatusCode::CONFLICT,
             Json(ErrorResponse {
-                error: e.to_string(),
+                error: format!("Execution {} has already finished", execution_id),
             }),
-        )
-    })?;
+        ));
+    }
 
-    Ok(Json(ExecutionResponse {
-        session_id,
-        task_id,
+    stop_run(&state, run.task_id, Some(run.id)).await.map(Json)
+}
+
+/// Stop a task's execution wherever it runs
+///
+/// Drops a dispatch still queued for a gateway slot; otherwise aborts the
+/// run on its gateway host and cancels any local executor session for the
+/// task, so neither kind of run is left behind.
+async fn stop_run(
+    state: &AppState,
+    task_id: Uuid,
+    run_id: Option<Uuid>,
+) -> Result<ExecutionResponse, (StatusCode, Json<ErrorResponse>)> {
+    let run_id = run_id.or_else(|| {
+        state
+            .executor()
+            .run_store()
+            .list_runs(task_id)
+            .ok()
+            .and_then(|runs| runs.into_iter().find(|run| !run.status.is_terminal()))
+            .map(|run| run.id)
+    });
+
+    // A gateway execution still waiting for a free slot is dropped from the queue
+    if let Some(host_id) = state
+        .gateway_manager()
+        .cancel_queued(&task_id.to_string())
+        .await
+    {
+        state
+            .gateway_manager()
+            .handle_task_failed(&host_id, &task_id.to_string(), "Cancelled before dispatch")
+            .await;
+
+        return Ok(ExecutionResponse {
+            session_id: run_id.unwrap_or(task_id),
+            task_id,
+            status: "cancelled".to_string(),
+            message: "Queued execution cancelled".to_string(),
+        });
+    }
+
+    let aborted_on_gateway = state
+        .gateway_manager()
+        .abort_task(&task_id.to_string())
+        .await
+        .is_ok();
+
+    let local_session_id = match state.executor().get_session_by_task(task_id).await {
+        Some(session) => {
+            let session_id = session.read().await.id;
+            state.executor().cancel_session(session_id).await.map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ErrorResponse {
+                        error: e.to_string(),
+                    }),
+                )
+            })?;
+            Some(session_id)
+        }
+        None => None,
+    };
+
+    if !aborted_on_gateway && local_session_id.is_none() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("No active session for task {}", task_id),
+            }),
+        ));
+    }
+
+    Ok(ExecutionResponse {
+        session_id: local_session_id.or(run_id).unwrap_or(task_id),
+        task_id,
         status: "cancelled".to_string(),
         message: "Execution cancelled".to_string(),
-    }))
+    })
 }
 
 /// DELETE /api/tasks/:id/worktree - Clean up worktree
@@ -690,6 +1313,221 @@ async fn get_session(
 // Helpers
 // ============================================================================
 
+/// Resolve the base branch for an execution
+///
+/// Fallback order: request → task → project default → the repository's own
+/// default branch. Git is only queried when no configured value is set.
+async fn resolve_base_branch(requested: Option<&str>, task: &Task, project: &Project) -> String {
+    let configured = [
+        requested,
+        task.base_branch.as_deref(),
+        Some(project.default_branch.as_str()),
+    ];
+    if let Some(branch) = configured
+        .into_iter()
+        .flatten()
+        .map(str::trim)
+        .find(|branch| !branch.is_empty())
+    {
+        return branch.to_string();
+    }
+
+    match git_worktree::default_branch(std::path::Path::new(&project.local_path)).await {
+        Ok(branch) => branch,
+        Err(e) => {
+            tracing::warn!(
+                "Failed to detect default branch for project {}: {}",
+                project.id,
+                e
+            );
+            "main".to_string()
+        }
+    }
+}
+
+/// Clone the project's remote into its local path on first execution
+///
+/// Only applies to projects with `clone_if_missing` and a remote URL. Clone
+/// failures answer `502 Bad Gateway`, distinct from dispatch or worktree errors.
+/// With a projects root configured, paths resolving outside it are refused.
+async fn ensure_project_checkout(
+    state: &AppState,
+    project: &Project,
+) -> Result<(), (StatusCode, Json<ErrorResponse>)> {
+    let Some(remote_url) = project.remote_url.as_deref().filter(|_| project.clone_if_missing) else {
+        return Ok(());
+    };
+
+    if let Some(root) = state.projects_root() {
+        if !is_path_within(std::path::Path::new(&project.local_path), root) {
+            return Err((
+                StatusCode::FORBIDDEN,
+                Json(ErrorResponse {
+                    error: format!(
+                        "Project path {} is outside the projects root {}",
+                        project.local_path,
+                        root.display()
+                    ),
+                }),
+            ));
+        }
+    }
+
+    match git_worktree::clone_if_missing(remote_url, std::path::Path::new(&project.local_path)).await {
+        Ok(true) => {
+            tracing::info!(
+                "Cloned {} into {} for project {}",
+                remote_url,
+                project.local_path,
+                project.id
+            );
+            Ok(())
+        }
+        Ok(false) => Ok(()),
+        Err(e) => Err((
+            StatusCode::BAD_GATEWAY,
+            Json(ErrorResponse {
+                error: format!("Clone failed: {}", e),
+            }),
+        )),
+    }
+}
+
+/// Parse the requested agent type, rejecting unknown or disallowed ones
+///
+/// `VK_ALLOWED_AGENTS` restricts the agent types a deployment accepts.
+fn resolve_agent_type(
+    state: &AppState,
+    agent_type: &str,
+) -> Result<AgentType, (StatusCode, Json<ErrorResponse>)> {
+    AgentType::from_str_allowed(agent_type, state.allowed_agents()).map_err(|e| {
+        (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+    })
+}
+
+/// Reject starting a run while another run of the task is still active
+///
+/// Covers gateway runs too, which have no local session.
+fn ensure_no_active_run(
+    state: &AppState,
+    task_id: Uuid,
+) -> Result<(), (StatusCode, Json<ErrorResponse>)> {
+    let runs = state.executor().list_runs(task_id).map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+    })?;
+
+    match runs.iter().find(|run| !run.status.is_terminal()) {
+        Some(active) => Err((
+            StatusCode::CONFLICT,
+            Json(ErrorResponse {
+                error: format!(
+                    "Task {} already has an active run {}; retry with force=true to start another",
+                    task_id, active.id
+                ),
+            }),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// The task's prompt from the project template, after its context files
+async fn render_task_prompt(
+    state: &AppState,
+    task: &Task,
+    project: &Project,
+) -> Result<String, (StatusCode, Json<ErrorResponse>)> {
+    let prompt = project.render_prompt(&task.title, task.description.as_deref(), &task.tags);
+    prepend_context_files(
+        &prompt,
+        std::path::Path::new(&project.local_path),
+        &task.context_files,
+        state.context_files_max_bytes(),
+    )
+    .await
+    .map_err(|error| (StatusCode::UNPROCESSABLE_ENTITY, Json(ErrorResponse { error })))
+}
+
+/// Load a task together with the project it is bound to
+async fn load_task_with_project(
+    state: &AppState,
+    task_id: Uuid,
+) -> Result<(Task, Project), (StatusCode, Json<ErrorResponse>)> {
+    let task = state
+        .task_store()
+        .get(task_id)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: e.to_string(),
+                }),
+            )
+        })?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Task {} not found", task_id),
+                }),
+            )
+        })?;
+
+    let project_id = task.project_id.ok_or_else(|| {
+        (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(ErrorResponse {
+                error: "Project is required".to_string(),
+            }),
+        )
+    })?;
+
+    let project = state.project_store().get(project_id).await.ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Project {} not found", project_id),
+            }),
+        )
+    })?;
+
+    Ok((task, project))
+}
+
+/// Build the prompt for a handoff, carrying the parent's outcome as context
+fn build_handoff_prompt(parent: &Run, instructions: Option<&str>) -> String {
+    let from = parent
+        .metadata
+        .agent_role
+        .clone()
+        .unwrap_or_else(|| parent.agent_type.as_str().to_string());
+    let outcome = parent
+        .summary
+        .as_deref()
+        .or(parent.error.as_deref())
+        .unwrap_or("No summary was recorded.");
+
+    let mut prompt = format!(
+        "{}\n\n---\nHandoff from {} (execution {}):\n{}",
+        parent.prompt, from, parent.id, outcome
+    );
+    if let Some(instructions) = instructions.filter(|i| !i.trim().is_empty()) {
+        prompt.push_str("\n\n");
+        prompt.push_str(instructions);
+    }
+    prompt
+}
+
 fn state_to_string(state: &SessionState) -> String {
     match state {
         SessionState::Pending => "pending".to_string(),
@@ -707,13 +1545,24 @@ fn state_to_string(state: &SessionState) -> String {
 // ============================================================================
 
 pub fn router() -> Router<AppState> {
-    Router::new()
-        // Task execution endpoints
+    // Legacy task execution endpoints (deprecated in favour of /api/v1/executions)
+    let legacy = Router::new()
         .route("/api/tasks/{id}/execute", post(start_execution))
         .route("/api/tasks/{id}/status", get(get_execution_status))
         .route("/api/tasks/{id}/stop", post(stop_execution))
         .route("/api/tasks/{id}/input", post(send_input))
         .route("/api/tasks/{id}/worktree", delete(cleanup_worktree))
+        .route_layer(middleware::from_fn_with_state(
+            (SunsetConfig::from_env(), "/api/v1/executions"),
+            deprecated_route,
+        ));
+
+    Router::new()
+        .merge(legacy)
+        .route("/api/v1/executions/validate", post(validate_execution))
+        .route("/api/v1/executions/{id}", get(get_execution))
+        .route("/api/v1/executions/{id}/handoff", post(handoff_execution))
+        .route("/api/v1/executions/{id}/stop", post(stop_execution_v1))
         // Session endpoints
         .route("/api/sessions", get(list_sessions))
         .route("/api/sessions/{id}", get(get_session))
@@ -739,13 +1588,19 @@ mod tests {
 
     use crate::{
         gateway::{
-            protocol::{HostCapabilities, ServerToGatewayMessage},
+            protocol::{GatewayAgentEvent, GatewayAgentEventType, HostCapabilities, ServerToGatewayMessage},
             GatewayManager,
         },
         state::AppState,
     };
 
     async fn build_state() -> (AppState, TempDir) {
+        build_state_with_manager(|manager| manager).await
+    }
+
+    async fn build_state_with_manager(
+        configure: impl FnOnce(GatewayManager) -> GatewayManager,
+    ) -> (AppState, TempDir) {
         let temp_dir = TempDir::new().unwrap();
         let data_dir = temp_dir.path().to_path_buf();
 
@@ -757,10 +1612,10 @@ mod tests {
                 .await
                 .unwrap(),
         );
-        let gateway_manager = Arc::new(GatewayManager::with_stores(
+        let gateway_manager = Arc::new(configure(GatewayManager::with_stores(
             Arc::clone(&task_store),
             Arc::clone(&kanban_store),
-        ));
+        )));
         let state = AppState::with_stores(
             data_dir,
             Arc::clone(&task_store),
@@ -783,6 +1638,48 @@ mod tests {
         )
     }
 
+    /// Register a project checked out at `local_path` and connect its
+    /// gateway host, advertising `opencode`
+    ///
+    /// Returns the project and the messages sent to its host.
+    async fn register_project_with_host(
+        state: &AppState,
+        name: &str,
+        local_path: String,
+    ) -> (Project, tokio::sync::mpsc::Receiver<ServerToGatewayMessage>) {
+        let project = state
+            .project_store()
+            .register(
+                Uuid::new_v4(),
+                CreateProjectRequest {
+                    name: name.to_string(),
+                    local_path,
+                    remote_url: None,
+                    default_branch: None,
+                    worktree_dir: None,
+                },
+            )
+            .await
+            .unwrap();
+        let (tx, rx) = tokio::sync::mpsc::channel(10);
+        state
+            .gateway_manager()
+            .register_host(
+                project.gateway_id.to_string(),
+                HostCapabilities {
+                    name: format!("{} host", name),
+                    agents: vec!["opencode".to_string()],
+                    max_concurrent: 2,
+                    cwd: "/tmp".to_string(),
+                    labels: HashMap::new(),
+                    agent_versions: HashMap::new(),
+                },
+                tx,
+            )
+            .await;
+        (project, rx)
+    }
+
     #[test]
     fn start_execution_request_deserializes_camel_case() {
         let value = serde_json::json!({
@@ -791,7 +1688,188 @@ mod tests {
         });
         let req: StartExecutionRequest = serde_json::from_value(value).expect("valid payload");
         assert_eq!(req.agent_type, "opencode");
-        assert_eq!(req.base_branch, "main");
+        assert_eq!(req.base_branch.as_deref(), Some("main"));
+    }
+
+    #[test]
+    fn gateway_chat_messages_use_locale_fallbacks() {
+        assert_eq!(completion_message(Locale::En, "", None), "Task completed");
+        assert_eq!(completion_message(Locale::Zh, "", None), "任务完成");
+        assert_eq!(completion_message(Locale::Zh, "output", Some("summary")), "output");
+        assert_eq!(failure_message(Locale::En, None), "❌ Task failed");
+        assert_eq!(failure_message(Locale::Zh, None), "❌ 任务执行失败");
+        assert_eq!(failure_message(Locale::Zh, Some("exit 1")), "❌ exit 1");
+    }
+
+    #[tokio::test]
+    async fn finalize_gateway_run_persists_success_and_failure_alike() {
+        let (state, _temp_dir) = build_state().await;
+        let mut events = state.subscribe_run_events();
+
+        let mut initial = Run::new(
+            Uuid::new_v4(),
+            AgentType::ClaudeCode,
+            "prompt".to_string(),
+            "main".to_string(),
+        );
+        initial.metadata.agent_role = Some("reviewer".to_string());
+        initial.mark_started();
+
+        let mut failed_initial = initial.clone();
+        failed_initial.id = Uuid::new_v4();
+
+        finalize_gateway_run(
+            &state,
+            &initial,
+            GatewayRunOutcome::Completed {
+                summary: Some("done".to_string()),
+            },
+            7,
+        ).await;
+        finalize_gateway_run(
+            &state,
+            &failed_initial,
+            GatewayRunOutcome::Failed {
+                error: "boom".to_string(),
+                exit_code: None,
+            },
+            7,
+        ).await;
+
+        let run_store = state.executor().run_store();
+        let completed = run_store.load_run(initial.task_id, initial.id).unwrap();
+        let failed = run_store.load_run(initial.task_id, failed_initial.id).unwrap();
+
+        assert_eq!(completed.status, ExecutionStatus::Completed);
+        assert_eq!(completed.summary.as_deref(), Some("done"));
+        assert_eq!(failed.status, ExecutionStatus::Failed);
+        assert_eq!(failed.error.as_deref(), Some("boom"));
+        for run in [&completed, &failed] {
+            assert_eq!(run.event_count, 7);
+            assert_eq!(run.agent_type, AgentType::ClaudeCode);
+            assert_eq!(run.prompt, "prompt");
+            assert_eq!(run.metadata.agent_role.as_deref(), Some("reviewer"));
+            assert_eq!(run.created_at, initial.created_at);
+            assert_eq!(run.started_at, initial.started_at);
+            assert!(run.ended_at.is_some());
+            assert!(run.duration_ms.is_some());
+        }
+
+        assert_eq!(events.recv().await.unwrap().status, ExecutionStatus::Completed);
+        assert_eq!(events.recv().await.unwrap().status, ExecutionStatus::Failed);
+    }
+
+    #[tokio::test]
+    async fn unsuccessful_completion_finalizes_the_run_as_failed() {
+        let (state, _temp_dir) = build_state().await;
+        let initial = Run::new(Uuid::new_v4(), AgentType::OpenCode, "prompt".to_string(), "main".to_string());
+
+        let data = json!({ "result": { "success": false, "exitCode": 2, "summary": "Tests fail" } });
+        let outcome = completed_outcome(&data, None);
+        let run = finalize_gateway_run(&state, &initial, outcome, 1).await;
+        assert_eq!(run.status, ExecutionStatus::Failed);
+        assert_eq!(run.exit_code, Some(2));
+        assert_eq!(run.error.as_deref(), Some("Task completed without success (exit code 2)"));
+
+        let data = json!({ "result": { "success": true, "summary": "All green" } });
+        assert!(matches!(
+            completed_outcome(&data, None),
+            GatewayRunOutcome::Completed { summary: Some(summary) } if summary == "All green"
+        ));
+    }
+
+    #[tokio::test]
+    async fn run_notifications_follow_task_notify_on() {
+        let (state, _temp_dir) = build_state().await;
+        let task = state
+            .task_store()
+            .create(Task::new("Quiet on success").with_notify_on(vec!["failed".to_string()]))
+            .await
+            .unwrap();
+        let mut events = state.subscribe_run_events();
+
+        let completed = Run::new(task.id, AgentType::OpenCode, "prompt".to_string(), "main".to_string());
+        finalize_gateway_run(&state, &completed, GatewayRunOutcome::Completed { summary: None }, 1).await;
+        assert!(matches!(
+            events.try_recv(),
+            Err(tokio::sync::broadcast::error::TryRecvError::Empty)
+        ));
+
+        let failed = Run::new(task.id, AgentType::OpenCode, "prompt".to_string(), "main".to_string());
+        finalize_gateway_run(
+            &state,
+            &failed,
+            GatewayRunOutcome::Failed {
+                error: "boom".to_string(),
+                exit_code: None,
+            },
+            1,
+        )
+        .await;
+        let event = events.try_recv().unwrap();
+        assert_eq!(event.execution_id, failed.id);
+        assert_eq!(event.status, ExecutionStatus::Failed);
+    }
+
+    #[tokio::test]
+    async fn finalize_gateway_run_keeps_metadata_set_mid_run() {
+        let (state, _temp_dir) = build_state().await;
+        let run_store = state.executor().run_store();
+
+        let mut initial = Run::new(
+            Uuid::new_v4(),
+            AgentType::OpenCode,
+            "prompt".to_string(),
+            "main".to_string(),
+        );
+        initial.mark_started();
+        run_store.save_run(&initial).unwrap();
+
+        let mut running = run_store.load_run(initial.task_id, initial.id).unwrap();
+        running.metadata.tools_called = 3;
+        running.metadata.files_modified.push("src/lib.rs".to_string());
+        run_store.save_run(&running).unwrap();
+
+        finalize_gateway_run(
+            &state,
+            &initial,
+            GatewayRunOutcome::Completed { summary: None },
+            4,
+        ).await;
+
+        let finished = run_store.load_run(initial.task_id, initial.id).unwrap();
+        assert_eq!(finished.status, ExecutionStatus::Completed);
+        assert_eq!(finished.event_count, 4);
+        assert_eq!(finished.metadata.tools_called, 3);
+        assert_eq!(finished.metadata.files_modified, vec!["src/lib.rs".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn finalize_gateway_run_counts_changed_files_in_summary() {
+        let (state, _temp_dir) = build_state().await;
+        let run_store = state.executor().run_store();
+
+        let mut initial = Run::new(Uuid::new_v4(), AgentType::OpenCode, "prompt".to_string(), "main".to_string());
+        initial.mark_started();
+        run_store.save_run(&initial).unwrap();
+        for path in ["src/a.rs", "src/b.rs", "src/a.rs"] {
+            let change = AgentEvent::FileChange {
+                path: path.to_string(),
+                action: agent_runner::FileAction::Modified,
+                diff: None,
+                additions: None,
+                deletions: None,
+            };
+            run_store
+                .append_event(initial.task_id, initial.id, &ExecutionEvent::agent_event(initial.id, initial.task_id, change))
+                .unwrap();
+        }
+
+        finalize_gateway_run(&state, &initial, GatewayRunOutcome::Completed { summary: None }, 3).await;
+
+        let summaries = run_store.list_runs(initial.task_id).unwrap();
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].changed_files, Some(2));
     }
 
     #[tokio::test]
@@ -887,15 +1965,15 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn start_execution_dispatches_project_cwd_to_bound_host() {
+    async fn validate_execution_reports_dispatch_conflict_without_creating_run() {
         let (state, _temp_dir) = build_state().await;
         let project = state
             .project_store()
             .register(
                 Uuid::new_v4(),
                 CreateProjectRequest {
-                    name: "bound-project".to_string(),
-                    local_path: "/tmp/bound-project".to_string(),
+                    name: "validate-project".to_string(),
+                    local_path: "/tmp/validate-project".to_string(),
                     remote_url: None,
                     default_branch: Some("develop".to_string()),
                     worktree_dir: None,
@@ -905,27 +1983,35 @@ mod tests {
             .unwrap();
         let task = state
             .task_store()
-            .create(Task::new("Dispatch cwd".to_string()).with_project_id(project.id))
+            .create(Task::new("Validate me".to_string()).with_project_id(project.id))
             .await
             .unwrap();
+        let app = router().with_state(state.clone());
 
-        let (tx, mut rx) = tokio::sync::mpsc::channel(10);
-        state
-            .gateway_manager()
-            .register_host(
-                project.gateway_id.to_string(),
-                HostCapabilities {
-                    name: "Bound host".to_string(),
-                    agents: vec!["opencode".to_string()],
-                    max_concurrent: 2,
-                    cwd: "/tmp".to_string(),
-                    labels: HashMap::new(),
-                },
-                tx,
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/v1/executions/validate")
+                    .header("Content-Type", "application/json")
+                    .body(Body::from(
+                        json!({ "taskId": task.id, "agentType": "opencode", "baseBranch": "release" })
+                            .to_string(),
+                    ))
+                    .unwrap(),
             )
-            .await;
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let report: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(report["valid"], false);
+        assert_eq!(report["baseBranch"], "release");
+        assert_eq!(report["hostId"], project.gateway_id.to_string());
+        assert_eq!(report["prompt"], "Validate me");
+        assert!(state.executor().run_store().list_runs(task.id).unwrap().is_empty());
 
-        let app = router().with_state(state);
         let response = app
             .oneshot(
                 Request::builder()
@@ -937,15 +2023,1537 @@ mod tests {
             )
             .await
             .unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let error: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(report["errors"], json!([error["error"]]));
+    }
 
-        assert_eq!(response.status(), StatusCode::ACCEPTED);
-
-        let msg = rx.recv().await.unwrap();
-        match msg {
-            ServerToGatewayMessage::TaskExecute { task } => {
-                assert_eq!(task.cwd, project.local_path);
+    #[tokio::test]
+    async fn validate_execution_checks_what_a_dispatch_would_send() {
+        let (state, temp_dir) = build_state().await;
+        let register = |name: &str, remote_url: Option<String>| {
+            let state = state.clone();
+            let request = CreateProjectRequest {
+                name: name.to_string(),
+                local_path: temp_dir.path().join(name).to_string_lossy().to_string(),
+                remote_url,
+                default_branch: None,
+                worktree_dir: None,
+            };
+            async move {
+                let project = state.project_store().register(Uuid::new_v4(), request).await.unwrap();
+                let task = state
+                    .task_store()
+                    .create(Task::new("Validate me".to_string()).with_project_id(project.id))
+                    .await
+                    .unwrap();
+                (project, task)
             }
-            _ => panic!("expected task dispatch message"),
-        }
+        };
+        let validate = |task_id: Uuid| {
+            let app = router().with_state(state.clone());
+            async move {
+                let response = app
+                    .oneshot(
+                        Request::builder()
+                            .method("POST")
+                            .uri("/api/v1/executions/validate")
+                            .header("Content-Type", "application/json")
+                            .body(Body::from(
+                                json!({ "taskId": task_id, "agentType": "claude_code" }).to_string(),
+                            ))
+                            .unwrap(),
+                    )
+                    .await
+                    .unwrap();
+                let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                serde_json::from_slice::<Value>(&body).unwrap()
+            }
+        };
+
+        // The alias resolves to the agent name the host advertises
+        let (project, task) = register("alias-project", None).await;
+        let (tx, _rx) = tokio::sync::mpsc::channel(10);
+        state
+            .gateway_manager()
+            .register_host(
+                project.gateway_id.to_string(),
+                HostCapabilities {
+                    name: "Claude host".to_string(),
+                    agents: vec!["claude-code".to_string()],
+                    max_concurrent: 2,
+                    cwd: "/tmp".to_string(),
+                    labels: HashMap::new(),
+                    agent_versions: HashMap::new(),
+                },
+                tx,
+            )
+            .await;
+        let report = validate(task.id).await;
+        assert_eq!(report["valid"], true, "{}", report);
+
+        // A checkout that cannot be cloned fails validation as it fails a dispatch
+        let missing = temp_dir.path().join("missing.git").to_string_lossy().to_string();
+        let (project, task) = register("clone-project", Some(missing)).await;
+        state
+            .project_store()
+            .update(project.with_clone_if_missing(true))
+            .await
+            .unwrap();
+        let report = validate(task.id).await;
+        assert_eq!(report["valid"], false);
+        assert!(report["errors"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|error| error.as_str().unwrap().starts_with("Clone failed")));
+    }
+
+    #[tokio::test]
+    async fn start_execution_dispatches_project_cwd_to_bound_host() {
+        let (state, _temp_dir) = build_state().await;
+        let project = state
+            .project_store()
+            .register(
+                Uuid::new_v4(),
+                CreateProjectRequest {
+                    name: "bound-project".to_string(),
+                    local_path: "/tmp/bound-project".to_string(),
+                    remote_url: None,
+                    default_branch: Some("develop".to_string()),
+                    worktree_dir: None,
+                },
+            )
+            .await
+            .unwrap();
+        let task = state
+            .task_store()
+            .create(Task::new("Dispatch cwd".to_string()).with_project_id(project.id))
+            .await
+            .unwrap();
+
+        let (tx, mut rx) = tokio::sync::mpsc::channel(10);
+        state
+            .gateway_manager()
+            .register_host(
+                project.gateway_id.to_string(),
+                HostCapabilities {
+                    name: "Bound host".to_string(),
+                    agents: vec!["opencode".to_string()],
+                    max_concurrent: 2,
+                    cwd: "/tmp".to_string(),
+                    labels: HashMap::new(),
+                    agent_versions: HashMap::new(),
+                },
+                tx,
+            )
+            .await;
+
+        let app = router().with_state(state);
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/tasks/{}/execute", task.id))
+                    .header("Content-Type", "application/json")
+                    .body(execution_body())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+        let msg = rx.recv().await.unwrap();
+        match msg {
+            ServerToGatewayMessage::TaskExecute { task } => {
+                assert_eq!(task.cwd, project.local_path);
+            }
+            _ => panic!("expected task dispatch message"),
+        }
+    }
+
+    #[tokio::test]
+    async fn start_execution_prepends_context_files_to_prompt() {
+        let (state, temp_dir) = build_state().await;
+        let checkout = temp_dir.path().join("context-project");
+        std::fs::create_dir_all(checkout.join("docs")).unwrap();
+        std::fs::write(checkout.join("docs/api.md"), "Endpoints live under /api/v1\n").unwrap();
+        let (project, mut rx) =
+            register_project_with_host(&state, "context-project", checkout.to_string_lossy().to_string()).await;
+        let task = state
+            .task_store()
+            .create(
+                Task::new("Use the context".to_string())
+                    .with_project_id(project.id)
+                    .with_context_files(vec!["docs/api.md".to_string()]),
+            )
+            .await
+            .unwrap();
+
+        let response = router()
+            .with_state(state)
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/tasks/{}/execute", task.id))
+                    .header("Content-Type", "application/json")
+                    .body(execution_body())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+        match rx.recv().await.unwrap() {
+            ServerToGatewayMessage::TaskExecute { task } => {
+                assert!(task.prompt.starts_with(
+                    "--- BEGIN CONTEXT FILE: docs/api.md ---\nEndpoints live under /api/v1\n--- END CONTEXT FILE: docs/api.md ---\n"
+                ));
+                assert!(task.prompt.contains("Use the context"));
+            }
+            _ => panic!("expected task dispatch message"),
+        }
+    }
+
+    #[tokio::test]
+    async fn start_execution_rejects_host_with_stale_heartbeat() {
+        let (state, _temp_dir) = build_state().await;
+        let (project, mut rx) =
+            register_project_with_host(&state, "stale-project", "/tmp/stale-project".to_string()).await;
+        let task = state
+            .task_store()
+            .create(Task::new("Stale host".to_string()).with_project_id(project.id))
+            .await
+            .unwrap();
+        state
+            .gateway_manager()
+            .age_heartbeat(&project.gateway_id.to_string(), crate::gateway::manager::HEARTBEAT_TIMEOUT * 2)
+            .await;
+
+        let app = router().with_state(state.clone());
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/tasks/{}/execute", task.id))
+                    .header("Content-Type", "application/json")
+                    .body(execution_body())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert!(rx.try_recv().is_err());
+        assert!(state.executor().run_store().list_runs(task.id).unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn start_execution_only_accepts_allowed_agent_types() {
+        let (mut state, _temp_dir) = build_state().await;
+        state.set_allowed_agents(Some(vec![AgentType::ClaudeCode]));
+        let project = state
+            .project_store()
+            .register(
+                Uuid::new_v4(),
+                CreateProjectRequest {
+                    name: "locked-down-project".to_string(),
+                    local_path: "/tmp/locked-down-project".to_string(),
+                    remote_url: None,
+                    default_branch: None,
+                    worktree_dir: None,
+                },
+            )
+            .await
+            .unwrap();
+        let task = state
+            .task_store()
+            .create(Task::new("Locked down".to_string()).with_project_id(project.id))
+            .await
+            .unwrap();
+
+        let (tx, mut rx) = tokio::sync::mpsc::channel(10);
+        state
+            .gateway_manager()
+            .register_host(
+                project.gateway_id.to_string(),
+                HostCapabilities {
+                    name: "Bound host".to_string(),
+                    agents: vec!["opencode".to_string(), "claude-code".to_string()],
+                    max_concurrent: 2,
+                    cwd: "/tmp".to_string(),
+                    labels: HashMap::new(),
+                    agent_versions: HashMap::new(),
+                },
+                tx,
+            )
+            .await;
+
+        let execute = |agent_type: &str| {
+            router().with_state(state.clone()).oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/tasks/{}/execute", task.id))
+                    .header("Content-Type", "application/json")
+                    .body(Body::from(json!({ "agentType": agent_type }).to_string()))
+                    .unwrap(),
+            )
+        };
+
+        for disallowed in ["opencode", "not-an-agent"] {
+            let response = execute(disallowed).await.unwrap();
+            assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        }
+        assert!(rx.try_recv().is_err());
+
+        let response = execute("claude-code").await.unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        assert!(rx.recv().await.is_some());
+        let runs = state.executor().list_runs(task.id).unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].agent_type, AgentType::ClaudeCode);
+    }
+
+    #[tokio::test]
+    async fn start_execution_rejects_second_active_run_without_force() {
+        let (state, _temp_dir) = build_state().await;
+        let project = state
+            .project_store()
+            .register(
+                Uuid::new_v4(),
+                CreateProjectRequest {
+                    name: "duplicate-project".to_string(),
+                    local_path: "/tmp/duplicate-project".to_string(),
+                    remote_url: None,
+                    default_branch: None,
+                    worktree_dir: None,
+                },
+            )
+            .await
+            .unwrap();
+        let task = state
+            .task_store()
+            .create(Task::new("Run once".to_string()).with_project_id(project.id))
+            .await
+            .unwrap();
+
+        let (tx, _rx) = tokio::sync::mpsc::channel(10);
+        state
+            .gateway_manager()
+            .register_host(
+                project.gateway_id.to_string(),
+                HostCapabilities {
+                    name: "Duplicate host".to_string(),
+                    agents: vec!["opencode".to_string()],
+                    max_concurrent: 4,
+                    cwd: "/tmp".to_string(),
+                    labels: HashMap::new(),
+                    agent_versions: HashMap::new(),
+                },
+                tx,
+            )
+            .await;
+
+        let app = router().with_state(state.clone());
+        let execute = |uri: String| {
+            let app = app.clone();
+            async move {
+                app.oneshot(
+                    Request::builder()
+                        .method("POST")
+                        .uri(uri)
+                        .header("Content-Type", "application/json")
+                        .body(execution_body())
+                        .unwrap(),
+                )
+                .await
+                .unwrap()
+                .status()
+            }
+        };
+
+        let uri = format!("/api/tasks/{}/execute", task.id);
+        assert_eq!(execute(uri.clone()).await, StatusCode::ACCEPTED);
+        assert_eq!(execute(uri.clone()).await, StatusCode::CONFLICT);
+        assert_eq!(state.executor().list_runs(task.id).unwrap().len(), 1);
+
+        assert_eq!(execute(format!("{}?force=true", uri)).await, StatusCode::ACCEPTED);
+        assert_eq!(state.executor().list_runs(task.id).unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn labeled_execution_is_dispatched_and_filterable_by_label() {
+        let (state, _temp_dir) = build_state().await;
+        let project = state
+            .project_store()
+            .register(
+                Uuid::new_v4(),
+                CreateProjectRequest {
+                    name: "labeled-project".to_string(),
+                    local_path: "/tmp/labeled-project".to_string(),
+                    remote_url: None,
+                    default_branch: None,
+                    worktree_dir: None,
+                },
+            )
+            .await
+            .unwrap();
+        let task = state
+            .task_store()
+            .create(Task::new("Labeled run".to_string()).with_project_id(project.id))
+            .await
+            .unwrap();
+
+        let (tx, mut rx) = tokio::sync::mpsc::channel(10);
+        state
+            .gateway_manager()
+            .register_host(
+                project.gateway_id.to_string(),
+                HostCapabilities {
+                    name: "Labeled host".to_string(),
+                    agents: vec!["opencode".to_string()],
+                    max_concurrent: 2,
+                    cwd: "/tmp".to_string(),
+                    labels: HashMap::new(),
+                    agent_versions: HashMap::new(),
+                },
+                tx,
+            )
+            .await;
+
+        let app = router()
+            .merge(crate::routes::task::router())
+            .with_state(state);
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/tasks/{}/execute", task.id))
+                    .header("Content-Type", "application/json")
+                    .body(Body::from(
+                        json!({
+                            "agentType": "opencode",
+                            "labels": { "team": "infra", "ticket": "OPS-42" }
+                        })
+                        .to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+        match rx.recv().await.unwrap() {
+            ServerToGatewayMessage::TaskExecute { task } => {
+                assert_eq!(task.metadata["labels"]["ticket"], "OPS-42");
+            }
+            _ => panic!("expected task dispatch message"),
+        }
+
+        let list = |query: &'static str| {
+            let app = app.clone();
+            let uri = format!("/api/tasks/{}/runs?{}", task.id, query);
+            async move {
+                let response = app
+                    .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                    .await
+                    .unwrap();
+                assert_eq!(response.status(), StatusCode::OK);
+                let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                serde_json::from_slice::<Value>(&body).unwrap()["runs"].clone()
+            }
+        };
+
+        let runs = list("label=team:infra&label=ticket:OPS-42").await;
+        assert_eq!(runs.as_array().unwrap().len(), 1);
+        assert_eq!(runs[0]["labels"]["team"], "infra");
+        assert!(list("label=team:web").await.as_array().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn start_execution_records_and_filters_by_creator() {
+        let (mut state, _temp_dir) = build_state().await;
+        state.set_trust_user_header(true);
+        let project = state
+            .project_store()
+            .register(
+                Uuid::new_v4(),
+                CreateProjectRequest {
+                    name: "attributed-project".to_string(),
+                    local_path: "/tmp/attributed-project".to_string(),
+                    remote_url: None,
+                    default_branch: None,
+                    worktree_dir: None,
+                },
+            )
+            .await
+            .unwrap();
+        let task = state
+            .task_store()
+            .create(Task::new("Attributed run".to_string()).with_project_id(project.id))
+            .await
+            .unwrap();
+
+        let (tx, _rx) = tokio::sync::mpsc::channel(10);
+        state
+            .gateway_manager()
+            .register_host(
+                project.gateway_id.to_string(),
+                HostCapabilities {
+                    name: "Attributed host".to_string(),
+                    agents: vec!["opencode".to_string()],
+                    max_concurrent: 2,
+                    cwd: "/tmp".to_string(),
+                    labels: HashMap::new(),
+                    agent_versions: HashMap::new(),
+                },
+                tx,
+            )
+            .await;
+
+        let app = router()
+            .merge(crate::routes::task::router())
+            .with_state(state);
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/tasks/{}/execute", task.id))
+                    .header("Content-Type", "application/json")
+                    .header(crate::identity::USER_HEADER, "alice")
+                    .body(Body::from(json!({ "agentType": "opencode" }).to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let session_id = serde_json::from_slice::<Value>(&body).unwrap()["sessionId"]
+            .as_str()
+            .unwrap()
+            .to_string();
+
+        let get = |uri: String| {
+            let app = app.clone();
+            async move {
+                let response = app
+                    .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                    .await
+                    .unwrap();
+                assert_eq!(response.status(), StatusCode::OK);
+                let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                serde_json::from_slice::<Value>(&body).unwrap()
+            }
+        };
+
+        let detail = get(format!("/api/v1/executions/{}", session_id)).await;
+        assert_eq!(detail["createdBy"], "alice");
+
+        let runs = get(format!("/api/tasks/{}/runs?createdBy=alice", task.id)).await["runs"].clone();
+        assert_eq!(runs.as_array().unwrap().len(), 1);
+        assert_eq!(runs[0]["createdBy"], "alice");
+        let runs = get(format!("/api/tasks/{}/runs?createdBy=bob", task.id)).await["runs"].clone();
+        assert!(runs.as_array().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn start_execution_renders_project_prompt_template() {
+        let (state, _temp_dir) = build_state().await;
+        let project = state
+            .project_store()
+            .register(
+                Uuid::new_v4(),
+                CreateProjectRequest {
+                    name: "templated-project".to_string(),
+                    local_path: "/tmp/templated-project".to_string(),
+                    remote_url: None,
+                    default_branch: None,
+                    worktree_dir: None,
+                },
+            )
+            .await
+            .unwrap();
+        let project = state
+            .project_store()
+            .update(project.with_prompt_template("Task: {title}\nTags: {tags}\n\n{description}"))
+            .await
+            .unwrap();
+        let task = state
+            .task_store()
+            .create(
+                Task::new("Fix login".to_string())
+                    .with_description("Session expires too early".to_string())
+                    .with_tags(vec!["auth".to_string(), "bug".to_string()])
+                    .with_project_id(project.id),
+            )
+            .await
+            .unwrap();
+
+        let (tx, mut rx) = tokio::sync::mpsc::channel(10);
+        state
+            .gateway_manager()
+            .register_host(
+                project.gateway_id.to_string(),
+                HostCapabilities {
+                    name: "Templated host".to_string(),
+                    agents: vec!["opencode".to_string()],
+                    max_concurrent: 2,
+                    cwd: "/tmp".to_string(),
+                    labels: HashMap::new(),
+                    agent_versions: HashMap::new(),
+                },
+                tx,
+            )
+            .await;
+
+        let response = router()
+            .with_state(state)
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/tasks/{}/execute", task.id))
+                    .header("Content-Type", "application/json")
+                    .body(execution_body())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+        match rx.recv().await.unwrap() {
+            ServerToGatewayMessage::TaskExecute { task } => {
+                assert_eq!(
+                    task.prompt,
+                    "Task: Fix login\nTags: auth, bug\n\nSession expires too early"
+                );
+            }
+            _ => panic!("expected task dispatch message"),
+        }
+    }
+
+    #[tokio::test]
+    async fn gateway_completion_publishes_run_lifecycle_event() {
+        let (state, _temp_dir) = build_state().await;
+        let (_layer, io) = socketioxide::SocketIo::new_layer();
+        state.set_socket_io(io).await;
+        let mut run_events = state.subscribe_run_events();
+
+        let project = state
+            .project_store()
+            .register(
+                Uuid::new_v4(),
+                CreateProjectRequest {
+                    name: "lifecycle-project".to_string(),
+                    local_path: "/tmp/lifecycle-project".to_string(),
+                    remote_url: None,
+                    default_branch: None,
+                    worktree_dir: None,
+                },
+            )
+            .await
+            .unwrap();
+        let task = state
+            .task_store()
+            .create(Task::new("Lifecycle".to_string()).with_project_id(project.id))
+            .await
+            .unwrap();
+
+        let host_id = project.gateway_id.to_string();
+        let (tx, mut rx) = tokio::sync::mpsc::channel(10);
+        state
+            .gateway_manager()
+            .register_host(
+                host_id.clone(),
+                HostCapabilities {
+                    name: "Bound host".to_string(),
+                    agents: vec!["opencode".to_string()],
+                    max_concurrent: 2,
+                    cwd: "/tmp".to_string(),
+                    labels: HashMap::new(),
+                    agent_versions: HashMap::new(),
+                },
+                tx,
+            )
+            .await;
+
+        let response = router()
+            .with_state(state.clone())
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/tasks/{}/execute", task.id))
+                    .header("Content-Type", "application/json")
+                    .body(execution_body())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let payload: Value = serde_json::from_slice(&body).unwrap();
+        assert!(rx.recv().await.is_some());
+
+        // Let the event forwarder subscribe before the gateway reports completion
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        state
+            .gateway_manager()
+            .handle_task_completed(
+                &host_id,
+                &task.id.to_string(),
+                crate::gateway::protocol::TaskResult {
+                    success: true,
+                    exit_code: Some(0),
+                    output: None,
+                    summary: None,
+                    duration: None,
+                    files_changed: vec![],
+                },
+            )
+            .await;
+
+        let event = tokio::time::timeout(std::time::Duration::from_secs(2), run_events.recv())
+            .await
+            .expect("timed out waiting for lifecycle event")
+            .unwrap();
+        assert_eq!(event.task_id, task.id);
+        assert_eq!(event.execution_id.to_string(), payload["sessionId"]);
+        assert_eq!(event.status, ExecutionStatus::Completed);
+    }
+
+    #[tokio::test]
+    async fn gateway_run_duration_follows_the_executor_clock() {
+        use chrono::TimeZone;
+
+        let (mut state, temp_dir) = build_state().await;
+        let start = chrono::Utc.with_ymd_and_hms(2024, 6, 1, 9, 0, 0).unwrap();
+        let clock = agent_runner::MockClock::new(start);
+        let config = agent_runner::ExecutorConfig {
+            data_dir: temp_dir.path().to_path_buf(),
+            clock: Arc::new(clock.clone()),
+            ..agent_runner::ExecutorConfig::default()
+        };
+        state.set_executor(agent_runner::TaskExecutor::new_with_dependencies(
+            config,
+            Arc::new(StubWorktreeManager),
+            Arc::new(HangingWorker::default()),
+        ));
+        let (_layer, io) = socketioxide::SocketIo::new_layer();
+        state.set_socket_io(io).await;
+        let mut run_events = state.subscribe_run_events();
+
+        let project = state
+            .project_store()
+            .register(
+                Uuid::new_v4(),
+                CreateProjectRequest {
+                    name: "clock-project".to_string(),
+                    local_path: "/tmp/clock-project".to_string(),
+                    remote_url: None,
+                    default_branch: None,
+                    worktree_dir: None,
+                },
+            )
+            .await
+            .unwrap();
+        let task = state
+            .task_store()
+            .create(Task::new("Timed".to_string()).with_project_id(project.id))
+            .await
+            .unwrap();
+
+        let host_id = project.gateway_id.to_string();
+        let (tx, mut rx) = tokio::sync::mpsc::channel(10);
+        state
+            .gateway_manager()
+            .register_host(
+                host_id.clone(),
+                HostCapabilities {
+                    name: "Timed host".to_string(),
+                    agents: vec!["opencode".to_string()],
+                    max_concurrent: 2,
+                    cwd: "/tmp".to_string(),
+                    labels: HashMap::new(),
+                    agent_versions: HashMap::new(),
+                },
+                tx,
+            )
+            .await;
+
+        let response = router()
+            .with_state(state.clone())
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/tasks/{}/execute", task.id))
+                    .header("Content-Type", "application/json")
+                    .body(execution_body())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        assert!(rx.recv().await.is_some());
+
+        // Let the event forwarder subscribe before the gateway reports completion
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        clock.advance(std::time::Duration::from_secs(95));
+        state
+            .gateway_manager()
+            .handle_task_completed(
+                &host_id,
+                &task.id.to_string(),
+                crate::gateway::protocol::TaskResult {
+                    success: true,
+                    exit_code: Some(0),
+                    output: None,
+                    summary: None,
+                    duration: None,
+                    files_changed: vec![],
+                },
+            )
+            .await;
+
+        let event = tokio::time::timeout(std::time::Duration::from_secs(2), run_events.recv())
+            .await
+            .expect("timed out waiting for lifecycle event")
+            .unwrap();
+        let run = state
+            .executor()
+            .run_store()
+            .load_run(task.id, event.execution_id)
+            .unwrap();
+        assert_eq!(run.created_at, start);
+        assert_eq!(run.started_at, Some(start));
+        assert_eq!(run.ended_at, Some(start + chrono::Duration::seconds(95)));
+        assert_eq!(run.duration_ms, Some(95_000));
+    }
+
+    #[tokio::test]
+    async fn gateway_completion_summary_is_kept_apart_from_output_message() {
+        let (state, _temp_dir) = build_state().await;
+        let (_layer, io) = socketioxide::SocketIo::new_layer();
+        state.set_socket_io(io).await;
+        let mut run_events = state.subscribe_run_events();
+
+        let project = state
+            .project_store()
+            .register(
+                Uuid::new_v4(),
+                CreateProjectRequest {
+                    name: "summary-project".to_string(),
+                    local_path: "/tmp/summary-project".to_string(),
+                    remote_url: None,
+                    default_branch: None,
+                    worktree_dir: None,
+                },
+            )
+            .await
+            .unwrap();
+        let task = state
+            .task_store()
+            .create(Task::new("Summarized".to_string()).with_project_id(project.id))
+            .await
+            .unwrap();
+
+        let host_id = project.gateway_id.to_string();
+        let (tx, mut rx) = tokio::sync::mpsc::channel(10);
+        state
+            .gateway_manager()
+            .register_host(
+                host_id.clone(),
+                HostCapabilities {
+                    name: "Summary host".to_string(),
+                    agents: vec!["opencode".to_string()],
+                    max_concurrent: 2,
+                    cwd: "/tmp".to_string(),
+                    labels: HashMap::new(),
+                    agent_versions: HashMap::new(),
+                },
+                tx,
+            )
+            .await;
+
+        let response = router()
+            .with_state(state.clone())
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/tasks/{}/execute", task.id))
+                    .header("Content-Type", "application/json")
+                    .body(execution_body())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        assert!(rx.recv().await.is_some());
+
+        // Let the event forwarder subscribe before the gateway reports progress
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        let manager = state.gateway_manager();
+        for (i, content) in ["Reading the session code. ", "Raised the timeout to 30 minutes."]
+            .into_iter()
+            .enumerate()
+        {
+            manager
+                .handle_task_event(
+                    &host_id,
+                    &task.id.to_string(),
+                    GatewayAgentEvent {
+                        event_type: GatewayAgentEventType::Message,
+                        content: Some(content.to_string()),
+                        data: Value::Null,
+                        timestamp: i as u64,
+                    },
+                )
+                .await;
+        }
+        manager
+            .handle_task_completed(
+                &host_id,
+                &task.id.to_string(),
+                crate::gateway::protocol::TaskResult {
+                    success: true,
+                    exit_code: Some(0),
+                    output: None,
+                    summary: Some("  Fixed the early session expiry.\n".to_string()),
+                    duration: None,
+                    files_changed: vec![],
+                },
+            )
+            .await;
+
+        let event = tokio::time::timeout(std::time::Duration::from_secs(2), run_events.recv())
+            .await
+            .expect("timed out waiting for lifecycle event")
+            .unwrap();
+        let run_store = state.executor().run_store();
+        let run = run_store.load_run(task.id, event.execution_id).unwrap();
+        assert_eq!(run.summary.as_deref(), Some("Fixed the early session expiry."));
+
+        let messages = run_store.load_messages(task.id, event.execution_id).unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(
+            messages[0].content,
+            "Reading the session code. Raised the timeout to 30 minutes."
+        );
+    }
+
+    #[tokio::test]
+    async fn lagging_event_forwarder_recovers_missed_events() {
+        let (state, _temp_dir) = build_state_with_manager(|manager| manager.with_event_buffer(4)).await;
+        let (_layer, io) = socketioxide::SocketIo::new_layer();
+        state.set_socket_io(io).await;
+        let mut run_events = state.subscribe_run_events();
+
+        let project = state
+            .project_store()
+            .register(
+                Uuid::new_v4(),
+                CreateProjectRequest {
+                    name: "chatty-host-project".to_string(),
+                    local_path: "/tmp/chatty-host-project".to_string(),
+                    remote_url: None,
+                    default_branch: None,
+                    worktree_dir: None,
+                },
+            )
+            .await
+            .unwrap();
+        let task = state
+            .task_store()
+            .create(Task::new("Chatty host".to_string()).with_project_id(project.id))
+            .await
+            .unwrap();
+
+        let host_id = project.gateway_id.to_string();
+        let (tx, mut rx) = tokio::sync::mpsc::channel(10);
+        state
+            .gateway_manager()
+            .register_host(
+                host_id.clone(),
+                HostCapabilities {
+                    name: "Chatty host".to_string(),
+                    agents: vec!["opencode".to_string()],
+                    max_concurrent: 2,
+                    cwd: "/tmp".to_string(),
+                    labels: HashMap::new(),
+                    agent_versions: HashMap::new(),
+                },
+                tx,
+            )
+            .await;
+
+        // The host sends far more events than the buffer holds before the
+        // forwarder gets to run
+        let host_state = state.clone();
+        let host_task_id = task.id.to_string();
+        let host = tokio::spawn(async move {
+            assert!(rx.recv().await.is_some());
+            let manager = host_state.gateway_manager();
+            for i in 0..40 {
+                manager
+                    .handle_task_event(
+                        &host_id,
+                        &host_task_id,
+                        GatewayAgentEvent {
+                            event_type: GatewayAgentEventType::FileChange,
+                            content: None,
+                            data: serde_json::json!({ "path": format!("src/file_{}.rs", i) }),
+                            timestamp: i,
+                        },
+                    )
+                    .await;
+            }
+            manager
+                .handle_task_completed(
+                    &host_id,
+                    &host_task_id,
+                    crate::gateway::protocol::TaskResult {
+                        success: true,
+                        exit_code: Some(0),
+                        output: None,
+                        summary: None,
+                        duration: None,
+                        files_changed: vec![],
+                    },
+                )
+                .await;
+        });
+
+        let response = router()
+            .with_state(state.clone())
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/tasks/{}/execute", task.id))
+                    .header("Content-Type", "application/json")
+                    .body(execution_body())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        host.await.unwrap();
+
+        let event = tokio::time::timeout(std::time::Duration::from_secs(2), run_events.recv())
+            .await
+            .expect("timed out waiting for lifecycle event")
+            .unwrap();
+        let paths: Vec<String> = state
+            .executor()
+            .run_store()
+            .load_events(task.id, event.execution_id)
+            .unwrap()
+            .into_iter()
+            .filter_map(|e| match e.event {
+                agent_runner::ExecutionEventType::AgentEvent {
+                    event: AgentEvent::FileChange { path, .. },
+                } => Some(path),
+                _ => None,
+            })
+            .collect();
+        let expected: Vec<String> = (0..40).map(|i| format!("src/file_{}.rs", i)).collect();
+        assert_eq!(paths, expected);
+        let run = state
+            .executor()
+            .run_store()
+            .load_run(task.id, event.execution_id)
+            .unwrap();
+        assert_eq!(run.changed_files, Some(40));
+    }
+
+    #[tokio::test]
+    async fn gateway_events_emitted_right_after_dispatch_are_persisted() {
+        let (state, _temp_dir) = build_state().await;
+        let (_layer, io) = socketioxide::SocketIo::new_layer();
+        state.set_socket_io(io).await;
+        let mut run_events = state.subscribe_run_events();
+
+        let project = state
+            .project_store()
+            .register(
+                Uuid::new_v4(),
+                CreateProjectRequest {
+                    name: "fast-host-project".to_string(),
+                    local_path: "/tmp/fast-host-project".to_string(),
+                    remote_url: None,
+                    default_branch: None,
+                    worktree_dir: None,
+                },
+            )
+            .await
+            .unwrap();
+        let task = state
+            .task_store()
+            .create(Task::new("Fast host".to_string()).with_project_id(project.id))
+            .await
+            .unwrap();
+
+        let host_id = project.gateway_id.to_string();
+        let (tx, mut rx) = tokio::sync::mpsc::channel(10);
+        state
+            .gateway_manager()
+            .register_host(
+                host_id.clone(),
+                HostCapabilities {
+                    name: "Fast host".to_string(),
+                    agents: vec!["opencode".to_string()],
+                    max_concurrent: 2,
+                    cwd: "/tmp".to_string(),
+                    labels: HashMap::new(),
+                    agent_versions: HashMap::new(),
+                },
+                tx,
+            )
+            .await;
+
+        // The host reports output as soon as it receives the task, before
+        // the handler has returned
+        let host_state = state.clone();
+        let host_task_id = task.id.to_string();
+        let host = tokio::spawn(async move {
+            assert!(rx.recv().await.is_some());
+            let manager = host_state.gateway_manager();
+            manager
+                .handle_task_event(
+                    &host_id,
+                    &host_task_id,
+                    GatewayAgentEvent {
+                        event_type: GatewayAgentEventType::FileChange,
+                        content: None,
+                        data: serde_json::json!({ "path": "src/early.rs", "changeType": "created" }),
+                        timestamp: 1,
+                    },
+                )
+                .await;
+            manager
+                .handle_task_completed(
+                    &host_id,
+                    &host_task_id,
+                    crate::gateway::protocol::TaskResult {
+                        success: true,
+                        exit_code: Some(0),
+                        output: None,
+                        summary: None,
+                        duration: None,
+                        files_changed: vec![],
+                    },
+                )
+                .await;
+        });
+
+        let response = router()
+            .with_state(state.clone())
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/tasks/{}/execute", task.id))
+                    .header("Content-Type", "application/json")
+                    .body(execution_body())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        host.await.unwrap();
+
+        let event = tokio::time::timeout(std::time::Duration::from_secs(2), run_events.recv())
+            .await
+            .expect("timed out waiting for lifecycle event")
+            .unwrap();
+        let events = state
+            .executor()
+            .run_store()
+            .load_events(task.id, event.execution_id)
+            .unwrap();
+        assert!(events.iter().any(|e| matches!(
+            &e.event,
+            agent_runner::ExecutionEventType::AgentEvent {
+                event: AgentEvent::FileChange { path, .. },
+            } if path == "src/early.rs"
+        )));
+    }
+
+    #[tokio::test]
+    async fn start_execution_reports_clone_failure_distinctly() {
+        let (state, temp_dir) = build_state().await;
+        let project = state
+            .project_store()
+            .register(
+                Uuid::new_v4(),
+                CreateProjectRequest {
+                    name: "clone-project".to_string(),
+                    local_path: temp_dir.path().join("checkout").to_string_lossy().to_string(),
+                    remote_url: Some(temp_dir.path().join("missing.git").to_string_lossy().to_string()),
+                    default_branch: None,
+                    worktree_dir: None,
+                },
+            )
+            .await
+            .unwrap();
+        state
+            .project_store()
+            .update(project.clone().with_clone_if_missing(true))
+            .await
+            .unwrap();
+        let task = state
+            .task_store()
+            .create(Task::new("Clone me".to_string()).with_project_id(project.id))
+            .await
+            .unwrap();
+
+        let response = router()
+            .with_state(state)
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/tasks/{}/execute", task.id))
+                    .header("Content-Type", "application/json")
+                    .body(execution_body())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let payload: Value = serde_json::from_slice(&body).unwrap();
+        assert!(payload["error"].as_str().unwrap().starts_with("Clone failed"));
+    }
+
+    #[tokio::test]
+    async fn resolve_base_branch_follows_fallback_chain() {
+        let repo = TempDir::new().unwrap();
+        for args in [&["init", "-q"][..], &["symbolic-ref", "HEAD", "refs/heads/trunk"]] {
+            let status = std::process::Command::new("git")
+                .args(args)
+                .current_dir(repo.path())
+                .status()
+                .unwrap();
+            assert!(status.success());
+        }
+
+        let mut project = Project::new("chain", repo.path().to_string_lossy(), Uuid::new_v4())
+            .with_default_branch("develop");
+        let mut task = Task::new("Chain").with_base_branch("feature");
+
+        assert_eq!(resolve_base_branch(Some("hotfix"), &task, &project).await, "hotfix");
+        assert_eq!(resolve_base_branch(None, &task, &project).await, "feature");
+
+        task.base_branch = None;
+        assert_eq!(resolve_base_branch(Some(" "), &task, &project).await, "develop");
+
+        project.default_branch = String::new();
+        assert_eq!(resolve_base_branch(None, &task, &project).await, "trunk");
+    }
+
+    #[tokio::test]
+    async fn handoff_creates_child_run_linked_to_parent() {
+        let (state, _temp_dir) = build_state().await;
+        let project = state
+            .project_store()
+            .register(
+                Uuid::new_v4(),
+                CreateProjectRequest {
+                    name: "handoff-project".to_string(),
+                    local_path: "/tmp/handoff-project".to_string(),
+                    remote_url: None,
+                    default_branch: None,
+                    worktree_dir: None,
+                },
+            )
+            .await
+            .unwrap();
+        let task = state
+            .task_store()
+            .create(Task::new("Handoff".to_string()).with_project_id(project.id))
+            .await
+            .unwrap();
+
+        let (tx, mut rx) = tokio::sync::mpsc::channel(10);
+        state
+            .gateway_manager()
+            .register_host(
+                project.gateway_id.to_string(),
+                HostCapabilities {
+                    name: "Bound host".to_string(),
+                    agents: vec!["opencode".to_string()],
+                    max_concurrent: 2,
+                    cwd: "/tmp".to_string(),
+                    labels: HashMap::new(),
+                    agent_versions: HashMap::new(),
+                },
+                tx,
+            )
+            .await;
+
+        let mut parent = Run::new(
+            task.id,
+            AgentType::OpenCode,
+            "Implement the feature".to_string(),
+            "main".to_string(),
+        );
+        parent.metadata.agent_role = Some("coder".to_string());
+        parent.mark_started();
+        parent.mark_completed(0, Some("Added the endpoint".to_string()));
+        state.executor().run_store().save_run(&parent).unwrap();
+
+        let app = router().with_state(state.clone());
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/v1/executions/{}/handoff", parent.id))
+                    .header("Content-Type", "application/json")
+                    .body(Body::from(json!({ "agentRole": "reviewer" }).to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let payload: Value = serde_json::from_slice(&body).unwrap();
+        let child_id = Uuid::parse_str(payload["sessionId"].as_str().unwrap()).unwrap();
+
+        let child = state.executor().run_store().load_run(task.id, child_id).unwrap();
+        assert_eq!(child.metadata.agent_role.as_deref(), Some("reviewer"));
+        assert_eq!(child.metadata.parent_execution_id, Some(parent.id));
+        assert_eq!(child.metadata.handoff_id, Some(parent.id));
+
+        match rx.recv().await.unwrap() {
+            ServerToGatewayMessage::TaskExecute { task } => {
+                assert!(task.prompt.contains("Added the endpoint"));
+            }
+            _ => panic!("expected task dispatch message"),
+        }
+    }
+
+    #[tokio::test]
+    async fn handoff_from_active_execution_returns_conflict() {
+        let (state, _temp_dir) = build_state().await;
+        let mut parent = Run::new(
+            Uuid::new_v4(),
+            AgentType::OpenCode,
+            "Still running".to_string(),
+            "main".to_string(),
+        );
+        parent.mark_started();
+        assert_eq!(parent.status, ExecutionStatus::Running);
+        state.executor().run_store().save_run(&parent).unwrap();
+
+        let app = router().with_state(state);
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/v1/executions/{}/handoff", parent.id))
+                    .header("Content-Type", "application/json")
+                    .body(Body::from(json!({ "agentRole": "reviewer" }).to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn handoff_while_task_has_another_active_run_returns_conflict() {
+        let (state, _temp_dir) = build_state().await;
+        let task_id = Uuid::new_v4();
+        let mut parent = Run::new(task_id, AgentType::OpenCode, "Done".to_string(), "main".to_string());
+        parent.mark_started();
+        parent.mark_completed(0, None);
+        state.executor().run_store().save_run(&parent).unwrap();
+        let mut sibling = Run::new(task_id, AgentType::OpenCode, "Running".to_string(), "main".to_string());
+        sibling.mark_started();
+        state.executor().run_store().save_run(&sibling).unwrap();
+
+        let response = router()
+            .with_state(state.clone())
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/v1/executions/{}/handoff", parent.id))
+                    .header("Content-Type", "application/json")
+                    .body(Body::from(json!({ "agentRole": "reviewer" }).to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let payload: Value = serde_json::from_slice(&body).unwrap();
+        assert!(payload["error"].as_str().unwrap().contains(&sibling.id.to_string()));
+        assert_eq!(state.executor().list_runs(task_id).unwrap().len(), 2);
+    }
+
+    type StubFuture<'a, T> =
+        std::pin::Pin<Box<dyn std::future::Future<Output = agent_runner::Result<T>> + Send + 'a>>;
+
+    struct StubWorktreeManager;
+
+    impl agent_runner::WorktreeManagerApi for StubWorktreeManager {
+        fn create(
+            &self,
+            task_id: String,
+            _base_branch: String,
+        ) -> StubFuture<'_, git_worktree::Worktree> {
+            Box::pin(async move {
+                Ok(git_worktree::Worktree {
+                    path: std::path::PathBuf::from("stub-worktree"),
+                    branch: format!("task/{}", task_id),
+                    head: "stub-head".to_string(),
+                    status: git_worktree::WorktreeStatus::Active,
+                    is_main: false,
+                })
+            })
+        }
+
+        fn remove(
+            &self,
+            _path: std::path::PathBuf,
+            _force: bool,
+            _delete_branches: bool,
+        ) -> StubFuture<'_, ()> {
+            Box::pin(async { Ok(()) })
+        }
+
+        fn check_mergeable(
+            &self,
+            _branch: String,
+            _into_branch: String,
+        ) -> StubFuture<'_, git_worktree::MergeStatus> {
+            Box::pin(async {
+                Ok(git_worktree::MergeStatus {
+                    mergeable: true,
+                    conflicts: Vec::new(),
+                })
+            })
+        }
+    }
+
+    /// Worker whose executions never finish on their own
+    #[derive(Default)]
+    struct HangingWorker {
+        stopped: Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    impl agent_runner::WorkerClientApi for HangingWorker {
+        fn execute(
+            &self,
+            _task_id: String,
+            _prompt: String,
+            _cwd: std::path::PathBuf,
+            _agent_type: AgentType,
+            _args: Vec<String>,
+            _event_tx: tokio::sync::mpsc::Sender<agent_runner::AgentEvent>,
+        ) -> StubFuture<'_, ()> {
+            Box::pin(std::future::pending())
+        }
+
+        fn stop(&self, task_id: String) -> StubFuture<'_, ()> {
+            self.stopped.lock().unwrap().push(task_id);
+            Box::pin(async { Ok(()) })
+        }
+
+        fn send_input(
+            &self,
+            _task_id: String,
+            _content: String,
+        ) -> StubFuture<'_, ()> {
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    #[tokio::test]
+    async fn v1_stop_cancels_local_session() {
+        let (mut state, temp_dir) = build_state().await;
+        let worker = HangingWorker::default();
+        let stopped = Arc::clone(&worker.stopped);
+        let config = agent_runner::ExecutorConfig {
+            data_dir: temp_dir.path().to_path_buf(),
+            ..agent_runner::ExecutorConfig::default()
+        };
+        state.set_executor(agent_runner::TaskExecutor::new_with_dependencies(
+            config,
+            Arc::new(StubWorktreeManager),
+            Arc::new(worker),
+        ));
+
+        let task_id = Uuid::new_v4();
+        let (session_id, _events) = state
+            .executor()
+            .execute(agent_runner::ExecuteRequest {
+                task_id,
+                agent_type: "opencode".to_string(),
+                base_branch: "main".to_string(),
+                prompt: "Run locally".to_string(),
+                worktree_strategy: vk_core::project::WorktreeStrategy::PerRun,
+            })
+            .await
+            .unwrap();
+
+        let app = router().with_state(state.clone());
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/v1/executions/{}/stop", session_id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let payload: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(payload["sessionId"], session_id.to_string());
+        assert_eq!(payload["status"], "cancelled");
+
+        let session = state.executor().get_session(session_id).await.unwrap();
+        let session_state = session.read().await.state().await;
+        assert!(matches!(session_state, agent_runner::SessionState::Cancelled { .. }));
+        assert_eq!(*stopped.lock().unwrap(), vec![task_id.to_string()]);
+    }
+
+    #[tokio::test]
+    async fn get_execution_returns_full_prompt() {
+        let (state, _temp_dir) = build_state().await;
+        let prompt = format!("Fix the flaky test\n\n{}", "context ".repeat(40));
+        let mut run = Run::new(
+            Uuid::new_v4(),
+            AgentType::OpenCode,
+            prompt.clone(),
+            "main".to_string(),
+        );
+        run.metadata.agent_role = Some("coder".to_string());
+        state.executor().run_store().save_run(&run).unwrap();
+
+        let app = router().with_state(state);
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/v1/executions/{}", run.id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let payload: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(payload["prompt"], prompt);
+        assert_eq!(payload["taskId"], run.task_id.to_string());
+        assert_eq!(payload["agentRole"], "coder");
     }
 }
@@ -0,0 +1,302 @@
+//! Kanban board API endpoints
+//!
+//! Board changes normally travel over Socket.IO; these routes cover actions
+//! that also need a plain HTTP entry point.
+
+use axum::{
+    extract::{Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+
+use vk_core::kanban::{KanbanBoardState, KanbanMismatch, KanbanReconcilePolicy};
+
+use crate::state::AppState;
+
+#[derive(Debug, Serialize)]
+pub struct ErrorResponse {
+    pub error: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReconcileQuery {
+    /// Side that wins a mismatch; defaults to the configured policy
+    #[serde(default)]
+    pub policy: Option<KanbanReconcilePolicy>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReconcileResponse {
+    pub policy: KanbanReconcilePolicy,
+    pub mismatches: Vec<KanbanMismatch>,
+}
+
+/// Strong ETag for a board version
+fn board_etag(version: u64) -> String {
+    format!("\"{}\"", version)
+}
+
+/// Whether an `If-None-Match` header matches `etag`
+fn if_none_match(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|tag| tag.trim())
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+}
+
+/// GET /api/kanban - Read-only snapshot of the board
+///
+/// Carries an `ETag` of the board version and answers `304 Not Modified`
+/// when `If-None-Match` names the current version.
+async fn get_board(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    let kanban_store = state.kanban_store();
+    kanban_store.sync_from_task_store().await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+    })?;
+
+    let snapshot = kanban_store.snapshot().await;
+    let etag = board_etag(snapshot.version);
+    let mut response = if if_none_match(&headers, &etag) {
+        StatusCode::NOT_MODIFIED.into_response()
+    } else {
+        Json(snapshot.state).into_response()
+    };
+    response.headers_mut().insert(
+        header::ETAG,
+        HeaderValue::from_str(&etag).expect("version ETag is a valid header value"),
+    );
+    Ok(response)
+}
+
+/// POST /api/kanban/undo - Undo the last user move on the board
+///
+/// Broadcasts `kanban:sync` with the restored board. Answers `409 Conflict`
+/// when the move back is not an allowed transition.
+async fn undo_last_move(
+    State(state): State<AppState>,
+) -> Result<Json<KanbanBoardState>, (StatusCode, Json<ErrorResponse>)> {
+    let kanban_store = state.kanban_store();
+    let undone = kanban_store.undo_last().await.map_err(|e| {
+        let status = match e {
+            vk_core::Error::InvalidInput(_) => StatusCode::CONFLICT,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (
+            status,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+    })?;
+
+    let Some(undone) = undone else {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(ErrorResponse {
+                error: "Nothing to undo".to_string(),
+            }),
+        ));
+    };
+    tracing::info!(
+        "Undid move of kanban task {} back to {:?}",
+        undone.task_id,
+        undone.from_status
+    );
+
+    state.request_kanban_sync();
+
+    Ok(Json(kanban_store.get_state().await))
+}
+
+/// POST /api/kanban/reconcile - Resolve cards whose column disagrees with their task
+///
+/// `?policy=status` moves cards to their task's column, `?policy=column` sets
+/// task statuses from the cards. Reports the mismatches that were resolved.
+async fn reconcile_board(
+    State(state): State<AppState>,
+    Query(query): Query<ReconcileQuery>,
+) -> Result<Json<ReconcileResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let kanban_store = state.kanban_store();
+    let policy = query.policy.unwrap_or_else(|| kanban_store.reconcile_policy());
+    let mismatches = kanban_store.reconcile_with_tasks(policy).await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+    })?;
+
+    if !mismatches.is_empty() {
+        tracing::info!("Reconciled {} kanban cards by {:?}", mismatches.len(), policy);
+        state.request_kanban_sync();
+    }
+
+    Ok(Json(ReconcileResponse { policy, mismatches }))
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/api/kanban", get(get_board))
+        .route("/api/kanban/undo", post(undo_last_move))
+        .route("/api/kanban/reconcile", post(reconcile_board))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    use axum::{
+        body::{to_bytes, Body},
+        http::Request,
+    };
+    use serde_json::Value;
+    use tempfile::TempDir;
+    use tower::ServiceExt;
+    use vk_core::kanban::{KanbanStore, KanbanTaskStatus};
+    use vk_core::task::{FileTaskStore, Task, TaskRepository, TaskStatus};
+
+    use crate::gateway::GatewayManager;
+
+    async fn build_state() -> (AppState, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path().to_path_buf();
+        let task_store = Arc::new(FileTaskStore::new(data_dir.join("tasks.json")).await.unwrap());
+        let kanban_store = Arc::new(
+            KanbanStore::with_task_store(data_dir.join("kanban.json"), Arc::clone(&task_store))
+                .await
+                .unwrap(),
+        );
+        let gateway_manager = Arc::new(GatewayManager::with_stores(
+            Arc::clone(&task_store),
+            Arc::clone(&kanban_store),
+        ));
+        let state = AppState::with_stores(data_dir, task_store, kanban_store, gateway_manager)
+            .await
+            .unwrap();
+        (state, temp_dir)
+    }
+
+    async fn post_undo(state: &AppState) -> (StatusCode, Value) {
+        let response = router()
+            .with_state(state.clone())
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/kanban/undo")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn undo_route_restores_last_move() {
+        let (state, _temp_dir) = build_state().await;
+        let kanban_store = state.kanban_store();
+        let task = kanban_store.create_task("Undo me", None).await.unwrap();
+        kanban_store
+            .move_task_checked(&task.id, KanbanTaskStatus::Doing, None, false)
+            .await
+            .unwrap();
+
+        let (status, body) = post_undo(&state).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["tasks"][&task.id]["status"], "todo");
+
+        let (status, _) = post_undo(&state).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn reconcile_route_moves_drifted_cards_back() {
+        let (state, _temp_dir) = build_state().await;
+        let mut task = Task::new("Drifted");
+        task.status = TaskStatus::InProgress;
+        let task = state.task_store().create(task).await.unwrap();
+        let kanban_store = state.kanban_store();
+        kanban_store.sync_from_task_store().await.unwrap();
+        let card_id = task.id.to_string();
+        kanban_store.move_task(&card_id, KanbanTaskStatus::Done, None).await.unwrap();
+
+        let response = router()
+            .with_state(state.clone())
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/kanban/reconcile?policy=status")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["policy"], "status");
+        assert_eq!(body["mismatches"][0]["taskId"], card_id);
+        assert_eq!(body["mismatches"][0]["column"], "done");
+        assert_eq!(
+            kanban_store.get_task(&card_id).await.unwrap().status,
+            KanbanTaskStatus::Doing
+        );
+    }
+
+    #[tokio::test]
+    async fn board_snapshot_honors_if_none_match() {
+        let (state, _temp_dir) = build_state().await;
+        let task = state.kanban_store().create_task("Cache me", None).await.unwrap();
+
+        let get = |etag: Option<String>| {
+            let mut request = Request::builder().uri("/api/kanban");
+            if let Some(etag) = etag {
+                request = request.header("if-none-match", etag);
+            }
+            router()
+                .with_state(state.clone())
+                .oneshot(request.body(Body::empty()).unwrap())
+        };
+
+        let first = get(None).await.unwrap();
+        assert_eq!(first.status(), StatusCode::OK);
+        let etag = first.headers()["etag"].to_str().unwrap().to_string();
+        let body = to_bytes(first.into_body(), usize::MAX).await.unwrap();
+        let board: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(board["tasks"][&task.id]["title"], "Cache me");
+
+        let unchanged = get(Some(etag.clone())).await.unwrap();
+        assert_eq!(unchanged.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(unchanged.headers()["etag"], etag.as_str());
+
+        state
+            .kanban_store()
+            .move_task(&task.id, KanbanTaskStatus::Doing, None)
+            .await
+            .unwrap();
+        let moved = get(Some(etag.clone())).await.unwrap();
+        assert_eq!(moved.status(), StatusCode::OK);
+        assert_ne!(moved.headers()["etag"], etag.as_str());
+        let body = to_bytes(moved.into_body(), usize::MAX).await.unwrap();
+        let board: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(board["tasks"][&task.id]["status"], "doing");
+    }
+}
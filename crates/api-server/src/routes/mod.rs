@@ -1,7 +1,12 @@
 //! Route handlers
 
+pub mod agent;
 pub mod executor;
 pub mod gateway;
 pub mod health;
+pub mod kanban;
+pub mod ops;
+pub mod openapi;
 pub mod project;
 pub mod task;
+pub mod template;
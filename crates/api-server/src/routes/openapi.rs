@@ -0,0 +1,227 @@
+//! OpenAPI document for the HTTP API
+//!
+//! Covers the execution, task and project routes. Schemas are generated from
+//! the serde request/response types with `schemars`, so the document follows
+//! the types as they change.
+
+use axum::{http::StatusCode, routing::get, Json, Router};
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::JsonSchema;
+use serde_json::{json, Map, Value};
+
+use vk_core::project::ProjectSummary;
+
+use super::executor::{
+    ErrorResponse, ExecutionDetailResponse, ExecutionResponse, HandoffRequest, StartExecutionRequest,
+    ValidateExecutionRequest, ValidateExecutionResponse,
+};
+use super::project::{ProjectDetailResponse, UpdateProjectRequest};
+use super::task::{CreateTaskRequest, MoveTaskRequest, TaskResponse, UpdateTaskRequest};
+use crate::state::AppState;
+
+/// Collects operations and the schemas they reference
+struct ApiDoc {
+    gen: SchemaGenerator,
+    paths: Map<String, Value>,
+}
+
+impl ApiDoc {
+    fn new() -> Self {
+        Self {
+            gen: SchemaSettings::openapi3().into_generator(),
+            paths: Map::new(),
+        }
+    }
+
+    /// Register an operation; `request` and `response` are schema references
+    fn operation(
+        &mut self,
+        method: &str,
+        path: &str,
+        summary: &str,
+        request: Option<Value>,
+        response: (StatusCode, Option<Value>),
+    ) -> &mut Value {
+        let error = json!(self.gen.subschema_for::<ErrorResponse>());
+        let (status, schema) = response;
+        let mut success = json!({ "description": status.canonical_reason().unwrap_or("Success") });
+        if let Some(schema) = schema {
+            success["content"] = json!({ "application/json": { "schema": schema } });
+        }
+
+        let mut operation = json!({
+            "summary": summary,
+            "responses": {
+                status.as_str(): success,
+                "default": {
+                    "description": "Error",
+                    "content": { "application/json": { "schema": error } },
+                },
+            },
+        });
+        let parameters = path_parameters(path);
+        if !parameters.is_empty() {
+            operation["parameters"] = Value::Array(parameters);
+        }
+        if let Some(schema) = request {
+            operation["requestBody"] = json!({
+                "required": true,
+                "content": { "application/json": { "schema": schema } },
+            });
+        }
+
+        let item = self
+            .paths
+            .entry(path.to_string())
+            .or_insert_with(|| json!({}));
+        item[method] = operation;
+        &mut item[method]
+    }
+
+    fn schema<T: JsonSchema>(&mut self) -> Option<Value> {
+        Some(json!(self.gen.subschema_for::<T>()))
+    }
+
+    fn into_document(mut self) -> Value {
+        json!({
+            "openapi": "3.0.3",
+            "info": {
+                "title": "Vibe Kanban API",
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+            "paths": self.paths,
+            "components": { "schemas": self.gen.take_definitions() },
+        })
+    }
+}
+
+/// `{name}` segments of a route path as OpenAPI path parameters
+fn path_parameters(path: &str) -> Vec<Value> {
+    path.split('/')
+        .filter_map(|segment| segment.strip_prefix('{')?.strip_suffix('}'))
+        .map(|name| {
+            json!({
+                "name": name,
+                "in": "path",
+                "required": true,
+                "schema": { "type": "string" },
+            })
+        })
+        .collect()
+}
+
+/// Build the OpenAPI document
+pub fn openapi_document() -> Value {
+    let mut doc = ApiDoc::new();
+
+    // Executions
+    let request = doc.schema::<StartExecutionRequest>();
+    let response = doc.schema::<ExecutionResponse>();
+    doc.operation(
+        "post",
+        "/api/tasks/{id}/execute",
+        "Start executing a task (deprecated)",
+        request,
+        (StatusCode::ACCEPTED, response),
+    )["deprecated"] = json!(true);
+    let request = doc.schema::<ValidateExecutionRequest>();
+    let response = doc.schema::<ValidateExecutionResponse>();
+    doc.operation(
+        "post",
+        "/api/v1/executions/validate",
+        "Check an execution without dispatching it",
+        request,
+        (StatusCode::OK, response),
+    );
+    let response = doc.schema::<ExecutionDetailResponse>();
+    doc.operation(
+        "get",
+        "/api/v1/executions/{id}",
+        "Get an execution, including the full dispatched prompt",
+        None,
+        (StatusCode::OK, response),
+    );
+    let request = doc.schema::<HandoffRequest>();
+    let response = doc.schema::<ExecutionResponse>();
+    doc.operation(
+        "post",
+        "/api/v1/executions/{id}/handoff",
+        "Hand a finished execution over to another agent role",
+        request,
+        (StatusCode::ACCEPTED, response),
+    );
+    let response = doc.schema::<ExecutionResponse>();
+    doc.operation(
+        "post",
+        "/api/v1/executions/{id}/stop",
+        "Stop a running execution, locally or on its gateway host",
+        None,
+        (StatusCode::OK, response),
+    );
+
+    // Tasks
+    let response = doc.schema::<Vec<TaskResponse>>();
+    doc.operation("get", "/api/v1/tasks", "List tasks", None, (StatusCode::OK, response));
+    let request = doc.schema::<CreateTaskRequest>();
+    let response = doc.schema::<TaskResponse>();
+    doc.operation("post", "/api/v1/tasks", "Create a task", request, (StatusCode::CREATED, response));
+    let response = doc.schema::<TaskResponse>();
+    doc.operation("get", "/api/v1/tasks/{id}", "Get a task", None, (StatusCode::OK, response));
+    let request = doc.schema::<UpdateTaskRequest>();
+    let response = doc.schema::<TaskResponse>();
+    doc.operation("patch", "/api/v1/tasks/{id}", "Update a task", request, (StatusCode::OK, response));
+    doc.operation("delete", "/api/v1/tasks/{id}", "Delete a task", None, (StatusCode::NO_CONTENT, None));
+    let request = doc.schema::<MoveTaskRequest>();
+    let response = doc.schema::<TaskResponse>();
+    doc.operation(
+        "post",
+        "/api/v1/tasks/{id}/move",
+        "Relink a task to another project on the same gateway",
+        request,
+        (StatusCode::OK, response),
+    );
+
+    // Projects
+    let response = doc.schema::<Vec<ProjectSummary>>();
+    doc.operation("get", "/api/projects", "List projects", None, (StatusCode::OK, response));
+    let response = doc.schema::<ProjectDetailResponse>();
+    doc.operation("get", "/api/projects/{id}", "Get a project", None, (StatusCode::OK, response));
+    let request = doc.schema::<UpdateProjectRequest>();
+    let response = doc.schema::<ProjectDetailResponse>();
+    doc.operation("patch", "/api/projects/{id}", "Update a project", request, (StatusCode::OK, response));
+
+    doc.into_document()
+}
+
+/// GET /api/openapi.json - OpenAPI 3 document for the HTTP API
+async fn get_openapi() -> Json<Value> {
+    Json(openapi_document())
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/api/openapi.json", get(get_openapi))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn document_includes_execution_paths_and_schemas() {
+        let doc = openapi_document();
+
+        assert_eq!(doc["openapi"], "3.0.3");
+        let paths = doc["paths"].as_object().unwrap();
+        assert!(paths.contains_key("/api/v1/executions/validate"));
+        assert!(paths.contains_key("/api/v1/executions/{id}/handoff"));
+        assert_eq!(
+            doc["paths"]["/api/tasks/{id}/execute"]["post"]["requestBody"]["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/CreateExecutionRequest"
+        );
+
+        let schema = &doc["components"]["schemas"]["CreateExecutionRequest"];
+        assert_eq!(schema["required"], json!(["agentType"]));
+        assert!(schema["properties"]["baseBranch"].is_object());
+        assert!(doc["components"]["schemas"]["TaskStatus"].is_object());
+    }
+}
@@ -0,0 +1,263 @@
+//! Operations endpoints: reporting rollups and the dead-letter queue
+
+use std::collections::HashMap;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::{NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use vk_core::task::TaskRepository;
+
+use crate::gateway::dead_letter::DeadLetterStatus;
+use crate::gateway::manager::{DispatchOutcome, RejectedTaskMessage};
+use crate::rollup::{compute_rollup, DailyRollup};
+use crate::state::AppState;
+
+#[derive(Debug, Serialize)]
+pub struct ErrorResponse {
+    pub error: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RollupQuery {
+    /// UTC day, as `YYYY-MM-DD`
+    pub date: NaiveDate,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeadLetterListResponse {
+    pub tasks: Vec<DeadLetterStatus>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeadLetterRetryResponse {
+    pub task_id: String,
+    pub host_id: String,
+    /// Position in the host queue when the host is saturated
+    pub queue_position: Option<usize>,
+}
+
+fn internal_error(error: impl ToString) -> (StatusCode, Json<ErrorResponse>) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(ErrorResponse {
+            error: error.to_string(),
+        }),
+    )
+}
+
+/// GET /api/ops/rollup?date=YYYY-MM-DD - Run metrics for one day, per gateway
+///
+/// Rollups of past days are served from the cache once computed.
+async fn get_rollup(
+    State(state): State<AppState>,
+    Query(query): Query<RollupQuery>,
+) -> Result<Json<DailyRollup>, (StatusCode, Json<ErrorResponse>)> {
+    if let Some(rollup) = state.rollup_cache().load(query.date) {
+        return Ok(Json(rollup));
+    }
+
+    let tasks = state.task_store().list().await.map_err(internal_error)?;
+    let mut gateways: HashMap<Uuid, Option<Uuid>> = HashMap::new();
+    let mut runs = Vec::new();
+    for task in tasks {
+        let gateway_id = match task.project_id {
+            Some(project_id) => match gateways.get(&project_id) {
+                Some(gateway_id) => *gateway_id,
+                None => {
+                    let gateway_id = state.project_store().get(project_id).await.map(|p| p.gateway_id);
+                    gateways.insert(project_id, gateway_id);
+                    gateway_id
+                }
+            },
+            None => None,
+        };
+        let task_runs = state
+            .executor()
+            .run_store()
+            .list_runs(task.id)
+            .map_err(internal_error)?;
+        runs.extend(task_runs.into_iter().map(|run| (gateway_id, run)));
+    }
+
+    let rollup = compute_rollup(query.date, &runs);
+    if query.date < Utc::now().date_naive() {
+        if let Err(e) = state.rollup_cache().store(&rollup) {
+            tracing::warn!("Failed to cache rollup for {}: {}", query.date, e);
+        }
+    }
+    Ok(Json(rollup))
+}
+
+/// GET /api/ops/deadletter - Undeliverable tasks and when they are retried next
+async fn list_dead_letters(State(state): State<AppState>) -> Json<DeadLetterListResponse> {
+    Json(DeadLetterListResponse {
+        tasks: state.gateway_manager().dead_letters().await,
+    })
+}
+
+/// GET /api/ops/gateway/rejected - Task messages hosts sent for tasks not dispatched to them
+async fn list_rejected_task_messages(State(state): State<AppState>) -> Json<Vec<RejectedTaskMessage>> {
+    Json(state.gateway_manager().rejected_task_messages())
+}
+
+/// POST /api/ops/deadletter/{task_id}/retry - Redeliver a dead-lettered task now
+///
+/// Resets the task's backoff. A failed delivery answers `502 Bad Gateway`
+/// and leaves the task dead-lettered with a fresh backoff.
+async fn retry_dead_letter(
+    State(state): State<AppState>,
+    Path(task_id): Path<String>,
+) -> Result<Json<DeadLetterRetryResponse>, (StatusCode, Json<ErrorResponse>)> {
+    match state.gateway_manager().retry_dead_letter(&task_id).await {
+        Some(Ok(outcome)) => {
+            let (host_id, queue_position) = match outcome {
+                DispatchOutcome::Dispatched(host_id) => (host_id, None),
+                DispatchOutcome::Queued { host_id, position } => (host_id, Some(position)),
+            };
+            Ok(Json(DeadLetterRetryResponse {
+                task_id,
+                host_id,
+                queue_position,
+            }))
+        }
+        Some(Err(error)) => Err((StatusCode::BAD_GATEWAY, Json(ErrorResponse { error }))),
+        None => Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Task {} is not dead-lettered", task_id),
+            }),
+        )),
+    }
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/api/ops/rollup", get(get_rollup))
+        .route("/api/ops/deadletter", get(list_dead_letters))
+        .route("/api/ops/deadletter/{task_id}/retry", post(retry_dead_letter))
+        .route("/api/ops/gateway/rejected", get(list_rejected_task_messages))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    use agent_runner::{AgentType, ExecutionStatus, Run};
+    use axum::{
+        body::{to_bytes, Body},
+        http::Request,
+    };
+    use chrono::TimeZone;
+    use serde_json::Value;
+    use tempfile::TempDir;
+    use tower::ServiceExt;
+    use vk_core::kanban::KanbanStore;
+    use vk_core::project::CreateProjectRequest;
+    use vk_core::task::{FileTaskStore, Task};
+
+    use crate::gateway::GatewayManager;
+
+    async fn build_state() -> (AppState, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path().to_path_buf();
+        let task_store = Arc::new(FileTaskStore::new(data_dir.join("tasks.json")).await.unwrap());
+        let kanban_store = Arc::new(
+            KanbanStore::with_task_store(data_dir.join("kanban.json"), Arc::clone(&task_store))
+                .await
+                .unwrap(),
+        );
+        let gateway_manager = Arc::new(GatewayManager::with_stores(
+            Arc::clone(&task_store),
+            Arc::clone(&kanban_store),
+        ));
+        let state = AppState::with_stores(data_dir, task_store, kanban_store, gateway_manager)
+            .await
+            .unwrap();
+        (state, temp_dir)
+    }
+
+    async fn get_rollup_json(state: &AppState, date: &str) -> Value {
+        let response = router()
+            .with_state(state.clone())
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/ops/rollup?date={}", date))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn rollup_aggregates_runs_per_day_and_caches_past_days() {
+        let (state, temp_dir) = build_state().await;
+        let gateway_id = Uuid::new_v4();
+        let project = state
+            .project_store()
+            .register(
+                gateway_id,
+                CreateProjectRequest {
+                    name: "rollup-project".to_string(),
+                    local_path: "/tmp/rollup-project".to_string(),
+                    remote_url: None,
+                    default_branch: None,
+                    worktree_dir: None,
+                },
+            )
+            .await
+            .unwrap();
+        let task = state
+            .task_store()
+            .create(Task::new("Reported task".to_string()).with_project_id(project.id))
+            .await
+            .unwrap();
+
+        let save = |day: u32, status: ExecutionStatus, duration_ms: u64, host: &str| {
+            let mut run = Run::new(task.id, AgentType::OpenCode, "prompt".to_string(), "main".to_string());
+            run.created_at = Utc.with_ymd_and_hms(2024, 5, day, 9, 0, 0).unwrap();
+            run.ended_at = Some(Utc.with_ymd_and_hms(2024, 5, day, 10, 0, 0).unwrap());
+            run.duration_ms = Some(duration_ms);
+            run.status = status;
+            run.metadata.host_id = Some(host.to_string());
+            state.executor().run_store().save_run(&run).unwrap();
+        };
+        save(1, ExecutionStatus::Completed, 1_000, "host-a");
+        save(1, ExecutionStatus::Failed, 3_000, "host-b");
+        save(2, ExecutionStatus::Completed, 5_000, "host-a");
+
+        let day_one = get_rollup_json(&state, "2024-05-01").await;
+        assert_eq!(day_one["date"], "2024-05-01");
+        let gateway = &day_one["gateways"][0];
+        assert_eq!(gateway["gatewayId"], gateway_id.to_string());
+        assert_eq!(gateway["created"], 2);
+        assert_eq!(gateway["completed"], 1);
+        assert_eq!(gateway["failed"], 1);
+        assert_eq!(gateway["avgDurationMs"], 2_000);
+        assert_eq!(gateway["activeHosts"], 2);
+
+        let day_two = get_rollup_json(&state, "2024-05-02").await;
+        let gateway = &day_two["gateways"][0];
+        assert_eq!((gateway["created"].as_u64(), gateway["completed"].as_u64()), (Some(1), Some(1)));
+        assert_eq!(gateway["avgDurationMs"], 5_000);
+        assert_eq!(gateway["activeHosts"], 1);
+
+        // Past days are served from the cache from now on
+        assert!(temp_dir.path().join("rollups/2024-05-01.json").exists());
+        save(1, ExecutionStatus::Completed, 9_000, "host-c");
+        assert_eq!(get_rollup_json(&state, "2024-05-01").await, day_one);
+    }
+}
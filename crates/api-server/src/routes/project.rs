@@ -6,11 +6,18 @@ use axum::{
     routing::get,
     Json, Router,
 };
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
+use crate::extract::JsonBody;
 use crate::state::AppState;
-use vk_core::project::ProjectSummary;
+use vk_core::kanban::{KanbanAutoMovePolicy, KanbanBoardState};
+use vk_core::project::{
+    validate_prompt_template, ProjectEnvVar, ProjectSummary, WorktreeStrategy, MASKED_SECRET,
+};
+use vk_core::task::TaskRepository;
 
 /// List all projects
 async fn list_projects(State(state): State<AppState>) -> Json<Vec<ProjectSummary>> {
@@ -19,16 +26,23 @@ async fn list_projects(State(state): State<AppState>) -> Json<Vec<ProjectSummary
 }
 
 /// Project detail response
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ProjectDetailResponse {
     pub id: String,
     pub name: String,
     pub local_path: String,
     pub remote_url: Option<String>,
+    pub clone_if_missing: bool,
     pub default_branch: String,
     pub gateway_id: String,
     pub worktree_dir: String,
+    pub worktree_strategy: WorktreeStrategy,
+    pub prompt_template: Option<String>,
+    pub kanban_policy: KanbanAutoMovePolicy,
+    pub required_labels: HashMap<String, String>,
+    /// Secret values are masked
+    pub env: Vec<ProjectEnvVar>,
 }
 
 /// Get a single project by ID
@@ -50,26 +64,78 @@ async fn get_project(
         name: project.name,
         local_path: project.local_path,
         remote_url: project.remote_url,
+        clone_if_missing: project.clone_if_missing,
         default_branch: project.default_branch,
         gateway_id: project.gateway_id.to_string(),
         worktree_dir: project.worktree_dir,
+        worktree_strategy: project.worktree_strategy,
+        prompt_template: project.prompt_template,
+        kanban_policy: project.kanban_policy,
+        required_labels: project.required_labels,
+        env: project.env.iter().map(ProjectEnvVar::masked).collect(),
     }))
 }
 
 /// Update project request
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct UpdateProjectRequest {
     pub name: Option<String>,
     pub default_branch: Option<String>,
     pub worktree_dir: Option<String>,
+    /// `per_run` for a fresh worktree per run, `per_task` to reuse the task's
+    pub worktree_strategy: Option<WorktreeStrategy>,
+    pub clone_if_missing: Option<bool>,
+    /// Prompt template; an empty string restores the default layout
+    pub prompt_template: Option<String>,
+    /// Kanban columns tasks move to as their runs start, succeed or fail
+    pub kanban_policy: Option<KanbanAutoMovePolicy>,
+    /// Host labels executions require; replaces the current set
+    pub required_labels: Option<HashMap<String, String>>,
+    /// Environment passed to executions; replaces the current set
+    /// A secret sent back masked keeps its stored value
+    pub env: Option<Vec<ProjectEnvVar>>,
+}
+
+/// Validate a replacement env, restoring stored values of masked secrets
+fn resolve_env(
+    current: &[ProjectEnvVar],
+    requested: Vec<ProjectEnvVar>,
+    can_seal: bool,
+) -> Result<Vec<ProjectEnvVar>, (StatusCode, String)> {
+    let invalid = |message: String| (StatusCode::UNPROCESSABLE_ENTITY, message);
+    let mut names = HashSet::new();
+    requested
+        .into_iter()
+        .map(|mut var| {
+            if var.name.is_empty() || var.name.contains('=') {
+                return Err(invalid(format!("Invalid environment variable name {:?}", var.name)));
+            }
+            if !names.insert(var.name.clone()) {
+                return Err(invalid(format!("Duplicate environment variable {}", var.name)));
+            }
+            if var.secret {
+                if !can_seal {
+                    return Err(invalid("Secret values require VK_SECRET_KEY to be set".to_string()));
+                }
+                if var.value == MASKED_SECRET {
+                    var.value = current
+                        .iter()
+                        .find(|stored| stored.secret && stored.name == var.name)
+                        .map(|stored| stored.value.clone())
+                        .ok_or_else(|| invalid(format!("Secret {} has no stored value", var.name)))?;
+                }
+            }
+            Ok(var)
+        })
+        .collect()
 }
 
 /// Update a project
 async fn update_project(
     State(state): State<AppState>,
     Path(id): Path<String>,
-    Json(req): Json<UpdateProjectRequest>,
+    JsonBody(req): JsonBody<UpdateProjectRequest>,
 ) -> Result<Json<ProjectDetailResponse>, (StatusCode, String)> {
     let project_id = Uuid::parse_str(&id)
         .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid project ID".to_string()))?;
@@ -90,6 +156,30 @@ async fn update_project(
     if let Some(dir) = req.worktree_dir {
         project.worktree_dir = dir;
     }
+    if let Some(strategy) = req.worktree_strategy {
+        project.worktree_strategy = strategy;
+    }
+    if let Some(clone_if_missing) = req.clone_if_missing {
+        project.clone_if_missing = clone_if_missing;
+    }
+    if let Some(policy) = req.kanban_policy {
+        project.kanban_policy = policy;
+    }
+    if let Some(labels) = req.required_labels {
+        project.required_labels = labels;
+    }
+    if let Some(env) = req.env {
+        project.env = resolve_env(&project.env, env, state.project_store().has_secret_key())?;
+    }
+    if let Some(template) = req.prompt_template {
+        if template.trim().is_empty() {
+            project.prompt_template = None;
+        } else {
+            validate_prompt_template(&template)
+                .map_err(|e| (StatusCode::UNPROCESSABLE_ENTITY, e.to_string()))?;
+            project.prompt_template = Some(template);
+        }
+    }
 
     let updated = state
         .project_store()
@@ -102,12 +192,51 @@ async fn update_project(
         name: updated.name,
         local_path: updated.local_path,
         remote_url: updated.remote_url,
+        clone_if_missing: updated.clone_if_missing,
         default_branch: updated.default_branch,
         gateway_id: updated.gateway_id.to_string(),
         worktree_dir: updated.worktree_dir,
+        worktree_strategy: updated.worktree_strategy,
+        prompt_template: updated.prompt_template,
+        kanban_policy: updated.kanban_policy,
+        required_labels: updated.required_labels,
+        env: updated.env.iter().map(ProjectEnvVar::masked).collect(),
     }))
 }
 
+/// Get the kanban board restricted to a project's tasks
+async fn get_project_kanban(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<KanbanBoardState>, (StatusCode, String)> {
+    let project_id = Uuid::parse_str(&id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid project ID".to_string()))?;
+
+    state
+        .project_store()
+        .get(project_id)
+        .await
+        .ok_or((StatusCode::NOT_FOUND, "Project not found".to_string()))?;
+
+    let task_ids: HashSet<String> = state
+        .task_store()
+        .list()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .into_iter()
+        .filter(|task| task.project_id == Some(project_id))
+        .map(|task| task.id.to_string())
+        .collect();
+
+    let board_state = state
+        .kanban_store()
+        .get_state_synced()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(board_state.filtered(|task| task_ids.contains(&task.id))))
+}
+
 /// Create the project router
 pub fn router() -> Router<AppState> {
     Router::new()
@@ -116,4 +245,168 @@ pub fn router() -> Router<AppState> {
             "/api/projects/{id}",
             get(get_project).put(update_project),
         )
+        .route("/api/projects/{id}/kanban", get(get_project_kanban))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    use axum::{
+        body::{to_bytes, Body},
+        http::Request,
+    };
+    use serde_json::Value;
+    use tempfile::TempDir;
+    use tower::ServiceExt;
+    use vk_core::kanban::KanbanStore;
+    use vk_core::project::CreateProjectRequest;
+    use vk_core::task::{FileTaskStore, Task};
+
+    use crate::gateway::GatewayManager;
+
+    async fn build_state() -> (AppState, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path().to_path_buf();
+        let task_store = Arc::new(FileTaskStore::new(data_dir.join("tasks.json")).await.unwrap());
+        let kanban_store = Arc::new(
+            KanbanStore::with_task_store(data_dir.join("kanban.json"), Arc::clone(&task_store))
+                .await
+                .unwrap(),
+        );
+        let gateway_manager = Arc::new(GatewayManager::with_stores(
+            Arc::clone(&task_store),
+            Arc::clone(&kanban_store),
+        ));
+        let state = AppState::with_stores(data_dir, task_store, kanban_store, gateway_manager)
+            .await
+            .unwrap();
+        (state, temp_dir)
+    }
+
+    async fn register_project(state: &AppState, name: &str) -> Uuid {
+        state
+            .project_store()
+            .register(
+                Uuid::new_v4(),
+                CreateProjectRequest {
+                    name: name.to_string(),
+                    local_path: format!("/tmp/{}", name),
+                    remote_url: None,
+                    default_branch: None,
+                    worktree_dir: None,
+                },
+            )
+            .await
+            .unwrap()
+            .id
+    }
+
+    #[tokio::test]
+    async fn project_kanban_returns_only_project_cards() {
+        let (state, _temp_dir) = build_state().await;
+        let alpha = register_project(&state, "alpha").await;
+        let beta = register_project(&state, "beta").await;
+
+        let mut alpha_ids = Vec::new();
+        for title in ["Alpha one", "Alpha two"] {
+            let task = state
+                .task_store()
+                .create(Task::new(title.to_string()).with_project_id(alpha))
+                .await
+                .unwrap();
+            alpha_ids.push(task.id.to_string());
+        }
+        let beta_task = state
+            .task_store()
+            .create(Task::new("Beta one".to_string()).with_project_id(beta))
+            .await
+            .unwrap();
+
+        let response = router()
+            .with_state(state)
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/projects/{}/kanban", alpha))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let board: Value = serde_json::from_slice(&body).unwrap();
+
+        let tasks = board["tasks"].as_object().unwrap();
+        assert_eq!(tasks.len(), 2);
+        assert!(alpha_ids.iter().all(|id| tasks.contains_key(id)));
+        assert!(!tasks.contains_key(&beta_task.id.to_string()));
+
+        let todo: Vec<String> =
+            serde_json::from_value(board["columns"]["todo"]["taskIds"].clone()).unwrap();
+        assert_eq!(todo.len(), 2);
+        assert_eq!(board["columnOrder"], serde_json::json!(["todo", "doing", "done"]));
+    }
+
+    #[tokio::test]
+    async fn secret_env_is_sealed_on_disk_and_masked_but_dispatched_in_clear() {
+        let (mut state, temp_dir) = build_state().await;
+        state.set_secret_key(
+            vk_core::project::SecretKey::from_base64("MDEyMzQ1Njc4OWFiY2RlZjAxMjM0NTY3ODlhYmNkZWY=").unwrap(),
+        );
+        let project_id = register_project(&state, "secretive").await;
+
+        let send = |method: &str, body: Option<Value>| {
+            let request = Request::builder()
+                .method(method)
+                .uri(format!("/api/projects/{}", project_id))
+                .header("Content-Type", "application/json");
+            let request = match body {
+                Some(body) => request.body(Body::from(body.to_string())).unwrap(),
+                None => request.body(Body::empty()).unwrap(),
+            };
+            let app = router().with_state(state.clone());
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<Value>(&body).unwrap_or(Value::Null))
+            }
+        };
+
+        let env = serde_json::json!({ "env": [
+            { "name": "API_TOKEN", "value": "s3cr3t", "secret": true },
+            { "name": "LOG_LEVEL", "value": "debug" },
+        ]});
+        let (status, updated) = send("PUT", Some(env)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(updated["env"][0]["value"], MASKED_SECRET);
+        assert_eq!(updated["env"][1]["value"], "debug");
+
+        let on_disk = std::fs::read_to_string(temp_dir.path().join("projects.json")).unwrap();
+        assert!(!on_disk.contains("s3cr3t"));
+        assert!(on_disk.contains("enc:v1:"));
+
+        // Sending the masked value back keeps the stored secret
+        let (_, fetched) = send("GET", None).await;
+        assert_eq!(fetched["env"][0]["value"], MASKED_SECRET);
+        let (status, _) = send("PUT", Some(serde_json::json!({ "env": fetched["env"] }))).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let project = state.project_store().get(project_id).await.unwrap();
+        let env = state.project_store().dispatch_env(&project).unwrap();
+        assert_eq!(env.get("API_TOKEN").map(String::as_str), Some("s3cr3t"));
+        assert_eq!(env.get("LOG_LEVEL").map(String::as_str), Some("debug"));
+
+        // A value that merely looks sealed is still plaintext to seal
+        let lookalike = serde_json::json!({ "env": [{ "name": "API_TOKEN", "value": "enc:v1:anything", "secret": true }] });
+        assert_eq!(send("PUT", Some(lookalike)).await.0, StatusCode::OK);
+        let project = state.project_store().get(project_id).await.unwrap();
+        let env = state.project_store().dispatch_env(&project).unwrap();
+        assert_eq!(env.get("API_TOKEN").map(String::as_str), Some("enc:v1:anything"));
+
+        let unknown = serde_json::json!({ "env": [{ "name": "OTHER", "value": MASKED_SECRET, "secret": true }] });
+        assert_eq!(send("PUT", Some(unknown)).await.0, StatusCode::UNPROCESSABLE_ENTITY);
+    }
 }
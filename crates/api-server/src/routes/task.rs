@@ -3,24 +3,38 @@
 //! RESTful API for task CRUD operations.
 
 use axum::{
+    body::Body,
     extract::{Path, Query, State},
-    http::StatusCode,
-    routing::{delete, get},
+    http::{header, HeaderMap, StatusCode},
+    middleware,
+    response::{IntoResponse, Response},
+    routing::{delete, get, post},
     Json, Router,
 };
-use agent_runner::{ChatMessage, ExecutionEvent, ExecutionStatus, RunSummary};
-use serde::{Deserialize, Serialize};
+use agent_runner::{ChatMessage, EventFilter, EventHistogram, ExecutionEvent, ExecutionStatus, RunDeleteFilter, RunSummary};
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
 use uuid::Uuid;
 
 use vk_core::task::{Task, TaskPriority, TaskRepository, TaskStatus};
 
+use crate::context_files::check_context_paths;
+use crate::cursor::{decode_event_cursor, encode_event_cursor};
+use crate::deprecation::{deprecated_route, SunsetConfig};
+use crate::extract::JsonBody;
+use crate::identity::UserIdentity;
+use crate::range::ranged_response;
+use crate::redaction::{check_admin, AdminAccess};
+use crate::run_compare::{compare_runs, RunComparison};
 use crate::state::AppState;
 
 // ============================================================================
 // Request/Response types
 // ============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateTaskRequest {
     pub title: String,
@@ -36,21 +50,98 @@ pub struct CreateTaskRequest {
     pub base_branch: Option<String>,
     #[serde(default)]
     pub model: Option<String>,
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+    #[serde(default)]
+    pub assignee: Option<String>,
+    /// Run outcomes that fire notifications; omitted means all
+    #[serde(default)]
+    pub notify_on: Option<Vec<String>>,
+    /// Project-relative files prepended to the prompt of each run
+    #[serde(default)]
+    pub context_files: Option<Vec<String>>,
 }
 
+/// Per-call overrides when instantiating a task from a template
 #[derive(Debug, Deserialize)]
-pub struct UpdateTaskRequest {
+#[serde(rename_all = "camelCase")]
+pub struct CreateTaskFromTemplateRequest {
     #[serde(default)]
     pub title: Option<String>,
     #[serde(default)]
+    pub project_id: Option<Uuid>,
+    #[serde(default)]
     pub description: Option<String>,
     #[serde(default)]
+    pub priority: Option<TaskPriority>,
+    #[serde(default)]
+    pub agent_type: Option<String>,
+    #[serde(default)]
+    pub base_branch: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+    #[serde(default)]
+    pub assignee: Option<String>,
+    /// Run outcomes that fire notifications; omitted means all
+    #[serde(default)]
+    pub notify_on: Option<Vec<String>>,
+    /// Project-relative files prepended to the prompt of each run
+    #[serde(default)]
+    pub context_files: Option<Vec<String>>,
+}
+
+/// Deserialize a field that is present, `null` included, as `Some`
+///
+/// Paired with `#[serde(default)]`, an omitted field stays `None` while an
+/// explicit `null` becomes `Some(None)`.
+fn double_option<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct UpdateTaskRequest {
+    #[serde(default)]
+    pub title: Option<String>,
+    /// `null` clears the description; omit it to leave it unchanged
+    #[serde(default, deserialize_with = "double_option")]
+    pub description: Option<Option<String>>,
+    /// `null` clears the agent type; omit it to leave it unchanged
+    #[serde(default, deserialize_with = "double_option")]
+    pub agent_type: Option<Option<String>>,
+    /// `null` clears the model; omit it to leave it unchanged
+    #[serde(default, deserialize_with = "double_option")]
+    pub model: Option<Option<String>>,
+    #[serde(default)]
     pub status: Option<TaskStatus>,
     #[serde(default)]
     pub priority: Option<TaskPriority>,
+    /// New assignee; an empty string unassigns the task
+    #[serde(default)]
+    pub assignee: Option<String>,
+    /// Run outcomes that fire notifications; an empty list restores all
+    #[serde(default)]
+    pub notify_on: Option<Vec<String>>,
+    /// Project-relative files prepended to the prompt; an empty list detaches all
+    #[serde(default)]
+    pub context_files: Option<Vec<String>>,
+    /// Allow moving the task out of a locked `Done` status
+    #[serde(default)]
+    pub reopen: bool,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveTaskRequest {
+    pub project_id: Uuid,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct TaskResponse {
     pub id: Uuid,
@@ -62,10 +153,42 @@ pub struct TaskResponse {
     pub agent_type: Option<String>,
     pub base_branch: Option<String>,
     pub model: Option<String>,
+    pub tags: Vec<String>,
+    pub assignee: Option<String>,
+    pub notify_on: Vec<String>,
+    pub watchers: Vec<String>,
+    pub context_files: Vec<String>,
     pub created_at: String,
     pub updated_at: String,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListTasksQuery {
+    /// Only list tasks assigned to this person
+    #[serde(default)]
+    pub assignee: Option<String>,
+    /// Only list tasks of this project
+    #[serde(default)]
+    pub project_id: Option<Uuid>,
+}
+
+impl ListTasksQuery {
+    fn matches(&self, task: &Task) -> bool {
+        self.assignee
+            .as_deref()
+            .is_none_or(|assignee| task.assignee.as_deref() == Some(assignee))
+            && self.project_id.is_none_or(|project_id| task.project_id == Some(project_id))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportTasksQuery {
+    pub format: String,
+    #[serde(flatten)]
+    pub filter: ListTasksQuery,
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RunSummaryResponse {
@@ -79,6 +202,29 @@ pub struct RunSummaryResponse {
     pub duration_ms: Option<u64>,
     pub status: ExecutionStatus,
     pub event_count: u32,
+    /// Concise summary of what the agent accomplished
+    pub summary: Option<String>,
+    /// Distinct files changed; absent for runs recorded before it was counted
+    pub changed_files: Option<usize>,
+    /// Events and messages were deleted by retention
+    pub events_purged: bool,
+    pub labels: HashMap<String, String>,
+    pub created_by: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskRunsResponse {
+    pub runs: Vec<RunSummaryResponse>,
+    /// Runs that exist on disk but could not be loaded
+    pub warnings: Vec<RunLoadWarning>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunLoadWarning {
+    pub run_id: Uuid,
+    pub error: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -86,12 +232,18 @@ pub struct RunSummaryResponse {
 pub struct RunEventsQuery {
     #[serde(default)]
     pub offset: Option<usize>,
+    /// `nextCursor` of the previous page; takes precedence over `offset`
+    #[serde(default)]
+    pub cursor: Option<String>,
     #[serde(default)]
     pub limit: Option<usize>,
     #[serde(default)]
     pub event_type: Option<String>,
     #[serde(default)]
     pub agent_event_type: Option<String>,
+    /// Count all matching events (reads the whole log)
+    #[serde(default)]
+    pub include_total: bool,
 }
 
 #[derive(Debug, Serialize)]
@@ -99,7 +251,69 @@ pub struct RunEventsQuery {
 pub struct RunEventsResponse {
     pub events: Vec<ExecutionEvent>,
     pub has_more: bool,
+    /// Only set when paging by offset
     pub next_offset: Option<usize>,
+    /// Resumes after the last event of this page, even across compaction
+    pub next_cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteRunsQuery {
+    /// Only delete runs with this terminal status
+    #[serde(default)]
+    pub status: Option<ExecutionStatus>,
+    /// Only delete runs created before this RFC 3339 timestamp
+    #[serde(default)]
+    pub older_than: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteRunsResponse {
+    pub deleted: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CompareRunsQuery {
+    pub a: Uuid,
+    pub b: Uuid,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MergeabilityQuery {
+    /// Branch to merge into (defaults to the run's base branch)
+    pub into: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeabilityResponse {
+    pub branch: String,
+    pub into_branch: String,
+    pub mergeable: bool,
+    pub conflicts: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SearchEventsQuery {
+    #[serde(default)]
+    pub q: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventSearchMatch {
+    pub offset: usize,
+    pub event: ExecutionEvent,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchEventsResponse {
+    pub matches: Vec<EventSearchMatch>,
 }
 
 #[derive(Debug, Serialize)]
@@ -121,6 +335,11 @@ impl From<RunSummary> for RunSummaryResponse {
             duration_ms: run.duration_ms,
             status: run.status,
             event_count: run.event_count,
+            summary: run.summary,
+            changed_files: run.changed_files,
+            events_purged: run.events_purged,
+            labels: run.labels,
+            created_by: run.created_by,
         }
     }
 }
@@ -137,6 +356,11 @@ impl From<Task> for TaskResponse {
             agent_type: task.agent_type,
             base_branch: task.base_branch,
             model: task.model,
+            tags: task.tags,
+            assignee: task.assignee,
+            notify_on: task.notify_on,
+            watchers: task.watchers,
+            context_files: task.context_files,
             created_at: task.created_at.to_rfc3339(),
             updated_at: task.updated_at.to_rfc3339(),
         }
@@ -148,13 +372,24 @@ pub struct ErrorResponse {
     pub error: String,
 }
 
+/// `410 Gone` for a run whose events retention has purged
+fn events_purged(run_id: Uuid) -> (StatusCode, Json<ErrorResponse>) {
+    (
+        StatusCode::GONE,
+        Json(ErrorResponse {
+            error: format!("Events of run {} were purged", run_id),
+        }),
+    )
+}
+
 // ============================================================================
 // Handlers
 // ============================================================================
 
-/// GET /api/tasks - List all tasks
+/// GET /api/tasks - List all tasks, optionally filtered by assignee or project
 async fn list_tasks(
     State(state): State<AppState>,
+    Query(query): Query<ListTasksQuery>,
 ) -> Result<Json<Vec<TaskResponse>>, (StatusCode, Json<ErrorResponse>)> {
     let tasks = state.task_store().list().await.map_err(|e| {
         (
@@ -165,13 +400,58 @@ async fn list_tasks(
         )
     })?;
 
-    Ok(Json(tasks.into_iter().map(TaskResponse::from).collect()))
+    Ok(Json(
+        tasks
+            .into_iter()
+            .filter(|task| query.matches(task))
+            .map(TaskResponse::from)
+            .collect(),
+    ))
+}
+
+/// GET /api/tasks/export?format=csv - Export tasks, with the filters of `list_tasks`
+async fn export_tasks(
+    State(state): State<AppState>,
+    Query(query): Query<ExportTasksQuery>,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    if query.format != "csv" {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: format!("Unsupported export format: {}", query.format),
+            }),
+        ));
+    }
+    let tasks = state.task_store().list().await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+    })?;
+
+    let rows = std::iter::once(Task::CSV_HEADER.to_string()).chain(
+        tasks
+            .into_iter()
+            .filter(move |task| query.filter.matches(task))
+            .map(|task| task.to_csv_row()),
+    );
+    let body = Body::from_stream(futures::stream::iter(rows.map(Ok::<_, std::convert::Infallible>)));
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8"),
+            (header::CONTENT_DISPOSITION, "attachment; filename=\"tasks.csv\""),
+        ],
+        body,
+    )
+        .into_response())
 }
 
 /// POST /api/tasks - Create a new task
 async fn create_task(
     State(state): State<AppState>,
-    Json(req): Json<CreateTaskRequest>,
+    JsonBody(req): JsonBody<CreateTaskRequest>,
 ) -> Result<(StatusCode, Json<TaskResponse>), (StatusCode, Json<ErrorResponse>)> {
     // Validate input
     if req.title.trim().is_empty() {
@@ -224,6 +504,122 @@ async fn create_task(
         task = task.with_model(model);
     }
 
+    if let Some(tags) = req.tags {
+        task = task.with_tags(tags);
+    }
+
+    if let Some(assignee) = req.assignee {
+        task = task.with_assignee(assignee);
+    }
+
+    if let Some(notify_on) = req.notify_on {
+        task = task.with_notify_on(notify_on);
+    }
+
+    if let Some(context_files) = req.context_files {
+        task = task.with_context_files(context_files);
+    }
+
+    validate_task(&state, &task).await?;
+
+    let created = state.task_store().create(task).await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+    })?;
+
+    Ok((StatusCode::CREATED, Json(TaskResponse::from(created))))
+}
+
+/// POST /api/tasks/from-template/:template_id - Create a task from a template
+async fn create_task_from_template(
+    State(state): State<AppState>,
+    Path(template_id): Path<Uuid>,
+    JsonBody(req): JsonBody<CreateTaskFromTemplateRequest>,
+) -> Result<(StatusCode, Json<TaskResponse>), (StatusCode, Json<ErrorResponse>)> {
+    let template = state.template_store().get(template_id).await.ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Template {} not found", template_id),
+            }),
+        )
+    })?;
+
+    let mut task = template.instantiate(req.title.as_deref());
+
+    if task.title.trim().is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "Title cannot be empty".to_string(),
+            }),
+        ));
+    }
+
+    if let Some(project_id) = req.project_id {
+        task = task.with_project_id(project_id);
+    }
+
+    let project_id = task.project_id.ok_or_else(|| {
+        (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(ErrorResponse {
+                error: "Project is required".to_string(),
+            }),
+        )
+    })?;
+
+    if state.project_store().get(project_id).await.is_none() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Project {} not found", project_id),
+            }),
+        ));
+    }
+
+    if let Some(desc) = req.description {
+        task = task.with_description(desc);
+    }
+
+    if let Some(priority) = req.priority {
+        task = task.with_priority(priority);
+    }
+
+    if let Some(agent_type) = req.agent_type {
+        task = task.with_agent_type(agent_type);
+    }
+
+    if let Some(base_branch) = req.base_branch {
+        task = task.with_base_branch(base_branch);
+    }
+
+    if let Some(model) = req.model {
+        task = task.with_model(model);
+    }
+
+    if let Some(tags) = req.tags {
+        task = task.with_tags(tags);
+    }
+
+    if let Some(assignee) = req.assignee {
+        task = task.with_assignee(assignee);
+    }
+
+    if let Some(notify_on) = req.notify_on {
+        task = task.with_notify_on(notify_on);
+    }
+
+    if let Some(context_files) = req.context_files {
+        task = task.with_context_files(context_files);
+    }
+
+    validate_task(&state, &task).await?;
+
     let created = state.task_store().create(task).await.map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -262,10 +658,24 @@ async fn get_task(
 }
 
 /// GET /api/tasks/:id/runs - List all runs for a task
+///
+/// Runs that fail to load are reported in `warnings` instead of being dropped silently.
+///
+/// Repeated `label=key:value` parameters keep only runs carrying every label;
+/// `createdBy` keeps only runs started by that user.
 async fn list_task_runs(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
-) -> Result<Json<Vec<RunSummaryResponse>>, (StatusCode, Json<ErrorResponse>)> {
+    Query(params): Query<Vec<(String, String)>>,
+) -> Result<Json<TaskRunsResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let labels = parse_label_filters(&params).map_err(|error| {
+        (StatusCode::BAD_REQUEST, Json(ErrorResponse { error }))
+    })?;
+    let created_by = params
+        .iter()
+        .find(|(name, _)| name == "createdBy")
+        .map(|(_, value)| value.as_str());
+
     let task = state.task_store().get(id).await.map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -284,25 +694,60 @@ async fn list_task_runs(
         ));
     }
 
-    let runs = state.executor().list_runs(id).map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: e.to_string(),
-            }),
-        )
-    })?;
+    let (runs, errors) = state
+        .executor()
+        .run_store()
+        .list_runs_with_errors(id)
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: e.to_string(),
+                }),
+            )
+        })?;
 
-    Ok(Json(
-        runs.into_iter().map(RunSummaryResponse::from).collect(),
-    ))
+    Ok(Json(TaskRunsResponse {
+        runs: runs
+            .into_iter()
+            .filter(|run| {
+                labels
+                    .iter()
+                    .all(|(key, value)| run.labels.get(*key).map(String::as_str) == Some(*value))
+                    && created_by.is_none_or(|user| run.created_by.as_deref() == Some(user))
+            })
+            .map(RunSummaryResponse::from)
+            .collect(),
+        warnings: errors
+            .into_iter()
+            .map(|(run_id, error)| RunLoadWarning { run_id, error })
+            .collect(),
+    }))
+}
+
+/// Parse repeated `label=key:value` query parameters
+fn parse_label_filters(params: &[(String, String)]) -> Result<Vec<(&str, &str)>, String> {
+    params
+        .iter()
+        .filter(|(name, _)| name == "label")
+        .map(|(_, label)| {
+            label
+                .split_once(':')
+                .filter(|(key, _)| !key.is_empty())
+                .ok_or_else(|| format!("Invalid label filter {:?}, expected key:value", label))
+        })
+        .collect()
 }
 
 /// DELETE /api/tasks/:id/runs - Delete all runs for a task
+///
+/// With `status` or `olderThan`, only matching terminal runs are deleted and
+/// the count is returned instead.
 async fn delete_task_runs(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
-) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+    Query(query): Query<DeleteRunsQuery>,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
     let task = state.task_store().get(id).await.map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -321,6 +766,36 @@ async fn delete_task_runs(
         ));
     }
 
+    if query.status.is_some() || query.older_than.is_some() {
+        if query.status.is_some_and(|status| !status.is_terminal()) {
+            return Err((
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(ErrorResponse {
+                    error: "Only terminal runs can be deleted".to_string(),
+                }),
+            ));
+        }
+
+        let filter = RunDeleteFilter {
+            status: query.status,
+            older_than: query.older_than,
+        };
+        let deleted = state
+            .executor()
+            .run_store()
+            .delete_task_runs_matching(id, &filter)
+            .map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ErrorResponse {
+                        error: e.to_string(),
+                    }),
+                )
+            })?;
+
+        return Ok(Json(DeleteRunsResponse { deleted }).into_response());
+    }
+
     let runs = state.executor().list_runs(id).map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -352,10 +827,13 @@ async fn delete_task_runs(
             )
         })?;
 
-    Ok(StatusCode::NO_CONTENT)
+    Ok(StatusCode::NO_CONTENT.into_response())
 }
 
 /// GET /api/tasks/:id/runs/:run_id/events - List events for a run
+///
+/// Pages by `offset`, or by the opaque `cursor` from the previous page's
+/// `nextCursor`, which stays valid when the event log is compacted.
 async fn list_run_events(
     State(state): State<AppState>,
     Path((task_id, run_id)): Path<(Uuid, Uuid)>,
@@ -388,26 +866,51 @@ async fn list_run_events(
         )
     })?;
 
-    if !runs.iter().any(|run| run.id == run_id) {
-        return Err((
-            StatusCode::NOT_FOUND,
-            Json(ErrorResponse {
-                error: format!("Run {} not found", run_id),
-            }),
-        ));
+    match runs.iter().find(|run| run.id == run_id) {
+        None => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Run {} not found", run_id),
+                }),
+            ))
+        }
+        Some(run) if run.events_purged => return Err(events_purged(run_id)),
+        Some(_) => {}
     }
 
+    let after_seq = match query.cursor.as_deref() {
+        Some(cursor) => Some(decode_event_cursor(cursor).ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: format!("Invalid cursor: {}", cursor),
+                }),
+            )
+        })?),
+        None => None,
+    };
     let offset = query.offset.unwrap_or(0);
     let limit = query.limit.unwrap_or(200).min(1000);
 
-    let (events, has_more) = state.executor().load_run_events(
-        task_id,
-        run_id,
-        offset,
-        limit,
-        query.event_type,
-        query.agent_event_type,
-    ).map_err(|e| {
+    let filter = EventFilter {
+        event_type: query.event_type.as_deref(),
+        agent_event_type: query.agent_event_type.as_deref(),
+    };
+    let page = match after_seq {
+        Some(after_seq) => state.executor().run_store().load_events_after(
+            task_id,
+            run_id,
+            after_seq,
+            limit,
+            filter,
+            query.include_total,
+        ),
+        None => state
+            .executor()
+            .load_run_events(task_id, run_id, offset, limit, filter, query.include_total),
+    }
+    .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(ErrorResponse {
@@ -416,21 +919,41 @@ async fn list_run_events(
         )
     })?;
 
-    let next_offset = if has_more { Some(offset + events.len()) } else { None };
+    let next_offset = if page.has_more && after_seq.is_none() {
+        Some(offset + page.events.len())
+    } else {
+        None
+    };
+    let next_cursor = if page.has_more {
+        page.events.last().and_then(|event| event.seq).map(encode_event_cursor)
+    } else {
+        None
+    };
 
     Ok(Json(RunEventsResponse {
-        events,
-        has_more,
+        events: page.events,
+        has_more: page.has_more,
         next_offset,
+        next_cursor,
+        total: page.total,
     }))
 }
 
-/// GET /api/tasks/:id/runs/:run_id/messages - List messages for a run
-async fn list_run_messages(
+/// GET /api/tasks/:id/runs/:run_id/events/search - Search events for a run
+async fn search_run_events(
     State(state): State<AppState>,
     Path((task_id, run_id)): Path<(Uuid, Uuid)>,
-) -> Result<Json<RunMessagesResponse>, (StatusCode, Json<ErrorResponse>)> {
-    // Verify task exists
+    Query(query): Query<SearchEventsQuery>,
+) -> Result<Json<SearchEventsResponse>, (StatusCode, Json<ErrorResponse>)> {
+    if query.q.trim().is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "Search query cannot be empty".to_string(),
+            }),
+        ));
+    }
+
     let task = state.task_store().get(task_id).await.map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -449,8 +972,7 @@ async fn list_run_messages(
         ));
     }
 
-    // Load messages from RunStore
-    let messages = state.executor().run_store().load_messages(task_id, run_id).map_err(|e| {
+    let runs = state.executor().list_runs(task_id).map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(ErrorResponse {
@@ -459,23 +981,56 @@ async fn list_run_messages(
         )
     })?;
 
-    Ok(Json(RunMessagesResponse { messages }))
+    match runs.iter().find(|run| run.id == run_id) {
+        None => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Run {} not found", run_id),
+                }),
+            ))
+        }
+        Some(run) if run.events_purged => return Err(events_purged(run_id)),
+        Some(_) => {}
+    }
+
+    let matches = state
+        .executor()
+        .run_store()
+        .search_events(task_id, run_id, &query.q)
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: e.to_string(),
+                }),
+            )
+        })?;
+
+    Ok(Json(SearchEventsResponse {
+        matches: matches
+            .into_iter()
+            .map(|(offset, event)| EventSearchMatch { offset, event })
+            .collect(),
+    }))
 }
 
-/// DELETE /api/tasks/:id/runs/:run_id - Delete a run
-async fn delete_run(
+/// GET /api/tasks/:id/runs/:run_id/events/summary - Event counts by type
+///
+/// Counted in one pass over the event log, so clients need not page
+/// through every event to triage a run.
+async fn get_run_event_summary(
     State(state): State<AppState>,
     Path((task_id, run_id)): Path<(Uuid, Uuid)>,
-) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
-    let task = state.task_store().get(task_id).await.map_err(|e| {
+) -> Result<Json<EventHistogram>, (StatusCode, Json<ErrorResponse>)> {
+    let internal = |e: String| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: e.to_string(),
-            }),
+            Json(ErrorResponse { error: e }),
         )
-    })?;
+    };
 
+    let task = state.task_store().get(task_id).await.map_err(|e| internal(e.to_string()))?;
     if task.is_none() {
         return Err((
             StatusCode::NOT_FOUND,
@@ -485,7 +1040,35 @@ async fn delete_run(
         ));
     }
 
-    let runs = state.executor().list_runs(task_id).map_err(|e| {
+    let runs = state.executor().list_runs(task_id).map_err(|e| internal(e.to_string()))?;
+    match runs.iter().find(|run| run.id == run_id) {
+        None => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Run {} not found", run_id),
+                }),
+            ))
+        }
+        Some(run) if run.events_purged => return Err(events_purged(run_id)),
+        Some(_) => {}
+    }
+
+    let histogram = state
+        .executor()
+        .run_store()
+        .event_histogram(task_id, run_id)
+        .map_err(|e| internal(e.to_string()))?;
+    Ok(Json(histogram))
+}
+
+/// GET /api/tasks/:id/runs/compare?a=&b= - Compare two runs of a task
+async fn compare_task_runs(
+    State(state): State<AppState>,
+    Path(task_id): Path<Uuid>,
+    Query(query): Query<CompareRunsQuery>,
+) -> Result<Json<RunComparison>, (StatusCode, Json<ErrorResponse>)> {
+    let task = state.task_store().get(task_id).await.map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(ErrorResponse {
@@ -494,28 +1077,97 @@ async fn delete_run(
         )
     })?;
 
-    let run = runs.iter().find(|run| run.id == run_id).ok_or_else(|| {
-        (
+    if task.is_none() {
+        return Err((
             StatusCode::NOT_FOUND,
             Json(ErrorResponse {
-                error: format!("Run {} not found", run_id),
+                error: format!("Task {} not found", task_id),
+            }),
+        ));
+    }
+
+    let run_store = state.executor().run_store();
+    let load = |run_id: Uuid| {
+        let run = run_store.load_run(task_id, run_id).map_err(|_| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Run {} not found for task {}", run_id, task_id),
+                }),
+            )
+        })?;
+        if run.events_purged {
+            return Err(events_purged(run_id));
+        }
+        let events = run_store.load_events(task_id, run_id).map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: e.to_string(),
+                }),
+            )
+        })?;
+        Ok::<_, (StatusCode, Json<ErrorResponse>)>((run, events))
+    };
+
+    let (a, a_events) = load(query.a)?;
+    let (b, b_events) = load(query.b)?;
+
+    Ok(Json(compare_runs(&a, &a_events, &b, &b_events)))
+}
+
+/// GET /api/tasks/:id/runs/:run_id/mergeability - Dry-run merge a run's branch
+async fn get_run_mergeability(
+    State(state): State<AppState>,
+    Path((task_id, run_id)): Path<(Uuid, Uuid)>,
+    Query(query): Query<MergeabilityQuery>,
+) -> Result<Json<MergeabilityResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let task = state.task_store().get(task_id).await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: e.to_string(),
             }),
         )
     })?;
 
-    if run.status.is_active() {
+    if task.is_none() {
         return Err((
-            StatusCode::CONFLICT,
+            StatusCode::NOT_FOUND,
             Json(ErrorResponse {
-                error: "Run is active".to_string(),
+                error: format!("Task {} not found", task_id),
             }),
         ));
     }
 
-    state
+    let run = state
         .executor()
         .run_store()
-        .delete_run(task_id, run_id)
+        .load_run(task_id, run_id)
+        .map_err(|_| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Run {} not found", run_id),
+                }),
+            )
+        })?;
+
+    let branch = run.worktree_branch.clone().ok_or_else(|| {
+        (
+            StatusCode::CONFLICT,
+            Json(ErrorResponse {
+                error: format!("Run {} has no worktree branch", run_id),
+            }),
+        )
+    })?;
+    let into_branch = query.into.unwrap_or_else(|| run.base_branch.clone());
+
+    let status = state
+        .executor()
+        .worktree_manager()
+        .check_mergeable(branch.clone(), into_branch.clone())
+        .await
         .map_err(|e| {
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
@@ -525,17 +1177,21 @@ async fn delete_run(
             )
         })?;
 
-    Ok(StatusCode::NO_CONTENT)
+    Ok(Json(MergeabilityResponse {
+        branch,
+        into_branch,
+        mergeable: status.mergeable,
+        conflicts: status.conflicts,
+    }))
 }
 
-/// PATCH /api/tasks/:id - Update a task
-async fn update_task(
+/// GET /api/tasks/:id/runs/:run_id/messages - List messages for a run
+async fn list_run_messages(
     State(state): State<AppState>,
-    Path(id): Path<Uuid>,
-    Json(req): Json<UpdateTaskRequest>,
-) -> Result<Json<TaskResponse>, (StatusCode, Json<ErrorResponse>)> {
-    // First get the existing task
-    let existing = state.task_store().get(id).await.map_err(|e| {
+    Path((task_id, run_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<RunMessagesResponse>, (StatusCode, Json<ErrorResponse>)> {
+    // Verify task exists
+    let task = state.task_store().get(task_id).await.map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(ErrorResponse {
@@ -544,44 +1200,124 @@ async fn update_task(
         )
     })?;
 
-    let mut task = match existing {
-        Some(t) => t,
-        None => {
+    if task.is_none() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Task {} not found", task_id),
+            }),
+        ));
+    }
+
+    let run_store = state.executor().run_store();
+    if run_store.load_run(task_id, run_id).is_ok_and(|run| run.events_purged) {
+        return Err(events_purged(run_id));
+    }
+
+    // Load messages from RunStore
+    let messages = run_store.load_messages(task_id, run_id).map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+    })?;
+
+    Ok(Json(RunMessagesResponse { messages }))
+}
+
+/// GET /api/tasks/:id/runs/:run_id/log - Human-readable run log as plain text
+///
+/// Honors `Range` so interrupted downloads can resume.
+async fn get_run_log(
+    State(state): State<AppState>,
+    Path((task_id, run_id)): Path<(Uuid, Uuid)>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    let task = state.task_store().get(task_id).await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+    })?;
+
+    if task.is_none() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Task {} not found", task_id),
+            }),
+        ));
+    }
+
+    let run_store = state.executor().run_store();
+    if run_store.load_run(task_id, run_id).is_err() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Run {} not found", run_id),
+            }),
+        ));
+    }
+
+    let log = run_store.load_log(task_id, run_id).map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+    })?;
+
+    Ok(ranged_response(&headers, "text/plain; charset=utf-8", log.into_bytes()))
+}
+
+/// GET /api/tasks/:id/runs/:run_id/events/raw - Originals of redacted events
+///
+/// Admin only: requires `Authorization: Bearer $VK_ADMIN_TOKEN`.
+async fn list_unredacted_run_events(
+    State(state): State<AppState>,
+    Path((task_id, run_id)): Path<(Uuid, Uuid)>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<ExecutionEvent>>, (StatusCode, Json<ErrorResponse>)> {
+    match check_admin(&headers, state.admin_token()) {
+        AdminAccess::Granted => {}
+        AdminAccess::Disabled => {
             return Err((
-                StatusCode::NOT_FOUND,
+                StatusCode::FORBIDDEN,
                 Json(ErrorResponse {
-                    error: format!("Task {} not found", id),
+                    error: "Raw events are disabled; set VK_ADMIN_TOKEN to enable them".to_string(),
                 }),
-            ))
+            ));
         }
-    };
-
-    // Apply updates
-    if let Some(title) = req.title {
-        if title.trim().is_empty() {
+        AdminAccess::Denied => {
             return Err((
-                StatusCode::BAD_REQUEST,
+                StatusCode::UNAUTHORIZED,
                 Json(ErrorResponse {
-                    error: "Title cannot be empty".to_string(),
+                    error: "Admin token required".to_string(),
                 }),
             ));
         }
-        task.title = title;
     }
 
-    if let Some(desc) = req.description {
-        task.description = Some(desc);
-    }
-
-    if let Some(status) = req.status {
-        task.status = status;
-    }
-
-    if let Some(priority) = req.priority {
-        task.priority = priority;
+    let run_store = state.executor().run_store();
+    match run_store.load_run(task_id, run_id) {
+        Err(_) => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Run {} not found", run_id),
+                }),
+            ))
+        }
+        Ok(run) if run.events_purged => return Err(events_purged(run_id)),
+        Ok(_) => {}
     }
 
-    let updated = state.task_store().update(task).await.map_err(|e| {
+    let events = run_store.load_unredacted_events(task_id, run_id).map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(ErrorResponse {
@@ -590,15 +1326,18 @@ async fn update_task(
         )
     })?;
 
-    Ok(Json(TaskResponse::from(updated)))
+    Ok(Json(events))
 }
 
-/// DELETE /api/tasks/:id - Delete a task
-async fn delete_task(
+/// GET /api/tasks/:id/runs/:run_id/tool-results/:idx - Offloaded tool call result
+///
+/// Honors `Range` so interrupted downloads can resume.
+async fn get_run_tool_result(
     State(state): State<AppState>,
-    Path(id): Path<Uuid>,
-) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
-    let deleted = state.task_store().delete(id).await.map_err(|e| {
+    Path((task_id, run_id, index)): Path<(Uuid, Uuid, usize)>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    let task = state.task_store().get(task_id).await.map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(ErrorResponse {
@@ -607,80 +1346,1129 @@ async fn delete_task(
         )
     })?;
 
-    if deleted {
-        Ok(StatusCode::NO_CONTENT)
-    } else {
-        Err((
+    if task.is_none() {
+        return Err((
             StatusCode::NOT_FOUND,
             Json(ErrorResponse {
-                error: format!("Task {} not found", id),
+                error: format!("Task {} not found", task_id),
             }),
-        ))
+        ));
     }
-}
 
-// ============================================================================
-// Router
-// ============================================================================
+    let payload = state
+        .executor()
+        .run_store()
+        .load_tool_result(task_id, run_id, index)
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: e.to_string(),
+                }),
+            )
+        })?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Tool result {} not found for run {}", index, run_id),
+                }),
+            )
+        })?;
+
+    Ok(ranged_response(&headers, "application/json", payload.into_bytes()))
+}
+
+/// DELETE /api/tasks/:id/runs/:run_id - Delete a run
+async fn delete_run(
+    State(state): State<AppState>,
+    Path((task_id, run_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+    let task = state.task_store().get(task_id).await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+    })?;
+
+    if task.is_none() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Task {} not found", task_id),
+            }),
+        ));
+    }
+
+    let runs = state.executor().list_runs(task_id).map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+    })?;
+
+    let run = runs.iter().find(|run| run.id == run_id).ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Run {} not found", run_id),
+            }),
+        )
+    })?;
+
+    if run.status.is_active() {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(ErrorResponse {
+                error: "Run is active".to_string(),
+            }),
+        ));
+    }
+
+    state
+        .executor()
+        .run_store()
+        .delete_run(task_id, run_id)
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: e.to_string(),
+                }),
+            )
+        })?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// PATCH /api/tasks/:id - Update a task
+async fn update_task(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    JsonBody(req): JsonBody<UpdateTaskRequest>,
+) -> Result<Json<TaskResponse>, (StatusCode, Json<ErrorResponse>)> {
+    // First get the existing task
+    let existing = state.task_store().get(id).await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+    })?;
+
+    let mut task = match existing {
+        Some(t) => t,
+        None => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Task {} not found", id),
+                }),
+            ))
+        }
+    };
+
+    // Apply updates
+    if let Some(title) = req.title {
+        if title.trim().is_empty() {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: "Title cannot be empty".to_string(),
+                }),
+            ));
+        }
+        task.title = title;
+    }
+
+    if let Some(description) = req.description {
+        task.description = description;
+    }
+
+    if let Some(agent_type) = req.agent_type {
+        task.agent_type = agent_type;
+    }
+
+    if let Some(model) = req.model {
+        task.model = model;
+    }
+
+    if let Some(status) = req.status {
+        state
+            .task_store()
+            .transitions()
+            .check(task.status, status, req.reopen)
+            .map_err(|e| {
+                (
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    Json(ErrorResponse {
+                        error: e.to_string(),
+                    }),
+                )
+            })?;
+        task.status = status;
+    }
+
+    if let Some(priority) = req.priority {
+        task.priority = priority;
+    }
+
+    if let Some(assignee) = req.assignee {
+        task.assignee = Some(assignee).filter(|a| !a.trim().is_empty());
+    }
+
+    if let Some(notify_on) = req.notify_on {
+        task.notify_on = notify_on;
+    }
+
+    if let Some(context_files) = req.context_files {
+        task.context_files = context_files;
+    }
+
+    validate_task(&state, &task).await?;
+
+    let updated = state.task_store().update(task).await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+    })?;
+
+    Ok(Json(TaskResponse::from(updated)))
+}
+
+/// POST /api/tasks/:id/move - Relink a task to another project
+///
+/// Both projects must be bound to the same gateway; moves across gateways
+/// are rejected with 409. The task's runs stay with it.
+async fn move_task(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    JsonBody(req): JsonBody<MoveTaskRequest>,
+) -> Result<Json<TaskResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let mut task = state
+        .task_store()
+        .get(id)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: e.to_string(),
+                }),
+            )
+        })?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Task {} not found", id),
+                }),
+            )
+        })?;
+
+    let target = state.project_store().get(req.project_id).await.ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Project {} not found", req.project_id),
+            }),
+        )
+    })?;
+
+    // A task whose project was deleted may move anywhere
+    let current = match task.project_id {
+        Some(project_id) => state.project_store().get(project_id).await,
+        None => None,
+    };
+    if let Some(current) = current.filter(|current| current.gateway_id != target.gateway_id) {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(ErrorResponse {
+                error: format!(
+                    "Project {} is bound to gateway {}, but the task's project {} is bound to gateway {}",
+                    target.id, target.gateway_id, current.id, current.gateway_id
+                ),
+            }),
+        ));
+    }
+
+    task.project_id = Some(target.id);
+    let updated = state.task_store().update(task).await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+    })?;
+
+    Ok(Json(TaskResponse::from(updated)))
+}
+
+/// POST /api/tasks/:id/watch - Add the caller to the task's watchers
+async fn watch_task(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    identity: Option<UserIdentity>,
+) -> Result<Json<TaskResponse>, (StatusCode, Json<ErrorResponse>)> {
+    update_watchers(&state, id, identity, |task, user| task.watch(user)).await
+}
+
+/// DELETE /api/tasks/:id/watch - Remove the caller from the task's watchers
+async fn unwatch_task(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    identity: Option<UserIdentity>,
+) -> Result<Json<TaskResponse>, (StatusCode, Json<ErrorResponse>)> {
+    update_watchers(&state, id, identity, |task, user| task.unwatch(user)).await
+}
+
+/// Apply a watcher change for the caller, saving the task if it changed
+async fn update_watchers(
+    state: &AppState,
+    id: Uuid,
+    identity: Option<UserIdentity>,
+    change: impl FnOnce(&mut Task, &str) -> bool,
+) -> Result<Json<TaskResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let identity = identity.ok_or_else(|| {
+        (
+            StatusCode::UNAUTHORIZED,
+            Json(ErrorResponse {
+                error: "Watching a task requires an X-VK-User header from a trusted proxy".to_string(),
+            }),
+        )
+    })?;
+
+    let mut task = state
+        .task_store()
+        .get(id)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: e.to_string(),
+                }),
+            )
+        })?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Task {} not found", id),
+                }),
+            )
+        })?;
+
+    if !change(&mut task, &identity.sub) {
+        return Ok(Json(TaskResponse::from(task)));
+    }
+    let updated = state.task_store().update(task).await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+    })?;
+
+    Ok(Json(TaskResponse::from(updated)))
+}
+
+/// DELETE /api/tasks/:id - Delete a task
+async fn delete_task(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+    let deleted = state.task_store().delete(id).await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+    })?;
+
+    if deleted {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Task {} not found", id),
+            }),
+        ))
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Reject tasks whose fields exceed the configured length limits
+async fn validate_task(state: &AppState, task: &Task) -> Result<(), (StatusCode, Json<ErrorResponse>)> {
+    let unprocessable = |error: String| (StatusCode::UNPROCESSABLE_ENTITY, Json(ErrorResponse { error }));
+    task.validate(state.task_store().limits())
+        .map_err(|e| unprocessable(e.to_string()))?;
+
+    if task.context_files.is_empty() {
+        return Ok(());
+    }
+    let project_id = task
+        .project_id
+        .ok_or_else(|| unprocessable("Context files require a project".to_string()))?;
+    if let Some(project) = state.project_store().get(project_id).await {
+        check_context_paths(std::path::Path::new(&project.local_path), &task.context_files)
+            .map_err(unprocessable)?;
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Router
+// ============================================================================
+
+/// Task and run routes under `prefix`
+fn task_routes(prefix: &str) -> Router<AppState> {
+    let path = |suffix: &str| format!("{}{}", prefix, suffix);
+    Router::new()
+        .route(prefix, get(list_tasks).post(create_task))
+        .route(&path("/export"), get(export_tasks))
+        .route(
+            &path("/from-template/{template_id}"),
+            post(create_task_from_template),
+        )
+        .route(
+            &path("/{id}"),
+            get(get_task).patch(update_task).delete(delete_task),
+        )
+        .route(&path("/{id}/move"), post(move_task))
+        .route(&path("/{id}/watch"), post(watch_task).delete(unwatch_task))
+        .route(
+            &path("/{id}/runs"),
+            get(list_task_runs).delete(delete_task_runs),
+        )
+        .route(&path("/{id}/runs/compare"), get(compare_task_runs))
+        .route(&path("/{id}/runs/{run_id}"), delete(delete_run))
+        .route(&path("/{id}/runs/{run_id}/events"), get(list_run_events))
+        .route(
+            &path("/{id}/runs/{run_id}/events/search"),
+            get(search_run_events),
+        )
+        .route(
+            &path("/{id}/runs/{run_id}/events/summary"),
+            get(get_run_event_summary),
+        )
+        .route(
+            &path("/{id}/runs/{run_id}/events/raw"),
+            get(list_unredacted_run_events),
+        )
+        .route(&path("/{id}/runs/{run_id}/messages"), get(list_run_messages))
+        .route(&path("/{id}/runs/{run_id}/log"), get(get_run_log))
+        .route(
+            &path("/{id}/runs/{run_id}/tool-results/{idx}"),
+            get(get_run_tool_result),
+        )
+        .route(
+            &path("/{id}/runs/{run_id}/mergeability"),
+            get(get_run_mergeability),
+        )
+}
+
+pub fn router() -> Router<AppState> {
+    // `/api/tasks` stays as a deprecated alias of `/api/v1/tasks`
+    let legacy = task_routes("/api/tasks").route_layer(middleware::from_fn_with_state(
+        (SunsetConfig::from_env(), "/api/v1/tasks"),
+        deprecated_route,
+    ));
+
+    Router::new().merge(task_routes("/api/v1/tasks")).merge(legacy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    use agent_runner::{AgentEvent, AgentType, Run, RunLogger};
+    use axum::{body::{to_bytes, Body}, http::Request};
+    use serde_json::{json, Value};
+    use tempfile::TempDir;
+    use tower::ServiceExt;
+    use vk_core::kanban::KanbanStore;
+    use vk_core::project::CreateProjectRequest;
+    use vk_core::task::{FileTaskStore, StatusTransitions};
+    use vk_core::template::TaskTemplate;
+
+    use crate::gateway::GatewayManager;
+
+    async fn build_state() -> (AppState, TempDir) {
+        build_state_with_transitions(StatusTransitions::default()).await
+    }
+
+    async fn build_state_with_transitions(transitions: StatusTransitions) -> (AppState, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path().to_path_buf();
+
+        let tasks_path = data_dir.join("tasks.json");
+        let task_store = Arc::new(
+            FileTaskStore::new(tasks_path)
+                .await
+                .unwrap()
+                .with_transitions(transitions),
+        );
+        let kanban_path = data_dir.join("kanban.json");
+        let kanban_store = Arc::new(
+            KanbanStore::with_task_store(kanban_path, Arc::clone(&task_store))
+                .await
+                .unwrap(),
+        );
+        let gateway_manager = Arc::new(GatewayManager::with_stores(
+            Arc::clone(&task_store),
+            Arc::clone(&kanban_store),
+        ));
+        let state = AppState::with_stores(
+            data_dir,
+            Arc::clone(&task_store),
+            Arc::clone(&kanban_store),
+            Arc::clone(&gateway_manager),
+        )
+        .await
+        .unwrap();
+
+        (state, temp_dir)
+    }
+
+    async fn patch_status(state: &AppState, id: Uuid, body: Value) -> StatusCode {
+        router()
+            .with_state(state.clone())
+            .oneshot(
+                Request::builder()
+                    .method("PATCH")
+                    .uri(format!("/api/tasks/{}", id))
+                    .header("Content-Type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn update_task_clears_fields_on_null_and_keeps_omitted_ones() {
+        let (state, _temp_dir) = build_state().await;
+        let mut task = Task::new("Patched".to_string()).with_description("Keep me".to_string());
+        task.agent_type = Some("claude-code".to_string());
+        task.model = Some("sonnet".to_string());
+        let task = state.task_store().create(task).await.unwrap();
+
+        let status = patch_status(&state, task.id, json!({ "title": "Renamed", "model": null })).await;
+        assert_eq!(status, StatusCode::OK);
+        let stored = state.task_store().get(task.id).await.unwrap().unwrap();
+        assert_eq!(stored.title, "Renamed");
+        assert_eq!(stored.description.as_deref(), Some("Keep me"));
+        assert_eq!(stored.agent_type.as_deref(), Some("claude-code"));
+        assert_eq!(stored.model, None);
+
+        let status = patch_status(&state, task.id, json!({ "description": null, "agent_type": null })).await;
+        assert_eq!(status, StatusCode::OK);
+        let stored = state.task_store().get(task.id).await.unwrap().unwrap();
+        assert_eq!(stored.description, None);
+        assert_eq!(stored.agent_type, None);
+    }
+
+    #[tokio::test]
+    async fn update_task_allows_permitted_status_transition() {
+        let (state, _temp_dir) =
+            build_state_with_transitions(StatusTransitions::permissive().lock_done()).await;
+        let task = state
+            .task_store()
+            .create(Task::new("Allowed move".to_string()))
+            .await
+            .unwrap();
+
+        let status = patch_status(&state, task.id, json!({ "status": "done" })).await;
+        assert_eq!(status, StatusCode::OK);
+        let stored = state.task_store().get(task.id).await.unwrap().unwrap();
+        assert_eq!(stored.status, TaskStatus::Done);
+    }
+
+    #[tokio::test]
+    async fn update_task_rejects_forbidden_status_transition_without_reopen() {
+        let (state, _temp_dir) =
+            build_state_with_transitions(StatusTransitions::permissive().lock_done()).await;
+        let mut task = Task::new("Locked move".to_string());
+        task.status = TaskStatus::Done;
+        let task = state.task_store().create(task).await.unwrap();
+
+        let status =
+            patch_status(&state, task.id, json!({ "status": "in_progress" })).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        let stored = state.task_store().get(task.id).await.unwrap().unwrap();
+        assert_eq!(stored.status, TaskStatus::Done);
+
+        let status = patch_status(
+            &state,
+            task.id,
+            json!({ "status": "in_progress", "reopen": true }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn list_tasks_filters_by_assignee() {
+        let (state, _temp_dir) = build_state().await;
+        let alice_task = state
+            .task_store()
+            .create(Task::new("Alice's task").with_assignee("alice"))
+            .await
+            .unwrap();
+        state
+            .task_store()
+            .create(Task::new("Bob's task").with_assignee("bob"))
+            .await
+            .unwrap();
+        state
+            .task_store()
+            .create(Task::new("Unassigned task"))
+            .await
+            .unwrap();
+
+        let app = router().with_state(state);
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/api/tasks?assignee=alice")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let tasks: Value = serde_json::from_slice(&body).unwrap();
+        let tasks = tasks.as_array().unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0]["id"], json!(alice_task.id));
+        assert_eq!(tasks[0]["assignee"], "alice");
+
+        let response = app
+            .oneshot(Request::builder().uri("/api/tasks").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let tasks: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(tasks.as_array().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn export_tasks_as_csv_with_list_filters() {
+        let (state, _temp_dir) = build_state().await;
+        let project_id = Uuid::new_v4();
+        let exported = state
+            .task_store()
+            .create(Task::new("Ship, then celebrate").with_project_id(project_id))
+            .await
+            .unwrap();
+        state
+            .task_store()
+            .create(Task::new("Other project").with_project_id(Uuid::new_v4()))
+            .await
+            .unwrap();
+
+        let app = router().with_state(state);
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/tasks/export?format=csv&projectId={}", project_id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "text/csv; charset=utf-8");
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let csv = String::from_utf8(body.to_vec()).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "id,title,status,priority,project_id,created_at,updated_at");
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[1],
+            format!(
+                "{},\"Ship, then celebrate\",todo,medium,{},{},{}",
+                exported.id,
+                project_id,
+                exported.created_at.to_rfc3339(),
+                exported.updated_at.to_rfc3339()
+            )
+        );
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/tasks/export?format=xlsx")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn v1_task_routes_are_not_deprecated() {
+        let (state, _temp_dir) = build_state().await;
+        let task = state.task_store().create(Task::new("Versioned task")).await.unwrap();
+        let app = router().with_state(state);
+
+        let response = app
+            .clone()
+            .oneshot(Request::builder().uri("/api/v1/tasks").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(!response.headers().contains_key("deprecation"));
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let tasks: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(tasks[0]["id"], json!(task.id));
+
+        let response = app
+            .oneshot(Request::builder().uri("/api/tasks").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["deprecation"], "true");
+        assert_eq!(response.headers()["link"], "</api/v1/tasks>; rel=\"successor-version\"");
+    }
+
+    #[tokio::test]
+    async fn raw_run_events_require_the_admin_token() {
+        let (mut state, _temp_dir) = build_state().await;
+        let task_id = Uuid::new_v4();
+        let run = Run::new(task_id, AgentType::OpenCode, "Test prompt".to_string(), "main".to_string());
+        let run_store = state.executor().run_store().clone();
+        run_store.save_run(&run).unwrap();
+        let original = ExecutionEvent::agent_event(
+            run.id,
+            task_id,
+            AgentEvent::Message {
+                content: "key sk-FAKEFAKEFAKEFAKEFAKE".to_string(),
+            },
+        );
+        run_store.append_unredacted_event(task_id, run.id, &original).unwrap();
+
+        let uri = format!("/api/tasks/{}/runs/{}/events/raw", task_id, run.id);
+        let get = |state: AppState, token: Option<&str>| {
+            let mut request = Request::builder().uri(uri.clone());
+            if let Some(token) = token {
+                request = request.header("authorization", format!("Bearer {}", token));
+            }
+            router()
+                .with_state(state)
+                .oneshot(request.body(Body::empty()).unwrap())
+        };
+
+        let disabled = get(state.clone(), Some("secret")).await.unwrap();
+        assert_eq!(disabled.status(), StatusCode::FORBIDDEN);
+
+        state.set_admin_token(Some("secret".to_string()));
+        let denied = get(state.clone(), Some("wrong")).await.unwrap();
+        assert_eq!(denied.status(), StatusCode::UNAUTHORIZED);
+
+        let granted = get(state.clone(), Some("secret")).await.unwrap();
+        assert_eq!(granted.status(), StatusCode::OK);
+        let body = to_bytes(granted.into_body(), usize::MAX).await.unwrap();
+        let events: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(events[0]["content"], "key sk-FAKEFAKEFAKEFAKEFAKE");
+    }
+
+    #[tokio::test]
+    async fn get_run_log_returns_plain_text() {
+        let (state, _temp_dir) = build_state().await;
+        let task = state
+            .task_store()
+            .create(Task::new("Run log test".to_string()))
+            .await
+            .unwrap();
+
+        let run = Run::new(
+            task.id,
+            AgentType::OpenCode,
+            "Test prompt".to_string(),
+            "main".to_string(),
+        );
+        state.executor().run_store().save_run(&run).unwrap();
+        RunLogger::new(state.executor().run_store().clone(), task.id, run.id).log("Run completed");
+
+        let app = router().with_state(state.clone());
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/tasks/{}/runs/{}/log", task.id, run.id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers()["content-type"]
+            .to_str()
+            .unwrap()
+            .starts_with("text/plain"));
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let log = String::from_utf8(body.to_vec()).unwrap();
+        assert!(log.contains("Run completed"));
+
+        let partial = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/tasks/{}/runs/{}/log", task.id, run.id))
+                    .header("range", "bytes=2-5")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(partial.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(
+            partial.headers()["content-range"],
+            format!("bytes 2-5/{}", log.len()).as_str()
+        );
+        assert_eq!(partial.headers()["accept-ranges"], "bytes");
+        let body = to_bytes(partial.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&body[..], &log.as_bytes()[2..6]);
+
+        let missing = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/tasks/{}/runs/{}/log", task.id, Uuid::new_v4()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn large_tool_results_are_offloaded_and_served() {
+        let (state, _temp_dir) = build_state().await;
+        let task = state
+            .task_store()
+            .create(Task::new("Tool result test".to_string()))
+            .await
+            .unwrap();
+        let run = Run::new(
+            task.id,
+            AgentType::OpenCode,
+            "Test prompt".to_string(),
+            "main".to_string(),
+        );
+        let run_store = state.executor().run_store();
+        run_store.save_run(&run).unwrap();
+
+        let dump = "x".repeat(200 * 1024);
+        let event = ExecutionEvent::agent_event(
+            run.id,
+            task.id,
+            AgentEvent::ToolCall {
+                tool: "read".to_string(),
+                args: json!({ "path": "big.txt" }),
+                result: Some(json!({ "content": dump })),
+            },
+        );
+        run_store.append_event(task.id, run.id, &event).unwrap();
+
+        let events = run_store.load_events(task.id, run.id).unwrap();
+        let stored = serde_json::to_value(&events[0]).unwrap();
+        assert_eq!(stored["result"]["offloaded"], true);
+        assert_eq!(stored["result"]["index"], 0);
+        assert!(stored.to_string().len() < 1024);
+
+        let app = router().with_state(state.clone());
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/tasks/{}/runs/{}/tool-results/0", task.id, run.id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let payload: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(payload["content"].as_str().unwrap().len(), 200 * 1024);
+
+        let missing = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/tasks/{}/runs/{}/tool-results/1", task.id, run.id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn list_run_events_includes_total_on_request() {
+        let (state, _temp_dir) = build_state().await;
+        let task = state
+            .task_store()
+            .create(Task::new("Event total test".to_string()))
+            .await
+            .unwrap();
+        let run = Run::new(
+            task.id,
+            AgentType::OpenCode,
+            "Test prompt".to_string(),
+            "main".to_string(),
+        );
+        let run_store = state.executor().run_store();
+        run_store.save_run(&run).unwrap();
+        for i in 0..5 {
+            let event = ExecutionEvent::agent_event(
+                run.id,
+                task.id,
+                AgentEvent::Message {
+                    content: format!("Line {}", i),
+                },
+            );
+            run_store.append_event(task.id, run.id, &event).unwrap();
+        }
+
+        let app = router().with_state(state.clone());
+        let get = |uri: String| {
+            let app = app.clone();
+            async move {
+                let response = app
+                    .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                    .await
+                    .unwrap();
+                assert_eq!(response.status(), StatusCode::OK);
+                let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                serde_json::from_slice::<Value>(&body).unwrap()
+            }
+        };
+
+        let body = get(format!(
+            "/api/tasks/{}/runs/{}/events?limit=2&includeTotal=true",
+            task.id, run.id
+        ))
+        .await;
+        assert_eq!(body["events"].as_array().unwrap().len(), 2);
+        assert_eq!(body["total"], 5);
+
+        let body = get(format!("/api/tasks/{}/runs/{}/events?limit=2", task.id, run.id)).await;
+        assert!(body.get("total").is_none());
+    }
+
+    #[tokio::test]
+    async fn run_events_cursor_pages_survive_compaction() {
+        let (state, _temp_dir) = build_state().await;
+        let task = state
+            .task_store()
+            .create(Task::new("Event cursor test".to_string()))
+            .await
+            .unwrap();
+        let run = Run::new(task.id, AgentType::OpenCode, "Test prompt".to_string(), "main".to_string());
+        let run_store = state.executor().run_store();
+        run_store.save_run(&run).unwrap();
+        for i in 0..6 {
+            let event = ExecutionEvent::agent_event(
+                run.id,
+                task.id,
+                AgentEvent::Message {
+                    content: format!("Line {}", i),
+                },
+            );
+            run_store.append_event(task.id, run.id, &event).unwrap();
+        }
+
+        let app = router().with_state(state.clone());
+        let get = |query: String| {
+            let app = app.clone();
+            let uri = format!("/api/tasks/{}/runs/{}/events?{}", task.id, run.id, query);
+            async move {
+                let response = app
+                    .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                    .await
+                    .unwrap();
+                let status = response.status();
+                let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<Value>(&body).unwrap())
+            }
+        };
+        let contents = |body: &Value| -> Vec<String> {
+            body["events"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|event| event["content"].as_str().unwrap().to_string())
+                .collect()
+        };
+
+        let (_, first) = get("limit=2".to_string()).await;
+        assert_eq!(contents(&first), vec!["Line 0", "Line 1"]);
+        assert_eq!(first["nextOffset"], 2);
+        let cursor = first["nextCursor"].as_str().unwrap().to_string();
+
+        // Drop one seen and one unseen event between pages
+        let dropped = run_store
+            .compact_events(task.id, run.id, &|event| !matches!(event.seq, Some(2) | Some(3)))
+            .unwrap();
+        assert_eq!(dropped, 2);
+
+        // The old offset now skips an event the client never saw
+        let (_, by_offset) = get("limit=2&offset=2".to_string()).await;
+        assert_eq!(contents(&by_offset), vec!["Line 4", "Line 5"]);
+
+        let mut seen = contents(&first);
+        let mut cursor = Some(cursor);
+        while let Some(next) = cursor {
+            let (status, page) = get(format!("limit=2&cursor={}", next)).await;
+            assert_eq!(status, StatusCode::OK);
+            assert!(page["nextOffset"].is_null());
+            seen.extend(contents(&page));
+            cursor = page["nextCursor"].as_str().map(str::to_string);
+        }
+        assert_eq!(seen, vec!["Line 0", "Line 1", "Line 3", "Line 4", "Line 5"]);
 
-pub fn router() -> Router<AppState> {
-    Router::new()
-        .route("/api/tasks", get(list_tasks).post(create_task))
-        .route(
-            "/api/tasks/{id}",
-            get(get_task).patch(update_task).delete(delete_task),
-        )
-        .route(
-            "/api/tasks/{id}/runs",
-            get(list_task_runs).delete(delete_task_runs),
-        )
-        .route("/api/tasks/{id}/runs/{run_id}", delete(delete_run))
-        .route("/api/tasks/{id}/runs/{run_id}/events", get(list_run_events))
-        .route("/api/tasks/{id}/runs/{run_id}/messages", get(list_run_messages))
-}
+        let (status, _) = get("cursor=bogus".to_string()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::sync::Arc;
+    #[tokio::test]
+    async fn purged_run_events_return_gone_but_metadata_survives() {
+        let (state, _temp_dir) = build_state().await;
+        let task = state
+            .task_store()
+            .create(Task::new("Retention test".to_string()))
+            .await
+            .unwrap();
+        let run_store = state.executor().run_store();
+        let mut old_run = Run::new(task.id, AgentType::OpenCode, "Old".to_string(), "main".to_string());
+        old_run.status = ExecutionStatus::Completed;
+        old_run.ended_at = Some(chrono::Utc::now() - chrono::Duration::days(10));
+        let mut recent_run = Run::new(task.id, AgentType::OpenCode, "Recent".to_string(), "main".to_string());
+        recent_run.status = ExecutionStatus::Completed;
+        recent_run.ended_at = Some(chrono::Utc::now());
+        for run in [&old_run, &recent_run] {
+            run_store.save_run(run).unwrap();
+            let event = ExecutionEvent::agent_event(
+                run.id,
+                task.id,
+                AgentEvent::Message {
+                    content: "hello".to_string(),
+                },
+            );
+            run_store.append_event(task.id, run.id, &event).unwrap();
+            run_store
+                .append_message(task.id, run.id, &ChatMessage::assistant("hello".to_string()))
+                .unwrap();
+        }
 
-    use agent_runner::{AgentType, Run};
-    use axum::{body::{to_bytes, Body}, http::Request};
-    use serde_json::{json, Value};
-    use tempfile::TempDir;
-    use tower::ServiceExt;
-    use vk_core::kanban::KanbanStore;
-    use vk_core::project::CreateProjectRequest;
-    use vk_core::task::FileTaskStore;
+        let purged = run_store.purge_events(std::time::Duration::from_secs(7 * 24 * 60 * 60)).unwrap();
+        assert_eq!(purged, 1);
+        let kept = run_store.load_run(task.id, old_run.id).unwrap();
+        assert!(kept.events_purged);
+        assert_eq!(kept.prompt, "Old");
 
-    use crate::gateway::GatewayManager;
+        let app = router().with_state(state.clone());
+        let get = |uri: String| {
+            let app = app.clone();
+            async move {
+                let response = app
+                    .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                    .await
+                    .unwrap();
+                let status = response.status();
+                let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<Value>(&body).unwrap_or(Value::Null))
+            }
+        };
+
+        for path in ["events", "messages", "events/search?q=hello"] {
+            let (status, _) = get(format!("/api/tasks/{}/runs/{}/{}", task.id, old_run.id, path)).await;
+            assert_eq!(status, StatusCode::GONE, "{}", path);
+            let (status, _) = get(format!("/api/tasks/{}/runs/{}/{}", task.id, recent_run.id, path)).await;
+            assert_eq!(status, StatusCode::OK, "{}", path);
+        }
 
-    async fn build_state() -> (AppState, TempDir) {
-        let temp_dir = TempDir::new().unwrap();
-        let data_dir = temp_dir.path().to_path_buf();
+        let (status, body) = get(format!("/api/tasks/{}/runs", task.id)).await;
+        assert_eq!(status, StatusCode::OK);
+        let runs = body["runs"].as_array().unwrap();
+        let purged_flag = |id: Uuid| runs.iter().find(|run| run["id"] == json!(id)).unwrap()["eventsPurged"].clone();
+        assert_eq!(purged_flag(old_run.id), json!(true));
+        assert_eq!(purged_flag(recent_run.id), json!(false));
+    }
 
-        let tasks_path = data_dir.join("tasks.json");
-        let task_store = Arc::new(FileTaskStore::new(tasks_path).await.unwrap());
-        let kanban_path = data_dir.join("kanban.json");
-        let kanban_store = Arc::new(
-            KanbanStore::with_task_store(kanban_path, Arc::clone(&task_store))
-                .await
-                .unwrap(),
+    #[tokio::test]
+    async fn list_task_runs_reports_corrupt_runs_as_warnings() {
+        let (state, temp_dir) = build_state().await;
+        let task = state
+            .task_store()
+            .create(Task::new("Corrupt run test".to_string()))
+            .await
+            .unwrap();
+
+        let run = Run::new(
+            task.id,
+            AgentType::OpenCode,
+            "Test prompt".to_string(),
+            "main".to_string(),
         );
-        let gateway_manager = Arc::new(GatewayManager::with_stores(
-            Arc::clone(&task_store),
-            Arc::clone(&kanban_store),
-        ));
-        let state = AppState::with_stores(
-            data_dir,
-            Arc::clone(&task_store),
-            Arc::clone(&kanban_store),
-            Arc::clone(&gateway_manager),
-        )
-        .await
-        .unwrap();
+        state.executor().run_store().save_run(&run).unwrap();
 
-        (state, temp_dir)
+        let corrupt_id = Uuid::new_v4();
+        let corrupt_dir = temp_dir
+            .path()
+            .join("runs")
+            .join(task.id.to_string())
+            .join(corrupt_id.to_string());
+        std::fs::create_dir_all(&corrupt_dir).unwrap();
+        std::fs::write(corrupt_dir.join("run.json"), "{ not json").unwrap();
+
+        let response = router()
+            .with_state(state)
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/tasks/{}/runs", task.id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["runs"].as_array().unwrap().len(), 1);
+        assert_eq!(body["runs"][0]["id"], json!(run.id));
+        let warnings = body["warnings"].as_array().unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0]["runId"], json!(corrupt_id));
+        assert!(warnings[0]["error"].as_str().unwrap().contains("deserialize"));
     }
 
     #[tokio::test]
@@ -839,6 +2627,62 @@ mod tests {
         assert!(state.executor().list_runs(task.id).unwrap().is_empty());
     }
 
+    #[tokio::test]
+    async fn delete_task_runs_with_status_filter_deletes_only_matching() {
+        let (state, _temp_dir) = build_state().await;
+        let task = state
+            .task_store()
+            .create(Task::new("Delete failed runs".to_string()))
+            .await
+            .unwrap();
+
+        for status in [
+            ExecutionStatus::Failed,
+            ExecutionStatus::Completed,
+            ExecutionStatus::Failed,
+            ExecutionStatus::Running,
+        ] {
+            let mut run = Run::new(
+                task.id,
+                AgentType::OpenCode,
+                "Test prompt".to_string(),
+                "main".to_string(),
+            );
+            run.update_status(status);
+            state.executor().run_store().save_run(&run).unwrap();
+        }
+
+        let app = router().with_state(state.clone());
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("DELETE")
+                    .uri(format!(
+                        "/api/tasks/{}/runs?status=failed&olderThan=2999-01-01T00:00:00Z",
+                        task.id
+                    ))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let payload: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(payload["deleted"], 2);
+
+        let mut remaining: Vec<_> = state
+            .executor()
+            .list_runs(task.id)
+            .unwrap()
+            .into_iter()
+            .map(|run| run.status)
+            .collect();
+        remaining.sort_by_key(|status| status.is_active());
+        assert_eq!(remaining, vec![ExecutionStatus::Completed, ExecutionStatus::Running]);
+    }
+
     #[tokio::test]
     async fn delete_task_runs_rejects_active_runs() {
         let (state, _temp_dir) = build_state().await;
@@ -872,6 +2716,130 @@ mod tests {
         assert_eq!(response.status(), StatusCode::CONFLICT);
     }
 
+    #[tokio::test]
+    async fn search_run_events_returns_matching_offsets() {
+        let (state, _temp_dir) = build_state().await;
+        let task = state
+            .task_store()
+            .create(Task::new("Search events".to_string()))
+            .await
+            .unwrap();
+
+        let run = Run::new(
+            task.id,
+            AgentType::OpenCode,
+            "Test prompt".to_string(),
+            "main".to_string(),
+        );
+        let run_store = state.executor().run_store();
+        run_store.save_run(&run).unwrap();
+        for content in ["compiling crate", "error: mismatched types", "retrying build"] {
+            let event = ExecutionEvent::agent_event(
+                run.id,
+                task.id,
+                AgentEvent::Message {
+                    content: content.to_string(),
+                },
+            );
+            run_store.append_event(task.id, run.id, &event).unwrap();
+        }
+
+        let app = router().with_state(state.clone());
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!(
+                        "/api/tasks/{}/runs/{}/events/search?q=mismatched",
+                        task.id, run.id
+                    ))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let payload: Value = serde_json::from_slice(&body).unwrap();
+        let matches = payload["matches"].as_array().unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0]["offset"], 1);
+        assert_eq!(matches[0]["event"]["content"], "error: mismatched types");
+    }
+
+    #[tokio::test]
+    async fn run_event_summary_counts_events_by_type() {
+        let (state, temp_dir) = build_state().await;
+        let task = state
+            .task_store()
+            .create(Task::new("Summarize events".to_string()))
+            .await
+            .unwrap();
+        let run = Run::new(task.id, AgentType::OpenCode, "Test prompt".to_string(), "main".to_string());
+        let run_store = state.executor().run_store();
+        run_store.save_run(&run).unwrap();
+
+        let agent_event = |event| ExecutionEvent::agent_event(run.id, task.id, event);
+        let events = vec![
+            ExecutionEvent::status_changed(run.id, task.id, ExecutionStatus::Starting, ExecutionStatus::Running),
+            agent_event(AgentEvent::Message { content: "one".to_string() }),
+            agent_event(AgentEvent::Message { content: "two".to_string() }),
+            agent_event(AgentEvent::ToolCall {
+                tool: "read".to_string(),
+                args: json!({ "path": "src/lib.rs" }),
+                result: None,
+            }),
+        ];
+        run_store.append_events(task.id, run.id, &events).unwrap();
+
+        let response = router()
+            .with_state(state.clone())
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/tasks/{}/runs/{}/events/summary", task.id, run.id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let payload: Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(payload["total"], 4);
+        assert_eq!(payload["eventTypes"], json!({ "agent_event": 3, "status_changed": 1 }));
+        assert_eq!(payload["agentEventTypes"], json!({ "message": 2, "tool_call": 1 }));
+        let log_path = temp_dir
+            .path()
+            .join(format!("runs/{}/{}/events.jsonl", task.id, run.id));
+        assert_eq!(payload["bytes"], std::fs::metadata(log_path).unwrap().len());
+    }
+
+    #[tokio::test]
+    async fn create_task_with_wrong_typed_field_names_the_field() {
+        let (state, _temp_dir) = build_state().await;
+
+        let app = router().with_state(state);
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/tasks")
+                    .header("Content-Type", "application/json")
+                    .body(Body::from(json!({ "title": "New task", "tags": "api" }).to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let payload: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(payload["field"], "tags");
+        assert!(payload["reason"].as_str().unwrap().contains("expected a sequence"));
+    }
+
     #[tokio::test]
     async fn create_task_without_project_id_returns_unprocessable_entity() {
         let (state, _temp_dir) = build_state().await;
@@ -965,4 +2933,175 @@ mod tests {
         let payload: Value = serde_json::from_slice(&body).unwrap();
         assert_eq!(payload["projectId"], project.id.to_string());
     }
+
+    #[tokio::test]
+    async fn move_task_relinks_within_a_gateway_and_rejects_cross_gateway_moves() {
+        let (state, _temp_dir) = build_state().await;
+        let gateway_id = Uuid::new_v4();
+        let register = |gateway_id: Uuid, name: &str| {
+            let state = state.clone();
+            let request = CreateProjectRequest {
+                name: name.to_string(),
+                local_path: format!("/tmp/{}", name),
+                remote_url: None,
+                default_branch: None,
+                worktree_dir: None,
+            };
+            async move { state.project_store().register(gateway_id, request).await.unwrap() }
+        };
+        let source = register(gateway_id, "source").await;
+        let sibling = register(gateway_id, "sibling").await;
+        let elsewhere = register(Uuid::new_v4(), "elsewhere").await;
+
+        let task = state
+            .task_store()
+            .create(Task::new("Movable").with_project_id(source.id))
+            .await
+            .unwrap();
+        let run = Run::new(task.id, AgentType::OpenCode, "prompt".to_string(), "main".to_string());
+        state.executor().run_store().save_run(&run).unwrap();
+
+        let move_to = |project_id: Uuid| {
+            router().with_state(state.clone()).oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/tasks/{}/move", task.id))
+                    .header("Content-Type", "application/json")
+                    .body(Body::from(json!({ "projectId": project_id }).to_string()))
+                    .unwrap(),
+            )
+        };
+
+        let response = move_to(sibling.id).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let payload: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(payload["projectId"], sibling.id.to_string());
+        let stored = state.task_store().get(task.id).await.unwrap().unwrap();
+        assert_eq!(stored.project_id, Some(sibling.id));
+        let runs = state.executor().run_store().list_runs(task.id).unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].id, run.id);
+
+        let response = move_to(elsewhere.id).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let stored = state.task_store().get(task.id).await.unwrap().unwrap();
+        assert_eq!(stored.project_id, Some(sibling.id));
+
+        assert_eq!(move_to(Uuid::new_v4()).await.unwrap().status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn create_task_with_oversized_fields_returns_unprocessable_entity() {
+        let (state, _temp_dir) = build_state().await;
+        let project = state
+            .project_store()
+            .register(
+                Uuid::new_v4(),
+                CreateProjectRequest {
+                    name: "test-project".to_string(),
+                    local_path: "/tmp/test-project".to_string(),
+                    remote_url: None,
+                    default_branch: None,
+                    worktree_dir: None,
+                },
+            )
+            .await
+            .unwrap();
+        let limits = *state.task_store().limits();
+
+        let cases = [
+            ("title", json!({ "title": "t".repeat(limits.max_title_len + 1), "projectId": project.id })),
+            (
+                "description",
+                json!({
+                    "title": "New task",
+                    "description": "d".repeat(limits.max_description_len + 1),
+                    "projectId": project.id,
+                }),
+            ),
+        ];
+
+        for (field, body) in cases {
+            let response = router()
+                .with_state(state.clone())
+                .oneshot(
+                    Request::builder()
+                        .method("POST")
+                        .uri("/api/tasks")
+                        .header("Content-Type", "application/json")
+                        .body(Body::from(body.to_string()))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+
+            assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+            let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            let payload: Value = serde_json::from_slice(&body).unwrap();
+            assert!(payload["error"].as_str().unwrap().starts_with(field));
+        }
+
+        assert!(state.task_store().list().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn create_task_from_template_inherits_defaults_and_overrides() {
+        let (state, _temp_dir) = build_state().await;
+        let project = state
+            .project_store()
+            .register(
+                Uuid::new_v4(),
+                CreateProjectRequest {
+                    name: "template-project".to_string(),
+                    local_path: "/tmp/template-project".to_string(),
+                    remote_url: None,
+                    default_branch: None,
+                    worktree_dir: None,
+                },
+            )
+            .await
+            .unwrap();
+        let template = state
+            .template_store()
+            .create(
+                TaskTemplate::new("Bug", "Bug: {title}")
+                    .with_project_id(project.id)
+                    .with_description("Steps to reproduce")
+                    .with_agent_type("claude-code")
+                    .with_model("anthropic/claude")
+                    .with_tags(vec!["bug".to_string()]),
+            )
+            .await
+            .unwrap();
+
+        let app = router().with_state(state);
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/tasks/from-template/{}", template.id))
+                    .header("Content-Type", "application/json")
+                    .body(Body::from(
+                        json!({
+                            "title": "crash on save",
+                            "model": "openai/gpt",
+                        })
+                        .to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let payload: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(payload["title"], "Bug: crash on save");
+        assert_eq!(payload["projectId"], project.id.to_string());
+        assert_eq!(payload["description"], "Steps to reproduce");
+        assert_eq!(payload["agentType"], "claude-code");
+        assert_eq!(payload["model"], "openai/gpt");
+        assert_eq!(payload["tags"], json!(["bug"]));
+    }
 }
@@ -0,0 +1,288 @@
+//! Task template API endpoints
+//!
+//! CRUD for reusable task templates.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::get,
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use vk_core::task::TaskPriority;
+use vk_core::template::TaskTemplate;
+
+use crate::state::AppState;
+
+// ============================================================================
+// Request/Response types
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListTemplatesQuery {
+    #[serde(default)]
+    pub project_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateTemplateRequest {
+    pub name: String,
+    pub title_pattern: String,
+    #[serde(default)]
+    pub project_id: Option<Uuid>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub priority: Option<TaskPriority>,
+    #[serde(default)]
+    pub agent_type: Option<String>,
+    #[serde(default)]
+    pub base_branch: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateTemplateRequest {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub title_pattern: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub priority: Option<TaskPriority>,
+    #[serde(default)]
+    pub agent_type: Option<String>,
+    #[serde(default)]
+    pub base_branch: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub project_id: Option<Uuid>,
+    pub title_pattern: String,
+    pub description: Option<String>,
+    pub priority: TaskPriority,
+    pub agent_type: Option<String>,
+    pub base_branch: Option<String>,
+    pub model: Option<String>,
+    pub tags: Vec<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<TaskTemplate> for TemplateResponse {
+    fn from(template: TaskTemplate) -> Self {
+        Self {
+            id: template.id,
+            name: template.name,
+            project_id: template.project_id,
+            title_pattern: template.title_pattern,
+            description: template.description,
+            priority: template.priority,
+            agent_type: template.agent_type,
+            base_branch: template.base_branch,
+            model: template.model,
+            tags: template.tags,
+            created_at: template.created_at.to_rfc3339(),
+            updated_at: template.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ErrorResponse {
+    pub error: String,
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// GET /api/templates - List templates
+async fn list_templates(
+    State(state): State<AppState>,
+    Query(query): Query<ListTemplatesQuery>,
+) -> Json<Vec<TemplateResponse>> {
+    let templates = state.template_store().list(query.project_id).await;
+    Json(templates.into_iter().map(TemplateResponse::from).collect())
+}
+
+/// POST /api/templates - Create a template
+async fn create_template(
+    State(state): State<AppState>,
+    Json(req): Json<CreateTemplateRequest>,
+) -> Result<(StatusCode, Json<TemplateResponse>), (StatusCode, Json<ErrorResponse>)> {
+    if req.name.trim().is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "Name cannot be empty".to_string(),
+            }),
+        ));
+    }
+
+    if let Some(project_id) = req.project_id {
+        if state.project_store().get(project_id).await.is_none() {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Project {} not found", project_id),
+                }),
+            ));
+        }
+    }
+
+    let mut template = TaskTemplate::new(req.name, req.title_pattern);
+    template.project_id = req.project_id;
+    template.description = req.description;
+    template.priority = req.priority.unwrap_or_default();
+    template.agent_type = req.agent_type;
+    template.base_branch = req.base_branch;
+    template.model = req.model;
+    template.tags = req.tags.unwrap_or_default();
+
+    let created = state.template_store().create(template).await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+    })?;
+
+    Ok((StatusCode::CREATED, Json(TemplateResponse::from(created))))
+}
+
+/// GET /api/templates/:id - Get a template
+async fn get_template(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<TemplateResponse>, (StatusCode, Json<ErrorResponse>)> {
+    state
+        .template_store()
+        .get(id)
+        .await
+        .map(|t| Json(TemplateResponse::from(t)))
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Template {} not found", id),
+                }),
+            )
+        })
+}
+
+/// PUT /api/templates/:id - Update a template
+async fn update_template(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<UpdateTemplateRequest>,
+) -> Result<Json<TemplateResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let mut template = state.template_store().get(id).await.ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Template {} not found", id),
+            }),
+        )
+    })?;
+
+    if let Some(name) = req.name {
+        if name.trim().is_empty() {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: "Name cannot be empty".to_string(),
+                }),
+            ));
+        }
+        template.name = name;
+    }
+    if let Some(title_pattern) = req.title_pattern {
+        template.title_pattern = title_pattern;
+    }
+    if let Some(description) = req.description {
+        template.description = Some(description);
+    }
+    if let Some(priority) = req.priority {
+        template.priority = priority;
+    }
+    if let Some(agent_type) = req.agent_type {
+        template.agent_type = Some(agent_type);
+    }
+    if let Some(base_branch) = req.base_branch {
+        template.base_branch = Some(base_branch);
+    }
+    if let Some(model) = req.model {
+        template.model = Some(model);
+    }
+    if let Some(tags) = req.tags {
+        template.tags = tags;
+    }
+
+    let updated = state.template_store().update(template).await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+    })?;
+
+    Ok(Json(TemplateResponse::from(updated)))
+}
+
+/// DELETE /api/templates/:id - Delete a template
+async fn delete_template(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+    let removed = state.template_store().delete(id).await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+    })?;
+
+    match removed {
+        Some(_) => Ok(StatusCode::NO_CONTENT),
+        None => Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Template {} not found", id),
+            }),
+        )),
+    }
+}
+
+// ============================================================================
+// Router
+// ============================================================================
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/api/templates", get(list_templates).post(create_template))
+        .route(
+            "/api/templates/{id}",
+            get(get_template).put(update_template).delete(delete_template),
+        )
+}
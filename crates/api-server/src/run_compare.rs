@@ -0,0 +1,185 @@
+//! Side-by-side comparison of two runs of a task
+//!
+//! Used to chase flaky tasks: reports how event-type counts, durations,
+//! outcomes and touched files differ between two runs.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use agent_runner::{AgentEvent, ExecutionEvent, ExecutionEventType, ExecutionStatus, Run};
+use serde::Serialize;
+use uuid::Uuid;
+
+/// Outcome of one of the compared runs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunSide {
+    pub run_id: Uuid,
+    pub status: ExecutionStatus,
+    pub duration_ms: Option<u64>,
+    pub exit_code: Option<i32>,
+    pub error: Option<String>,
+}
+
+/// Count of one event type in both runs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventCountDelta {
+    pub a: usize,
+    pub b: usize,
+    /// `b - a`
+    pub delta: i64,
+}
+
+/// Differences between run `a` and run `b`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunComparison {
+    pub a: RunSide,
+    pub b: RunSide,
+    pub status_changed: bool,
+    /// `b - a`, when both runs have a duration
+    pub duration_delta_ms: Option<i64>,
+    /// Per event type; agent events are keyed by their agent event type
+    pub event_counts: BTreeMap<String, EventCountDelta>,
+    pub files_only_in_a: Vec<String>,
+    pub files_only_in_b: Vec<String>,
+    pub files_in_both: Vec<String>,
+}
+
+/// Event type name used for counting
+fn event_kind(event: &ExecutionEvent) -> &'static str {
+    match &event.event {
+        ExecutionEventType::StatusChanged { .. } => "status_changed",
+        ExecutionEventType::SessionStarted { .. } => "session_started",
+        ExecutionEventType::SessionEnded { .. } => "session_ended",
+        ExecutionEventType::Progress { .. } => "progress",
+        ExecutionEventType::AgentEvent { event } => match event {
+            AgentEvent::Thinking { .. } => "thinking",
+            AgentEvent::Command { .. } => "command",
+            AgentEvent::FileChange { .. } => "file_change",
+            AgentEvent::ToolCall { .. } => "tool_call",
+            AgentEvent::Message { .. } => "message",
+            AgentEvent::Error { .. } => "error",
+            AgentEvent::Completed { .. } => "completed",
+            AgentEvent::RawOutput { .. } => "raw_output",
+        },
+    }
+}
+
+fn count_events(events: &[ExecutionEvent]) -> BTreeMap<&'static str, usize> {
+    let mut counts = BTreeMap::new();
+    for event in events {
+        *counts.entry(event_kind(event)).or_insert(0) += 1;
+    }
+    counts
+}
+
+fn changed_files(events: &[ExecutionEvent]) -> BTreeSet<String> {
+    events
+        .iter()
+        .filter_map(|event| match &event.event {
+            ExecutionEventType::AgentEvent {
+                event: AgentEvent::FileChange { path, .. },
+            } => Some(path.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn side(run: &Run) -> RunSide {
+    RunSide {
+        run_id: run.id,
+        status: run.status,
+        duration_ms: run.duration_ms,
+        exit_code: run.exit_code,
+        error: run.error.clone(),
+    }
+}
+
+/// Compare run `a` with run `b` given their persisted events
+pub fn compare_runs(a: &Run, a_events: &[ExecutionEvent], b: &Run, b_events: &[ExecutionEvent]) -> RunComparison {
+    let a_counts = count_events(a_events);
+    let b_counts = count_events(b_events);
+    let event_counts = a_counts
+        .keys()
+        .chain(b_counts.keys())
+        .map(|kind| {
+            let a = a_counts.get(kind).copied().unwrap_or(0);
+            let b = b_counts.get(kind).copied().unwrap_or(0);
+            let delta = b as i64 - a as i64;
+            (kind.to_string(), EventCountDelta { a, b, delta })
+        })
+        .collect();
+
+    let a_files = changed_files(a_events);
+    let b_files = changed_files(b_events);
+
+    RunComparison {
+        a: side(a),
+        b: side(b),
+        status_changed: a.status != b.status,
+        duration_delta_ms: a
+            .duration_ms
+            .zip(b.duration_ms)
+            .map(|(a, b)| b as i64 - a as i64),
+        event_counts,
+        files_only_in_a: a_files.difference(&b_files).cloned().collect(),
+        files_only_in_b: b_files.difference(&a_files).cloned().collect(),
+        files_in_both: a_files.intersection(&b_files).cloned().collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use agent_runner::{AgentType, FileAction};
+
+    fn file_change(run: &Run, path: &str) -> ExecutionEvent {
+        ExecutionEvent::new(
+            run.id,
+            run.task_id,
+            ExecutionEventType::AgentEvent {
+                event: AgentEvent::FileChange {
+                    path: path.to_string(),
+                    action: FileAction::Modified,
+                    diff: None,
+                    additions: None,
+                    deletions: None,
+                },
+            },
+        )
+    }
+
+    #[test]
+    fn reports_deltas_between_passing_and_failing_runs() {
+        let task_id = Uuid::new_v4();
+        let mut a = Run::new(task_id, AgentType::OpenCode, "Prompt".to_string(), "main".to_string());
+        a.status = ExecutionStatus::Completed;
+        a.duration_ms = Some(1_000);
+        let mut b = Run::new(task_id, AgentType::OpenCode, "Prompt".to_string(), "main".to_string());
+        b.status = ExecutionStatus::Failed;
+        b.duration_ms = Some(4_000);
+        b.error = Some("exit 1".to_string());
+
+        let progress = |run: &Run| ExecutionEvent::progress(run.id, task_id, "Step".to_string(), None);
+        let a_events = vec![progress(&a), file_change(&a, "src/lib.rs"), file_change(&a, "README.md")];
+        let b_events = vec![
+            progress(&b),
+            progress(&b),
+            progress(&b),
+            file_change(&b, "src/lib.rs"),
+            file_change(&b, "src/main.rs"),
+        ];
+
+        let comparison = compare_runs(&a, &a_events, &b, &b_events);
+
+        assert!(comparison.status_changed);
+        assert_eq!(comparison.b.error.as_deref(), Some("exit 1"));
+        assert_eq!(comparison.duration_delta_ms, Some(3_000));
+        assert_eq!(comparison.event_counts["progress"], EventCountDelta { a: 1, b: 3, delta: 2 });
+        assert_eq!(comparison.event_counts["file_change"], EventCountDelta { a: 2, b: 2, delta: 0 });
+        assert_eq!(comparison.files_only_in_a, vec!["README.md".to_string()]);
+        assert_eq!(comparison.files_only_in_b, vec!["src/main.rs".to_string()]);
+        assert_eq!(comparison.files_in_both, vec!["src/lib.rs".to_string()]);
+    }
+}
@@ -7,21 +7,24 @@ use serde::{Deserialize, Serialize};
 use socketioxide::extract::{Data, SocketRef, State};
 use socketioxide::{SocketIo, TransportType};
 use std::collections::HashMap;
+use std::future::Future;
 use std::path::PathBuf;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::time::Duration;
+use tokio::sync::{mpsc, RwLock};
 use tracing::{error, info, warn};
+use uuid::Uuid;
 
+use agent_runner::{ChatMessage, ExecutionEvent, MessageContentType, RunStorage};
 use vk_core::agent::{OpencodeClient, OpencodeConfig};
 use vk_core::kanban::{KanbanStore, KanbanTaskStatus};
 use vk_core::task::{FileTaskStore, TaskRepository};
 
+use crate::env::env_parse;
+
 /// Task execution session
-#[allow(dead_code)]
 struct TaskSession {
-    task_id: String,
     client: OpencodeClient,
-    status: String,
 }
 
 /// Shared state for Socket.IO handlers
@@ -30,6 +33,8 @@ pub struct SocketState {
     pub kanban_store: Arc<KanbanStore>,
     pub task_store: Arc<FileTaskStore>,
     pub data_dir: PathBuf,
+    /// Persisted runs, used to replay missed output on reconnect
+    pub run_store: Arc<dyn RunStorage>,
     /// Active task execution sessions
     sessions: Arc<RwLock<HashMap<String, Arc<RwLock<TaskSession>>>>>,
 }
@@ -39,10 +44,12 @@ impl SocketState {
         kanban_store: Arc<KanbanStore>,
         task_store: Arc<FileTaskStore>,
         data_dir: PathBuf,
+        run_store: Arc<dyn RunStorage>,
     ) -> Self {
         Self {
             kanban_store,
             task_store,
+            run_store,
             data_dir,
             sessions: Arc::new(RwLock::new(HashMap::new())),
         }
@@ -64,6 +71,9 @@ pub struct MoveTaskPayload {
     pub task_id: String,
     pub target_status: KanbanTaskStatus,
     pub target_index: Option<usize>,
+    /// Allow moving the task out of a locked Done column
+    #[serde(default)]
+    pub reopen: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -78,6 +88,15 @@ pub struct TaskHistoryPayload {
     pub task_id: String,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecuteTaskPayload {
+    pub task_id: String,
+    /// Continue the task's previous OpenCode session when it still exists
+    #[serde(default)]
+    pub reuse_session: bool,
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TaskHistoryResponse {
@@ -119,6 +138,7 @@ pub struct TaskMessage {
     pub id: String,
     pub role: String,
     pub content: String,
+    pub content_type: MessageContentType,
     pub timestamp: u64,
 }
 
@@ -128,13 +148,72 @@ pub struct TaskStopPayload {
     pub task_id: String,
 }
 
+/// Default number of events replayed per `task:subscribe` request
+const REPLAY_EVENT_LIMIT: usize = 500;
+
+/// Upper bound for a client-requested `maxReplay`
+const MAX_REPLAY_EVENT_LIMIT: usize = 5000;
+
+/// Position in a run's persisted output the client has already seen
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayCursor {
+    pub run_id: Option<Uuid>,
+    #[serde(default)]
+    pub event_offset: usize,
+    #[serde(default)]
+    pub message_offset: usize,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskSubscribePayload {
+    pub task_id: String,
+    #[serde(default)]
+    pub cursor: Option<ReplayCursor>,
+    /// Cap on historical events replayed before switching to live output
+    #[serde(default)]
+    pub max_replay: Option<usize>,
+}
+
+/// Marker sent when a replay stopped at the `maxReplay` cap
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayTruncatedPayload {
+    pub task_id: String,
+    pub run_id: Uuid,
+    /// Events replayed in this batch
+    pub replayed: usize,
+    /// Event offset to page the rest from via the run events endpoint
+    pub next_offset: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskReplayPayload {
+    pub task_id: String,
+    pub run_id: Option<Uuid>,
+    pub events: Vec<ExecutionEvent>,
+    pub messages: Vec<ChatMessage>,
+    /// Cursor to send on the next reconnect
+    pub cursor: ReplayCursor,
+    /// More events remain past `cursor`
+    pub has_more: bool,
+    /// Set when the replay was capped by `maxReplay`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub truncated: Option<ReplayTruncatedPayload>,
+}
+
 // ============ Event Handlers ============
 
 /// Handle new socket connection
-pub async fn on_connect(socket: SocketRef, State(_state): State<SocketState>) {
+pub async fn on_connect(socket: SocketRef, State(state): State<SocketState>) {
     let id = socket.id.to_string();
     info!("Client connected: {}", id);
 
+    // Push a fresh board so reconnecting clients catch up on missed moves
+    handle_request_sync(socket.clone(), state).await;
+
     // Register event handlers
     socket.on(
         "kanban:request-sync",
@@ -169,6 +248,13 @@ pub async fn on_connect(socket: SocketRef, State(_state): State<SocketState>) {
     });
 
     // Task events
+    socket.on(
+        "task:subscribe",
+        |socket: SocketRef, State(state): State<SocketState>, Data(data): Data<TaskSubscribePayload>| async move {
+            handle_task_subscribe(socket, state, data).await;
+        },
+    );
+
     socket.on(
         "task:history",
         |socket: SocketRef, Data(data): Data<TaskHistoryPayload>| async move {
@@ -185,7 +271,7 @@ pub async fn on_connect(socket: SocketRef, State(_state): State<SocketState>) {
 
     socket.on(
         "task:execute",
-        |socket: SocketRef, State(state): State<SocketState>, Data(data): Data<TaskHistoryPayload>| async move {
+        |socket: SocketRef, State(state): State<SocketState>, Data(data): Data<ExecuteTaskPayload>| async move {
             let task_id = data.task_id.clone();
             info!("Task execution requested: {}", task_id);
 
@@ -235,6 +321,19 @@ pub async fn on_connect(socket: SocketRef, State(_state): State<SocketState>) {
             // Create prompt from task info
             let prompt = format!("{}\n\n{}", title, description);
 
+            // Session to continue, if the caller asked for it
+            let stored_task_id = Uuid::parse_str(&task_id).ok();
+            let previous_session = match stored_task_id {
+                Some(id) if data.reuse_session => state
+                    .task_store
+                    .get(id)
+                    .await
+                    .ok()
+                    .flatten()
+                    .and_then(|task| task.opencode_session_id),
+                _ => None,
+            };
+
             // Create OpenCode client
             let config = OpencodeConfig {
                 cwd: state.data_dir.clone(),
@@ -243,11 +342,7 @@ pub async fn on_connect(socket: SocketRef, State(_state): State<SocketState>) {
             let client = OpencodeClient::new(config);
 
             // Create task session
-            let session = Arc::new(RwLock::new(TaskSession {
-                task_id: task_id.clone(),
-                client,
-                status: "starting".to_string(),
-            }));
+            let session = Arc::new(RwLock::new(TaskSession { client }));
 
             // Store session
             {
@@ -260,6 +355,7 @@ pub async fn on_connect(socket: SocketRef, State(_state): State<SocketState>) {
             let task_id_clone = task_id.clone();
             let sessions = state.sessions.clone();
             let kanban_store = state.kanban_store.clone();
+            let task_store = state.task_store.clone();
 
             // Spawn task execution in background
             tokio::spawn(async move {
@@ -336,7 +432,7 @@ pub async fn on_connect(socket: SocketRef, State(_state): State<SocketState>) {
                             "bash.output" => {
                                 event.properties.get("output")
                                     .and_then(|o| o.as_str())
-                                    .map(|s| format!("{}", s))
+                                    .map(|s| s.to_string())
                             }
                             // Claude thinking
                             "assistant.thinking" => {
@@ -392,6 +488,7 @@ pub async fn on_connect(socket: SocketRef, State(_state): State<SocketState>) {
                                     id: format!("msg-{}", message_counter),
                                     role: "assistant".to_string(),
                                     content: text,
+                                    content_type: MessageContentType::Markdown,
                                     timestamp: std::time::SystemTime::now()
                                         .duration_since(std::time::UNIX_EPOCH)
                                         .unwrap()
@@ -407,9 +504,24 @@ pub async fn on_connect(socket: SocketRef, State(_state): State<SocketState>) {
                 // Run the actual task
                 let result = {
                     let session_guard = session.read().await;
-                    session_guard.client.run(&prompt).await
+                    session_guard
+                        .client
+                        .run_in_session(&prompt, previous_session.as_deref())
+                        .await
                 };
 
+                // Remember the session so a later run can continue it
+                if let (Ok(session_id), Some(id)) = (&result, stored_task_id) {
+                    if let Ok(Some(mut task)) = task_store.get(id).await {
+                        if task.opencode_session_id.as_deref() != Some(session_id.as_str()) {
+                            task.opencode_session_id = Some(session_id.clone());
+                            if let Err(e) = task_store.update(task).await {
+                                warn!("Failed to store OpenCode session for task {}: {}", id, e);
+                            }
+                        }
+                    }
+                }
+
                 // Wait a bit for remaining events
                 tokio::time::sleep(std::time::Duration::from_millis(500)).await;
                 event_handle.abort();
@@ -514,6 +626,106 @@ async fn handle_request_sync(socket: SocketRef, state: SocketState) {
     }
 }
 
+async fn handle_task_subscribe(socket: SocketRef, state: SocketState, data: TaskSubscribePayload) {
+    let task_id = match Uuid::parse_str(&data.task_id) {
+        Ok(id) => id,
+        Err(_) => {
+            let _ = socket.emit("task:error", &TaskErrorPayload {
+                task_id: data.task_id,
+                error: "Invalid task ID".to_string(),
+            });
+            return;
+        }
+    };
+
+    let cursor = data.cursor.unwrap_or_default();
+    let max_replay = data
+        .max_replay
+        .unwrap_or(REPLAY_EVENT_LIMIT)
+        .min(MAX_REPLAY_EVENT_LIMIT);
+    match replay_since(state.run_store.as_ref(), task_id, &cursor, max_replay) {
+        Ok(payload) => {
+            info!(
+                "Replaying {} events and {} messages for task {} to {}",
+                payload.events.len(),
+                payload.messages.len(),
+                task_id,
+                socket.id
+            );
+            let _ = socket.emit("task:replay", &payload);
+            if let Some(marker) = &payload.truncated {
+                let _ = socket.emit("task:replay:truncated", marker);
+            }
+        }
+        Err(e) => {
+            warn!("Failed to replay task {}: {}", task_id, e);
+            let _ = socket.emit("task:error", &TaskErrorPayload {
+                task_id: data.task_id,
+                error: e.to_string(),
+            });
+        }
+    }
+}
+
+/// Collect persisted output of a task's latest run past `cursor`
+///
+/// A cursor pointing at an older run replays the latest run from the start.
+/// At most `max_replay` events are returned; past that the payload carries a
+/// truncation marker with the offset to page the rest from.
+fn replay_since(
+    run_store: &dyn RunStorage,
+    task_id: Uuid,
+    cursor: &ReplayCursor,
+    max_replay: usize,
+) -> agent_runner::Result<TaskReplayPayload> {
+    let Some(latest) = run_store.list_runs(task_id)?.into_iter().next() else {
+        return Ok(TaskReplayPayload {
+            task_id: task_id.to_string(),
+            run_id: None,
+            events: Vec::new(),
+            messages: Vec::new(),
+            cursor: ReplayCursor::default(),
+            has_more: false,
+            truncated: None,
+        });
+    };
+
+    let (event_offset, message_offset) = if cursor.run_id == Some(latest.id) {
+        (cursor.event_offset, cursor.message_offset)
+    } else {
+        (0, 0)
+    };
+
+    let (events, has_more) =
+        run_store.load_events_paginated(task_id, latest.id, event_offset, max_replay)?;
+    let messages: Vec<ChatMessage> = run_store
+        .load_messages(task_id, latest.id)?
+        .into_iter()
+        .skip(message_offset)
+        .collect();
+
+    let truncated = has_more.then(|| ReplayTruncatedPayload {
+        task_id: task_id.to_string(),
+        run_id: latest.id,
+        replayed: events.len(),
+        next_offset: event_offset + events.len(),
+    });
+
+    Ok(TaskReplayPayload {
+        task_id: task_id.to_string(),
+        run_id: Some(latest.id),
+        cursor: ReplayCursor {
+            run_id: Some(latest.id),
+            event_offset: event_offset + events.len(),
+            message_offset: message_offset + messages.len(),
+        },
+        events,
+        messages,
+        has_more,
+        truncated,
+    })
+}
+
 async fn handle_create_task(socket: SocketRef, state: SocketState, data: CreateTaskPayload) {
     info!("Creating task: {}", data.title);
 
@@ -542,7 +754,7 @@ async fn handle_move_task(socket: SocketRef, state: SocketState, data: MoveTaskP
 
     match state
         .kanban_store
-        .move_task(&data.task_id, data.target_status, data.target_index)
+        .move_task_checked(&data.task_id, data.target_status, data.target_index, data.reopen)
         .await
     {
         Ok(true) => {
@@ -615,3 +827,175 @@ pub fn create_socket_layer(state: SocketState) -> (socketioxide::layer::SocketIo
 
     (layer, io)
 }
+
+/// Environment variable with the milliseconds board changes are coalesced over
+pub const KANBAN_SYNC_DEBOUNCE_ENV: &str = "VK_KANBAN_SYNC_DEBOUNCE_MS";
+
+/// Coalescing window when none is configured
+const DEFAULT_KANBAN_SYNC_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Load the `kanban:sync` coalescing window from the process environment
+pub fn kanban_sync_debounce_from_env() -> Duration {
+    env_parse(KANBAN_SYNC_DEBOUNCE_ENV).map_or(DEFAULT_KANBAN_SYNC_DEBOUNCE, Duration::from_millis)
+}
+
+/// Coalesces board changes into one `kanban:sync` per window
+///
+/// The first request schedules an emit `window` later and requests made
+/// before it fires join it. The emit reads the board when it fires, so the
+/// latest state of the window is always sent.
+#[derive(Clone)]
+pub struct KanbanSyncDebouncer {
+    requests: mpsc::UnboundedSender<()>,
+}
+
+impl KanbanSyncDebouncer {
+    /// Start the debouncer task, running `emit` once per window with requests
+    pub fn spawn<F, Fut>(window: Duration, emit: F) -> Self
+    where
+        F: Fn() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let (requests, mut rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while rx.recv().await.is_some() {
+                tokio::time::sleep(window).await;
+                while rx.try_recv().is_ok() {}
+                emit().await;
+            }
+        });
+        Self { requests }
+    }
+
+    /// Ask for a `kanban:sync` at the end of the current window
+    pub fn request(&self) {
+        let _ = self.requests.send(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use agent_runner::{AgentType, Run, RunStore};
+    use tempfile::TempDir;
+
+    #[test]
+    fn reconnect_with_cursor_replays_missed_output() {
+        let temp_dir = TempDir::new().unwrap();
+        let run_store = RunStore::new(temp_dir.path());
+        let task_id = Uuid::new_v4();
+        let run = Run::new(task_id, AgentType::OpenCode, "Prompt".to_string(), "main".to_string());
+        run_store.save_run(&run).unwrap();
+
+        let progress = |i: usize| ExecutionEvent::progress(run.id, task_id, format!("Step {}", i), None);
+        for i in 0..3 {
+            run_store.append_event(task_id, run.id, &progress(i)).unwrap();
+        }
+        run_store
+            .append_message(task_id, run.id, &ChatMessage::user("Start".to_string()))
+            .unwrap();
+
+        // Initial subscribe sees everything persisted so far
+        let first = replay_since(&run_store, task_id, &ReplayCursor::default(), REPLAY_EVENT_LIMIT).unwrap();
+        assert_eq!(first.events.len(), 3);
+        assert_eq!(first.messages.len(), 1);
+
+        // Output produced while the client is disconnected
+        for i in 3..5 {
+            run_store.append_event(task_id, run.id, &progress(i)).unwrap();
+        }
+        run_store
+            .append_message(task_id, run.id, &ChatMessage::assistant("Done".to_string()))
+            .unwrap();
+
+        let replay = replay_since(&run_store, task_id, &first.cursor, REPLAY_EVENT_LIMIT).unwrap();
+        assert_eq!(replay.run_id, Some(run.id));
+        assert_eq!(replay.events.len(), 2);
+        assert_eq!(replay.messages.len(), 1);
+        assert_eq!(replay.messages[0].content, "Done");
+        assert!(!replay.has_more);
+        assert!(replay.truncated.is_none());
+        assert_eq!(
+            replay.cursor,
+            ReplayCursor {
+                run_id: Some(run.id),
+                event_offset: 5,
+                message_offset: 2,
+            }
+        );
+
+        // A stale cursor from another run replays the latest run in full
+        let stale = ReplayCursor {
+            run_id: Some(Uuid::new_v4()),
+            event_offset: 4,
+            message_offset: 2,
+        };
+        assert_eq!(replay_since(&run_store, task_id, &stale, REPLAY_EVENT_LIMIT).unwrap().events.len(), 5);
+    }
+
+    #[test]
+    fn large_backlog_replay_is_capped_with_marker() {
+        let temp_dir = TempDir::new().unwrap();
+        let run_store = RunStore::new(temp_dir.path());
+        let task_id = Uuid::new_v4();
+        let run = Run::new(task_id, AgentType::OpenCode, "Prompt".to_string(), "main".to_string());
+        run_store.save_run(&run).unwrap();
+        for i in 0..1200 {
+            let event = ExecutionEvent::progress(run.id, task_id, format!("Step {}", i), None);
+            run_store.append_event(task_id, run.id, &event).unwrap();
+        }
+
+        let replay = replay_since(&run_store, task_id, &ReplayCursor::default(), REPLAY_EVENT_LIMIT).unwrap();
+        assert_eq!(replay.events.len(), REPLAY_EVENT_LIMIT);
+        assert!(replay.has_more);
+        assert_eq!(
+            replay.truncated,
+            Some(ReplayTruncatedPayload {
+                task_id: task_id.to_string(),
+                run_id: run.id,
+                replayed: REPLAY_EVENT_LIMIT,
+                next_offset: REPLAY_EVENT_LIMIT,
+            })
+        );
+
+        // A smaller cap resumes from the cursor of the previous batch
+        let next = replay_since(&run_store, task_id, &replay.cursor, 100).unwrap();
+        assert_eq!(next.events.len(), 100);
+        assert_eq!(next.truncated.as_ref().map(|t| t.next_offset), Some(600));
+    }
+
+    #[tokio::test]
+    async fn rapid_board_moves_are_coalesced_into_one_sync() {
+        let temp_dir = TempDir::new().unwrap();
+        let kanban_store = Arc::new(KanbanStore::new(temp_dir.path().join("kanban.json")).await.unwrap());
+        let emitted = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let debouncer = {
+            let kanban_store = Arc::clone(&kanban_store);
+            let emitted = Arc::clone(&emitted);
+            KanbanSyncDebouncer::spawn(Duration::from_millis(100), move || {
+                let kanban_store = Arc::clone(&kanban_store);
+                let emitted = Arc::clone(&emitted);
+                async move {
+                    let board_state = kanban_store.get_state().await;
+                    emitted.lock().unwrap().push(board_state);
+                }
+            })
+        };
+
+        let task = kanban_store.create_task("Busy task", None).await.unwrap();
+        for status in [
+            KanbanTaskStatus::Doing,
+            KanbanTaskStatus::Done,
+            KanbanTaskStatus::Todo,
+            KanbanTaskStatus::Done,
+        ] {
+            kanban_store.move_task(&task.id, status, None).await.unwrap();
+            debouncer.request();
+        }
+        tokio::time::sleep(Duration::from_millis(400)).await;
+
+        let emitted = emitted.lock().unwrap();
+        assert_eq!(emitted.len(), 1);
+        assert_eq!(emitted[0].tasks[&task.id].status, KanbanTaskStatus::Done);
+    }
+}
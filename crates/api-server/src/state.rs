@@ -1,17 +1,123 @@
 //! Application state
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use serde::Serialize;
+use tokio::sync::{broadcast, RwLock};
 use socketioxide::SocketIo;
+use uuid::Uuid;
 
-use agent_runner::{ExecutorConfig, TaskExecutor};
+use agent_runner::{
+    validate_agent_args, AgentType, DEFAULT_EVENT_QUEUE_CAPACITY, ExecutionStatus, ExecutorConfig, RawOutputPersistence,
+    SystemClock, TaskExecutor,
+};
 use git_worktree::WorktreeConfig;
 use vk_core::kanban::KanbanStore;
-use vk_core::project::ProjectStore;
-use vk_core::task::FileTaskStore;
+use vk_core::project::{ProjectStore, SecretKey};
+use vk_core::task::{FileTaskStore, TaskRepository};
+use vk_core::template::TemplateStore;
 
+use crate::context_files::context_files_max_bytes_from_env;
+use crate::env::{env_parse, positive_env_or_default};
+use crate::gateway::output::output_max_bytes_from_env;
 use crate::gateway::GatewayManager;
+use crate::identity::trust_user_header_from_env;
+use crate::locale::Locale;
+use crate::redaction::{admin_token_from_env, redactor_from_env};
+use crate::rollup::RollupCache;
+use crate::socket::{kanban_sync_debounce_from_env, KanbanSyncDebouncer};
+
+/// Published whenever a run reaches a terminal status
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunLifecycleEvent {
+    pub execution_id: Uuid,
+    pub task_id: Uuid,
+    pub status: ExecutionStatus,
+}
+
+/// Environment variable selecting which raw output events are persisted
+///
+/// Suffix with an agent name (e.g. `_CLAUDE_CODE`) for a per-agent override.
+const RAW_OUTPUT_PERSISTENCE_ENV: &str = "VK_PERSIST_RAW_OUTPUT";
+
+fn raw_output_persistence_from_env(var: &str) -> Option<RawOutputPersistence> {
+    let raw = std::env::var(var).ok()?;
+    let policy = RawOutputPersistence::parse(&raw);
+    if policy.is_none() {
+        tracing::warn!("Ignoring invalid {} value {:?}", var, raw);
+    }
+    policy
+}
+
+/// Environment variable with extra agent command line arguments, as a JSON array
+///
+/// Suffix with an agent name (e.g. `_CLAUDE_CODE`) for arguments passed to
+/// that agent only, after the shared ones.
+const AGENT_ARGS_ENV: &str = "VK_AGENT_ARGS";
+
+fn agent_args_from_env(var: &str) -> Option<Vec<String>> {
+    let raw = std::env::var(var).ok()?;
+    let args = serde_json::from_str::<Vec<String>>(&raw)
+        .map_err(|e| e.to_string())
+        .and_then(|args| validate_agent_args(&args).map(|()| args).map_err(|e| e.to_string()));
+    match args {
+        Ok(args) => Some(args),
+        Err(e) => {
+            tracing::warn!("Ignoring invalid {} value {:?}: {}", var, raw, e);
+            None
+        }
+    }
+}
+
+/// Per-agent variable name, e.g. `VK_AGENT_ARGS_CLAUDE_CODE`
+fn agent_env_var(base: &str, agent: &AgentType) -> String {
+    format!("{}_{}", base, agent.as_str().replace('-', "_").to_uppercase())
+}
+
+/// Environment variable sizing each run's queue of events awaiting persistence
+const EVENT_QUEUE_CAPACITY_ENV: &str = "VK_EVENT_QUEUE_CAPACITY";
+
+fn event_queue_capacity_from_env() -> usize {
+    positive_env_or_default(EVENT_QUEUE_CAPACITY_ENV, DEFAULT_EVENT_QUEUE_CAPACITY)
+}
+
+/// Environment variable with the directory the server may clone projects into
+const PROJECTS_ROOT_ENV: &str = "VK_PROJECTS_ROOT";
+
+/// Environment variable with the base64 key sealing secret project env values
+const SECRET_KEY_ENV: &str = "VK_SECRET_KEY";
+
+/// Key from `VK_SECRET_KEY`; a malformed key is an error, as stored
+/// secrets could not be opened at dispatch without it
+fn secret_key_from_env() -> vk_core::Result<Option<SecretKey>> {
+    let Ok(raw) = std::env::var(SECRET_KEY_ENV) else {
+        return Ok(None);
+    };
+    SecretKey::from_base64(&raw)
+        .map(Some)
+        .map_err(|e| vk_core::Error::InvalidInput(format!("Invalid {}: {}", SECRET_KEY_ENV, e)))
+}
+
+/// Environment variable with the comma-separated agent types executions may use
+const ALLOWED_AGENTS_ENV: &str = "VK_ALLOWED_AGENTS";
+
+/// Agent types allowed by `VK_ALLOWED_AGENTS`; `None` when unset allows all
+fn allowed_agents_from_env() -> Option<Vec<AgentType>> {
+    let raw = std::env::var(ALLOWED_AGENTS_ENV).ok()?;
+    if raw.trim().is_empty() {
+        return None;
+    }
+    let mut allowed = Vec::new();
+    for name in raw.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+        match AgentType::from_str(name) {
+            Ok(agent) if !allowed.contains(&agent) => allowed.push(agent),
+            Ok(_) => {}
+            Err(_) => tracing::warn!("Ignoring unknown agent type {:?} in {}", name, ALLOWED_AGENTS_ENV),
+        }
+    }
+    Some(allowed)
+}
 
 /// Shared application state
 #[derive(Clone)]
@@ -23,11 +129,22 @@ struct AppStateInner {
     pub task_store: Arc<FileTaskStore>,
     pub kanban_store: Arc<KanbanStore>,
     pub project_store: Arc<ProjectStore>,
+    pub template_store: Arc<TemplateStore>,
     pub executor: Arc<TaskExecutor>,
-    #[allow(dead_code)]
     pub repo_path: PathBuf,
     pub socket_io: Arc<RwLock<Option<SocketIo>>>,
+    pub kanban_sync: KanbanSyncDebouncer,
     pub gateway_manager: Arc<GatewayManager>,
+    pub run_events: broadcast::Sender<RunLifecycleEvent>,
+    pub locale: Locale,
+    pub gateway_output_max_bytes: usize,
+    pub admin_token: Option<String>,
+    pub rollup_cache: RollupCache,
+    pub projects_root: Option<PathBuf>,
+    pub event_queue_capacity: usize,
+    pub allowed_agents: Option<Vec<AgentType>>,
+    pub context_files_max_bytes: usize,
+    pub trust_user_header: bool,
 }
 
 impl AppState {
@@ -53,7 +170,13 @@ impl AppState {
         gateway_manager: Arc<GatewayManager>,
     ) -> vk_core::Result<Self> {
         let project_path = data_dir.join("projects.json");
-        let project_store = Arc::new(ProjectStore::new(project_path).await?);
+        let mut project_store = ProjectStore::new(project_path).await?;
+        if let Some(key) = secret_key_from_env()? {
+            project_store = project_store.with_secret_key(key);
+        }
+        let project_store = Arc::new(project_store);
+        let template_path = data_dir.join("templates.json");
+        let template_store = Arc::new(TemplateStore::new(template_path).await?);
 
         // Get repository path (current directory or from env)
         let repo_path = std::env::var("VK_REPO_PATH")
@@ -66,10 +189,49 @@ impl AppState {
             repo_path: repo_path.clone(),
             worktree_config: WorktreeConfig {
                 worktree_dir: data_dir.join("worktrees"),
+                worktree_root: std::env::var("VK_WORKTREE_ROOT").ok().map(PathBuf::from),
                 branch_prefix: "task/".to_string(),
             },
             auto_cleanup: false,
             delete_branches: false,
+            max_event_file_bytes: env_parse("VK_EVENT_FILE_MAX_BYTES"),
+            raw_output_persistence: raw_output_persistence_from_env(RAW_OUTPUT_PERSISTENCE_ENV)
+                .unwrap_or_default(),
+            raw_output_persistence_overrides: AgentType::ALL
+                .iter()
+                .filter_map(|agent| {
+                    let var = agent_env_var(RAW_OUTPUT_PERSISTENCE_ENV, agent);
+                    raw_output_persistence_from_env(&var).map(|policy| (agent.clone(), policy))
+                })
+                .collect(),
+            redactor: redactor_from_env(),
+            agent_args: agent_args_from_env(AGENT_ARGS_ENV).unwrap_or_default(),
+            per_agent_args: AgentType::ALL
+                .iter()
+                .filter_map(|agent| {
+                    agent_args_from_env(&agent_env_var(AGENT_ARGS_ENV, agent)).map(|args| (agent.clone(), args))
+                })
+                .collect(),
+            clock: Arc::new(SystemClock),
+        };
+
+        let (run_events, _) = broadcast::channel(256);
+
+        let socket_io: Arc<RwLock<Option<SocketIo>>> = Arc::new(RwLock::new(None));
+        let kanban_sync = {
+            let socket_io = Arc::clone(&socket_io);
+            let kanban_store = Arc::clone(&kanban_store);
+            KanbanSyncDebouncer::spawn(kanban_sync_debounce_from_env(), move || {
+                let socket_io = Arc::clone(&socket_io);
+                let kanban_store = Arc::clone(&kanban_store);
+                async move {
+                    let io = socket_io.read().await.clone();
+                    if let Some(io) = io {
+                        let board_state = kanban_store.get_state().await;
+                        let _ = io.emit("kanban:sync", &board_state);
+                    }
+                }
+            })
         };
 
         // Create task executor
@@ -82,14 +244,65 @@ impl AppState {
                 task_store,
                 kanban_store,
                 project_store,
+                template_store,
                 executor: Arc::new(executor),
                 repo_path,
-                socket_io: Arc::new(RwLock::new(None)),
+                socket_io,
+                kanban_sync,
                 gateway_manager,
+                run_events,
+                locale: Locale::from_env(),
+                gateway_output_max_bytes: output_max_bytes_from_env(),
+                admin_token: admin_token_from_env(),
+                rollup_cache: RollupCache::new(data_dir.join("rollups")),
+                projects_root: std::env::var(PROJECTS_ROOT_ENV).ok().map(PathBuf::from),
+                event_queue_capacity: event_queue_capacity_from_env(),
+                allowed_agents: allowed_agents_from_env(),
+                context_files_max_bytes: context_files_max_bytes_from_env(),
+                trust_user_header: trust_user_header_from_env(),
             }),
         })
     }
 
+    /// Replace the task executor before the state is shared
+    #[cfg(test)]
+    pub fn set_executor(&mut self, executor: TaskExecutor) {
+        Arc::get_mut(&mut self.inner)
+            .expect("state is not shared yet")
+            .executor = Arc::new(executor);
+    }
+
+    /// Seal secret project env values with `key`
+    #[cfg(test)]
+    pub fn set_secret_key(&mut self, key: SecretKey) {
+        let inner = Arc::get_mut(&mut self.inner).expect("state is not shared yet");
+        inner.project_store = Arc::new((*inner.project_store).clone().with_secret_key(key));
+    }
+
+    /// Require `token` for admin-only endpoints
+    #[cfg(test)]
+    pub fn set_admin_token(&mut self, token: Option<String>) {
+        Arc::get_mut(&mut self.inner)
+            .expect("state is not shared yet")
+            .admin_token = token;
+    }
+
+    /// Restrict executions to `agents`
+    #[cfg(test)]
+    pub fn set_allowed_agents(&mut self, agents: Option<Vec<AgentType>>) {
+        Arc::get_mut(&mut self.inner)
+            .expect("state is not shared yet")
+            .allowed_agents = agents;
+    }
+
+    /// Honour the user header as if a trusted proxy set it
+    #[cfg(test)]
+    pub fn set_trust_user_header(&mut self, trust: bool) {
+        Arc::get_mut(&mut self.inner)
+            .expect("state is not shared yet")
+            .trust_user_header = trust;
+    }
+
     /// Set Socket.IO instance
     pub async fn set_socket_io(&self, io: SocketIo) {
         let mut w = self.inner.socket_io.write().await;
@@ -101,6 +314,57 @@ impl AppState {
         self.inner.socket_io.read().await.clone()
     }
 
+    /// Broadcast the board with `kanban:sync`, coalesced with other changes
+    /// made within `VK_KANBAN_SYNC_DEBOUNCE_MS`
+    pub fn request_kanban_sync(&self) {
+        self.inner.kanban_sync.request();
+    }
+
+    /// Locale used for status messages posted to the task chat
+    pub fn locale(&self) -> Locale {
+        self.inner.locale
+    }
+
+    /// Cap on the streamed output kept for a gateway run's final message
+    pub fn gateway_output_max_bytes(&self) -> usize {
+        self.inner.gateway_output_max_bytes
+    }
+
+    /// Cap on the total size of the context files prepended to a prompt
+    pub fn context_files_max_bytes(&self) -> usize {
+        self.inner.context_files_max_bytes
+    }
+
+    /// Bearer token for admin-only endpoints, if enabled
+    pub fn admin_token(&self) -> Option<&str> {
+        self.inner.admin_token.as_deref()
+    }
+
+    /// Whether the user header comes from a trusted proxy
+    pub fn trust_user_header(&self) -> bool {
+        self.inner.trust_user_header
+    }
+
+    /// Agent types executions may use
+    pub fn allowed_agents(&self) -> &[AgentType] {
+        self.inner.allowed_agents.as_deref().unwrap_or(&AgentType::ALL)
+    }
+
+    /// Events a run may queue before persisting them holds up its forwarder
+    pub fn event_queue_capacity(&self) -> usize {
+        self.inner.event_queue_capacity
+    }
+
+    /// Directory project checkouts must stay inside, if restricted
+    pub fn projects_root(&self) -> Option<&Path> {
+        self.inner.projects_root.as_deref()
+    }
+
+    /// On-disk cache of daily rollups for past days
+    pub fn rollup_cache(&self) -> &RollupCache {
+        &self.inner.rollup_cache
+    }
+
     /// Get reference to the task store
     pub fn task_store(&self) -> &FileTaskStore {
         &self.inner.task_store
@@ -134,13 +398,17 @@ impl AppState {
         Arc::clone(&self.inner.project_store)
     }
 
+    /// Get reference to the task template store
+    pub fn template_store(&self) -> &TemplateStore {
+        &self.inner.template_store
+    }
+
     /// Get reference to the task executor
     pub fn executor(&self) -> &TaskExecutor {
         &self.inner.executor
     }
 
     /// Get the repository path
-    #[allow(dead_code)]
     pub fn repo_path(&self) -> &PathBuf {
         &self.inner.repo_path
     }
@@ -154,4 +422,28 @@ impl AppState {
     pub fn gateway_manager_arc(&self) -> Arc<GatewayManager> {
         Arc::clone(&self.inner.gateway_manager)
     }
+
+    /// Subscribe to run lifecycle events (completion, failure, cancellation)
+    pub fn subscribe_run_events(&self) -> broadcast::Receiver<RunLifecycleEvent> {
+        self.inner.run_events.subscribe()
+    }
+
+    /// Publish a run lifecycle event to all subscribers
+    ///
+    /// Skipped when the run's task opted out of notifications for its outcome.
+    pub async fn publish_run_event(&self, event: RunLifecycleEvent) {
+        let outcome = serde_json::to_value(event.status)
+            .ok()
+            .and_then(|value| value.as_str().map(str::to_string))
+            .unwrap_or_default();
+        if let Ok(Some(task)) = self.task_store().get(event.task_id).await {
+            if !task.notifies_on(&outcome) {
+                tracing::debug!("Task {} does not notify on {} runs", event.task_id, outcome);
+                return;
+            }
+        }
+
+        // No subscribers is fine; the event is simply dropped
+        let _ = self.inner.run_events.send(event);
+    }
 }
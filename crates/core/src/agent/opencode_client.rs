@@ -287,6 +287,43 @@ impl OpencodeClient {
             .ok_or_else(|| Error::Agent("Session ID not found in response".into()))
     }
 
+    /// Check whether a session still exists on the server
+    pub async fn session_exists(&self, session_id: &str) -> Result<bool> {
+        let base_url = self.base_url.read().await.clone()
+            .ok_or_else(|| Error::Agent("Server not started".into()))?;
+
+        let client = reqwest::Client::new();
+        let directory = self.config.cwd.to_string_lossy();
+
+        let resp = client
+            .get(format!(
+                "{}/session/{}?directory={}",
+                base_url,
+                urlencoding::encode(session_id),
+                urlencoding::encode(&directory)
+            ))
+            .headers(self.get_headers())
+            .send()
+            .await
+            .map_err(|e| Error::Agent(format!("Failed to look up session: {}", e)))?;
+
+        Ok(resp.status().is_success())
+    }
+
+    /// Reuse `existing` if the server still knows it, otherwise create a session
+    ///
+    /// Returns the session id and whether it was reused.
+    pub async fn open_session(&self, existing: Option<&str>) -> Result<(String, bool)> {
+        if let Some(session_id) = existing {
+            match self.session_exists(session_id).await {
+                Ok(true) => return Ok((session_id.to_string(), true)),
+                Ok(false) => info!("Session {} no longer exists, creating a new one", session_id),
+                Err(e) => warn!("Failed to validate session {}: {}", session_id, e),
+            }
+        }
+        Ok((self.create_session().await?, false))
+    }
+
     /// Send a message to a session
     pub async fn send_message(&self, session_id: &str, prompt: &str) -> Result<()> {
         let base_url = self.base_url.read().await.clone()
@@ -371,8 +408,8 @@ impl OpencodeClient {
                                 let line = buffer[..newline_pos].to_string();
                                 buffer = buffer[newline_pos + 1..].to_string();
                                 
-                                if line.starts_with("data: ") {
-                                    if let Ok(event) = serde_json::from_str::<OpencodeEvent>(&line[6..]) {
+                                if let Some(data) = line.strip_prefix("data: ") {
+                                    if let Ok(event) = serde_json::from_str::<OpencodeEvent>(data) {
                                         // Filter by session ID
                                         if let Some(event_session_id) = Self::extract_session_id(&event) {
                                             if event_session_id != session_id {
@@ -451,6 +488,13 @@ impl OpencodeClient {
 
     /// Run a complete session with a prompt
     pub async fn run(&self, prompt: &str) -> Result<()> {
+        self.run_in_session(prompt, None).await.map(|_| ())
+    }
+
+    /// Run a prompt, continuing `existing` when it is still valid
+    ///
+    /// Returns the id of the session the prompt ran in.
+    pub async fn run_in_session(&self, prompt: &str, existing: Option<&str>) -> Result<String> {
         // Start server
         self.start().await?;
 
@@ -461,9 +505,13 @@ impl OpencodeClient {
             return Err(Error::Agent("OpenCode server failed health check".into()));
         }
 
-        // Create session
-        let session_id = self.create_session().await?;
-        info!("Created session: {}", session_id);
+        // Create or reuse session
+        let (session_id, reused) = self.open_session(existing).await?;
+        if reused {
+            info!("Reusing session: {}", session_id);
+        } else {
+            info!("Created session: {}", session_id);
+        }
 
         // Emit session event
         let base_url = self.base_url.read().await.clone().unwrap_or_default();
@@ -472,6 +520,7 @@ impl OpencodeClient {
             properties: serde_json::json!({
                 "sessionID": session_id,
                 "baseUrl": base_url,
+                "reused": reused,
             }),
         });
 
@@ -537,8 +586,8 @@ impl OpencodeClient {
                                         let line = buffer[..newline_pos].to_string();
                                         buffer = buffer[newline_pos + 1..].to_string();
                                         
-                                        if line.starts_with("data: ") {
-                                            if let Ok(event) = serde_json::from_str::<OpencodeEvent>(&line[6..]) {
+                                        if let Some(data) = line.strip_prefix("data: ") {
+                                            if let Ok(event) = serde_json::from_str::<OpencodeEvent>(data) {
                                                 if let Some(event_session_id) = OpencodeClient::extract_session_id(&event) {
                                                     if event_session_id != session_id_clone {
                                                         continue;
@@ -591,7 +640,7 @@ impl OpencodeClient {
             }),
         });
 
-        Ok(())
+        Ok(session_id)
     }
 
     fn get_headers(&self) -> reqwest::header::HeaderMap {
@@ -615,3 +664,56 @@ impl Drop for OpencodeClient {
         // For now, the process cleanup will happen via process handle drop
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Minimal OpenCode server that knows a single session
+    async fn mock_server(known_session: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buf = vec![0u8; 4096];
+                let n = stream.read(&mut buf).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..n]);
+                let (status, body) = if request.starts_with(&format!("GET /session/{}?", known_session)) {
+                    ("200 OK", format!(r#"{{"id":"{}"}}"#, known_session))
+                } else if request.starts_with("POST /session?") {
+                    ("200 OK", r#"{"id":"ses_new"}"#.to_string())
+                } else {
+                    ("404 Not Found", "{}".to_string())
+                };
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn open_session_reuses_existing_and_falls_back_when_gone() {
+        let client = OpencodeClient::new(OpencodeConfig::default());
+        *client.base_url.write().await = Some(mock_server("ses_existing").await);
+
+        let (id, reused) = client.open_session(Some("ses_existing")).await.unwrap();
+        assert_eq!(id, "ses_existing");
+        assert!(reused);
+
+        let (id, reused) = client.open_session(Some("ses_deleted")).await.unwrap();
+        assert_eq!(id, "ses_new");
+        assert!(!reused);
+
+        let (id, reused) = client.open_session(None).await.unwrap();
+        assert_eq!(id, "ses_new");
+        assert!(!reused);
+    }
+}
@@ -4,7 +4,9 @@
 //! the three-column layout (Todo, Doing, Done) used by the frontend.
 
 mod model;
+mod rank;
 mod store;
 
 pub use model::*;
+pub use rank::*;
 pub use store::*;
@@ -2,21 +2,111 @@
 //!
 //! These types are designed to be compatible with the frontend's expected format.
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use super::rank::{rank_between, spread_ranks, MAX_RANK_LEN};
+
 /// Kanban task status - matches frontend's three-column layout
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum KanbanTaskStatus {
+    #[default]
     Todo,
     Doing,
     Done,
 }
 
-impl Default for KanbanTaskStatus {
+impl From<KanbanTaskStatus> for crate::task::TaskStatus {
+    fn from(status: KanbanTaskStatus) -> Self {
+        match status {
+            KanbanTaskStatus::Todo => Self::Todo,
+            KanbanTaskStatus::Doing => Self::InProgress,
+            KanbanTaskStatus::Done => Self::Done,
+        }
+    }
+}
+
+impl From<crate::task::TaskStatus> for KanbanTaskStatus {
+    fn from(status: crate::task::TaskStatus) -> Self {
+        match status {
+            crate::task::TaskStatus::Todo => Self::Todo,
+            crate::task::TaskStatus::InProgress | crate::task::TaskStatus::InReview => Self::Doing,
+            crate::task::TaskStatus::Done => Self::Done,
+        }
+    }
+}
+
+/// Which side wins when a card's column and its task's status disagree
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KanbanReconcilePolicy {
+    /// Move the card to the column matching the task status
+    #[default]
+    Status,
+    /// Set the task status from the card's column
+    Column,
+}
+
+impl KanbanReconcilePolicy {
+    /// Parse a policy name (`status` or `column`)
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "status" => Some(Self::Status),
+            "column" => Some(Self::Column),
+            _ => None,
+        }
+    }
+}
+
+/// A card whose column disagreed with its task's status
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KanbanMismatch {
+    pub task_id: String,
+    pub column: KanbanTaskStatus,
+    pub task_status: crate::task::TaskStatus,
+}
+
+/// Run lifecycle transitions that can move a task's card
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunTransition {
+    Started,
+    Succeeded,
+    Failed,
+}
+
+/// Columns a task's card moves to as its runs progress
+///
+/// `None` leaves the card where it is. Omitted fields keep the defaults:
+/// start moves to Doing, success to Done and failure back to Todo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(default, rename_all = "camelCase")]
+pub struct KanbanAutoMovePolicy {
+    pub on_start: Option<KanbanTaskStatus>,
+    pub on_success: Option<KanbanTaskStatus>,
+    pub on_failure: Option<KanbanTaskStatus>,
+}
+
+impl Default for KanbanAutoMovePolicy {
     fn default() -> Self {
-        Self::Todo
+        Self {
+            on_start: Some(KanbanTaskStatus::Doing),
+            on_success: Some(KanbanTaskStatus::Done),
+            on_failure: Some(KanbanTaskStatus::Todo),
+        }
+    }
+}
+
+impl KanbanAutoMovePolicy {
+    /// Column to move the card to for a transition, if any
+    pub fn target(&self, transition: RunTransition) -> Option<KanbanTaskStatus> {
+        match transition {
+            RunTransition::Started => self.on_start,
+            RunTransition::Succeeded => self.on_success,
+            RunTransition::Failed => self.on_failure,
+        }
     }
 }
 
@@ -35,6 +125,9 @@ pub struct KanbanTask {
     /// Associated agent session ID
     #[serde(skip_serializing_if = "Option::is_none")]
     pub session_id: Option<String>,
+    /// Fractional position within the column; see `rank_between`
+    #[serde(default)]
+    pub rank: String,
 }
 
 impl KanbanTask {
@@ -49,6 +142,7 @@ impl KanbanTask {
             created_at: now,
             updated_at: None,
             session_id: None,
+            rank: String::new(),
         }
     }
 
@@ -123,13 +217,19 @@ impl KanbanBoardState {
         }
     }
 
-    /// Add a task to the board
-    pub fn add_task(&mut self, task: KanbanTask) {
+    /// Add a task to the end of its column
+    pub fn add_task(&mut self, mut task: KanbanTask) {
         let status = task.status;
         let id = task.id.clone();
-        self.tasks.insert(id.clone(), task);
         if let Some(column) = self.columns.get_mut(&status) {
-            column.task_ids.push(id);
+            let last = column.task_ids.last().and_then(|last| self.tasks.get(last));
+            task.rank = rank_between(last.map(|t| t.rank.as_str()), None);
+            column.task_ids.push(id.clone());
+        }
+        let needs_rebalance = task.rank.len() > MAX_RANK_LEN;
+        self.tasks.insert(id, task);
+        if needs_rebalance {
+            self.rebalance_column(status);
         }
     }
 
@@ -154,16 +254,69 @@ impl KanbanBoardState {
             old_column.task_ids.retain(|id| id != task_id);
         }
 
-        // Add to new column
+        // Add to new column, ranked between its new neighbours
+        let mut needs_rebalance = false;
         if let Some(new_column) = self.columns.get_mut(&target_status) {
             let index = target_index.unwrap_or(new_column.task_ids.len());
             let index = index.min(new_column.task_ids.len());
+            let neighbour_rank = |i: Option<usize>| {
+                i.and_then(|i| new_column.task_ids.get(i))
+                    .and_then(|id| self.tasks.get(id))
+                    .map(|t| t.rank.as_str())
+            };
+            let rank = rank_between(neighbour_rank(index.checked_sub(1)), neighbour_rank(Some(index)));
+            needs_rebalance = rank.len() > MAX_RANK_LEN;
+            if let Some(task) = self.tasks.get_mut(task_id) {
+                task.rank = rank;
+            }
             new_column.task_ids.insert(index, task_id.to_string());
         }
+        if needs_rebalance {
+            self.rebalance_column(target_status);
+        }
 
         true
     }
 
+    /// Reassign evenly spaced ranks to a column, keeping its order
+    ///
+    /// Called when a rank grows past `MAX_RANK_LEN` after repeated
+    /// insertions at the same spot.
+    pub fn rebalance_column(&mut self, status: KanbanTaskStatus) {
+        let Some(column) = self.columns.get(&status) else {
+            return;
+        };
+        for (id, rank) in column.task_ids.iter().zip(spread_ranks(column.task_ids.len())) {
+            if let Some(task) = self.tasks.get_mut(id) {
+                task.rank = rank;
+            }
+        }
+    }
+
+    /// Rank columns whose cards are unranked or out of order
+    ///
+    /// Boards saved before ranks existed get ranks matching their current
+    /// card order.
+    pub fn ensure_ranks(&mut self) {
+        let stale: Vec<KanbanTaskStatus> = self
+            .columns
+            .iter()
+            .filter(|(_, column)| {
+                let ranks: Vec<Option<&str>> = column
+                    .task_ids
+                    .iter()
+                    .map(|id| self.tasks.get(id).map(|t| t.rank.as_str()))
+                    .collect();
+                ranks.iter().any(|rank| rank.is_none_or(str::is_empty))
+                    || ranks.windows(2).any(|pair| pair[0] >= pair[1])
+            })
+            .map(|(status, _)| *status)
+            .collect();
+        for status in stale {
+            self.rebalance_column(status);
+        }
+    }
+
     /// Delete a task from the board
     pub fn delete_task(&mut self, task_id: &str) -> Option<KanbanTask> {
         let task = self.tasks.remove(task_id)?;
@@ -180,6 +333,33 @@ impl KanbanBoardState {
     pub fn get_task(&self, task_id: &str) -> Option<&KanbanTask> {
         self.tasks.get(task_id)
     }
+
+    /// Copy of the board with only the tasks `keep` accepts
+    ///
+    /// Columns and the relative order of the kept cards are unchanged.
+    pub fn filtered(&self, keep: impl Fn(&KanbanTask) -> bool) -> Self {
+        let tasks: HashMap<String, KanbanTask> = self
+            .tasks
+            .iter()
+            .filter(|(_, task)| keep(task))
+            .map(|(id, task)| (id.clone(), task.clone()))
+            .collect();
+        let columns = self
+            .columns
+            .iter()
+            .map(|(status, column)| {
+                let mut column = column.clone();
+                column.task_ids.retain(|id| tasks.contains_key(id));
+                (*status, column)
+            })
+            .collect();
+
+        Self {
+            tasks,
+            columns,
+            column_order: self.column_order.clone(),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -256,4 +436,63 @@ mod tests {
             .task_ids
             .is_empty());
     }
+
+    fn column_ranks(state: &KanbanBoardState, status: KanbanTaskStatus) -> Vec<String> {
+        state.columns[&status]
+            .task_ids
+            .iter()
+            .map(|id| state.tasks[id].rank.clone())
+            .collect()
+    }
+
+    #[test]
+    fn insert_between_adjacent_cards_only_reranks_moved_card() {
+        let mut state = KanbanBoardState::new();
+        for id in ["a", "b", "c"] {
+            state.add_task(KanbanTask::new(id, id));
+        }
+        let before = column_ranks(&state, KanbanTaskStatus::Todo);
+        assert!(before.windows(2).all(|pair| pair[0] < pair[1]));
+
+        // Move "c" between "a" and "b"
+        assert!(state.move_task("c", KanbanTaskStatus::Todo, Some(1)));
+
+        let column = &state.columns[&KanbanTaskStatus::Todo];
+        assert_eq!(column.task_ids, vec!["a", "c", "b"]);
+        assert_eq!(state.tasks["a"].rank, before[0]);
+        assert_eq!(state.tasks["b"].rank, before[1]);
+        let after = column_ranks(&state, KanbanTaskStatus::Todo);
+        assert!(after.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn repeated_front_inserts_trigger_rebalance() {
+        let mut state = KanbanBoardState::new();
+        for i in 0..300 {
+            let id = format!("task-{}", i);
+            state.add_task(KanbanTask::new(&id, "Card"));
+            state.move_task(&id, KanbanTaskStatus::Todo, Some(0));
+        }
+
+        let ranks = column_ranks(&state, KanbanTaskStatus::Todo);
+        assert!(ranks.iter().all(|rank| rank.len() <= MAX_RANK_LEN));
+        assert!(ranks.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(state.columns[&KanbanTaskStatus::Todo].task_ids[0], "task-299");
+    }
+
+    #[test]
+    fn ensure_ranks_fills_boards_saved_without_ranks() {
+        let mut state = KanbanBoardState::new();
+        for id in ["a", "b"] {
+            state.add_task(KanbanTask::new(id, id));
+        }
+        for task in state.tasks.values_mut() {
+            task.rank.clear();
+        }
+
+        state.ensure_ranks();
+        let ranks = column_ranks(&state, KanbanTaskStatus::Todo);
+        assert!(!ranks[0].is_empty());
+        assert!(ranks[0] < ranks[1]);
+    }
 }
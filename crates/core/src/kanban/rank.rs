@@ -0,0 +1,98 @@
+//! Fractional (lexicographic) card ranks
+//!
+//! A card's position in a column is a base-62 string that sorts between its
+//! neighbours, so moving a card only rewrites that card's rank. Ranks never
+//! end in the lowest digit, which keeps room to insert before any of them.
+
+/// Digits in ascending byte order
+const DIGITS: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+const BASE: usize = DIGITS.len();
+
+/// Ranks longer than this trigger a rebalance of their column
+pub const MAX_RANK_LEN: usize = 12;
+
+fn digit(byte: u8) -> usize {
+    DIGITS.iter().position(|&d| d == byte).unwrap_or(0)
+}
+
+/// Rank sorting strictly between `before` and `after`
+///
+/// `None` stands for the start or end of the column. `before` must sort
+/// lower than `after`.
+pub fn rank_between(before: Option<&str>, after: Option<&str>) -> String {
+    let low = before.unwrap_or_default().as_bytes();
+    let mut high = after.map(str::as_bytes);
+    let mut rank = Vec::new();
+
+    for i in 0.. {
+        let lo = low.get(i).map_or(0, |&b| digit(b));
+        let hi = high.and_then(|h| h.get(i)).map_or(BASE, |&b| digit(b));
+        if hi > lo + 1 {
+            rank.push(DIGITS[(lo + hi) / 2]);
+            break;
+        }
+        rank.push(DIGITS[lo]);
+        if hi > lo {
+            // Already below `after`; later digits are unbounded above
+            high = None;
+        }
+    }
+
+    String::from_utf8(rank).expect("rank digits are ASCII")
+}
+
+/// `count` evenly spaced ranks of equal length, in ascending order
+pub fn spread_ranks(count: usize) -> Vec<String> {
+    let mut width = 1;
+    let mut span = BASE as u128;
+    while span <= count as u128 + 1 {
+        width += 1;
+        span *= BASE as u128;
+    }
+
+    (1..=count as u128)
+        .map(|k| {
+            let mut value = k * span / (count as u128 + 1);
+            let mut rank = vec![DIGITS[0]; width];
+            for slot in rank.iter_mut().rev() {
+                *slot = DIGITS[(value % BASE as u128) as usize];
+                value /= BASE as u128;
+            }
+            if rank.last() == Some(&DIGITS[0]) {
+                rank.push(DIGITS[BASE / 2]);
+            }
+            String::from_utf8(rank).expect("rank digits are ASCII")
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rank_between_sorts_between_neighbours() {
+        let cases = [
+            (None, None),
+            (None, Some("0V")),
+            (Some("a"), Some("b")),
+            (Some("a"), Some("a1")),
+            (Some("az"), None),
+            (Some("V"), Some("W")),
+        ];
+        for (before, after) in cases {
+            let rank = rank_between(before, after);
+            assert!(before.is_none_or(|b| b < rank.as_str()), "{:?} < {}", before, rank);
+            assert!(after.is_none_or(|a| rank.as_str() < a), "{} < {:?}", rank, after);
+            assert!(!rank.ends_with('0'));
+        }
+    }
+
+    #[test]
+    fn spread_ranks_are_ordered_and_short() {
+        let ranks = spread_ranks(200);
+        assert_eq!(ranks.len(), 200);
+        assert!(ranks.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(ranks.iter().all(|rank| rank.len() <= 3 && !rank.ends_with('0')));
+    }
+}
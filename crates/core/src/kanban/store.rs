@@ -3,28 +3,62 @@
 //! Provides file-based persistence for kanban board state.
 //! Can initialize from TaskStore (tasks.json) for backward compatibility.
 
+use std::collections::VecDeque;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
 
 use crate::error::Error;
-use crate::task::{FileTaskStore, TaskRepository, TaskStatus};
+use crate::task::{FileTaskStore, StatusTransitions, TaskRepository, TaskStatus};
 use crate::Result;
 
-use super::model::{KanbanBoardState, KanbanTask, KanbanTaskStatus};
+use super::model::{
+    KanbanBoardState, KanbanMismatch, KanbanReconcilePolicy, KanbanTask, KanbanTaskStatus,
+};
+
+/// Number of user moves kept for undo by default
+pub const DEFAULT_UNDO_LIMIT: usize = 20;
+
+/// Where a task sat before a user moved it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KanbanMove {
+    pub task_id: String,
+    pub from_status: KanbanTaskStatus,
+    pub from_index: usize,
+}
+
+/// Board state tagged with the store version it was read at
+#[derive(Debug, Clone)]
+pub struct KanbanSnapshot {
+    pub version: u64,
+    pub state: KanbanBoardState,
+}
+
+/// Initial board version: the load time in milliseconds, so versions keep
+/// increasing across restarts
+fn initial_version() -> Arc<AtomicU64> {
+    Arc::new(AtomicU64::new(chrono::Utc::now().timestamp_millis().max(0) as u64))
+}
 
 /// Thread-safe kanban store with file persistence
 #[derive(Clone)]
 pub struct KanbanStore {
     state: Arc<RwLock<KanbanBoardState>>,
+    /// Bumped on every persisted mutation
+    version: Arc<AtomicU64>,
     file_path: PathBuf,
     task_store: Option<Arc<FileTaskStore>>,
+    transitions: StatusTransitions,
+    undo_stack: Arc<Mutex<VecDeque<KanbanMove>>>,
+    undo_limit: usize,
+    reconcile_policy: KanbanReconcilePolicy,
 }
 
 impl KanbanStore {
     /// Create a new KanbanStore with the given file path
     pub async fn new(file_path: PathBuf) -> Result<Self> {
-        let state = if file_path.exists() {
+        let mut state: KanbanBoardState = if file_path.exists() {
             let content = tokio::fs::read_to_string(&file_path).await.map_err(|e| {
                 Error::Storage(format!("Failed to read kanban file: {}", e))
             })?;
@@ -34,11 +68,17 @@ impl KanbanStore {
         } else {
             KanbanBoardState::new()
         };
+        state.ensure_ranks();
 
         Ok(Self {
             state: Arc::new(RwLock::new(state)),
+            version: initial_version(),
             file_path,
             task_store: None,
+            transitions: StatusTransitions::default(),
+            undo_stack: Arc::default(),
+            undo_limit: DEFAULT_UNDO_LIMIT,
+            reconcile_policy: KanbanReconcilePolicy::default(),
         })
     }
 
@@ -54,6 +94,7 @@ impl KanbanStore {
         } else {
             KanbanBoardState::new()
         };
+        state.ensure_ranks();
 
         // Sync tasks from TaskStore that aren't in KanbanStore yet
         let tasks = task_store.list().await?;
@@ -61,15 +102,8 @@ impl KanbanStore {
             let task_id = task.id.to_string();
             if !state.tasks.contains_key(&task_id) {
                 // Convert Task to KanbanTask
-                let kanban_status = match task.status {
-                    TaskStatus::Todo => KanbanTaskStatus::Todo,
-                    TaskStatus::InProgress => KanbanTaskStatus::Doing,
-                    TaskStatus::InReview => KanbanTaskStatus::Doing,
-                    TaskStatus::Done => KanbanTaskStatus::Done,
-                };
-                
                 let mut kanban_task = KanbanTask::new(&task_id, &task.title);
-                kanban_task.status = kanban_status;
+                kanban_task.status = task.status.into();
                 if let Some(desc) = &task.description {
                     kanban_task = kanban_task.with_description(desc);
                 }
@@ -82,8 +116,13 @@ impl KanbanStore {
 
         let store = Self {
             state: Arc::new(RwLock::new(state)),
+            version: initial_version(),
             file_path,
+            transitions: task_store.transitions().clone(),
             task_store: Some(task_store),
+            undo_stack: Arc::default(),
+            undo_limit: DEFAULT_UNDO_LIMIT,
+            reconcile_policy: KanbanReconcilePolicy::default(),
         };
         
         // Persist the synced state
@@ -92,6 +131,31 @@ impl KanbanStore {
         Ok(store)
     }
 
+    /// Set the status transitions enforced by `move_task_checked`
+    ///
+    /// Stores created with `with_task_store` inherit the task store's rules.
+    pub fn with_transitions(mut self, transitions: StatusTransitions) -> Self {
+        self.transitions = transitions;
+        self
+    }
+
+    /// Set how many user moves are kept for undo
+    pub fn with_undo_limit(mut self, limit: usize) -> Self {
+        self.undo_limit = limit;
+        self
+    }
+
+    /// Set the policy used when reconciling without an explicit one
+    pub fn with_reconcile_policy(mut self, policy: KanbanReconcilePolicy) -> Self {
+        self.reconcile_policy = policy;
+        self
+    }
+
+    /// Policy used when reconciling without an explicit one
+    pub fn reconcile_policy(&self) -> KanbanReconcilePolicy {
+        self.reconcile_policy
+    }
+
     /// Sync new tasks from TaskStore that aren't in KanbanStore yet
     pub async fn sync_from_task_store(&self) -> Result<bool> {
         let Some(task_store) = &self.task_store else {
@@ -107,15 +171,8 @@ impl KanbanStore {
                 let task_id = task.id.to_string();
                 if !state.tasks.contains_key(&task_id) {
                     // Convert Task to KanbanTask
-                    let kanban_status = match task.status {
-                        TaskStatus::Todo => KanbanTaskStatus::Todo,
-                        TaskStatus::InProgress => KanbanTaskStatus::Doing,
-                        TaskStatus::InReview => KanbanTaskStatus::Doing,
-                        TaskStatus::Done => KanbanTaskStatus::Done,
-                    };
-
                     let mut kanban_task = KanbanTask::new(&task_id, &task.title);
-                    kanban_task.status = kanban_status;
+                    kanban_task.status = task.status.into();
                     if let Some(desc) = &task.description {
                         kanban_task = kanban_task.with_description(desc);
                     }
@@ -135,11 +192,79 @@ impl KanbanStore {
         Ok(added)
     }
 
+    /// Resolve cards whose column disagrees with their task's status
+    ///
+    /// `InReview` tasks belong in `Doing`. With `Status` the card is moved to
+    /// its task's column; with `Column` the task status is set from the card.
+    /// Returns the mismatches found, as they were before resolving.
+    pub async fn reconcile_with_tasks(&self, policy: KanbanReconcilePolicy) -> Result<Vec<KanbanMismatch>> {
+        let Some(task_store) = &self.task_store else {
+            return Ok(Vec::new());
+        };
+
+        let tasks = task_store.list().await?;
+        let mut mismatched = Vec::new();
+        {
+            let mut state = self.state.write().await;
+            for task in tasks {
+                let task_id = task.id.to_string();
+                let Some(card) = state.tasks.get(&task_id) else {
+                    continue;
+                };
+                let column = card.status;
+                if column == KanbanTaskStatus::from(task.status) {
+                    continue;
+                }
+                if policy == KanbanReconcilePolicy::Status {
+                    state.move_task(&task_id, task.status.into(), None);
+                }
+                mismatched.push((
+                    KanbanMismatch {
+                        task_id,
+                        column,
+                        task_status: task.status,
+                    },
+                    task,
+                ));
+            }
+        }
+
+        if mismatched.is_empty() {
+            return Ok(Vec::new());
+        }
+        if policy == KanbanReconcilePolicy::Column {
+            for (mismatch, task) in &mismatched {
+                let mut task = task.clone();
+                task.status = TaskStatus::from(mismatch.column);
+                task_store.update(task).await?;
+            }
+        }
+        self.persist().await?;
+
+        Ok(mismatched.into_iter().map(|(mismatch, _)| mismatch).collect())
+    }
+
     /// Get the current board state
     pub async fn get_state(&self) -> KanbanBoardState {
         self.state.read().await.clone()
     }
 
+    /// Current board version; changes whenever the board does
+    pub fn version(&self) -> u64 {
+        self.version.load(Ordering::SeqCst)
+    }
+
+    /// Get the current board state together with its version
+    pub async fn snapshot(&self) -> KanbanSnapshot {
+        let state = self.state.read().await;
+        // Read under the lock; a version bump only follows its mutation, so
+        // the version never runs ahead of the state it is paired with
+        KanbanSnapshot {
+            version: self.version(),
+            state: state.clone(),
+        }
+    }
+
     /// Get the current board state after syncing from TaskStore
     pub async fn get_state_synced(&self) -> Result<KanbanBoardState> {
         self.sync_from_task_store().await?;
@@ -191,6 +316,85 @@ impl KanbanStore {
         Ok(result)
     }
 
+    /// Move a task on behalf of a user, enforcing the allowed transitions
+    ///
+    /// Moving out of a locked `Done` column requires `reopen`. The previous
+    /// position is recorded so the move can be undone with `undo_last`.
+    pub async fn move_task_checked(
+        &self,
+        task_id: &str,
+        target_status: KanbanTaskStatus,
+        target_index: Option<usize>,
+        reopen: bool,
+    ) -> Result<bool> {
+        let previous = {
+            let mut state = self.state.write().await;
+            let Some(task) = state.get_task(task_id) else {
+                return Ok(false);
+            };
+            let from_status = task.status;
+            self.transitions
+                .check(from_status.into(), target_status.into(), reopen)?;
+            let from_index = state
+                .columns
+                .get(&from_status)
+                .and_then(|column| column.task_ids.iter().position(|id| id == task_id))
+                .unwrap_or(0);
+            state.move_task(task_id, target_status, target_index);
+            KanbanMove {
+                task_id: task_id.to_string(),
+                from_status,
+                from_index,
+            }
+        };
+
+        {
+            let mut undo_stack = self.undo_stack.lock().await;
+            undo_stack.push_back(previous);
+            while undo_stack.len() > self.undo_limit {
+                undo_stack.pop_front();
+            }
+        }
+
+        self.persist().await?;
+        Ok(true)
+    }
+
+    /// Return the most recently moved task to where it was before the move
+    ///
+    /// Moves of tasks that have since been deleted are skipped. The move back
+    /// must be an allowed transition, otherwise the move stays on the undo
+    /// stack and an error is returned. Returns the undone move, or `None`
+    /// when there is nothing to undo.
+    pub async fn undo_last(&self) -> Result<Option<KanbanMove>> {
+        let undone = {
+            let mut undo_stack = self.undo_stack.lock().await;
+            let mut state = self.state.write().await;
+            loop {
+                let Some(previous) = undo_stack.pop_back() else {
+                    break None;
+                };
+                let Some(current) = state.get_task(&previous.task_id).map(|task| task.status) else {
+                    continue;
+                };
+                if let Err(err) = self
+                    .transitions
+                    .check(current.into(), previous.from_status.into(), false)
+                {
+                    undo_stack.push_back(previous);
+                    return Err(err.into());
+                }
+                state.move_task(&previous.task_id, previous.from_status, Some(previous.from_index));
+                break Some(previous);
+            }
+        };
+
+        if undone.is_some() {
+            self.persist().await?;
+        }
+        Ok(undone)
+    }
+
     /// Delete a task
     pub async fn delete_task(&self, task_id: &str) -> Result<Option<KanbanTask>> {
         let task = {
@@ -204,9 +408,10 @@ impl KanbanStore {
         Ok(task)
     }
 
-    /// Persist the current state to file
+    /// Persist the current state to file and bump the board version
     async fn persist(&self) -> Result<()> {
         let state = self.state.read().await;
+        self.version.fetch_add(1, Ordering::SeqCst);
         let content = serde_json::to_string_pretty(&*state).map_err(|e| {
             Error::Storage(format!("Failed to serialize kanban state: {}", e))
         })?;
@@ -242,6 +447,139 @@ mod tests {
         assert_eq!(state.tasks.len(), 0);
     }
 
+    #[tokio::test]
+    async fn version_changes_on_every_mutation() {
+        let dir = tempdir().unwrap();
+        let store = KanbanStore::new(dir.path().join("kanban.json")).await.unwrap();
+        let initial = store.version();
+
+        let task = store.create_task("Versioned", None).await.unwrap();
+        let created = store.snapshot().await;
+        assert!(created.version > initial);
+        assert!(created.state.tasks.contains_key(&task.id));
+
+        assert!(!store.move_task("missing", KanbanTaskStatus::Done, None).await.unwrap());
+        assert_eq!(store.version(), created.version);
+
+        store.move_task(&task.id, KanbanTaskStatus::Doing, None).await.unwrap();
+        assert!(store.version() > created.version);
+    }
+
+    #[tokio::test]
+    async fn reconcile_resolves_mismatches_per_policy() {
+        use crate::task::Task;
+
+        let dir = tempdir().unwrap();
+        let task_store = Arc::new(FileTaskStore::new(dir.path().join("tasks.json")).await.unwrap());
+        let create = |title: &str, status: TaskStatus| {
+            let mut task = Task::new(title);
+            task.status = status;
+            task_store.create(task)
+        };
+        let in_progress = create("Drifted", TaskStatus::InProgress).await.unwrap();
+        let in_review = create("Reviewing", TaskStatus::InReview).await.unwrap();
+        let store = KanbanStore::with_task_store(dir.path().join("kanban.json"), Arc::clone(&task_store))
+            .await
+            .unwrap();
+        let drifted = in_progress.id.to_string();
+        store.move_task(&drifted, KanbanTaskStatus::Done, None).await.unwrap();
+
+        // The task status wins: the card goes back to Doing
+        let mismatches = store.reconcile_with_tasks(KanbanReconcilePolicy::Status).await.unwrap();
+        assert_eq!(
+            mismatches,
+            vec![KanbanMismatch {
+                task_id: drifted.clone(),
+                column: KanbanTaskStatus::Done,
+                task_status: TaskStatus::InProgress,
+            }]
+        );
+        assert_eq!(store.get_task(&drifted).await.unwrap().status, KanbanTaskStatus::Doing);
+        assert!(store.reconcile_with_tasks(KanbanReconcilePolicy::Status).await.unwrap().is_empty());
+
+        // The column wins: the task becomes Done and InReview in Doing is left alone
+        store.move_task(&drifted, KanbanTaskStatus::Done, None).await.unwrap();
+        let mismatches = store.reconcile_with_tasks(KanbanReconcilePolicy::Column).await.unwrap();
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(task_store.get(in_progress.id).await.unwrap().unwrap().status, TaskStatus::Done);
+        assert_eq!(task_store.get(in_review.id).await.unwrap().unwrap().status, TaskStatus::InReview);
+        assert_eq!(store.get_task(&drifted).await.unwrap().status, KanbanTaskStatus::Done);
+    }
+
+    #[tokio::test]
+    async fn test_move_task_checked_enforces_locked_done() {
+        let dir = tempdir().unwrap();
+        let store = KanbanStore::new(dir.path().join("kanban.json"))
+            .await
+            .unwrap()
+            .with_transitions(StatusTransitions::permissive().lock_done());
+        let task = store.create_task("Locked", None).await.unwrap();
+
+        assert!(store.move_task_checked(&task.id, KanbanTaskStatus::Done, None, false).await.unwrap());
+        assert!(store
+            .move_task_checked(&task.id, KanbanTaskStatus::Doing, None, false)
+            .await
+            .is_err());
+        assert!(store.move_task_checked(&task.id, KanbanTaskStatus::Doing, None, true).await.unwrap());
+        assert_eq!(store.get_task(&task.id).await.unwrap().status, KanbanTaskStatus::Doing);
+    }
+
+    #[tokio::test]
+    async fn test_undo_last_enforces_locked_done() {
+        let dir = tempdir().unwrap();
+        let store = KanbanStore::new(dir.path().join("kanban.json"))
+            .await
+            .unwrap()
+            .with_transitions(StatusTransitions::permissive().lock_done());
+        let task = store.create_task("Locked", None).await.unwrap();
+
+        store.move_task_checked(&task.id, KanbanTaskStatus::Done, None, false).await.unwrap();
+        assert!(store.undo_last().await.is_err());
+        assert_eq!(store.get_task(&task.id).await.unwrap().status, KanbanTaskStatus::Done);
+
+        // The rejected move stays undoable once the task is reopened
+        store.move_task(&task.id, KanbanTaskStatus::Doing, None).await.unwrap();
+        let undone = store.undo_last().await.unwrap().unwrap();
+        assert_eq!(undone.from_status, KanbanTaskStatus::Todo);
+        assert_eq!(store.get_task(&task.id).await.unwrap().status, KanbanTaskStatus::Todo);
+    }
+
+    #[tokio::test]
+    async fn test_undo_last_restores_previous_positions() {
+        let dir = tempdir().unwrap();
+        let store = KanbanStore::new(dir.path().join("kanban.json"))
+            .await
+            .unwrap()
+            .with_undo_limit(2);
+        let first = store.create_task("First", None).await.unwrap();
+        let second = store.create_task("Second", None).await.unwrap();
+        let original = store.get_state().await.columns[&KanbanTaskStatus::Todo].task_ids.clone();
+
+        store.move_task_checked(&first.id, KanbanTaskStatus::Doing, None, false).await.unwrap();
+        store.move_task_checked(&first.id, KanbanTaskStatus::Done, None, false).await.unwrap();
+
+        let undone = store.undo_last().await.unwrap().unwrap();
+        assert_eq!(undone.from_status, KanbanTaskStatus::Doing);
+        assert_eq!(store.get_task(&first.id).await.unwrap().status, KanbanTaskStatus::Doing);
+
+        store.undo_last().await.unwrap().unwrap();
+        let state = store.get_state().await;
+        assert_eq!(state.tasks[&first.id].status, KanbanTaskStatus::Todo);
+        assert_eq!(state.columns[&KanbanTaskStatus::Todo].task_ids, original);
+        assert!(state.columns[&KanbanTaskStatus::Todo].task_ids.contains(&second.id));
+
+        assert!(store.undo_last().await.unwrap().is_none());
+
+        // Only the last `undo_limit` moves are kept
+        for status in [KanbanTaskStatus::Doing, KanbanTaskStatus::Done, KanbanTaskStatus::Todo] {
+            store.move_task_checked(&second.id, status, None, false).await.unwrap();
+        }
+        assert!(store.undo_last().await.unwrap().is_some());
+        assert!(store.undo_last().await.unwrap().is_some());
+        assert!(store.undo_last().await.unwrap().is_none());
+        assert_eq!(store.get_task(&second.id).await.unwrap().status, KanbanTaskStatus::Doing);
+    }
+
     #[tokio::test]
     async fn test_create_and_persist_task() {
         let dir = tempdir().unwrap();
@@ -4,6 +4,7 @@
 //! - Task management
 //! - Kanban board management
 //! - Project management
+//! - Task templates
 //! - Agent configuration
 
 pub mod agent;
@@ -11,6 +12,7 @@ pub mod error;
 pub mod kanban;
 pub mod project;
 pub mod task;
+pub mod template;
 
 pub use error::Error;
 pub type Result<T> = std::result::Result<T, Error>;
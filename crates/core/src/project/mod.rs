@@ -4,7 +4,11 @@
 //! Tasks belong to Projects.
 
 mod model;
+mod path;
+mod secret;
 mod store;
 
 pub use model::*;
+pub use path::is_path_within;
+pub use secret::{SecretKey, MASKED_SECRET};
 pub use store::*;
@@ -1,9 +1,29 @@
 //! Project model definitions
 
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::error::Error;
+use crate::kanban::KanbanAutoMovePolicy;
+
+/// Placeholders accepted in a project prompt template
+pub const PROMPT_PLACEHOLDERS: &[&str] = &["title", "description", "tags"];
+
+/// How the runs of a project's tasks get their worktree
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WorktreeStrategy {
+    /// A fresh worktree for every run
+    #[default]
+    PerRun,
+    /// One persistent worktree per task, reset to the base branch before each run
+    PerTask,
+}
+
 /// A Project represents a Git repository managed by a Gateway.
 ///
 /// Each project is bound to exactly one Gateway (one-to-one relationship).
@@ -20,9 +40,13 @@ pub struct Project {
     pub local_path: String,
 
     /// Remote Git repository URL (e.g., "git@github.com:user/repo.git")
-    /// Used for display and reference only
+    /// Cloned into `local_path` on first execution when `clone_if_missing` is set
     pub remote_url: Option<String>,
 
+    /// Clone `remote_url` into `local_path` if the checkout doesn't exist yet
+    #[serde(default)]
+    pub clone_if_missing: bool,
+
     /// Default branch name (e.g., "main" or "master")
     pub default_branch: String,
 
@@ -33,6 +57,28 @@ pub struct Project {
     /// Default: ".worktrees"
     pub worktree_dir: String,
 
+    /// Whether runs get a fresh worktree or reuse their task's
+    #[serde(default)]
+    pub worktree_strategy: WorktreeStrategy,
+
+    /// Template used to build agent prompts from tasks
+    /// Supports `{title}`, `{description}` and `{tags}`; None keeps the default layout
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_template: Option<String>,
+
+    /// Kanban columns the project's tasks move to as their runs progress
+    #[serde(default)]
+    pub kanban_policy: KanbanAutoMovePolicy,
+
+    /// Host labels a gateway host must carry to run this project's tasks
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub required_labels: HashMap<String, String>,
+
+    /// Environment variables passed to the agent on every dispatch
+    /// Secret values are stored sealed, see `ProjectStore::dispatch_env`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env: Vec<ProjectEnvVar>,
+
     /// Timestamp when the project was created
     pub created_at: DateTime<Utc>,
 
@@ -49,9 +95,15 @@ impl Project {
             name: name.into(),
             local_path: local_path.into(),
             remote_url: None,
+            clone_if_missing: false,
             default_branch: "main".to_string(),
             gateway_id,
             worktree_dir: ".worktrees".to_string(),
+            worktree_strategy: WorktreeStrategy::default(),
+            prompt_template: None,
+            kanban_policy: KanbanAutoMovePolicy::default(),
+            required_labels: HashMap::new(),
+            env: Vec::new(),
             created_at: now,
             updated_at: now,
         }
@@ -63,6 +115,12 @@ impl Project {
         self
     }
 
+    /// Clone the remote into the local path on first execution
+    pub fn with_clone_if_missing(mut self, clone_if_missing: bool) -> Self {
+        self.clone_if_missing = clone_if_missing;
+        self
+    }
+
     /// Set the default branch
     pub fn with_default_branch(mut self, branch: impl Into<String>) -> Self {
         self.default_branch = branch.into();
@@ -75,12 +133,103 @@ impl Project {
         self
     }
 
+    /// Set how runs get their worktree
+    pub fn with_worktree_strategy(mut self, strategy: WorktreeStrategy) -> Self {
+        self.worktree_strategy = strategy;
+        self
+    }
+
+    /// Set the prompt template
+    pub fn with_prompt_template(mut self, template: impl Into<String>) -> Self {
+        self.prompt_template = Some(template.into());
+        self
+    }
+
+    /// Set the kanban auto-move policy
+    pub fn with_kanban_policy(mut self, policy: KanbanAutoMovePolicy) -> Self {
+        self.kanban_policy = policy;
+        self
+    }
+
+    /// Require a host label for this project's executions
+    pub fn with_required_label(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.required_labels.insert(key.into(), value.into());
+        self
+    }
+
+    /// Render the agent prompt for a task
+    ///
+    /// Without a template this is the title, followed by the description
+    /// separated by a blank line when present.
+    pub fn render_prompt(&self, title: &str, description: Option<&str>, tags: &[String]) -> String {
+        match &self.prompt_template {
+            Some(template) => template
+                .replace("{title}", title)
+                .replace("{description}", description.unwrap_or_default())
+                .replace("{tags}", &tags.join(", ")),
+            None => match description {
+                Some(desc) => format!("{}\n\n{}", title, desc),
+                None => title.to_string(),
+            },
+        }
+    }
+
     /// Get the full path to the worktrees directory
     pub fn worktrees_path(&self) -> std::path::PathBuf {
         std::path::PathBuf::from(&self.local_path).join(&self.worktree_dir)
     }
 }
 
+/// Check that a prompt template only uses known placeholders
+pub fn validate_prompt_template(template: &str) -> Result<(), Error> {
+    let mut rest = template;
+    while let Some(open) = rest.find(['{', '}']) {
+        if rest[open..].starts_with('}') {
+            return Err(Error::InvalidInput(
+                "Prompt template has an unmatched '}'".to_string(),
+            ));
+        }
+        let after = &rest[open + 1..];
+        let close = after.find('}').ok_or_else(|| {
+            Error::InvalidInput("Prompt template has an unclosed '{'".to_string())
+        })?;
+        let name = &after[..close];
+        if !PROMPT_PLACEHOLDERS.contains(&name) {
+            return Err(Error::InvalidInput(format!(
+                "Unknown prompt placeholder {{{}}}; expected one of {}",
+                name,
+                PROMPT_PLACEHOLDERS.join(", ")
+            )));
+        }
+        rest = &after[close + 1..];
+    }
+    Ok(())
+}
+
+/// An environment variable set for a project's executions
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ProjectEnvVar {
+    pub name: String,
+    pub value: String,
+    /// Encrypted at rest and masked in API responses
+    #[serde(default)]
+    pub secret: bool,
+}
+
+impl ProjectEnvVar {
+    /// This variable as shown in API responses
+    pub fn masked(&self) -> Self {
+        Self {
+            value: if self.secret {
+                super::MASKED_SECRET.to_string()
+            } else {
+                self.value.clone()
+            },
+            ..self.clone()
+        }
+    }
+}
+
 /// Request to create or register a project (usually from Gateway)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateProjectRequest {
@@ -101,7 +250,7 @@ pub struct CreateProjectRequest {
 }
 
 /// Summary view of a project for listing
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ProjectSummary {
     pub id: Uuid,
@@ -164,6 +313,29 @@ mod tests {
         assert_eq!(project.worktree_dir, ".git-worktrees");
     }
 
+    #[test]
+    fn test_render_prompt_with_and_without_template() {
+        let project = Project::new("my-project", "/path/to/project", Uuid::new_v4());
+        assert_eq!(project.render_prompt("Fix bug", Some("Details"), &[]), "Fix bug\n\nDetails");
+        assert_eq!(project.render_prompt("Fix bug", None, &[]), "Fix bug");
+
+        let project = project.with_prompt_template("[{tags}] {title}: {description}");
+        let tags = vec!["api".to_string(), "urgent".to_string()];
+        assert_eq!(
+            project.render_prompt("Fix bug", Some("Details"), &tags),
+            "[api, urgent] Fix bug: Details"
+        );
+    }
+
+    #[test]
+    fn test_validate_prompt_template() {
+        assert!(validate_prompt_template("{title}\n\n{description} ({tags})").is_ok());
+        assert!(validate_prompt_template("no placeholders").is_ok());
+        assert!(validate_prompt_template("{title} {assignee}").is_err());
+        assert!(validate_prompt_template("{title").is_err());
+        assert!(validate_prompt_template("title}").is_err());
+    }
+
     #[test]
     fn test_worktrees_path() {
         let gateway_id = Uuid::new_v4();
@@ -0,0 +1,74 @@
+//! Containment checks for project paths
+//!
+//! A lexical prefix check lets a symlink inside the root point anywhere, so
+//! paths that exist are compared after resolving symlinks.
+
+use std::path::{Component, Path, PathBuf};
+
+/// Drop `.` components and fold `..` into the preceding component
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// `path` with symlinks resolved in the part of it that exists
+///
+/// The missing remainder is appended and normalized lexically.
+fn resolve(path: &Path) -> PathBuf {
+    for ancestor in path.ancestors() {
+        if let Ok(canonical) = ancestor.canonicalize() {
+            let rest = path.strip_prefix(ancestor).expect("ancestor is a prefix of the path");
+            return normalize(&canonical.join(rest));
+        }
+    }
+    normalize(path)
+}
+
+/// Whether `path` lies inside `root`
+///
+/// Relative paths are taken relative to `root`. Symlinks are resolved in
+/// both paths as far as they exist, so a link inside the root can't lead
+/// out of it; components that don't exist yet are checked lexically.
+pub fn is_path_within(path: &Path, root: &Path) -> bool {
+    resolve(&root.join(path)).starts_with(resolve(root))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn lexical_check_applies_to_missing_paths() {
+        let root = TempDir::new().unwrap();
+        assert!(is_path_within(Path::new("repos/app"), root.path()));
+        assert!(is_path_within(&root.path().join("repos/../app"), root.path()));
+        assert!(!is_path_within(Path::new("../elsewhere"), root.path()));
+        assert!(!is_path_within(Path::new("/definitely/not/inside"), root.path()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_escaping_the_root_is_rejected() {
+        let root = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        std::fs::create_dir(root.path().join("repo")).unwrap();
+        std::os::unix::fs::symlink(outside.path(), root.path().join("escape")).unwrap();
+
+        assert!(is_path_within(&root.path().join("repo"), root.path()));
+        assert!(!is_path_within(&root.path().join("escape"), root.path()));
+
+        // Missing paths below the link escape too
+        assert!(!is_path_within(&root.path().join("escape/new-repo"), root.path()));
+        assert!(!is_path_within(&root.path().join("escape/../outside"), root.path()));
+    }
+}
@@ -0,0 +1,111 @@
+//! Encryption of secret project environment values
+//!
+//! Secret values are sealed with AES-256-GCM under a server key before the
+//! project is persisted, and only opened to assemble a dispatch environment.
+//! The variable name is bound as associated data, so a sealed value can't be
+//! moved to another variable.
+
+use base64::Engine;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+
+use crate::error::Error;
+use crate::Result;
+
+/// Prefix marking a sealed value
+const SEALED_PREFIX: &str = "enc:v1:";
+
+/// Shown in place of secret values in API responses
+pub const MASKED_SECRET: &str = "********";
+
+/// Server key for sealing secret values
+pub struct SecretKey {
+    key: LessSafeKey,
+}
+
+impl std::fmt::Debug for SecretKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SecretKey(..)")
+    }
+}
+
+impl SecretKey {
+    /// Key from its base64 encoding; must decode to 32 bytes
+    pub fn from_base64(encoded: &str) -> Result<Self> {
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(encoded.trim())
+            .map_err(|e| Error::InvalidInput(format!("Secret key is not valid base64: {}", e)))?;
+        let key = UnboundKey::new(&AES_256_GCM, &bytes)
+            .map_err(|_| Error::InvalidInput("Secret key must be 32 bytes".to_string()))?;
+        Ok(Self {
+            key: LessSafeKey::new(key),
+        })
+    }
+
+    /// Whether `value` was produced by `seal`
+    pub fn is_sealed(value: &str) -> bool {
+        value.starts_with(SEALED_PREFIX)
+    }
+
+    /// Encrypt the value of variable `name`
+    pub fn seal(&self, name: &str, value: &str) -> Result<String> {
+        let mut nonce = [0u8; NONCE_LEN];
+        SystemRandom::new()
+            .fill(&mut nonce)
+            .map_err(|_| Error::Storage("Failed to generate nonce".to_string()))?;
+
+        let mut sealed = value.as_bytes().to_vec();
+        self.key
+            .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::from(name.as_bytes()), &mut sealed)
+            .map_err(|_| Error::Storage(format!("Failed to encrypt {}", name)))?;
+
+        let mut payload = nonce.to_vec();
+        payload.extend(sealed);
+        Ok(format!(
+            "{}{}",
+            SEALED_PREFIX,
+            base64::engine::general_purpose::STANDARD.encode(payload)
+        ))
+    }
+
+    /// Decrypt a value sealed for variable `name`
+    pub fn open(&self, name: &str, sealed: &str) -> Result<String> {
+        let undecryptable = || Error::Storage(format!("Failed to decrypt {}", name));
+        let encoded = sealed.strip_prefix(SEALED_PREFIX).ok_or_else(undecryptable)?;
+        let mut payload = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(|_| undecryptable())?;
+        if payload.len() < NONCE_LEN {
+            return Err(undecryptable());
+        }
+
+        let mut ciphertext = payload.split_off(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(&payload).map_err(|_| undecryptable())?;
+        let plaintext = self
+            .key
+            .open_in_place(nonce, Aad::from(name.as_bytes()), &mut ciphertext)
+            .map_err(|_| undecryptable())?;
+        String::from_utf8(plaintext.to_vec()).map_err(|_| undecryptable())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "MDEyMzQ1Njc4OWFiY2RlZjAxMjM0NTY3ODlhYmNkZWY=";
+
+    #[test]
+    fn sealed_values_open_only_for_their_variable() {
+        let key = SecretKey::from_base64(KEY).unwrap();
+        let sealed = key.seal("API_TOKEN", "s3cr3t").unwrap();
+
+        assert!(SecretKey::is_sealed(&sealed));
+        assert!(!sealed.contains("s3cr3t"));
+        assert_eq!(key.open("API_TOKEN", &sealed).unwrap(), "s3cr3t");
+        assert!(key.open("OTHER_TOKEN", &sealed).is_err());
+        assert!(key.open("API_TOKEN", "s3cr3t").is_err());
+
+        assert!(SecretKey::from_base64("c2hvcnQ=").is_err());
+    }
+}
@@ -11,7 +11,8 @@ use uuid::Uuid;
 use crate::error::Error;
 use crate::Result;
 
-use super::model::{CreateProjectRequest, Project, ProjectSummary};
+use super::model::{validate_prompt_template, CreateProjectRequest, Project, ProjectSummary};
+use super::secret::SecretKey;
 
 /// Thread-safe project store with file persistence
 #[derive(Clone)]
@@ -20,6 +21,8 @@ pub struct ProjectStore {
     projects: Arc<RwLock<HashMap<Uuid, Project>>>,
     /// Path to the projects JSON file
     file_path: PathBuf,
+    /// Key sealing secret environment values
+    secret_key: Option<Arc<SecretKey>>,
 }
 
 impl ProjectStore {
@@ -39,9 +42,40 @@ impl ProjectStore {
         Ok(Self {
             projects: Arc::new(RwLock::new(projects)),
             file_path,
+            secret_key: None,
         })
     }
 
+    /// Seal secret environment values with `key`
+    pub fn with_secret_key(mut self, key: SecretKey) -> Self {
+        self.secret_key = Some(Arc::new(key));
+        self
+    }
+
+    /// Whether secret environment values can be stored
+    pub fn has_secret_key(&self) -> bool {
+        self.secret_key.is_some()
+    }
+
+    /// Environment for dispatching one of the project's tasks, secrets decrypted
+    pub fn dispatch_env(&self, project: &Project) -> Result<HashMap<String, String>> {
+        project
+            .env
+            .iter()
+            .map(|var| {
+                let value = if var.secret {
+                    let key = self.secret_key.as_ref().ok_or_else(|| {
+                        Error::Storage(format!("No secret key to decrypt {}", var.name))
+                    })?;
+                    key.open(&var.name, &var.value)?
+                } else {
+                    var.value.clone()
+                };
+                Ok((var.name.clone(), value))
+            })
+            .collect()
+    }
+
     /// Create or update a project from a Gateway registration request
     ///
     /// If a project with the same (gateway_id, local_path) exists, update it.
@@ -128,11 +162,28 @@ impl ProjectStore {
     pub async fn update(&self, project: Project) -> Result<Project> {
         let mut projects = self.projects.write().await;
 
-        if !projects.contains_key(&project.id) {
+        let Some(current) = projects.get(&project.id) else {
             return Err(Error::NotFound(format!("Project {} not found", project.id)));
+        };
+        if let Some(template) = &project.prompt_template {
+            validate_prompt_template(template)?;
         }
 
+        // Only the stored ciphertext of the same secret is kept as is; any
+        // other value is plaintext from the client, whatever it looks like
+        let is_stored = |name: &str, value: &str| {
+            current
+                .env
+                .iter()
+                .any(|stored| stored.secret && stored.name == name && stored.value == value)
+        };
         let mut updated = project;
+        for var in updated.env.iter_mut().filter(|var| var.secret && !is_stored(&var.name, &var.value)) {
+            let key = self.secret_key.as_ref().ok_or_else(|| {
+                Error::InvalidInput(format!("Secret {} requires a secret key", var.name))
+            })?;
+            var.value = key.seal(&var.name, &var.value)?;
+        }
         updated.updated_at = chrono::Utc::now();
         projects.insert(updated.id, updated.clone());
 
@@ -9,7 +9,7 @@ use std::path::PathBuf;
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
-use super::model::{Task, TaskStatus};
+use super::model::{StatusTransitions, Task, TaskLimits, TaskStatus};
 use super::repository::TaskRepository;
 use crate::{Error, Result};
 
@@ -19,6 +19,10 @@ pub struct FileTaskStore {
     path: PathBuf,
     /// In-memory cache of tasks
     cache: RwLock<HashMap<Uuid, Task>>,
+    /// Field length limits enforced on write
+    limits: TaskLimits,
+    /// Status transitions allowed for user-initiated moves
+    transitions: StatusTransitions,
 }
 
 impl FileTaskStore {
@@ -38,9 +42,33 @@ impl FileTaskStore {
         Ok(Self {
             path,
             cache: RwLock::new(cache),
+            limits: TaskLimits::default(),
+            transitions: StatusTransitions::default(),
         })
     }
 
+    /// Set the field length limits enforced on create/update
+    pub fn with_limits(mut self, limits: TaskLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Get the field length limits
+    pub fn limits(&self) -> &TaskLimits {
+        &self.limits
+    }
+
+    /// Set the status transitions allowed for user-initiated moves
+    pub fn with_transitions(mut self, transitions: StatusTransitions) -> Self {
+        self.transitions = transitions;
+        self
+    }
+
+    /// Get the allowed status transitions
+    pub fn transitions(&self) -> &StatusTransitions {
+        &self.transitions
+    }
+
     /// Persist the cache to disk
     async fn persist(&self) -> Result<()> {
         let cache = self.cache.read().await;
@@ -60,6 +88,7 @@ impl FileTaskStore {
 #[async_trait]
 impl TaskRepository for FileTaskStore {
     async fn create(&self, task: Task) -> Result<Task> {
+        task.validate(&self.limits)?;
         {
             let mut cache = self.cache.write().await;
             if cache.contains_key(&task.id) {
@@ -83,7 +112,7 @@ impl TaskRepository for FileTaskStore {
         let cache = self.cache.read().await;
         let mut tasks: Vec<Task> = cache.values().cloned().collect();
         // Sort by created_at descending (newest first)
-        tasks.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        tasks.sort_by_key(|t| std::cmp::Reverse(t.created_at));
         Ok(tasks)
     }
 
@@ -91,9 +120,10 @@ impl TaskRepository for FileTaskStore {
         task.updated_at = Utc::now();
         {
             let mut cache = self.cache.write().await;
-            if !cache.contains_key(&task.id) {
+            let Some(current) = cache.get(&task.id) else {
                 return Err(Error::TaskNotFound(task.id.to_string()));
-            }
+            };
+            task.validate_changes(current, &self.limits)?;
             cache.insert(task.id, task.clone());
         }
         self.persist().await?;
@@ -118,7 +148,7 @@ impl TaskRepository for FileTaskStore {
             .filter(|t| t.status == status)
             .cloned()
             .collect();
-        tasks.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        tasks.sort_by_key(|t| std::cmp::Reverse(t.created_at));
         Ok(tasks)
     }
 }
@@ -199,6 +229,33 @@ mod tests {
         assert_eq!(retrieved.title, "Updated title");
     }
 
+    #[tokio::test]
+    async fn test_update_checks_only_changed_fields_against_limits() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("tasks.json");
+        let task = FileTaskStore::new(&path)
+            .await
+            .unwrap()
+            .create(Task::new("A title written under the old limit").with_description("Short"))
+            .await
+            .unwrap();
+
+        let store = FileTaskStore::new(&path).await.unwrap().with_limits(TaskLimits {
+            max_title_len: 8,
+            max_description_len: 8,
+        });
+        let mut updated = task.clone();
+        updated.status = TaskStatus::Done;
+        updated.description = Some("Shorter".to_string());
+        store.update(updated.clone()).await.unwrap();
+
+        updated.title = "Still too long".to_string();
+        match store.update(updated).await.unwrap_err() {
+            Error::InvalidInput(msg) => assert!(msg.contains("title")),
+            e => panic!("Expected InvalidInput error, got: {:?}", e),
+        }
+    }
+
     #[tokio::test]
     async fn test_update_nonexistent_task() {
         let (store, _temp) = create_test_store().await;
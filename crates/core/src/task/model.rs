@@ -1,37 +1,159 @@
 //! Task model definitions
 
+use std::collections::HashSet;
+
 use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 use uuid::Uuid;
 
 /// Task status in the kanban board
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum TaskStatus {
+    #[default]
     Todo,
     InProgress,
     InReview,
     Done,
 }
 
-impl Default for TaskStatus {
-    fn default() -> Self {
-        Self::Todo
+impl TaskStatus {
+    /// Every task status
+    pub const ALL: [TaskStatus; 4] = [Self::Todo, Self::InProgress, Self::InReview, Self::Done];
+
+    /// Get the canonical string representation
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Todo => "todo",
+            Self::InProgress => "in_progress",
+            Self::InReview => "in_review",
+            Self::Done => "done",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|status| status.as_str() == s)
+    }
+}
+
+/// Status transitions a task may not make
+///
+/// Permissive by default. Forbidden moves out of `Done` are still allowed
+/// when the caller explicitly reopens the task.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StatusTransitions {
+    forbidden: HashSet<(TaskStatus, TaskStatus)>,
+}
+
+impl StatusTransitions {
+    /// Allow every transition
+    pub fn permissive() -> Self {
+        Self::default()
+    }
+
+    /// Forbid moving from one status to another
+    pub fn forbid(mut self, from: TaskStatus, to: TaskStatus) -> Self {
+        if from != to {
+            self.forbidden.insert((from, to));
+        }
+        self
+    }
+
+    /// Forbid moving out of `Done` without a reopen
+    pub fn lock_done(self) -> Self {
+        TaskStatus::ALL
+            .into_iter()
+            .fold(self, |rules, to| rules.forbid(TaskStatus::Done, to))
+    }
+
+    /// Parse a comma-separated list of forbidden moves, e.g. `done->*,todo->done`
+    pub fn parse(spec: &str) -> Result<Self, crate::Error> {
+        let mut rules = Self::permissive();
+        for rule in spec.split(',').map(str::trim).filter(|r| !r.is_empty()) {
+            let invalid = || crate::Error::InvalidInput(format!("Invalid status transition rule: {}", rule));
+            let (from, to) = rule.split_once("->").ok_or_else(invalid)?;
+            let from = TaskStatus::parse(from.trim()).ok_or_else(invalid)?;
+            rules = match to.trim() {
+                "*" => TaskStatus::ALL.into_iter().fold(rules, |rules, to| rules.forbid(from, to)),
+                to => rules.forbid(from, TaskStatus::parse(to).ok_or_else(invalid)?),
+            };
+        }
+        Ok(rules)
+    }
+
+    /// Check whether a task may move from `from` to `to`
+    pub fn check(&self, from: TaskStatus, to: TaskStatus, reopen: bool) -> Result<(), TransitionError> {
+        if !self.forbidden.contains(&(from, to)) || (reopen && from == TaskStatus::Done) {
+            return Ok(());
+        }
+        Err(TransitionError { from, to })
+    }
+}
+
+/// A status change rejected by `StatusTransitions`
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("Cannot move task from {} to {}", from.as_str(), to.as_str())]
+pub struct TransitionError {
+    pub from: TaskStatus,
+    pub to: TaskStatus,
+}
+
+impl From<TransitionError> for crate::Error {
+    fn from(err: TransitionError) -> Self {
+        crate::Error::InvalidInput(err.to_string())
     }
 }
 
 /// Task priority level
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum TaskPriority {
     Low,
+    #[default]
     Medium,
     High,
 }
 
-impl Default for TaskPriority {
+impl TaskPriority {
+    /// Get the canonical string representation
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+        }
+    }
+}
+
+/// Maximum field lengths (in bytes) accepted for a task
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TaskLimits {
+    pub max_title_len: usize,
+    pub max_description_len: usize,
+}
+
+impl Default for TaskLimits {
     fn default() -> Self {
-        Self::Medium
+        Self {
+            max_title_len: 512,
+            max_description_len: 64 * 1024,
+        }
+    }
+}
+
+/// A task field that failed validation
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("{field} exceeds maximum length of {max} bytes")]
+pub struct TaskValidationError {
+    pub field: &'static str,
+    pub max: usize,
+}
+
+impl From<TaskValidationError> for crate::Error {
+    fn from(err: TaskValidationError) -> Self {
+        crate::Error::InvalidInput(err.to_string())
     }
 }
 
@@ -47,11 +169,56 @@ pub struct Task {
     pub agent_type: Option<String>,
     pub base_branch: Option<String>,
     pub model: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Who owns the task
+    #[serde(default)]
+    pub assignee: Option<String>,
+    /// OpenCode session continued by runs that ask to reuse it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opencode_session_id: Option<String>,
+    /// Run outcomes that fire notifications (`completed`, `failed`,
+    /// `cancelled`); empty means every terminal outcome
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notify_on: Vec<String>,
+    /// Users receiving a digest of the task's run activity
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub watchers: Vec<String>,
+    /// Project-relative files prepended to the prompt of each run
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub context_files: Vec<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// Fail when `value` is longer than `max` bytes
+fn check_len(field: &'static str, value: &str, max: usize) -> Result<(), TaskValidationError> {
+    if value.len() > max {
+        return Err(TaskValidationError { field, max });
+    }
+    Ok(())
+}
+
+/// Quote a CSV field when it contains a delimiter, quote or line break
+///
+/// Fields a spreadsheet would run as a formula are prefixed with `'`.
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
 impl Task {
+    /// Header line matching the rows of `to_csv_row`
+    pub const CSV_HEADER: &'static str = "id,title,status,priority,project_id,created_at,updated_at\n";
+
     /// Create a new task with the given title
     pub fn new(title: impl Into<String>) -> Self {
         let now = Utc::now();
@@ -65,6 +232,12 @@ impl Task {
             agent_type: Some("opencode".to_string()),
             base_branch: Some("main".to_string()),
             model: None,
+            tags: Vec::new(),
+            assignee: None,
+            opencode_session_id: None,
+            notify_on: Vec::new(),
+            watchers: Vec::new(),
+            context_files: Vec::new(),
             created_at: now,
             updated_at: now,
         }
@@ -105,12 +278,152 @@ impl Task {
         self.model = Some(model.into());
         self
     }
+
+    /// Set the tags
+    pub fn with_tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    /// Set the assignee
+    pub fn with_assignee(mut self, assignee: impl Into<String>) -> Self {
+        self.assignee = Some(assignee.into());
+        self
+    }
+
+    /// Only notify about these run outcomes
+    pub fn with_notify_on(mut self, outcomes: Vec<String>) -> Self {
+        self.notify_on = outcomes;
+        self
+    }
+
+    /// Set the files attached as prompt context
+    pub fn with_context_files(mut self, paths: Vec<String>) -> Self {
+        self.context_files = paths;
+        self
+    }
+
+    /// Add `user` to the watchers; returns false if already watching
+    pub fn watch(&mut self, user: &str) -> bool {
+        if self.watchers.iter().any(|watcher| watcher == user) {
+            return false;
+        }
+        self.watchers.push(user.to_string());
+        true
+    }
+
+    /// Remove `user` from the watchers; returns false if not watching
+    pub fn unwatch(&mut self, user: &str) -> bool {
+        let before = self.watchers.len();
+        self.watchers.retain(|watcher| watcher != user);
+        self.watchers.len() != before
+    }
+
+    /// Whether a run ending in `outcome` should fire a notification
+    pub fn notifies_on(&self, outcome: &str) -> bool {
+        self.notify_on.is_empty()
+            || self
+                .notify_on
+                .iter()
+                .any(|wanted| wanted.trim().eq_ignore_ascii_case(outcome))
+    }
+
+    /// One CSV line for the task export, ending in a newline
+    pub fn to_csv_row(&self) -> String {
+        let project_id = self.project_id.map(|id| id.to_string()).unwrap_or_default();
+        let fields = [
+            self.id.to_string(),
+            csv_field(&self.title),
+            self.status.as_str().to_string(),
+            self.priority.as_str().to_string(),
+            project_id,
+            self.created_at.to_rfc3339(),
+            self.updated_at.to_rfc3339(),
+        ];
+        format!("{}\n", fields.join(","))
+    }
+
+    /// Check field lengths against the given limits
+    pub fn validate(&self, limits: &TaskLimits) -> Result<(), TaskValidationError> {
+        check_len("title", &self.title, limits.max_title_len)?;
+        if let Some(description) = &self.description {
+            check_len("description", description, limits.max_description_len)?;
+        }
+        Ok(())
+    }
+
+    /// Check the lengths of the fields changed since `previous`
+    ///
+    /// Unchanged fields are not checked, so tasks stored before the limits
+    /// were lowered stay editable.
+    pub fn validate_changes(&self, previous: &Task, limits: &TaskLimits) -> Result<(), TaskValidationError> {
+        if self.title != previous.title {
+            check_len("title", &self.title, limits.max_title_len)?;
+        }
+        if self.description != previous.description {
+            if let Some(description) = &self.description {
+                check_len("description", description, limits.max_description_len)?;
+            }
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn csv_row_quotes_titles_with_delimiters() {
+        let mut task = Task::new("Fix \"login\", then deploy").with_priority(TaskPriority::High);
+        task.status = TaskStatus::InReview;
+        let row = task.to_csv_row();
+        assert_eq!(
+            row,
+            format!(
+                "{},\"Fix \"\"login\"\", then deploy\",in_review,high,,{},{}\n",
+                task.id,
+                task.created_at.to_rfc3339(),
+                task.updated_at.to_rfc3339()
+            )
+        );
+        assert_eq!(Task::CSV_HEADER.split(',').count(), 7);
+    }
+
+    #[test]
+    fn csv_row_neutralizes_formulas() {
+        for (title, field) in [
+            ("=HYPERLINK(\"http://evil\")", "\"'=HYPERLINK(\"\"http://evil\"\")\""),
+            ("+1", "'+1"),
+            ("-2+3", "'-2+3"),
+            ("@SUM(A1)", "'@SUM(A1)"),
+            ("Plain title", "Plain title"),
+        ] {
+            let task = Task::new(title);
+            assert_eq!(task.to_csv_row().split(',').nth(1), Some(field));
+        }
+    }
+
+    #[test]
+    fn test_status_transitions_default_is_permissive() {
+        let rules = StatusTransitions::default();
+        assert!(rules.check(TaskStatus::Done, TaskStatus::InProgress, false).is_ok());
+        assert!(rules.check(TaskStatus::Todo, TaskStatus::Done, false).is_ok());
+    }
+
+    #[test]
+    fn test_locked_done_requires_reopen() {
+        let rules = StatusTransitions::parse("done->*, todo->in_review").unwrap();
+        assert_eq!(rules, StatusTransitions::permissive().lock_done().forbid(TaskStatus::Todo, TaskStatus::InReview));
+
+        let err = rules.check(TaskStatus::Done, TaskStatus::InProgress, false).unwrap_err();
+        assert_eq!(err.to_string(), "Cannot move task from done to in_progress");
+        assert!(rules.check(TaskStatus::Done, TaskStatus::InProgress, true).is_ok());
+        assert!(rules.check(TaskStatus::Todo, TaskStatus::InReview, true).is_err());
+        assert!(rules.check(TaskStatus::Todo, TaskStatus::InProgress, false).is_ok());
+        assert!(StatusTransitions::parse("done->archived").is_err());
+    }
+
     #[test]
     fn test_create_task() {
         let task = Task::new("Test task");
@@ -140,4 +453,38 @@ mod tests {
         let task = Task::new("Test task").with_priority(TaskPriority::High);
         assert_eq!(task.priority, TaskPriority::High);
     }
+
+    #[test]
+    fn test_task_assignee_defaults_to_none_for_legacy_tasks() {
+        let mut value = serde_json::to_value(Task::new("Legacy task")).unwrap();
+        value.as_object_mut().unwrap().remove("assignee");
+        let task: Task = serde_json::from_value(value).unwrap();
+        assert!(task.assignee.is_none());
+
+        let task = Task::new("Owned task").with_assignee("alice");
+        assert_eq!(task.assignee.as_deref(), Some("alice"));
+    }
+
+    #[test]
+    fn test_validate_rejects_long_title() {
+        let limits = TaskLimits::default();
+        let task = Task::new("x".repeat(limits.max_title_len + 1));
+
+        let err = task.validate(&limits).unwrap_err();
+        assert_eq!(err.field, "title");
+        assert!(Task::new("x".repeat(limits.max_title_len)).validate(&limits).is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_long_description() {
+        let limits = TaskLimits {
+            max_title_len: 512,
+            max_description_len: 16,
+        };
+        let task = Task::new("Test task").with_description("d".repeat(17));
+
+        let err = task.validate(&limits).unwrap_err();
+        assert_eq!(err.field, "description");
+        assert_eq!(err.to_string(), "description exceeds maximum length of 16 bytes");
+    }
 }
@@ -0,0 +1,10 @@
+//! Task template module
+//!
+//! A TaskTemplate captures reusable defaults (title pattern, description,
+//! agent, model, tags) that tasks can be instantiated from.
+
+mod model;
+mod store;
+
+pub use model::*;
+pub use store::*;
@@ -0,0 +1,179 @@
+//! Task template model definitions
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::task::{Task, TaskPriority};
+
+/// Placeholder in a title pattern replaced by the per-call title
+pub const TITLE_PLACEHOLDER: &str = "{title}";
+
+/// Reusable defaults for creating tasks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskTemplate {
+    /// Unique template identifier
+    pub id: Uuid,
+
+    /// Human-readable template name
+    pub name: String,
+
+    /// Project the template belongs to (None = available everywhere)
+    #[serde(default)]
+    pub project_id: Option<Uuid>,
+
+    /// Title pattern, may contain `{title}`
+    pub title_pattern: String,
+
+    /// Default description
+    #[serde(default)]
+    pub description: Option<String>,
+
+    /// Default priority
+    #[serde(default)]
+    pub priority: TaskPriority,
+
+    /// Default agent type
+    #[serde(default)]
+    pub agent_type: Option<String>,
+
+    /// Default base branch
+    #[serde(default)]
+    pub base_branch: Option<String>,
+
+    /// Default model
+    #[serde(default)]
+    pub model: Option<String>,
+
+    /// Default tags
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    /// Timestamp when the template was created
+    pub created_at: DateTime<Utc>,
+
+    /// Timestamp when the template was last updated
+    pub updated_at: DateTime<Utc>,
+}
+
+impl TaskTemplate {
+    /// Create a new template with a name and title pattern
+    pub fn new(name: impl Into<String>, title_pattern: impl Into<String>) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            name: name.into(),
+            project_id: None,
+            title_pattern: title_pattern.into(),
+            description: None,
+            priority: TaskPriority::default(),
+            agent_type: None,
+            base_branch: None,
+            model: None,
+            tags: Vec::new(),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Set the project id
+    pub fn with_project_id(mut self, project_id: Uuid) -> Self {
+        self.project_id = Some(project_id);
+        self
+    }
+
+    /// Set the description
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Set the agent type
+    pub fn with_agent_type(mut self, agent_type: impl Into<String>) -> Self {
+        self.agent_type = Some(agent_type.into());
+        self
+    }
+
+    /// Set the model
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    /// Set the tags
+    pub fn with_tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    /// Render the task title from the pattern
+    ///
+    /// A given title fills `{title}`; a pattern without the placeholder is
+    /// replaced by the given title entirely.
+    pub fn render_title(&self, title: Option<&str>) -> String {
+        match title {
+            Some(title) if self.title_pattern.contains(TITLE_PLACEHOLDER) => {
+                self.title_pattern.replace(TITLE_PLACEHOLDER, title)
+            }
+            Some(title) => title.to_string(),
+            None => self.title_pattern.replace(TITLE_PLACEHOLDER, "").trim().to_string(),
+        }
+    }
+
+    /// Build a task carrying this template's defaults
+    pub fn instantiate(&self, title: Option<&str>) -> Task {
+        let mut task = Task::new(self.render_title(title))
+            .with_priority(self.priority)
+            .with_tags(self.tags.clone());
+
+        if let Some(project_id) = self.project_id {
+            task = task.with_project_id(project_id);
+        }
+        if let Some(description) = &self.description {
+            task = task.with_description(description.clone());
+        }
+        if let Some(agent_type) = &self.agent_type {
+            task = task.with_agent_type(agent_type.clone());
+        }
+        if let Some(base_branch) = &self.base_branch {
+            task = task.with_base_branch(base_branch.clone());
+        }
+        if let Some(model) = &self.model {
+            task = task.with_model(model.clone());
+        }
+
+        task
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_title() {
+        let template = TaskTemplate::new("Bug", "Bug: {title}");
+        assert_eq!(template.render_title(Some("crash on save")), "Bug: crash on save");
+        assert_eq!(template.render_title(None), "Bug:");
+
+        let fixed = TaskTemplate::new("Release", "Cut release");
+        assert_eq!(fixed.render_title(None), "Cut release");
+        assert_eq!(fixed.render_title(Some("Cut 1.2")), "Cut 1.2");
+    }
+
+    #[test]
+    fn test_instantiate_applies_defaults() {
+        let template = TaskTemplate::new("Bug", "Bug: {title}")
+            .with_description("Steps to reproduce")
+            .with_agent_type("claude-code")
+            .with_model("anthropic/claude")
+            .with_tags(vec!["bug".to_string()]);
+
+        let task = template.instantiate(Some("login fails"));
+        assert_eq!(task.title, "Bug: login fails");
+        assert_eq!(task.description.as_deref(), Some("Steps to reproduce"));
+        assert_eq!(task.agent_type.as_deref(), Some("claude-code"));
+        assert_eq!(task.model.as_deref(), Some("anthropic/claude"));
+        assert_eq!(task.tags, vec!["bug".to_string()]);
+    }
+}
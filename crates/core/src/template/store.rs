@@ -0,0 +1,192 @@
+//! Task template persistent store
+//!
+//! Provides file-based persistence for task templates.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::error::Error;
+use crate::Result;
+
+use super::model::TaskTemplate;
+
+/// Thread-safe template store with file persistence
+#[derive(Clone)]
+pub struct TemplateStore {
+    /// In-memory cache of templates
+    templates: Arc<RwLock<HashMap<Uuid, TaskTemplate>>>,
+    /// Path to the templates JSON file
+    file_path: PathBuf,
+}
+
+impl TemplateStore {
+    /// Create a new TemplateStore with the given file path
+    pub async fn new(file_path: PathBuf) -> Result<Self> {
+        let templates = if file_path.exists() {
+            let content = tokio::fs::read_to_string(&file_path).await.map_err(|e| {
+                Error::Storage(format!("Failed to read templates file: {}", e))
+            })?;
+            serde_json::from_str(&content).map_err(|e| {
+                Error::Storage(format!("Failed to parse templates file: {}", e))
+            })?
+        } else {
+            HashMap::new()
+        };
+
+        Ok(Self {
+            templates: Arc::new(RwLock::new(templates)),
+            file_path,
+        })
+    }
+
+    /// Create a new template
+    pub async fn create(&self, template: TaskTemplate) -> Result<TaskTemplate> {
+        let mut templates = self.templates.write().await;
+
+        if templates.contains_key(&template.id) {
+            return Err(Error::InvalidInput(format!(
+                "Template {} already exists",
+                template.id
+            )));
+        }
+
+        templates.insert(template.id, template.clone());
+
+        drop(templates);
+        self.persist().await?;
+        Ok(template)
+    }
+
+    /// Get a template by ID
+    pub async fn get(&self, id: Uuid) -> Option<TaskTemplate> {
+        let templates = self.templates.read().await;
+        templates.get(&id).cloned()
+    }
+
+    /// List templates, optionally scoped to a project
+    ///
+    /// Project-scoped listings also include global templates.
+    pub async fn list(&self, project_id: Option<Uuid>) -> Vec<TaskTemplate> {
+        let templates = self.templates.read().await;
+        let mut result: Vec<TaskTemplate> = templates
+            .values()
+            .filter(|t| match project_id {
+                Some(id) => t.project_id.is_none() || t.project_id == Some(id),
+                None => true,
+            })
+            .cloned()
+            .collect();
+        result.sort_by(|a, b| a.name.cmp(&b.name));
+        result
+    }
+
+    /// Update a template
+    pub async fn update(&self, template: TaskTemplate) -> Result<TaskTemplate> {
+        let mut templates = self.templates.write().await;
+
+        if !templates.contains_key(&template.id) {
+            return Err(Error::NotFound(format!("Template {} not found", template.id)));
+        }
+
+        let mut updated = template;
+        updated.updated_at = chrono::Utc::now();
+        templates.insert(updated.id, updated.clone());
+
+        drop(templates);
+        self.persist().await?;
+        Ok(updated)
+    }
+
+    /// Delete a template
+    pub async fn delete(&self, id: Uuid) -> Result<Option<TaskTemplate>> {
+        let mut templates = self.templates.write().await;
+        let removed = templates.remove(&id);
+
+        if removed.is_some() {
+            drop(templates);
+            self.persist().await?;
+        }
+
+        Ok(removed)
+    }
+
+    /// Persist the current state to file
+    async fn persist(&self) -> Result<()> {
+        let templates = self.templates.read().await;
+        let content = serde_json::to_string_pretty(&*templates).map_err(|e| {
+            Error::Storage(format!("Failed to serialize templates: {}", e))
+        })?;
+
+        // Ensure parent directory exists
+        if let Some(parent) = self.file_path.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| {
+                Error::Storage(format!("Failed to create directory: {}", e))
+            })?;
+        }
+
+        tokio::fs::write(&self.file_path, content).await.map_err(|e| {
+            Error::Storage(format!("Failed to write templates file: {}", e))
+        })?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_template_crud_persists() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("templates.json");
+
+        let store = TemplateStore::new(path.clone()).await.unwrap();
+        let template = store
+            .create(TaskTemplate::new("Bug", "Bug: {title}"))
+            .await
+            .unwrap();
+
+        let mut changed = template.clone();
+        changed.name = "Bug report".to_string();
+        store.update(changed).await.unwrap();
+
+        let reloaded = TemplateStore::new(path).await.unwrap();
+        assert_eq!(reloaded.get(template.id).await.unwrap().name, "Bug report");
+
+        assert!(reloaded.delete(template.id).await.unwrap().is_some());
+        assert!(reloaded.get(template.id).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_list_scoped_to_project() {
+        let dir = tempdir().unwrap();
+        let store = TemplateStore::new(dir.path().join("templates.json"))
+            .await
+            .unwrap();
+        let project_id = Uuid::new_v4();
+
+        store.create(TaskTemplate::new("Global", "{title}")).await.unwrap();
+        store
+            .create(TaskTemplate::new("Scoped", "{title}").with_project_id(project_id))
+            .await
+            .unwrap();
+        store
+            .create(TaskTemplate::new("Other", "{title}").with_project_id(Uuid::new_v4()))
+            .await
+            .unwrap();
+
+        let names: Vec<String> = store
+            .list(Some(project_id))
+            .await
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        assert_eq!(names, vec!["Global".to_string(), "Scoped".to_string()]);
+        assert_eq!(store.list(None).await.len(), 3);
+    }
+}
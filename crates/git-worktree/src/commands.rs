@@ -115,12 +115,71 @@ pub async fn delete_branch(repo_path: &Path, branch: &str, force: bool) -> Resul
     Ok(())
 }
 
+/// Get the repository's default branch
+///
+/// Prefers the branch `origin/HEAD` points at, falling back to the branch
+/// currently checked out in `repo_path`.
+pub async fn default_branch(repo_path: &Path) -> Result<String> {
+    let remote_head = git_command(
+        repo_path,
+        &["symbolic-ref", "--short", "refs/remotes/origin/HEAD"],
+    )
+    .await?;
+    if remote_head.success {
+        let head = remote_head.stdout.trim();
+        if let Some(branch) = head.strip_prefix("origin/") {
+            return Ok(branch.to_string());
+        }
+    }
+
+    let head = git_command_checked(repo_path, &["symbolic-ref", "--short", "HEAD"]).await?;
+    let branch = head.trim();
+    if branch.is_empty() {
+        return Err(WorktreeError::ParseError {
+            message: "HEAD does not point to a branch".to_string(),
+        });
+    }
+    Ok(branch.to_string())
+}
+
 /// Fetch from remote
 pub async fn fetch(repo_path: &Path, remote: &str) -> Result<()> {
     git_command_checked(repo_path, &["fetch", remote]).await?;
     Ok(())
 }
 
+/// Clone `url` into `dest` unless `dest` already exists
+///
+/// Returns whether a clone was made.
+pub async fn clone_if_missing(url: &str, dest: &Path) -> Result<bool> {
+    if dest.exists() {
+        return Ok(false);
+    }
+
+    let clone_failed = |message: String| WorktreeError::CloneFailed {
+        url: url.to_string(),
+        path: dest.to_path_buf(),
+        message,
+    };
+    let parent = match dest.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    tokio::fs::create_dir_all(parent)
+        .await
+        .map_err(|e| clone_failed(e.to_string()))?;
+
+    let dest_arg = dest.to_string_lossy();
+    let output = git_command(parent, &["clone", "--", url, &dest_arg])
+        .await
+        .map_err(|e| clone_failed(e.to_string()))?;
+    if !output.success {
+        return Err(clone_failed(output.stderr.trim().to_string()));
+    }
+
+    Ok(true)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -169,6 +228,19 @@ mod tests {
         assert!(!branch_exists(dir.path(), "nonexistent").await.unwrap());
     }
 
+    #[tokio::test]
+    async fn test_default_branch() {
+        let dir = init_test_repo().await;
+        git_command_checked(dir.path(), &["checkout", "-b", "trunk"])
+            .await
+            .unwrap();
+
+        assert_eq!(default_branch(dir.path()).await.unwrap(), "trunk");
+
+        let non_git = TempDir::new().unwrap();
+        assert!(default_branch(non_git.path()).await.is_err());
+    }
+
     #[tokio::test]
     async fn test_create_and_delete_branch() {
         let dir = init_test_repo().await;
@@ -192,4 +264,36 @@ mod tests {
             .unwrap();
         assert!(!branch_exists(dir.path(), "test-branch").await.unwrap());
     }
+
+    #[tokio::test]
+    async fn test_clone_if_missing_clones_bare_remote() {
+        let source = init_test_repo().await;
+        let remotes = TempDir::new().unwrap();
+        let bare = remotes.path().join("origin.git");
+        git_command_checked(
+            remotes.path(),
+            &["clone", "--bare", &source.path().to_string_lossy(), &bare.to_string_lossy()],
+        )
+        .await
+        .unwrap();
+
+        let checkouts = TempDir::new().unwrap();
+        let dest = checkouts.path().join("nested").join("project");
+        let url = bare.to_string_lossy();
+        assert!(clone_if_missing(&url, &dest).await.unwrap());
+        assert!(dest.join("test.txt").exists());
+
+        let worktrees = crate::WorktreeManager::new(dest.clone()).await.unwrap();
+        let branch = default_branch(&dest).await.unwrap();
+        let worktree = worktrees.create("cloned-task", &branch).await.unwrap();
+        assert!(worktree.path.join("test.txt").exists());
+
+        // Existing checkouts are left alone
+        assert!(!clone_if_missing(&url, &dest).await.unwrap());
+
+        let err = clone_if_missing("/nonexistent/remote.git", &checkouts.path().join("missing"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, WorktreeError::CloneFailed { .. }));
+    }
 }
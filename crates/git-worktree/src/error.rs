@@ -49,9 +49,25 @@ pub enum WorktreeError {
     #[error("Failed to parse git output: {message}")]
     ParseError { message: String },
 
+    /// Cloning the project remote failed
+    #[error("Failed to clone {url} into {path}: {message}")]
+    CloneFailed {
+        url: String,
+        path: PathBuf,
+        message: String,
+    },
+
+    /// Worktree root directory cannot be created or written to
+    #[error("Worktree root {path} is not writable: {message}")]
+    RootNotWritable { path: PathBuf, message: String },
+
     /// Worktree is locked
     #[error("Worktree at {path} is locked: {reason}")]
     WorktreeLocked { path: PathBuf, reason: String },
+
+    /// Worktree has uncommitted changes that a reset would discard
+    #[error("Worktree at {path} has uncommitted changes")]
+    DirtyWorktree { path: PathBuf },
 }
 
 impl WorktreeError {
@@ -7,5 +7,6 @@ mod commands;
 mod error;
 mod worktree;
 
+pub use commands::{clone_if_missing, default_branch};
 pub use error::{WorktreeError, Result};
-pub use worktree::{Worktree, WorktreeConfig, WorktreeManager, WorktreeStatus};
+pub use worktree::{MergeStatus, Worktree, WorktreeConfig, WorktreeManager, WorktreeStatus};
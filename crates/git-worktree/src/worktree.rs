@@ -38,11 +38,22 @@ pub struct Worktree {
     pub is_main: bool,
 }
 
+/// Result of a dry-run merge of a worktree branch
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MergeStatus {
+    /// Whether the branch merges without conflicts
+    pub mergeable: bool,
+    /// Files that would conflict
+    pub conflicts: Vec<String>,
+}
+
 /// Configuration for WorktreeManager
 #[derive(Debug, Clone)]
 pub struct WorktreeConfig {
     /// Directory where worktrees will be created
     pub worktree_dir: PathBuf,
+    /// Base directory for worktrees; overrides `worktree_dir` when set
+    pub worktree_root: Option<PathBuf>,
     /// Prefix for worktree branch names
     pub branch_prefix: String,
 }
@@ -51,11 +62,55 @@ impl Default for WorktreeConfig {
     fn default() -> Self {
         Self {
             worktree_dir: PathBuf::from(".worktrees"),
+            worktree_root: None,
             branch_prefix: "task/".to_string(),
         }
     }
 }
 
+impl WorktreeConfig {
+    /// Set the worktree root
+    pub fn with_worktree_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.worktree_root = Some(root.into());
+        self
+    }
+
+    /// Resolve the worktree root for a project
+    ///
+    /// Picks the project's `worktree_dir`, then the server default, then
+    /// `.worktrees` under the host-provided `cwd`. Relative paths are taken
+    /// relative to `cwd`. The chosen directory is created and checked for
+    /// write access.
+    pub async fn resolve_root(
+        project_dir: Option<&str>,
+        default_root: Option<&Path>,
+        cwd: &Path,
+    ) -> Result<PathBuf> {
+        let root = project_dir
+            .map(str::trim)
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| default_root.map(Path::to_path_buf))
+            .unwrap_or_else(|| PathBuf::from(".worktrees"));
+        let root = cwd.join(root);
+        ensure_writable(&root).await?;
+        Ok(root)
+    }
+}
+
+/// Create `dir` if needed and check that files can be written into it
+async fn ensure_writable(dir: &Path) -> Result<()> {
+    let not_writable = |e: std::io::Error| WorktreeError::RootNotWritable {
+        path: dir.to_path_buf(),
+        message: e.to_string(),
+    };
+    tokio::fs::create_dir_all(dir).await.map_err(not_writable)?;
+    let probe = dir.join(format!(".vk-write-check-{}", Uuid::new_v4()));
+    tokio::fs::write(&probe, b"").await.map_err(not_writable)?;
+    let _ = tokio::fs::remove_file(&probe).await;
+    Ok(())
+}
+
 /// Manages Git worktrees for task isolation
 #[derive(Debug)]
 pub struct WorktreeManager {
@@ -96,6 +151,10 @@ impl WorktreeManager {
             });
         }
 
+        if let Some(root) = &config.worktree_root {
+            ensure_writable(root).await?;
+        }
+
         Ok(Self { repo_path, config })
     }
 
@@ -106,7 +165,10 @@ impl WorktreeManager {
 
     /// Get the worktree directory
     pub fn worktree_dir(&self) -> PathBuf {
-        self.repo_path.join(&self.config.worktree_dir)
+        match &self.config.worktree_root {
+            Some(root) => self.repo_path.join(root),
+            None => self.repo_path.join(&self.config.worktree_dir),
+        }
     }
 
     /// Create a new worktree for a task
@@ -180,6 +242,36 @@ impl WorktreeManager {
         })
     }
 
+    /// Reuse the task's worktree, reset to `base_branch`, or create it
+    ///
+    /// A worktree with uncommitted changes is not reset, so no work is lost;
+    /// it is reported as `DirtyWorktree` instead.
+    pub async fn reuse_or_create(&self, task_id: &str, base_branch: &str) -> Result<Worktree> {
+        let Some(existing) = self.get_by_task_id(task_id).await? else {
+            return self.create(task_id, base_branch).await;
+        };
+
+        if self.has_uncommitted_changes(&existing.path).await? {
+            return Err(WorktreeError::DirtyWorktree {
+                path: existing.path,
+            });
+        }
+        if !branch_exists(&self.repo_path, base_branch).await? {
+            return Err(WorktreeError::BranchNotFound {
+                branch: base_branch.to_string(),
+            });
+        }
+
+        info!("Resetting worktree at {:?} to {}", existing.path, base_branch);
+        git_command_checked(&existing.path, &["reset", "--hard", base_branch]).await?;
+        let head = git_command_checked(&existing.path, &["rev-parse", "HEAD"]).await?;
+
+        Ok(Worktree {
+            head: head.trim().to_string(),
+            ..existing
+        })
+    }
+
     /// Create a worktree with an auto-generated task ID
     pub async fn create_auto(&self, base_branch: &str) -> Result<Worktree> {
         let task_id = Uuid::new_v4().to_string();
@@ -369,6 +461,56 @@ impl WorktreeManager {
         Ok(output)
     }
 
+    /// Check whether a worktree's branch merges cleanly into `into_branch`
+    pub async fn check_mergeable(&self, worktree: &Worktree, into_branch: &str) -> Result<MergeStatus> {
+        self.check_branch_mergeable(&worktree.branch, into_branch).await
+    }
+
+    /// Dry-run merge `branch` into `into_branch` and report conflicting files
+    ///
+    /// The merge happens in a temporary detached worktree that is removed
+    /// afterwards, so neither branch nor any existing worktree is touched.
+    pub async fn check_branch_mergeable(&self, branch: &str, into_branch: &str) -> Result<MergeStatus> {
+        for name in [branch, into_branch] {
+            if !branch_exists(&self.repo_path, name).await? {
+                return Err(WorktreeError::BranchNotFound {
+                    branch: name.to_string(),
+                });
+            }
+        }
+
+        let check_path = self
+            .worktree_dir()
+            .join(format!(".merge-check-{}", Uuid::new_v4()));
+        let check_path_str = check_path
+            .to_str()
+            .ok_or_else(|| WorktreeError::InvalidPath {
+                path: check_path.clone(),
+            })?
+            .to_string();
+
+        git_command_checked(
+            &self.repo_path,
+            &["worktree", "add", "--detach", &check_path_str, into_branch],
+        )
+        .await?;
+
+        let result = dry_run_merge(&check_path, branch).await;
+
+        // Clean up the temporary merge state and worktree
+        let _ = git_command(&check_path, &["merge", "--abort"]).await;
+        if let Err(e) = git_command_checked(
+            &self.repo_path,
+            &["worktree", "remove", "--force", &check_path_str],
+        )
+        .await
+        {
+            warn!("Failed to remove merge check worktree {:?}: {}", check_path, e);
+        }
+
+        result
+    }
+
     /// Commit all changes in a worktree
     pub async fn commit_all(&self, worktree_path: &Path, message: &str) -> Result<String> {
         // Stage all changes
@@ -383,6 +525,39 @@ impl WorktreeManager {
     }
 }
 
+/// Attempt a non-committing merge of `branch` in `path`
+async fn dry_run_merge(path: &Path, branch: &str) -> Result<MergeStatus> {
+    let merge = git_command(path, &["merge", "--no-commit", "--no-ff", branch]).await?;
+    if merge.success {
+        return Ok(MergeStatus {
+            mergeable: true,
+            conflicts: Vec::new(),
+        });
+    }
+
+    let output = git_command_checked(path, &["diff", "--name-only", "--diff-filter=U"]).await?;
+    let conflicts: Vec<String> = output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect();
+
+    if conflicts.is_empty() {
+        return Err(WorktreeError::git_failed(format!(
+            "git merge {} failed: {}",
+            branch,
+            merge.stderr.trim()
+        )));
+    }
+
+    debug!("Merge of {} would conflict in {:?}", branch, conflicts);
+    Ok(MergeStatus {
+        mergeable: false,
+        conflicts,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -449,6 +624,33 @@ mod tests {
         assert!(found.is_some());
     }
 
+    #[tokio::test]
+    async fn test_worktree_created_under_configured_root() {
+        let dir = init_test_repo().await;
+        let root_parent = TempDir::new().unwrap();
+        let root = WorktreeConfig::resolve_root(None, Some(&root_parent.path().join("fast")), dir.path())
+            .await
+            .unwrap();
+        assert_eq!(root, root_parent.path().join("fast"));
+
+        let config = WorktreeConfig::default().with_worktree_root(&root);
+        let manager = WorktreeManager::with_config(dir.path(), config).await.unwrap();
+        let worktree = manager.create("rooted-task", "main").await.unwrap();
+        assert_eq!(worktree.path, root.join("rooted-task"));
+        assert!(worktree.path.join("test.txt").exists());
+
+        // Project setting wins, relative to the host cwd
+        let root = WorktreeConfig::resolve_root(Some("wt"), Some(root_parent.path()), dir.path())
+            .await
+            .unwrap();
+        assert_eq!(root, dir.path().join("wt"));
+
+        let file = root_parent.path().join("file");
+        tokio::fs::write(&file, "x").await.unwrap();
+        let result = WorktreeConfig::resolve_root(None, Some(&file.join("sub")), dir.path()).await;
+        assert!(matches!(result, Err(WorktreeError::RootNotWritable { .. })));
+    }
+
     #[tokio::test]
     async fn test_create_duplicate_worktree() {
         let dir = init_test_repo().await;
@@ -462,6 +664,31 @@ mod tests {
         assert!(matches!(result, Err(WorktreeError::BranchExists { .. })));
     }
 
+    #[tokio::test]
+    async fn test_reuse_or_create_keeps_one_worktree_per_task() {
+        let dir = init_test_repo().await;
+        let manager = WorktreeManager::new(dir.path()).await.unwrap();
+        let main_head = git_command_checked(dir.path(), &["rev-parse", "main"]).await.unwrap();
+
+        // First run creates the worktree and commits to the task branch
+        let first = manager.reuse_or_create("reused-task", "main").await.unwrap();
+        tokio::fs::write(first.path.join("run1.txt"), "first run").await.unwrap();
+        manager.commit_all(&first.path, "First run").await.unwrap();
+
+        // Second run gets the same directory, reset to the base branch
+        let second = manager.reuse_or_create("reused-task", "main").await.unwrap();
+        assert_eq!(second.path, first.path);
+        assert_eq!(second.branch, "task/reused-task");
+        assert_eq!(second.head, main_head.trim());
+        assert!(!second.path.join("run1.txt").exists());
+
+        // Uncommitted work is never reset away
+        tokio::fs::write(second.path.join("wip.txt"), "unsaved").await.unwrap();
+        let result = manager.reuse_or_create("reused-task", "main").await;
+        assert!(matches!(result, Err(WorktreeError::DirtyWorktree { .. })));
+        assert!(second.path.join("wip.txt").exists());
+    }
+
     #[tokio::test]
     async fn test_remove_worktree() {
         let dir = init_test_repo().await;
@@ -516,4 +743,45 @@ mod tests {
         // Now should have changes
         assert!(manager.has_uncommitted_changes(&worktree.path).await.unwrap());
     }
+
+    #[tokio::test]
+    async fn test_check_mergeable_reports_conflicts() {
+        let dir = init_test_repo().await;
+        let manager = WorktreeManager::new(dir.path()).await.unwrap();
+
+        let worktree = manager.create("merge-task", "main").await.unwrap();
+        tokio::fs::write(worktree.path.join("test.txt"), "agent change")
+            .await
+            .unwrap();
+        tokio::fs::write(worktree.path.join("extra.txt"), "no conflict")
+            .await
+            .unwrap();
+        manager.commit_all(&worktree.path, "Agent change").await.unwrap();
+
+        // Clean merge while main has not moved
+        let status = manager.check_mergeable(&worktree, "main").await.unwrap();
+        assert!(status.mergeable);
+        assert!(status.conflicts.is_empty());
+
+        tokio::fs::write(dir.path().join("test.txt"), "main change")
+            .await
+            .unwrap();
+        git_command_checked(dir.path(), &["commit", "-am", "Main change"])
+            .await
+            .unwrap();
+
+        let status = manager.check_mergeable(&worktree, "main").await.unwrap();
+        assert!(!status.mergeable);
+        assert_eq!(status.conflicts, vec!["test.txt".to_string()]);
+
+        // Temporary merge worktree is gone and main is untouched
+        let worktrees = manager.list().await.unwrap();
+        assert!(!worktrees
+            .iter()
+            .any(|wt| wt.path.to_string_lossy().contains(".merge-check-")));
+        let status = git_command_checked(dir.path(), &["status", "--porcelain", "--untracked-files=no"])
+            .await
+            .unwrap();
+        assert!(status.trim().is_empty());
+    }
 }
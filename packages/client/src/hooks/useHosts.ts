@@ -17,6 +17,19 @@ export interface HostCapabilities {
   maxConcurrent: number;
   cwd: string;
   labels?: Record<string, string>;
+  agentVersions?: Record<string, string>;
+}
+
+/** Task currently dispatched to a host */
+export interface InFlightTask {
+  taskId: string;
+  /** Unix timestamp (ms) when the task was dispatched */
+  dispatchedAt: number;
+  /** Unix timestamp (ms) when the host reported the task as started */
+  startedAt: number | null;
+  sessionId: string | null;
+  /** Unix timestamp (ms) of the last event the host sent for the task */
+  lastEventAt: number | null;
 }
 
 /** Host status information */
@@ -26,6 +39,8 @@ export interface HostStatus {
   status: HostConnectionStatus;
   capabilities: HostCapabilities;
   activeTasks: string[];
+  inFlight?: InFlightTask[];
+  queueDepth?: number;
   lastHeartbeat: number;
   connectedAt: number;
 }
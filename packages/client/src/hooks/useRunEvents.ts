@@ -10,7 +10,8 @@ export interface RunEventFilters {
 interface RunEventsResponse {
   events: ExecutionEvent[];
   hasMore: boolean;
-  nextOffset?: number;
+  nextOffset?: number | null;
+  nextCursor?: string | null;
 }
 
 export interface UseRunEventsResult {
@@ -31,7 +32,7 @@ export function useRunEvents(
   const [events, setEvents] = useState<ExecutionEvent[]>([]);
   const [isLoading, setIsLoading] = useState(false);
   const [error, setError] = useState<string | null>(null);
-  const [offset, setOffset] = useState(0);
+  const [cursor, setCursor] = useState<string | null>(null);
   const [hasMore, setHasMore] = useState(false);
   const baseUrl = resolveApiBaseUrl();
 
@@ -40,7 +41,7 @@ export function useRunEvents(
     filters.agentEventType,
   ]);
 
-  const fetchPage = useCallback(async (nextOffset: number, replace: boolean) => {
+  const fetchPage = useCallback(async (after: string | null, replace: boolean) => {
     if (!taskId || !runId) {
       setEvents([]);
       setHasMore(false);
@@ -52,7 +53,9 @@ export function useRunEvents(
 
     try {
       const params = new URLSearchParams();
-      params.set('offset', String(nextOffset));
+      if (after) {
+        params.set('cursor', after);
+      }
       params.set('limit', String(limit));
       if (filters.eventType) {
         params.set('eventType', filters.eventType);
@@ -62,7 +65,7 @@ export function useRunEvents(
       }
 
       const response = await fetch(
-        `${baseUrl}/api/tasks/${taskId}/runs/${runId}/events?${params.toString()}`,
+        `${baseUrl}/api/v1/tasks/${taskId}/runs/${runId}/events?${params.toString()}`,
       );
 
       if (!response.ok) {
@@ -78,7 +81,7 @@ export function useRunEvents(
       const data: RunEventsResponse = await response.json();
       setEvents(prev => (replace ? data.events : [...prev, ...data.events]));
       setHasMore(data.hasMore);
-      setOffset(data.nextOffset ?? nextOffset + data.events.length);
+      setCursor(data.nextCursor ?? null);
     } catch (err) {
       setError(err instanceof Error ? err.message : 'Unknown error');
     } finally {
@@ -87,15 +90,15 @@ export function useRunEvents(
   }, [baseUrl, filters.agentEventType, filters.eventType, limit, runId, taskId]);
 
   const refresh = useCallback(async () => {
-    setOffset(0);
-    await fetchPage(0, true);
+    setCursor(null);
+    await fetchPage(null, true);
   }, [fetchPage]);
 
   const loadMore = useCallback(async () => {
     if (hasMore && !isLoading) {
-      await fetchPage(offset, false);
+      await fetchPage(cursor, false);
     }
-  }, [fetchPage, hasMore, isLoading, offset]);
+  }, [cursor, fetchPage, hasMore, isLoading]);
 
   useEffect(() => {
     void refresh();
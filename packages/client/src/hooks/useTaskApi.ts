@@ -75,7 +75,7 @@ export function useTaskApi(): UseTaskApiResult {
     setIsLoading(true);
     setError(null);
     try {
-      const response = await fetch(`${baseUrl}/api/tasks`);
+      const response = await fetch(`${baseUrl}/api/v1/tasks`);
       if (!response.ok) {
         const errorData = await response.json();
         throw new Error(errorData.error || 'Failed to fetch tasks');
@@ -93,7 +93,7 @@ export function useTaskApi(): UseTaskApiResult {
     setIsLoading(true);
     setError(null);
     try {
-      const response = await fetch(`${baseUrl}/api/tasks`, {
+      const response = await fetch(`${baseUrl}/api/v1/tasks`, {
         method: 'POST',
         headers: {
           'Content-Type': 'application/json',
@@ -118,7 +118,7 @@ export function useTaskApi(): UseTaskApiResult {
   const getTask = useCallback(async (id: string): Promise<Task | null> => {
     setError(null);
     try {
-      const response = await fetch(`${baseUrl}/api/tasks/${id}`);
+      const response = await fetch(`${baseUrl}/api/v1/tasks/${id}`);
       if (!response.ok) {
         if (response.status === 404) {
           return null;
@@ -137,7 +137,7 @@ export function useTaskApi(): UseTaskApiResult {
     setIsLoading(true);
     setError(null);
     try {
-      const response = await fetch(`${baseUrl}/api/tasks/${id}`, {
+      const response = await fetch(`${baseUrl}/api/v1/tasks/${id}`, {
         method: 'PATCH',
         headers: {
           'Content-Type': 'application/json',
@@ -163,7 +163,7 @@ export function useTaskApi(): UseTaskApiResult {
     setIsLoading(true);
     setError(null);
     try {
-      const response = await fetch(`${baseUrl}/api/tasks/${id}`, {
+      const response = await fetch(`${baseUrl}/api/v1/tasks/${id}`, {
         method: 'DELETE',
       });
       if (!response.ok && response.status !== 204) {
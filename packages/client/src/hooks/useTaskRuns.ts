@@ -23,6 +23,17 @@ export interface RunSummary {
   durationMs: number | null;
   status: RunStatus;
   eventCount: number;
+  labels: Record<string, string>;
+}
+
+export interface RunLoadWarning {
+  runId: string;
+  error: string;
+}
+
+interface TaskRunsResponse {
+  runs: RunSummary[];
+  warnings: RunLoadWarning[];
 }
 
 export interface ChatMessage {
@@ -59,7 +70,7 @@ export function useTaskRuns(taskId: string | null): UseTaskRunsResult {
     setError(null);
 
     try {
-      const response = await fetch(`${baseUrl}/api/tasks/${taskId}/runs`);
+      const response = await fetch(`${baseUrl}/api/v1/tasks/${taskId}/runs`);
       if (!response.ok) {
         if (response.status === 404) {
           setRuns([]);
@@ -68,8 +79,11 @@ export function useTaskRuns(taskId: string | null): UseTaskRunsResult {
         const errorData = await response.json();
         throw new Error(errorData.error || 'Failed to fetch runs');
       }
-      const data: RunSummary[] = await response.json();
-      setRuns(data);
+      const data: TaskRunsResponse = await response.json();
+      if (data.warnings.length > 0) {
+        console.warn('Some runs could not be loaded', data.warnings);
+      }
+      setRuns(data.runs);
     } catch (err) {
       setError(err instanceof Error ? err.message : 'Unknown error');
     } finally {
@@ -83,7 +97,7 @@ export function useTaskRuns(taskId: string | null): UseTaskRunsResult {
     }
 
     try {
-      const response = await fetch(`${baseUrl}/api/tasks/${taskId}/runs/${runId}/messages`);
+      const response = await fetch(`${baseUrl}/api/v1/tasks/${taskId}/runs/${runId}/messages`);
       if (!response.ok) {
         if (response.status === 404) {
           return [];
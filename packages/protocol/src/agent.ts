@@ -280,7 +280,7 @@ export type TaskSessionEventType =
 /** Client → Server: 任务会话事件 */
 export interface TaskSessionClientEvents {
   /** 开始执行任务 */
-  'task:execute': (payload: { taskId: string }) => void;
+  'task:execute': (payload: { taskId: string; reuseSession?: boolean }) => void;
   /** 发送用户消息 */
   'task:message': (payload: { taskId: string; content: string }) => void;
   /** 停止任务执行 */
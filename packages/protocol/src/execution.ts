@@ -21,7 +21,7 @@ export type ExecutionEvent = ExecutionEventBase & (
 export type AgentEvent = 
   | { type: 'thinking'; content: string }
   | { type: 'command'; command: string; output: string; exit_code?: number }
-  | { type: 'file_change'; path: string; action: FileAction; diff?: string }
+  | { type: 'file_change'; path: string; action: FileAction; diff?: string; additions?: number; deletions?: number }
   | { type: 'tool_call'; tool: string; args: any; result?: any }
   | { type: 'message'; content: string }
   | { type: 'error'; message: string; recoverable: boolean }
@@ -9,6 +9,8 @@ export interface KanbanTask {
   updatedAt?: number;
   /** 关联的 Agent 会话 ID */
   sessionId?: string;
+  /** 列内的分数排序键，按字典序比较 */
+  rank?: string;
 }
 
 export interface KanbanColumn {
@@ -7,6 +7,7 @@ export interface HostCapabilities {
   maxConcurrent: number;
   cwd: string;
   labels?: Record<string, string>;
+  agentVersions?: Record<string, string>;
 }
 
 /** Task request sent from server to gateway */
@@ -67,6 +68,8 @@ export interface TaskResult {
   success: boolean;
   exitCode?: number;
   output?: string;
+  /** Concise summary of what the agent accomplished */
+  summary?: string;
   duration?: number;
   filesChanged?: string[];
 }
@@ -79,7 +82,8 @@ export type GatewayToServerMessage =
   | { type: 'task:event'; taskId: string; event: GatewayAgentEvent }
   | { type: 'task:completed'; taskId: string; result: TaskResult }
   | { type: 'task:failed'; taskId: string; error: string; details?: unknown }
-  | { type: 'models:response'; requestId: string; providers: ProviderInfo[] };
+  | { type: 'models:response'; requestId: string; providers: ProviderInfo[] }
+  | { type: 'tasks:query'; taskIds: string[] };
 
 /** Server -> Gateway messages */
 export type ServerToGatewayMessage =
@@ -88,7 +92,8 @@ export type ServerToGatewayMessage =
   | { type: 'task:execute'; task: TaskRequest }
   | { type: 'task:abort'; taskId: string }
   | { type: 'task:input'; taskId: string; content: string }
-  | { type: 'models:request'; requestId: string };
+  | { type: 'models:request'; requestId: string }
+  | { type: 'tasks:status'; active: string[]; abandoned: string[] };
 
 /** Gateway connection options */
 export interface GatewayOptions {